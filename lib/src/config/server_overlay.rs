@@ -1,6 +1,6 @@
 use std::path::{Path, PathBuf};
 
-use super::v2::server::ScanDepth;
+use super::v2::server::{AudioOutput, ScanDepth};
 
 /// The Server Settings to use, with possible overwrite (like from CLI)
 #[derive(Debug, Clone, PartialEq, Default)]
@@ -19,6 +19,8 @@ pub struct ServerOverlay {
     pub disable_discord_status: bool,
     /// Overwrite the Library scan depth
     pub library_scan_depth: Option<ScanDepth>,
+    /// Overwrite where to send the audio to
+    pub output: Option<AudioOutput>,
 }
 
 impl ServerOverlay {
@@ -31,6 +33,11 @@ impl ServerOverlay {
         }
     }
 
+    /// Get where to send the audio to, either the overwrite if present, otherwise the config itself
+    pub fn get_output(&self) -> &AudioOutput {
+        self.output.as_ref().unwrap_or(&self.settings.player.output)
+    }
+
     /// Get whether to enable the discord status
     pub fn get_discord_status_enable(&self) -> bool {
         if self.disable_discord_status {
@@ -0,0 +1,220 @@
+use std::fmt::Write as _;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// The highest gain a band may have, in both directions
+pub const MAX_BAND_GAIN: f32 = 24.0;
+
+/// Default quality of a band, which is about one octave wide
+pub const DEFAULT_BAND_Q: f32 = std::f32::consts::SQRT_2;
+
+/// Settings for the equalizer
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[serde(default)] // allow missing fields and fill them with the `..Self::default()` in this struct
+pub struct EqualizerSettings {
+    /// The name of the active preset, [`None`] to disable the equalizer
+    pub preset: Option<String>,
+    /// All available presets
+    pub presets: Vec<EqualizerPreset>,
+}
+
+impl Default for EqualizerSettings {
+    fn default() -> Self {
+        Self {
+            preset: None,
+            presets: default_presets(),
+        }
+    }
+}
+
+impl EqualizerSettings {
+    /// Get the preset with the given name
+    pub fn get_preset(&self, name: &str) -> Option<&EqualizerPreset> {
+        self.presets.iter().find(|v| v.name == name)
+    }
+
+    /// Get the bands of the active preset, or [`None`] if disabled or the preset does not exist
+    pub fn active_bands(&self) -> Option<&[EqualizerBand]> {
+        let name = self.preset.as_deref()?;
+
+        self.get_preset(name).map(|v| v.bands.as_slice())
+    }
+
+    /// Get the names of all presets
+    pub fn preset_names(&self) -> Vec<String> {
+        self.presets.iter().map(|v| v.name.clone()).collect()
+    }
+}
+
+/// A named set of bands
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct EqualizerPreset {
+    pub name: String,
+    pub bands: Vec<EqualizerBand>,
+}
+
+/// A single peaking filter
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
+pub struct EqualizerBand {
+    /// Center frequency in Hz
+    pub frequency: f32,
+    /// Gain in dB, positive to boost and negative to cut
+    pub gain: f32,
+    /// Quality, higher values affect a narrower range of frequencies
+    #[serde(default = "default_q")]
+    pub q: f32,
+}
+
+fn default_q() -> f32 {
+    DEFAULT_BAND_Q
+}
+
+impl EqualizerBand {
+    /// Create a new band with the default quality
+    pub fn new(frequency: f32, gain: f32) -> Self {
+        Self {
+            frequency,
+            gain,
+            q: DEFAULT_BAND_Q,
+        }
+    }
+
+    /// Parse a list of bands in the form of `frequency:gain[:q]`, separated by whitespace.
+    ///
+    /// Example: `60:3 1000:-2.5 8000:1:0.7`
+    pub fn parse_list(input: &str) -> Result<Vec<Self>> {
+        input
+            .split_whitespace()
+            .map(|band| Self::parse(band).with_context(|| format!("Invalid band {band:?}")))
+            .collect()
+    }
+
+    fn parse(input: &str) -> Result<Self> {
+        let mut parts = input.split(':');
+        let (Some(frequency), Some(gain)) = (parts.next(), parts.next()) else {
+            bail!("Expected \"frequency:gain[:q]\"");
+        };
+        let q = parts.next();
+        if parts.next().is_some() {
+            bail!("Expected \"frequency:gain[:q]\"");
+        }
+
+        let frequency: f32 = frequency.parse().context("frequency")?;
+        let gain: f32 = gain.parse().context("gain")?;
+        let q: f32 = q.map_or(Ok(DEFAULT_BAND_Q), str::parse).context("q")?;
+
+        if !frequency.is_finite() || frequency <= 0.0 {
+            bail!("Frequency needs to be above 0");
+        }
+        if !gain.is_finite() || gain.abs() > MAX_BAND_GAIN {
+            bail!("Gain needs to be between -{MAX_BAND_GAIN} and {MAX_BAND_GAIN}");
+        }
+        if !q.is_finite() || q <= 0.0 {
+            bail!("Q needs to be above 0");
+        }
+
+        Ok(Self { frequency, gain, q })
+    }
+
+    /// Format the bands in the form accepted by [`EqualizerBand::parse_list`]
+    pub fn format_list(bands: &[Self]) -> String {
+        let mut out = String::new();
+        for band in bands {
+            if !out.is_empty() {
+                out.push(' ');
+            }
+            let _ = write!(out, "{}:{}", band.frequency, band.gain);
+            if (band.q - DEFAULT_BAND_Q).abs() > 0.001 {
+                let _ = write!(out, ":{}", band.q);
+            }
+        }
+
+        out
+    }
+}
+
+/// The frequencies used by the default presets
+const DEFAULT_FREQUENCIES: [f32; 10] = [
+    31.0, 62.0, 125.0, 250.0, 500.0, 1000.0, 2000.0, 4000.0, 8000.0, 16000.0,
+];
+
+fn preset(name: &str, gains: [f32; 10]) -> EqualizerPreset {
+    EqualizerPreset {
+        name: name.to_string(),
+        bands: DEFAULT_FREQUENCIES
+            .iter()
+            .zip(gains)
+            .map(|(frequency, gain)| EqualizerBand::new(*frequency, gain))
+            .collect(),
+    }
+}
+
+fn default_presets() -> Vec<EqualizerPreset> {
+    Vec::from([
+        preset("flat", [0.0; 10]),
+        preset("bass", [6.0, 5.0, 4.0, 2.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0]),
+        preset("treble", [0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 3.0, 5.0, 6.0]),
+        preset(
+            "vocal",
+            [-2.0, -2.0, -1.0, 0.0, 2.0, 3.0, 3.0, 2.0, 0.0, -1.0],
+        ),
+        preset(
+            "loudness",
+            [5.0, 4.0, 2.0, 0.0, -1.0, 0.0, 0.0, 1.0, 3.0, 4.0],
+        ),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EqualizerBand, EqualizerSettings, DEFAULT_BAND_Q};
+
+    #[test]
+    fn should_parse_and_format_bands() {
+        let bands = EqualizerBand::parse_list("60:3  1000:-2.5 8000:1:0.7").unwrap();
+        assert_eq!(
+            bands,
+            vec![
+                EqualizerBand::new(60.0, 3.0),
+                EqualizerBand::new(1000.0, -2.5),
+                EqualizerBand {
+                    frequency: 8000.0,
+                    gain: 1.0,
+                    q: 0.7
+                },
+            ]
+        );
+        assert_eq!(
+            EqualizerBand::format_list(&bands),
+            "60:3 1000:-2.5 8000:1:0.7"
+        );
+
+        assert!(EqualizerBand::parse_list("").unwrap().is_empty());
+        assert!(EqualizerBand::parse_list("60").is_err());
+        assert!(EqualizerBand::parse_list("60:3:1:1").is_err());
+        assert!(EqualizerBand::parse_list("0:3").is_err());
+        assert!(EqualizerBand::parse_list("60:30").is_err());
+        assert!(EqualizerBand::parse_list("60:3:0").is_err());
+    }
+
+    #[test]
+    fn should_get_active_bands() {
+        let mut settings = EqualizerSettings::default();
+        assert!(settings.active_bands().is_none());
+
+        settings.preset = Some("bass".to_string());
+        let bands = settings.active_bands().unwrap();
+        assert_eq!(bands.len(), 10);
+        assert!((bands[0].q - DEFAULT_BAND_Q).abs() < f32::EPSILON);
+
+        settings.preset = Some("does not exist".to_string());
+        assert!(settings.active_bands().is_none());
+    }
+
+    #[test]
+    fn should_default_band_q() {
+        let band: EqualizerBand = toml::from_str("frequency = 60\ngain = 3").unwrap();
+        assert_eq!(band, EqualizerBand::new(60.0, 3.0));
+    }
+}
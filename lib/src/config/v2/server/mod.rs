@@ -4,12 +4,16 @@ use std::{
     path::PathBuf,
 };
 
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 
 use crate::track::MediaType;
 
+use self::equalizer::EqualizerSettings;
+
 /// Extra things necessary for a config file, like wrappers for versioning
 pub mod config_extra;
+/// Equalizer bands and presets
+pub mod equalizer;
 
 pub type MusicDirsOwned = Vec<PathBuf>;
 
@@ -33,6 +37,10 @@ pub struct PodcastSettings {
     pub max_download_retries: u8,
     /// Directory for downloaded Podcasts
     pub download_dir: PathBuf,
+    /// Max size of the on-disk cache for streamed episodes in MiB, `0` for no limit
+    ///
+    /// The least recently used episodes are removed first.
+    pub stream_cache_max_mib: u64,
 }
 
 /// Get the default podcast dir, which uses OS-specific paths, or home/Music/podcast
@@ -52,6 +60,7 @@ impl Default for PodcastSettings {
             concurrent_downloads_max: NonZeroU8::new(3).unwrap(),
             max_download_retries: 3,
             download_dir: default_podcast_dir(),
+            stream_cache_max_mib: 1024,
         }
     }
 }
@@ -237,6 +246,12 @@ pub struct PlayerSettings {
     pub library_scan_depth: ScanDepth,
     /// Set if the position should be remembered for tracks
     pub remember_position: RememberLastPosition,
+    /// How much of a track, in percent, needs to be played before it counts as played in the library statistics
+    ///
+    /// Skipping a track before that counts it as skipped instead.
+    /// Values above 100 are clamped to 100.
+    #[serde(deserialize_with = "deserialize_percent")]
+    pub play_count_threshold: u8,
 
     /// Playlist loop mode
     pub loop_mode: LoopMode,
@@ -247,8 +262,34 @@ pub struct PlayerSettings {
     /// speed / 10 = actual speed (float but not floats)
     // the number should never be 0, because that would effectively be paused forever
     pub speed: i32,
+    /// How to change the speed, and whether to keep the pitch while doing so
+    ///
+    /// Currently only supported by the rusty backend
+    pub time_stretch: TimeStretchMode,
     /// Enable gapless decoding & prefetching the next track
     pub gapless: bool,
+    /// Which replay gain values to apply to the volume, if any
+    ///
+    /// Currently only supported by the rusty backend
+    pub replaygain: ReplayGainMode,
+    /// Length of the crossfade between tracks in milliseconds, `0` to disable
+    ///
+    /// Only used if `gapless` is enabled and currently only supported by the rusty backend.
+    /// Live radio and consecutive tracks of the same album are never crossfaded.
+    pub crossfade_ms: u32,
+    /// Equalizer bands and presets
+    ///
+    /// Currently only supported by the rusty backend
+    pub equalizer: EqualizerSettings,
+    /// Name of the audio output device, unset to use the system default
+    ///
+    /// Falls back to the system default if the device is not available.
+    /// Currently only supported by the rusty backend
+    pub output_device: Option<String>,
+    /// Where to send the audio to, a audio device or headless outputs for testing and recording
+    ///
+    /// Currently only supported by the rusty backend and only applied on server start
+    pub output: AudioOutput,
     /// How much to seek on a seek event
     pub seek_step: SeekStep,
 
@@ -276,12 +317,19 @@ impl Default for PlayerSettings {
             music_dirs: default_music_dirs(),
             library_scan_depth: ScanDepth::Limited(10),
             remember_position: RememberLastPosition::default(),
+            play_count_threshold: 50,
 
             loop_mode: LoopMode::default(),
             // rather use a lower value than a high so that ears dont get blown off
             volume: 30,
             speed: 10,
+            time_stretch: TimeStretchMode::default(),
             gapless: true,
+            replaygain: ReplayGainMode::default(),
+            crossfade_ms: 0,
+            equalizer: EqualizerSettings::default(),
+            output_device: None,
+            output: AudioOutput::default(),
             seek_step: SeekStep::default(),
 
             use_mediacontrols: true,
@@ -293,6 +341,61 @@ impl Default for PlayerSettings {
     }
 }
 
+/// Deserialize a percentage, clamping values above 100
+fn deserialize_percent<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u8, D::Error> {
+    Ok(u8::deserialize(deserializer)?.min(100))
+}
+
+/// Which replay gain values to use when playing a track
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReplayGainMode {
+    /// Dont apply any replay gain
+    #[default]
+    Off,
+    /// Use the track gain, falling back to the album gain
+    Track,
+    /// Use the album gain, falling back to the track gain
+    Album,
+}
+
+/// How to apply a speed other than `1.0`
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeStretchMode {
+    /// Resample the audio, which also changes the pitch
+    Off,
+    /// Keep the pitch with the built-in sonic algorithm, which works best for speech
+    #[default]
+    Sonic,
+    /// Keep the pitch with the higher-quality soundtouch library
+    ///
+    /// Requires the `rusty-soundtouch` feature, falls back to [`TimeStretchMode::Sonic`] with a warning otherwise
+    SoundTouch,
+}
+
+/// Where to send the audio to
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum AudioOutput {
+    /// Play on a audio device, see `output_device`
+    #[default]
+    Device,
+    /// Discard the audio, for running without a sound card
+    Null {
+        /// Process the audio as fast as possible instead of at real time
+        #[serde(default)]
+        fast: bool,
+    },
+    /// Write the audio to a 32-bit float WAV file, overwriting it if it exists
+    Wav {
+        path: PathBuf,
+        /// Process the audio as fast as possible instead of at real time
+        #[serde(default)]
+        fast: bool,
+    },
+}
+
 /// Playlist loop modes
 #[derive(Debug, Clone, Copy, Deserialize, Serialize, Default, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
@@ -307,6 +410,25 @@ pub enum LoopMode {
 }
 
 impl LoopMode {
+    /// Get a stable number representation, for example for use in grpc
+    pub fn as_u32(self) -> u32 {
+        match self {
+            Self::Single => 0,
+            Self::Playlist => 1,
+            Self::Random => 2,
+        }
+    }
+
+    /// Get the [`LoopMode`] from the number representation of [`LoopMode::as_u32`]
+    pub fn tryfrom_u32(mode: u32) -> Option<Self> {
+        Some(match mode {
+            0 => Self::Single,
+            1 => Self::Playlist,
+            2 => Self::Random,
+            _ => return None,
+        })
+    }
+
     pub fn display(self, display_symbol: bool) -> &'static str {
         if display_symbol {
             match self {
@@ -325,7 +447,7 @@ impl LoopMode {
 }
 
 /// Settings for the gRPC server (and potentially future ways to communicate)
-#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
 // for now, require that both port and ip are specified at once
 // #[serde(default)] // allow missing fields and fill them with the `..Self::default()` in this struct
 pub struct ComSettings {
@@ -333,6 +455,14 @@ pub struct ComSettings {
     pub port: u16,
     /// gRPC server interface / address
     pub address: IpAddr,
+    /// Unix socket path to use instead of `address` and `port`, only accessible by the owning user
+    ///
+    /// Only supported on unix platforms.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub socket_path: Option<PathBuf>,
+    /// Shared secret clients have to send, recommended when `address` is reachable from other machines
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth_token: Option<String>,
 }
 
 impl Default for ComSettings {
@@ -340,12 +470,14 @@ impl Default for ComSettings {
         Self {
             port: 50101,
             address: "::1".parse().unwrap(),
+            socket_path: None,
+            auth_token: None,
         }
     }
 }
 
-impl From<ComSettings> for SocketAddr {
-    fn from(value: ComSettings) -> Self {
+impl From<&ComSettings> for SocketAddr {
+    fn from(value: &ComSettings) -> Self {
         Self::new(value.address, value.port)
     }
 }
@@ -354,9 +486,9 @@ mod v1_interop {
     use std::{error::Error, fmt::Display, num::TryFromIntError};
 
     use super::{
-        ComSettings, LoopMode, NonZeroU32, NonZeroU8, PlayerSettings, PodcastSettings,
-        PositionYesNo, PositionYesNoLower, RememberLastPosition, ScanDepth, SeekStep,
-        ServerSettings,
+        AudioOutput, ComSettings, EqualizerSettings, LoopMode, NonZeroU32, NonZeroU8,
+        PlayerSettings, PodcastSettings, PositionYesNo, PositionYesNoLower, RememberLastPosition,
+        ReplayGainMode, ScanDepth, SeekStep, ServerSettings, TimeStretchMode,
     };
     use crate::config::v1;
 
@@ -430,6 +562,8 @@ mod v1_interop {
             let com_settings = ComSettings {
                 port: value.player_port,
                 address: value.player_interface,
+                socket_path: None,
+                auth_token: None,
             };
 
             let podcast_settings = PodcastSettings {
@@ -447,6 +581,7 @@ mod v1_interop {
                 })?,
                 max_download_retries: value.podcast_max_retries.clamp(0, u8::MAX as usize) as u8,
                 download_dir: value.podcast_dir,
+                stream_cache_max_mib: PodcastSettings::default().stream_cache_max_mib,
             };
 
             let player_settings = PlayerSettings {
@@ -455,10 +590,17 @@ mod v1_interop {
                 // library_scan_depth: ScanDepth::Limited(value.max_depth_cli),
                 library_scan_depth: ScanDepth::Limited(10),
                 remember_position: value.player_remember_last_played_position.into(),
+                play_count_threshold: 50,
                 loop_mode: value.player_loop_mode.into(),
                 volume: value.player_volume,
                 speed: value.player_speed,
+                time_stretch: TimeStretchMode::default(),
                 gapless: value.player_gapless,
+                replaygain: ReplayGainMode::default(),
+                crossfade_ms: 0,
+                equalizer: EqualizerSettings::default(),
+                output_device: None,
+                output: AudioOutput::default(),
                 seek_step: value.player_seek_step.into(),
 
                 use_mediacontrols: value.player_use_mpris,
@@ -517,7 +659,8 @@ mod v1_interop {
                 PodcastSettings {
                     concurrent_downloads_max: NonZeroU8::new(3).unwrap(),
                     max_download_retries: 3,
-                    download_dir: PathBuf::new()
+                    download_dir: PathBuf::new(),
+                    stream_cache_max_mib: 1024,
                 }
             );
 
@@ -525,7 +668,9 @@ mod v1_interop {
                 converted.com,
                 ComSettings {
                     port: 50101,
-                    address: "::1".parse().unwrap()
+                    address: "::1".parse().unwrap(),
+                    socket_path: None,
+                    auth_token: None,
                 }
             );
 
@@ -547,10 +692,17 @@ mod v1_interop {
                         music: PositionYesNo::Simple(PositionYesNoLower::No),
                         podcast: PositionYesNo::Simple(PositionYesNoLower::Yes),
                     },
+                    play_count_threshold: 50,
                     loop_mode: LoopMode::Random,
                     volume: 70,
                     speed: 10,
+                    time_stretch: TimeStretchMode::Sonic,
                     gapless: true,
+                    replaygain: ReplayGainMode::Off,
+                    crossfade_ms: 0,
+                    equalizer: EqualizerSettings::default(),
+                    output_device: None,
+                    output: AudioOutput::Device,
                     seek_step: SeekStep::Depends {
                         short_tracks: NonZeroU32::new(5).unwrap(),
                         long_tracks: NonZeroU32::new(30).unwrap(),
@@ -564,3 +716,77 @@ mod v1_interop {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::{AudioOutput, ComSettings, PlayerSettings, TimeStretchMode};
+
+    #[test]
+    fn should_parse_com_with_and_without_socket() {
+        let parsed: ComSettings = toml::from_str("port = 50101\naddress = \"::1\"").unwrap();
+        assert_eq!(parsed, ComSettings::default());
+
+        let parsed: ComSettings = toml::from_str(
+            "port = 50101\naddress = \"::1\"\nsocket_path = \"/run/user/1000/termusic.sock\"",
+        )
+        .unwrap();
+        assert_eq!(
+            parsed.socket_path,
+            Some(PathBuf::from("/run/user/1000/termusic.sock"))
+        );
+
+        // dont write the key at all if unset
+        assert!(!toml::to_string(&ComSettings::default())
+            .unwrap()
+            .contains("socket_path"));
+    }
+
+    #[test]
+    fn should_parse_audio_output() {
+        #[derive(Debug, serde::Deserialize, serde::Serialize, PartialEq)]
+        struct Wrapper {
+            output: AudioOutput,
+        }
+
+        let parsed: Wrapper = toml::from_str("output = { type = \"device\" }").unwrap();
+        assert_eq!(parsed.output, AudioOutput::Device);
+
+        let parsed: Wrapper =
+            toml::from_str("output = { type = \"wav\", path = \"/tmp/out.wav\" }").unwrap();
+        assert_eq!(
+            parsed.output,
+            AudioOutput::Wav {
+                path: PathBuf::from("/tmp/out.wav"),
+                fast: false
+            }
+        );
+
+        let output = Wrapper {
+            output: AudioOutput::Null { fast: true },
+        };
+        let serialized = toml::to_string(&output).unwrap();
+        assert_eq!(toml::from_str::<Wrapper>(&serialized).unwrap(), output);
+    }
+
+    #[test]
+    fn should_parse_time_stretch_mode() {
+        assert_eq!(
+            PlayerSettings::default().time_stretch,
+            TimeStretchMode::Sonic
+        );
+
+        let parsed: PlayerSettings = toml::from_str("time_stretch = \"soundtouch\"").unwrap();
+        assert_eq!(parsed.time_stretch, TimeStretchMode::SoundTouch);
+    }
+
+    #[test]
+    fn should_clamp_play_count_threshold() {
+        let parsed: PlayerSettings = toml::from_str("play_count_threshold = 75").unwrap();
+        assert_eq!(parsed.play_count_threshold, 75);
+
+        let parsed: PlayerSettings = toml::from_str("play_count_threshold = 150").unwrap();
+        assert_eq!(parsed.play_count_threshold, 100);
+    }
+}
@@ -78,6 +78,10 @@ pub struct Keys {
     pub podcast_keys: KeysPodcast,
     #[serde(rename = "adjust_cover_art")]
     pub move_cover_art_keys: KeysMoveCoverArt,
+    #[serde(rename = "radio_history")]
+    pub radio_history_keys: KeysRadioHistory,
+    #[serde(rename = "radio_stations")]
+    pub radio_station_keys: KeysRadioStations,
     #[serde(rename = "config")]
     pub config_keys: KeysConfigEditor,
 }
@@ -107,6 +111,8 @@ impl Default for Keys {
             database_keys: KeysDatabase::default(),
             podcast_keys: KeysPodcast::default(),
             move_cover_art_keys: KeysMoveCoverArt::default(),
+            radio_history_keys: KeysRadioHistory::default(),
+            radio_station_keys: KeysRadioStations::default(),
             config_keys: KeysConfigEditor::default(),
         }
     }
@@ -217,6 +223,22 @@ impl CheckConflict for Keys {
             conflicts.extend(new);
         }
         key_path.pop();
+        key_path.push("radio_history");
+        if let Err(new) = self
+            .radio_history_keys
+            .check_conflict(key_path, global_keys)
+        {
+            conflicts.extend(new);
+        }
+        key_path.pop();
+        key_path.push("radio_stations");
+        if let Err(new) = self
+            .radio_station_keys
+            .check_conflict(key_path, global_keys)
+        {
+            conflicts.extend(new);
+        }
+        key_path.pop();
 
         // -------------
         if !conflicts.is_empty() {
@@ -237,6 +259,8 @@ pub struct KeysSelectView {
     pub view_database: KeyBinding,
     /// Key to switch to the Podcast view
     pub view_podcasts: KeyBinding,
+    /// Key to switch to the Radio Stations view
+    pub view_radio: KeyBinding,
 
     /// Key to open the Config view
     pub open_config: KeyBinding,
@@ -250,6 +274,7 @@ impl Default for KeysSelectView {
             view_library: tuievents::Key::Char('1').into(),
             view_database: tuievents::Key::Char('2').into(),
             view_podcasts: tuievents::Key::Char('3').into(),
+            view_radio: tuievents::Key::Char('4').into(),
             open_config: tuievents::KeyEvent::new(
                 tuievents::Key::Char('C'),
                 tuievents::KeyModifiers::SHIFT,
@@ -270,6 +295,7 @@ impl CheckConflict for KeysSelectView {
             (&self.view_library, "view_library"),
             (&self.view_database, "view_database"),
             (&self.view_podcasts, "view_podcasts"),
+            (&self.view_radio, "view_radio"),
 
             (&self.open_config, "open_config"),
             (&self.open_help, "open_help")
@@ -663,7 +689,9 @@ pub struct KeysLibrary {
     pub search: KeyBinding,
     /// Key to open youtube search
     pub youtube_search: KeyBinding,
-    /// Key to open the tag editor on that node (only works for files)
+    /// Key to open the tag editor on that node, or the batch tag editor on a directory
+    ///
+    /// Also opens the batch tag editor on all tracks listed in the database view
     pub open_tag_editor: KeyBinding,
 }
 
@@ -779,6 +807,12 @@ pub struct KeysPlaylist {
     /// previously known as `cmus_lqueue`
     // NOTE: currently this can be somewhat broken sometimes, cause unknown
     pub add_random_album: KeyBinding,
+    /// Key to search the radio station directory and add a station to the playlist
+    pub search_radio: KeyBinding,
+    /// Key to rate the currently selected track one star higher
+    pub rating_up: KeyBinding,
+    /// Key to rate the currently selected track one star lower
+    pub rating_down: KeyBinding,
 }
 
 impl Default for KeysPlaylist {
@@ -810,6 +844,13 @@ impl Default for KeysPlaylist {
                 tuievents::KeyModifiers::SHIFT,
             )
             .into(),
+            search_radio: tuievents::KeyEvent::new(
+                tuievents::Key::Char('R'),
+                tuievents::KeyModifiers::SHIFT,
+            )
+            .into(),
+            rating_up: tuievents::Key::Char('>').into(),
+            rating_down: tuievents::Key::Char('<').into(),
         }
     }
 }
@@ -828,6 +869,9 @@ impl CheckConflict for KeysPlaylist {
 
             (&self.add_random_songs, "add_random_songs"),
             (&self.add_random_album, "add_random_album"),
+            (&self.search_radio, "search_radio"),
+            (&self.rating_up, "rating_up"),
+            (&self.rating_down, "rating_down"),
         }
     }
 
@@ -1172,6 +1216,10 @@ pub struct KeysDatabase {
     pub add_selected: KeyBinding,
     /// Add all tracks in the Database view "Tracks" section
     pub add_all: KeyBinding,
+    /// Rate the currently selected track one star higher
+    pub rating_up: KeyBinding,
+    /// Rate the currently selected track one star lower
+    pub rating_down: KeyBinding,
 }
 
 impl Default for KeysDatabase {
@@ -1183,6 +1231,8 @@ impl Default for KeysDatabase {
                 tuievents::KeyModifiers::SHIFT,
             )
             .into(),
+            rating_up: tuievents::Key::Char('>').into(),
+            rating_down: tuievents::Key::Char('<').into(),
         }
     }
 }
@@ -1191,6 +1241,148 @@ impl CheckConflict for KeysDatabase {
     fn iter(&self) -> impl Iterator<Item = (&KeyBinding, &'static str)> {
         once_chain! {
             (&self.add_all, "add_all"),
+            (&self.rating_up, "rating_up"),
+            (&self.rating_down, "rating_down"),
+        }
+    }
+
+    fn check_conflict(
+        &self,
+        key_path: &mut KeyPath,
+        global_keys: &mut KeyHashMapOwned,
+    ) -> Result<(), Vec<KeyConflictError>> {
+        let mut conflicts: Vec<KeyConflictError> = Vec::new();
+        let mut current_keys = KeyHashMap::new();
+
+        for (key, path) in self.iter() {
+            // check global first
+            if let Some(existing_path) = global_keys.get(key) {
+                conflicts.push(KeyConflictError {
+                    key_path_first: existing_path.to_string(),
+                    key_path_second: key_path.join_with_field(path),
+                    key: key.clone(),
+                });
+                continue;
+            }
+
+            if let Some(existing_path) = current_keys.get(key) {
+                conflicts.push(KeyConflictError {
+                    key_path_first: key_path.join_with_field(existing_path),
+                    key_path_second: key_path.join_with_field(path),
+                    key: key.clone(),
+                });
+                continue;
+            }
+
+            current_keys.insert(key, path);
+        }
+
+        if !conflicts.is_empty() {
+            return Err(conflicts);
+        }
+
+        Ok(())
+    }
+}
+
+/// Keys for the radio title history in the lyric view
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[serde(default)] // allow missing fields and fill them with the `..Self::default()` in this struct
+pub struct KeysRadioHistory {
+    /// Copy the selected title to the clipboard
+    pub copy_title: KeyBinding,
+    /// Save the selected title to the "heard on radio" list
+    pub save_title: KeyBinding,
+}
+
+impl Default for KeysRadioHistory {
+    fn default() -> Self {
+        Self {
+            copy_title: tuievents::Key::Char('y').into(),
+            save_title: tuievents::Key::Char('s').into(),
+        }
+    }
+}
+
+impl CheckConflict for KeysRadioHistory {
+    fn iter(&self) -> impl Iterator<Item = (&KeyBinding, &'static str)> {
+        once_chain! {
+            (&self.copy_title, "copy_title"),
+            (&self.save_title, "save_title"),
+        }
+    }
+
+    fn check_conflict(
+        &self,
+        key_path: &mut KeyPath,
+        global_keys: &mut KeyHashMapOwned,
+    ) -> Result<(), Vec<KeyConflictError>> {
+        let mut conflicts: Vec<KeyConflictError> = Vec::new();
+        let mut current_keys = KeyHashMap::new();
+
+        for (key, path) in self.iter() {
+            // check global first
+            if let Some(existing_path) = global_keys.get(key) {
+                conflicts.push(KeyConflictError {
+                    key_path_first: existing_path.to_string(),
+                    key_path_second: key_path.join_with_field(path),
+                    key: key.clone(),
+                });
+                continue;
+            }
+
+            if let Some(existing_path) = current_keys.get(key) {
+                conflicts.push(KeyConflictError {
+                    key_path_first: key_path.join_with_field(existing_path),
+                    key_path_second: key_path.join_with_field(path),
+                    key: key.clone(),
+                });
+                continue;
+            }
+
+            current_keys.insert(key, path);
+        }
+
+        if !conflicts.is_empty() {
+            return Err(conflicts);
+        }
+
+        Ok(())
+    }
+}
+
+/// Keys for the saved radio stations list
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[serde(default)] // allow missing fields and fill them with the `..Self::default()` in this struct
+pub struct KeysRadioStations {
+    /// Key to open the search for stations to save
+    pub search: KeyBinding,
+    /// Key to add a station by url
+    pub add_station: KeyBinding,
+    /// Key to edit the currently selected station
+    pub edit_station: KeyBinding,
+    /// Key to delete the currently selected station
+    pub delete_station: KeyBinding,
+}
+
+impl Default for KeysRadioStations {
+    fn default() -> Self {
+        Self {
+            search: tuievents::Key::Char('s').into(),
+            add_station: tuievents::Key::Char('a').into(),
+            edit_station: tuievents::Key::Char('e').into(),
+            delete_station: tuievents::Key::Char('x').into(),
+        }
+    }
+}
+
+impl CheckConflict for KeysRadioStations {
+    fn iter(&self) -> impl Iterator<Item = (&KeyBinding, &'static str)> {
+        once_chain! {
+            (&self.search, "search"),
+            (&self.add_station, "add_station"),
+            (&self.edit_station, "edit_station"),
+            (&self.delete_station, "delete_station"),
         }
     }
 
@@ -1824,7 +2016,8 @@ impl From<SupportedModifiers> for tuievents::KeyModifiers {
 mod v1_interop {
     use super::{
         tuievents, KeyBinding, Keys, KeysConfigEditor, KeysDatabase, KeysLibrary, KeysLyric,
-        KeysMoveCoverArt, KeysNavigation, KeysPlayer, KeysPlaylist, KeysPodcast, KeysSelectView,
+        KeysMoveCoverArt, KeysNavigation, KeysPlayer, KeysPlaylist, KeysPodcast, KeysRadioHistory,
+        KeysRadioStations, KeysSelectView,
     };
     use crate::config::v1;
 
@@ -1907,6 +2100,8 @@ mod v1_interop {
                     view_library: value.global_layout_treeview.into(),
                     view_database: value.global_layout_database.into(),
                     view_podcasts: value.global_layout_podcast.into(),
+                    // not available in v1
+                    view_radio: tuievents::Key::Char('4').into(),
                     open_config: value.global_config_open.into(),
                     open_help: value.global_help.into(),
                 },
@@ -1961,11 +2156,18 @@ mod v1_interop {
                     swap_down: value.playlist_swap_down.into(),
                     add_random_songs: value.playlist_add_random_tracks.into(),
                     add_random_album: value.playlist_add_random_album.into(),
+                    // not available in v1
+                    search_radio: KeysPlaylist::default().search_radio,
+                    rating_up: KeysPlaylist::default().rating_up,
+                    rating_down: KeysPlaylist::default().rating_down,
                 },
                 database_keys: KeysDatabase {
                     // this is weird, but the previous implementation used "global_right" as the loading key to not conflict
                     add_selected: value.global_right.into(),
                     add_all: value.database_add_all.into(),
+                    // not available in v1
+                    rating_up: KeysDatabase::default().rating_up,
+                    rating_down: KeysDatabase::default().rating_down,
                 },
                 podcast_keys: KeysPodcast {
                     search: value.podcast_search_add_feed.into(),
@@ -1987,6 +2189,10 @@ mod v1_interop {
                     decrease_size: value.global_xywh_zoom_out.into(),
                     toggle_hide: value.global_xywh_hide.into(),
                 },
+                // not available in v1
+                radio_history_keys: KeysRadioHistory::default(),
+                // not available in v1
+                radio_station_keys: KeysRadioStations::default(),
                 config_keys: KeysConfigEditor {
                     save: value.config_save.into(),
                 },
@@ -2010,6 +2216,7 @@ mod v1_interop {
                 view_library: tuievents::Key::Char('1').into(),
                 view_database: tuievents::Key::Char('2').into(),
                 view_podcasts: tuievents::Key::Char('3').into(),
+                view_radio: tuievents::Key::Char('4').into(),
                 open_config: tuievents::KeyEvent::new(
                     tuievents::Key::Char('C'),
                     tuievents::KeyModifiers::SHIFT,
@@ -2150,6 +2357,13 @@ mod v1_interop {
                     tuievents::KeyModifiers::SHIFT,
                 )
                 .into(),
+                search_radio: tuievents::KeyEvent::new(
+                    tuievents::Key::Char('R'),
+                    tuievents::KeyModifiers::SHIFT,
+                )
+                .into(),
+                rating_up: tuievents::Key::Char('>').into(),
+                rating_down: tuievents::Key::Char('<').into(),
             };
             assert_eq!(converted.playlist_keys, expected_playlist_keys);
 
@@ -2160,6 +2374,8 @@ mod v1_interop {
                     tuievents::KeyModifiers::SHIFT,
                 )
                 .into(),
+                rating_up: tuievents::Key::Char('>').into(),
+                rating_down: tuievents::Key::Char('<').into(),
             };
             assert_eq!(converted.database_keys, expected_database_keys);
 
@@ -2252,6 +2468,8 @@ mod v1_interop {
                 database_keys: expected_database_keys,
                 podcast_keys: expected_podcast_keys,
                 move_cover_art_keys: expected_move_cover_art_keys,
+                radio_history_keys: KeysRadioHistory::default(),
+                radio_station_keys: KeysRadioStations::default(),
                 config_keys: expected_config_editor_keys,
             };
 
@@ -36,7 +36,7 @@ impl TuiSettings {
         match self.com {
             MaybeComSettings::ComSettings(ref v) => {
                 // this could likely be avoided, but for simplicity this is set
-                self.com_resolved = Some(*v);
+                self.com_resolved = Some(v.clone());
                 return Ok(());
             }
             MaybeComSettings::Same => (),
@@ -61,12 +61,21 @@ impl TuiSettings {
     }
 }
 
+/// The rename template termusic always used before it was configurable
+pub const DEFAULT_RENAME_TEMPLATE: &str = "{artist}-{title}";
+
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default)] // allow missing fields and fill them with the `..Self::default()` in this struct
 pub struct BehaviorSettings {
     /// Stop / Exit the Server on TUI quit
     pub quit_server_on_exit: bool,
     /// Ask before exiting the TUI (popup)
     pub confirm_quit: bool,
+    /// Template to rename files by after saving their tags in the tag editor, empty to never rename
+    ///
+    /// The template is relative to the music directory the file is in and `/` creates directories,
+    /// see [`crate::track::Track::rename_by_template`] for the placeholders.
+    pub rename_on_save: String,
 }
 
 impl Default for BehaviorSettings {
@@ -74,6 +83,7 @@ impl Default for BehaviorSettings {
         Self {
             quit_server_on_exit: true,
             confirm_quit: true,
+            rename_on_save: DEFAULT_RENAME_TEMPLATE.to_string(),
         }
     }
 }
@@ -150,6 +160,8 @@ mod v1_interop {
                 behavior: BehaviorSettings {
                     quit_server_on_exit: value.kill_daemon_when_quit,
                     confirm_quit: value.enable_exit_confirmation,
+                    // not available in v1
+                    rename_on_save: BehaviorSettings::default().rename_on_save,
                 },
                 coverart: value.album_photo_xywh.into(),
                 theme,
@@ -171,7 +183,8 @@ mod v1_interop {
                 converted.behavior,
                 BehaviorSettings {
                     quit_server_on_exit: true,
-                    confirm_quit: true
+                    confirm_quit: true,
+                    rename_on_save: "{artist}-{title}".to_string()
                 }
             );
 
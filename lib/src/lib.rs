@@ -11,6 +11,7 @@ pub mod library_db;
 pub mod player;
 pub mod playlist;
 pub mod podcast;
+pub mod radio_browser;
 pub mod songtag;
 pub mod taskpool;
 pub mod track;
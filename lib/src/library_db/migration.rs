@@ -2,7 +2,7 @@ use anyhow::{bail, Context, Result};
 use rusqlite::Connection;
 
 /// The Current Database schema version this application is meant to run against
-pub(super) const DB_VERSION: u32 = 2;
+pub(super) const DB_VERSION: u32 = 6;
 /// The Lowest Database schema version this application supports migration up against
 ///
 /// Expection being "0" as that indicates a fresh database
@@ -28,8 +28,21 @@ fn set_user_version(conn: &Connection, version: u32) -> Result<u32> {
     Ok(version)
 }
 
+/// Apply the migration `sql` and set the `user_version` to `version` in one transaction,
+/// so that a failing statement does not leave a partially applied migration behind
+///
+/// Returns the passed version for re-use
+fn apply_migration(conn: &mut Connection, sql: &str, version: u32) -> Result<u32> {
+    let transaction = conn.transaction()?;
+    transaction.execute_batch(sql)?;
+    set_user_version(&transaction, version)?;
+    transaction.commit()?;
+
+    Ok(version)
+}
+
 /// Create / Migrate everything in the database, if necessary
-pub(super) fn migrate(conn: &Connection) -> Result<()> {
+pub(super) fn migrate(conn: &mut Connection) -> Result<()> {
     let mut user_version: u32 = get_user_version(conn)?;
 
     if user_version > DB_VERSION {
@@ -59,13 +72,32 @@ pub(super) fn migrate(conn: &Connection) -> Result<()> {
 ///
 /// Migrates from [`LOWEST_MIGRATEABLE_VERSION`] / `0` to [`DB_VERSION`]
 #[allow(unused_assignments)] // for future possible migrations
-fn apply_migrations(conn: &Connection, mut user_version: u32) -> Result<()> {
+fn apply_migrations(conn: &mut Connection, mut user_version: u32) -> Result<()> {
     // do all migrations in steps, this way everyone is in the same state and had the same things applied, even for new things
     if user_version == 0 {
         // Version 2 is the base version, so there are basically no migrations, only creations
-        conn.execute_batch(include_str!("./migrations/002.sql"))
+        user_version = apply_migration(conn, include_str!("./migrations/002.sql"), 2)
             .context("Database version 2 could not be created")?;
-        user_version = set_user_version(conn, 2)?;
+    }
+
+    if user_version == 2 {
+        user_version = apply_migration(conn, include_str!("./migrations/003.sql"), 3)
+            .context("Database version 3 could not be applied")?;
+    }
+
+    if user_version == 3 {
+        user_version = apply_migration(conn, include_str!("./migrations/004.sql"), 4)
+            .context("Database version 4 could not be applied")?;
+    }
+
+    if user_version == 4 {
+        user_version = apply_migration(conn, include_str!("./migrations/005.sql"), 5)
+            .context("Database version 5 could not be applied")?;
+    }
+
+    if user_version == 5 {
+        user_version = apply_migration(conn, include_str!("./migrations/006.sql"), 6)
+            .context("Database version 6 could not be applied")?;
     }
 
     Ok(())
@@ -79,20 +111,105 @@ mod tests {
 
     #[test]
     fn should_create_from_fresh() {
-        let conn = gen_database();
+        let mut conn = gen_database();
 
         assert_eq!(0, get_user_version(&conn).unwrap());
-        migrate(&conn).unwrap();
-        assert_eq!(2, get_user_version(&conn).unwrap());
-
-        let all_tracks: Vec<String> = {
-            let mut prep = conn.prepare("SELECT name FROM sqlite_schema WHERE type ='table' AND name NOT LIKE 'sqlite_%';").unwrap();
-            prep.query_map([], |r| r.get(0))
-                .unwrap()
-                .flatten()
-                .collect()
-        };
-
-        assert_eq!(&all_tracks, &["tracks"]);
+        migrate(&mut conn).unwrap();
+        assert_eq!(6, get_user_version(&conn).unwrap());
+
+        assert_eq!(&get_tables(&conn), &["tracks", "radio_stations"]);
+    }
+
+    #[test]
+    fn should_migrate_from_2() {
+        let mut conn = gen_database();
+
+        conn.execute_batch(include_str!("./migrations/002.sql"))
+            .unwrap();
+        set_user_version(&conn, 2).unwrap();
+        conn.execute(
+            "INSERT INTO tracks (file, name) VALUES ('/music/a.mp3', 'a.mp3')",
+            [],
+        )
+        .unwrap();
+
+        migrate(&mut conn).unwrap();
+        assert_eq!(6, get_user_version(&conn).unwrap());
+
+        assert_eq!(&get_tables(&conn), &["tracks", "radio_stations"]);
+        let tracks: u32 = conn
+            .query_row("SELECT COUNT(*) FROM tracks", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(tracks, 1);
+    }
+
+    #[test]
+    fn should_migrate_from_3() {
+        let mut conn = gen_database();
+
+        conn.execute_batch(include_str!("./migrations/002.sql"))
+            .unwrap();
+        conn.execute_batch(include_str!("./migrations/003.sql"))
+            .unwrap();
+        set_user_version(&conn, 3).unwrap();
+        conn.execute(
+            "INSERT INTO tracks (file, name) VALUES ('/music/a.mp3', 'a.mp3')",
+            [],
+        )
+        .unwrap();
+
+        migrate(&mut conn).unwrap();
+        assert_eq!(6, get_user_version(&conn).unwrap());
+
+        let (play_count, skip_count, last_played, rating): (u32, u32, Option<u64>, u8) = conn
+            .query_row(
+                "SELECT play_count, skip_count, last_played, rating FROM tracks",
+                [],
+                |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?)),
+            )
+            .unwrap();
+        assert_eq!(
+            (play_count, skip_count, last_played, rating),
+            (0, 0, None, 0)
+        );
+    }
+
+    #[test]
+    fn should_not_partially_apply_migration() {
+        let mut conn = gen_database();
+
+        conn.execute_batch(include_str!("./migrations/002.sql"))
+            .unwrap();
+        conn.execute_batch(include_str!("./migrations/003.sql"))
+            .unwrap();
+        // make the second statement of migration 4 fail
+        conn.execute_batch("ALTER TABLE tracks ADD COLUMN skip_count INTEGER;")
+            .unwrap();
+        set_user_version(&conn, 3).unwrap();
+
+        assert!(migrate(&mut conn).is_err());
+        assert_eq!(3, get_user_version(&conn).unwrap());
+
+        let play_count_columns: u32 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('tracks') WHERE name = 'play_count'",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        assert_eq!(play_count_columns, 0);
+    }
+
+    /// Get the names of all non-internal tables
+    fn get_tables(conn: &Connection) -> Vec<String> {
+        let mut prep = conn
+            .prepare(
+                "SELECT name FROM sqlite_schema WHERE type ='table' AND name NOT LIKE 'sqlite_%';",
+            )
+            .unwrap();
+        prep.query_map([], |r| r.get(0))
+            .unwrap()
+            .flatten()
+            .collect()
     }
 }
@@ -0,0 +1,8 @@
+CREATE TABLE IF NOT EXISTS radio_stations(
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL,
+    url TEXT NOT NULL UNIQUE,
+    genre TEXT NOT NULL,
+    favicon TEXT NOT NULL,
+    last_played INTEGER
+);
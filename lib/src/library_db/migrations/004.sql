@@ -0,0 +1,3 @@
+ALTER TABLE tracks ADD COLUMN play_count INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE tracks ADD COLUMN skip_count INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE tracks ADD COLUMN last_played INTEGER;
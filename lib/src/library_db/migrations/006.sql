@@ -0,0 +1,9 @@
+ALTER TABLE tracks ADD COLUMN album_artist TEXT;
+ALTER TABLE tracks ADD COLUMN composer TEXT;
+ALTER TABLE tracks ADD COLUMN year INTEGER;
+ALTER TABLE tracks ADD COLUMN track_number INTEGER;
+ALTER TABLE tracks ADD COLUMN disc_number INTEGER;
+ALTER TABLE tracks ADD COLUMN performer TEXT;
+ALTER TABLE tracks ADD COLUMN comment TEXT;
+-- force all tracks to be read again on the next scan to fill the new columns
+UPDATE tracks SET last_modified = '0';
@@ -28,14 +28,17 @@ use crate::utils::{filetype_supported, get_app_config_path, get_pin_yin};
 use anyhow::Context;
 use parking_lot::Mutex;
 use rusqlite::{params, Connection, Error, Result};
+use std::cmp::Reverse;
 use std::path::Path;
 use std::sync::Arc;
-use std::time::{Duration, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use track_db::TrackDBInsertable;
 
 mod migration;
+mod radio_db;
 mod track_db;
 
+pub use radio_db::{RadioStationDB, RadioStationDBInsertable};
 pub use track_db::{const_unknown, Indexable, TrackDB};
 
 pub struct DataBase {
@@ -52,6 +55,29 @@ pub enum SearchCriteria {
     Genre,
     Directory,
     Playlist,
+
+    /// Tracks which were played at least once, most often played first
+    MostPlayed,
+    /// Tracks which were played at least once, last played first
+    RecentlyPlayed,
+    /// Tracks which were never played
+    NeverPlayed,
+    /// Tracks rated with at least [`FAVOURITE_RATING`] stars, best rated first
+    Favourites,
+}
+
+/// The minimal rating in stars for a track to be listed in [`SearchCriteria::Favourites`]
+pub const FAVOURITE_RATING: u8 = 4;
+
+impl SearchCriteria {
+    /// Whether this criteria is based on the play statistics or ratings instead of a column value
+    #[must_use]
+    pub fn is_statistic(self) -> bool {
+        matches!(
+            self,
+            Self::MostPlayed | Self::RecentlyPlayed | Self::NeverPlayed | Self::Favourites
+        )
+    }
 }
 
 impl From<usize> for SearchCriteria {
@@ -61,6 +87,10 @@ impl From<usize> for SearchCriteria {
             2 => Self::Genre,
             3 => Self::Directory,
             4 => Self::Playlist,
+            5 => Self::MostPlayed,
+            6 => Self::RecentlyPlayed,
+            7 => Self::NeverPlayed,
+            8 => Self::Favourites,
             /* 0 | */ _ => Self::Artist,
         }
     }
@@ -74,6 +104,10 @@ impl std::fmt::Display for SearchCriteria {
             Self::Genre => write!(f, "genre"),
             Self::Directory => write!(f, "directory"),
             Self::Playlist => write!(f, "playlist"),
+            Self::MostPlayed => write!(f, "most played"),
+            Self::RecentlyPlayed => write!(f, "recently played"),
+            Self::NeverPlayed => write!(f, "never played"),
+            Self::Favourites => write!(f, "favourites"),
         }
     }
 }
@@ -86,9 +120,9 @@ impl DataBase {
     pub fn new(config: &ServerOverlay) -> anyhow::Result<Self> {
         let mut db_path = get_app_config_path().context("failed to get app configuration path")?;
         db_path.push("library.db");
-        let conn = Connection::open(db_path).context("open/create database")?;
+        let mut conn = Connection::open(db_path).context("open/create database")?;
 
-        migration::migrate(&conn).context("Database creation / migration")?;
+        migration::migrate(&mut conn).context("Database creation / migration")?;
 
         let max_depth = config.get_library_scan_depth();
 
@@ -96,13 +130,16 @@ impl DataBase {
         Ok(Self { conn, max_depth })
     }
 
-    /// Insert multiple tracks into the database
+    /// Insert multiple tracks into the database, updating tracks which are already stored
     fn add_records(conn: &Arc<Mutex<Connection>>, tracks: Vec<Track>) -> Result<()> {
         let mut conn = conn.lock();
         let tx = conn.transaction()?;
 
         for track in tracks {
-            TrackDBInsertable::from(&track).insert_track(&tx)?;
+            let insertable = TrackDBInsertable::from(&track);
+            if insertable.update_track(&tx)? == 0 {
+                insertable.insert_track(&tx)?;
+            }
         }
 
         tx.commit()?;
@@ -237,11 +274,17 @@ impl DataBase {
     }
 
     /// Get Tracks by [`SearchCriteria`]
+    ///
+    /// `criteria_val` is ignored for [statistic criterias](SearchCriteria::is_statistic)
     pub fn get_record_by_criteria(
         &mut self,
         criteria_val: &str,
         criteria: &SearchCriteria,
     ) -> Result<Vec<TrackDB>> {
+        if criteria.is_statistic() {
+            return self.get_record_by_statistic(*criteria);
+        }
+
         let search_str = format!("SELECT * FROM tracks WHERE {criteria} = ?");
         let conn = self.conn.lock();
         let mut stmt = conn.prepare(&search_str)?;
@@ -256,12 +299,59 @@ impl DataBase {
         // error!("criteria: {}", criteria);
         // error!("vec: {:?}", vec_records);
 
-        vec_records.sort_by_cached_key(|k| get_pin_yin(&k.name));
+        if *criteria == SearchCriteria::Album {
+            // keep the order of the release, tracks without numbers are sorted by name
+            vec_records
+                .sort_by_cached_key(|k| (k.disc_number, k.track_number, get_pin_yin(&k.name)));
+        } else {
+            vec_records.sort_by_cached_key(|k| get_pin_yin(&k.name));
+        }
+        Ok(vec_records)
+    }
+
+    /// Get Tracks by a [statistic criteria](SearchCriteria::is_statistic), in the order the criteria defines
+    fn get_record_by_statistic(&mut self, criteria: SearchCriteria) -> Result<Vec<TrackDB>> {
+        let search_str = match criteria {
+            SearchCriteria::MostPlayed => {
+                "SELECT * FROM tracks WHERE play_count > 0 ORDER BY play_count DESC, last_played DESC".to_string()
+            }
+            SearchCriteria::RecentlyPlayed => {
+                "SELECT * FROM tracks WHERE last_played IS NOT NULL ORDER BY last_played DESC".to_string()
+            }
+            SearchCriteria::Favourites => {
+                format!("SELECT * FROM tracks WHERE rating >= {FAVOURITE_RATING}")
+            }
+            _ => "SELECT * FROM tracks WHERE play_count = 0".to_string(),
+        };
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(&search_str)?;
+
+        let mut vec_records: Vec<TrackDB> = stmt
+            .query_map([], TrackDB::try_from_row_named)?
+            .flatten()
+            .collect();
+
+        // the other statistics are already ordered by the query
+        match criteria {
+            SearchCriteria::NeverPlayed => {
+                vec_records.sort_by_cached_key(|k| get_pin_yin(&k.name));
+            }
+            SearchCriteria::Favourites => {
+                vec_records.sort_by_cached_key(|k| (Reverse(k.rating), get_pin_yin(&k.name)));
+            }
+            _ => (),
+        }
         Ok(vec_records)
     }
 
     /// Get a list of available distinct [`SearchCriteria`] (ie get Artist names deduplicated)
+    ///
+    /// [Statistic criterias](SearchCriteria::is_statistic) only have one value, the criteria itself
     pub fn get_criterias(&mut self, criteria: &SearchCriteria) -> Result<Vec<String>> {
+        if criteria.is_statistic() {
+            return Ok(vec![criteria.to_string()]);
+        }
+
         let search_str = format!("SELECT DISTINCT {criteria} FROM tracks");
         let conn = self.conn.lock();
         let mut stmt = conn.prepare(&search_str)?;
@@ -309,6 +399,60 @@ impl DataBase {
         Ok(())
     }
 
+    /// Count a play of the track with the full file path `file` and set its `last_played` time
+    pub fn add_track_played(&mut self, file: &str, time: SystemTime) -> Result<()> {
+        let last_played = time
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let conn = self.conn.lock();
+        conn.execute(
+            "UPDATE tracks SET play_count = play_count + 1, last_played = ?1 WHERE file = ?2",
+            params![last_played, file],
+        )?;
+        Ok(())
+    }
+
+    /// Set the rating in stars of the track with the full file path `file`
+    pub fn set_track_rating(&mut self, file: &str, rating: u8) -> Result<()> {
+        let conn = self.conn.lock();
+        conn.execute(
+            "UPDATE tracks SET rating = ?1 WHERE file = ?2",
+            params![rating, file],
+        )?;
+        Ok(())
+    }
+
+    /// Move the record of the track at the full file path `old_file` to the current file of `track` and update its metadata
+    ///
+    /// Play statistics and the last position are kept, the track is inserted if there is no record yet.
+    pub fn rename_record(&mut self, old_file: &str, track: &Track) -> Result<()> {
+        let insertable = TrackDBInsertable::from(track);
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction()?;
+
+        tx.execute(
+            "UPDATE tracks SET file = ?1 WHERE file = ?2",
+            params![insertable.file, old_file],
+        )?;
+        if insertable.update_track(&tx)? == 0 {
+            insertable.insert_track(&tx)?;
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Count a skip of the track with the full file path `file`
+    pub fn add_track_skipped(&mut self, file: &str) -> Result<()> {
+        let conn = self.conn.lock();
+        conn.execute(
+            "UPDATE tracks SET skip_count = skip_count + 1 WHERE file = ?1",
+            params![file],
+        )?;
+        Ok(())
+    }
+
     /// Get a Track by the given full file path
     pub fn get_record_by_path(&mut self, file_path: &str) -> Result<TrackDB> {
         let search_str = "SELECT * FROM tracks WHERE file = ?";
@@ -326,14 +470,228 @@ impl DataBase {
 
         Err(Error::QueryReturnedNoRows)
     }
+
+    /// Get all saved radio stations, sorted by name
+    pub fn get_radio_stations(&mut self) -> Result<Vec<RadioStationDB>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare("SELECT * FROM radio_stations")?;
+        let mut vec: Vec<RadioStationDB> = stmt
+            .query_map([], RadioStationDB::try_from_row_named)?
+            .flatten()
+            .collect();
+
+        vec.sort_by_cached_key(|k| get_pin_yin(&k.name));
+        Ok(vec)
+    }
+
+    /// Get the saved radio station with the stream `url`, if any
+    pub fn get_radio_station_by_url(&mut self, url: &str) -> Result<Option<RadioStationDB>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare("SELECT * FROM radio_stations WHERE url = ?")?;
+        let mut rows = stmt.query_map([url], RadioStationDB::try_from_row_named)?;
+
+        rows.next().transpose()
+    }
+
+    /// Save a radio station, updates the existing station if the url is already saved
+    pub fn add_radio_station(&mut self, station: &RadioStationDBInsertable<'_>) -> Result<()> {
+        let conn = self.conn.lock();
+        station.insert_station(&conn)?;
+        Ok(())
+    }
+
+    /// Overwrite the radio station with `id`
+    pub fn update_radio_station(
+        &mut self,
+        id: u64,
+        station: &RadioStationDBInsertable<'_>,
+    ) -> Result<()> {
+        let conn = self.conn.lock();
+        station.update_station(&conn, id)?;
+        Ok(())
+    }
+
+    /// Delete the radio station with `id`
+    pub fn delete_radio_station(&mut self, id: u64) -> Result<()> {
+        let conn = self.conn.lock();
+        conn.execute("DELETE FROM radio_stations WHERE id = ?", params![id])?;
+        Ok(())
+    }
+
+    /// Set the `last_played` time of the saved radio station with `url`
+    ///
+    /// Returns whether a saved station matched the url
+    pub fn set_radio_station_last_played(&mut self, url: &str, time: SystemTime) -> Result<bool> {
+        let last_played = time
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let conn = self.conn.lock();
+        let changed = conn.execute(
+            "UPDATE radio_stations SET last_played = ?1 WHERE url = ?2",
+            params![last_played, url],
+        )?;
+        Ok(changed > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use pretty_assertions::assert_eq;
+    use rusqlite::params;
+
+    use super::test_utils::gen_library;
+    use super::{DataBase, SearchCriteria};
+    use crate::player::playlist_helpers::PlaylistTrackSource;
+    use crate::track::Track;
+
+    fn files(db: &mut DataBase, criteria: SearchCriteria) -> Vec<String> {
+        db.get_record_by_criteria("", &criteria)
+            .unwrap()
+            .into_iter()
+            .map(|v| v.file)
+            .collect()
+    }
+
+    #[test]
+    fn should_count_plays_and_skips() {
+        let mut db = gen_library(&["a", "b"]);
+        let time = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+
+        db.add_track_played("a", time).unwrap();
+        db.add_track_played("a", time).unwrap();
+        db.add_track_skipped("a").unwrap();
+        db.add_track_skipped("b").unwrap();
+
+        let a = db.get_record_by_path("a").unwrap();
+        assert_eq!((a.play_count, a.skip_count), (2, 1));
+        assert_eq!(a.last_played, Some(time));
+        let b = db.get_record_by_path("b").unwrap();
+        assert_eq!((b.play_count, b.skip_count), (0, 1));
+        assert_eq!(b.last_played, None);
+    }
+
+    #[test]
+    fn should_get_records_by_statistic() {
+        let mut db = gen_library(&["c", "a", "b", "d"]);
+        let earlier = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let later = earlier + Duration::from_secs(60);
+
+        db.add_track_played("a", earlier).unwrap();
+        db.add_track_played("a", earlier).unwrap();
+        db.add_track_played("b", later).unwrap();
+
+        assert_eq!(files(&mut db, SearchCriteria::MostPlayed), ["a", "b"]);
+        assert_eq!(files(&mut db, SearchCriteria::RecentlyPlayed), ["b", "a"]);
+        assert_eq!(files(&mut db, SearchCriteria::NeverPlayed), ["c", "d"]);
+
+        assert_eq!(
+            db.get_criterias(&SearchCriteria::MostPlayed).unwrap(),
+            ["most played"]
+        );
+    }
+
+    #[test]
+    fn should_update_existing_records() {
+        let mut db = gen_library(&[]);
+        let track = Track::new_from_source(&PlaylistTrackSource::Path("/music/a.mp3".to_string()));
+
+        DataBase::add_records(&db.conn, vec![track.clone()]).unwrap();
+        db.add_track_played("/music/a.mp3", UNIX_EPOCH).unwrap();
+        DataBase::add_records(&db.conn, vec![track]).unwrap();
+
+        let records = db.get_all_records().unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].play_count, 1);
+    }
+
+    #[test]
+    fn should_rename_records() {
+        let mut db = gen_library(&["/music/a.mp3"]);
+        db.add_track_played("/music/a.mp3", UNIX_EPOCH).unwrap();
+
+        let track = Track::new_from_source(&PlaylistTrackSource::Path(
+            "/music/Artist/b.mp3".to_string(),
+        ));
+        db.rename_record("/music/a.mp3", &track).unwrap();
+
+        let records = db.get_all_records().unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].file, "/music/Artist/b.mp3");
+        assert_eq!(records[0].name, "b.mp3");
+        assert_eq!(records[0].play_count, 1);
+    }
+
+    #[test]
+    fn should_sort_albums_by_disc_and_track() {
+        let mut db = gen_library(&["a", "b", "c", "d"]);
+        {
+            let conn = db.conn.lock();
+            for (file, disc, track) in [("a", 2, 1), ("b", 1, 2), ("c", 1, 1), ("d", 1, 10)] {
+                conn.execute(
+                    "UPDATE tracks SET album = 'x', disc_number = ?1, track_number = ?2 WHERE file = ?3",
+                    params![disc, track, file],
+                )
+                .unwrap();
+            }
+        }
+
+        let files: Vec<String> = db
+            .get_record_by_criteria("x", &SearchCriteria::Album)
+            .unwrap()
+            .into_iter()
+            .map(|v| v.file)
+            .collect();
+        assert_eq!(files, ["c", "b", "d", "a"]);
+    }
+
+    #[test]
+    fn should_get_favourites() {
+        let mut db = gen_library(&["c", "a", "b", "d"]);
+
+        db.set_track_rating("a", 4).unwrap();
+        db.set_track_rating("b", 5).unwrap();
+        db.set_track_rating("c", 4).unwrap();
+        db.set_track_rating("d", 3).unwrap();
+
+        assert_eq!(db.get_record_by_path("b").unwrap().rating, 5);
+        assert_eq!(files(&mut db, SearchCriteria::Favourites), ["b", "a", "c"]);
+    }
 }
 
 #[cfg(test)]
 mod test_utils {
+    use std::sync::Arc;
+
+    use parking_lot::Mutex;
     use rusqlite::Connection;
 
+    use super::{migration, DataBase};
+    use crate::config::v2::server::ScanDepth;
+
     /// Open a new In-Memory sqlite database
     pub fn gen_database() -> Connection {
         Connection::open_in_memory().expect("open db failed")
     }
+
+    /// Open a new In-Memory library with the current schema, containing empty tracks for the given `files`
+    pub fn gen_library(files: &[&str]) -> DataBase {
+        let mut conn = gen_database();
+        migration::migrate(&mut conn).unwrap();
+        for file in files {
+            conn.execute(
+                "INSERT INTO tracks (artist, title, album, genre, file, duration, name, ext, directory, last_modified, last_position)
+                VALUES ('', '', '', '', ?1, 0, ?1, '', '', '0', 0)",
+                [file],
+            )
+            .unwrap();
+        }
+
+        DataBase {
+            conn: Arc::new(Mutex::new(conn)),
+            max_depth: ScanDepth::Limited(1),
+        }
+    }
 }
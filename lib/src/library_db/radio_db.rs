@@ -0,0 +1,177 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use chrono::{DateTime, Local};
+use rusqlite::{named_params, Connection, Row};
+
+/// A saved radio station in the database
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RadioStationDB {
+    pub id: u64,
+    pub name: String,
+    /// The stream url, unique across all stations
+    pub url: String,
+    pub genre: String,
+    /// Url of the station icon, may be empty
+    pub favicon: String,
+    /// The last time the station was started, `None` if it was never played
+    pub last_played: Option<SystemTime>,
+}
+
+impl RadioStationDB {
+    /// Try to convert a given row to a [`RadioStationDB`] instance, using column names to resolve the values
+    pub fn try_from_row_named(row: &Row<'_>) -> Result<Self, rusqlite::Error> {
+        // NOTE: all the names in "get" below are the *column names* as defined in migrations/003.sql#table_radio_stations (pseudo link)
+        let last_played: Option<u64> = row.get("last_played")?;
+        Ok(RadioStationDB {
+            id: row.get("id")?,
+            name: row.get("name")?,
+            url: row.get("url")?,
+            genre: row.get("genre")?,
+            favicon: row.get("favicon")?,
+            last_played: last_played.map(|v| UNIX_EPOCH + Duration::from_secs(v)),
+        })
+    }
+
+    /// Format the last played date in the local timezone, like `2024-01-31`
+    pub fn last_played_formatted(&self) -> Option<String> {
+        self.last_played
+            .map(|v| DateTime::<Local>::from(v).format("%Y-%m-%d").to_string())
+    }
+}
+
+/// A struct representing a [`RadioStationDB`] to be inserted or updated
+///
+/// This is required as `id` is generated and `last_played` is managed by the database
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RadioStationDBInsertable<'a> {
+    pub name: &'a str,
+    pub url: &'a str,
+    pub genre: &'a str,
+    pub favicon: &'a str,
+}
+
+impl RadioStationDBInsertable<'_> {
+    /// Insert the current [`RadioStationDBInsertable`] into the `radio_stations` table,
+    /// or update the existing station with the same url
+    #[inline]
+    pub fn insert_station(&self, con: &Connection) -> Result<usize, rusqlite::Error> {
+        con.execute(
+            "INSERT INTO radio_stations (name, url, genre, favicon)
+            values (:name, :url, :genre, :favicon)
+            ON CONFLICT(url) DO UPDATE SET name = excluded.name, genre = excluded.genre, favicon = excluded.favicon",
+            named_params![
+                ":name": &self.name,
+                ":url": &self.url,
+                ":genre": &self.genre,
+                ":favicon": &self.favicon,
+            ],
+        )
+    }
+
+    /// Overwrite the station with `id` with the current [`RadioStationDBInsertable`]
+    #[inline]
+    pub fn update_station(&self, con: &Connection, id: u64) -> Result<usize, rusqlite::Error> {
+        con.execute(
+            "UPDATE radio_stations SET name = :name, url = :url, genre = :genre, favicon = :favicon WHERE id = :id",
+            named_params![
+                ":name": &self.name,
+                ":url": &self.url,
+                ":genre": &self.genre,
+                ":favicon": &self.favicon,
+                ":id": id,
+            ],
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use pretty_assertions::assert_eq;
+
+    use super::super::test_utils::gen_library;
+    use super::RadioStationDBInsertable;
+
+    fn station<'a>(name: &'a str, url: &'a str) -> RadioStationDBInsertable<'a> {
+        RadioStationDBInsertable {
+            name,
+            url,
+            genre: "jazz",
+            favicon: "",
+        }
+    }
+
+    #[test]
+    fn should_add_and_list_stations() {
+        let mut db = gen_library(&[]);
+
+        db.add_radio_station(&station("b station", "http://b"))
+            .unwrap();
+        db.add_radio_station(&station("A Station", "http://a"))
+            .unwrap();
+        // same url updates the existing station
+        db.add_radio_station(&station("B Station", "http://b"))
+            .unwrap();
+
+        let stations = db.get_radio_stations().unwrap();
+        let names: Vec<_> = stations.iter().map(|v| v.name.as_str()).collect();
+        assert_eq!(names, ["A Station", "B Station"]);
+        assert_eq!(stations[0].genre, "jazz");
+        assert_eq!(stations[0].last_played, None);
+    }
+
+    #[test]
+    fn should_get_station_by_url() {
+        let mut db = gen_library(&[]);
+
+        db.add_radio_station(&station("A", "http://a")).unwrap();
+
+        assert_eq!(
+            db.get_radio_station_by_url("http://a")
+                .unwrap()
+                .map(|v| v.name),
+            Some("A".to_string())
+        );
+        assert_eq!(db.get_radio_station_by_url("http://b").unwrap(), None);
+    }
+
+    #[test]
+    fn should_update_and_delete_stations() {
+        let mut db = gen_library(&[]);
+
+        db.add_radio_station(&station("A", "http://a")).unwrap();
+        db.add_radio_station(&station("B", "http://b")).unwrap();
+        let id = db.get_radio_stations().unwrap()[0].id;
+
+        db.update_radio_station(id, &station("C", "http://c"))
+            .unwrap();
+        // urls have to stay unique
+        assert!(db
+            .update_radio_station(id, &station("C", "http://b"))
+            .is_err());
+
+        let stations = db.get_radio_stations().unwrap();
+        assert_eq!(stations[1].name, "C");
+        assert_eq!(stations[1].url, "http://c");
+
+        db.delete_radio_station(id).unwrap();
+        let stations = db.get_radio_stations().unwrap();
+        assert_eq!(stations.len(), 1);
+        assert_eq!(stations[0].name, "B");
+    }
+
+    #[test]
+    fn should_set_last_played() {
+        let mut db = gen_library(&[]);
+        let time = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+
+        db.add_radio_station(&station("A", "http://a")).unwrap();
+
+        assert!(db.set_radio_station_last_played("http://a", time).unwrap());
+        // not a saved station
+        assert!(!db.set_radio_station_last_played("http://b", time).unwrap());
+
+        assert_eq!(db.get_radio_stations().unwrap()[0].last_played, Some(time));
+    }
+}
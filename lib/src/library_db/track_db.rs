@@ -1,5 +1,6 @@
-use std::time::{Duration, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use chrono::{DateTime, Local};
 use rusqlite::{named_params, Connection, Row};
 
 use crate::track::Track;
@@ -19,6 +20,21 @@ pub struct TrackDB {
     pub directory: String,
     pub last_modified: String,
     pub last_position: Duration,
+    /// How often the track was played past the configured threshold
+    pub play_count: u32,
+    /// How often the track was skipped before reaching the configured threshold
+    pub skip_count: u32,
+    /// The last time the track was counted as played, `None` if it was never played
+    pub last_played: Option<SystemTime>,
+    /// Rating in stars, `0` if unrated
+    pub rating: u8,
+    pub album_artist: Option<String>,
+    pub composer: Option<String>,
+    pub year: Option<u32>,
+    pub track_number: Option<u32>,
+    pub disc_number: Option<u32>,
+    pub performer: Option<String>,
+    pub comment: Option<String>,
 }
 
 impl TrackDB {
@@ -28,6 +44,7 @@ impl TrackDB {
     pub fn try_from_row_id(row: &Row<'_>) -> Result<Self, rusqlite::Error> {
         let d_u64: u64 = row.get(6)?;
         let last_position_u64: u64 = row.get(11)?;
+        let last_played: Option<u64> = row.get(14)?;
         Ok(TrackDB {
             id: row.get(0)?,
             artist: row.get(1)?,
@@ -41,6 +58,17 @@ impl TrackDB {
             directory: row.get(9)?,
             last_modified: row.get(10)?,
             last_position: Duration::from_secs(last_position_u64),
+            play_count: row.get(12)?,
+            skip_count: row.get(13)?,
+            last_played: last_played.map(|v| UNIX_EPOCH + Duration::from_secs(v)),
+            rating: row.get(15)?,
+            album_artist: row.get(16)?,
+            composer: row.get(17)?,
+            year: row.get(18)?,
+            track_number: row.get(19)?,
+            disc_number: row.get(20)?,
+            performer: row.get(21)?,
+            comment: row.get(22)?,
         })
     }
 
@@ -49,6 +77,8 @@ impl TrackDB {
         // NOTE: all the names in "get" below are the *column names* as defined in migrations/002.sql#table_tracks (pseudo link)
         let d_u64: u64 = row.get("duration")?;
         let last_position_u64: u64 = row.get("last_position")?;
+        // added in migrations/004.sql
+        let last_played: Option<u64> = row.get("last_played")?;
         Ok(TrackDB {
             id: row.get("id")?,
             artist: row.get("artist")?,
@@ -62,8 +92,27 @@ impl TrackDB {
             directory: row.get("directory")?,
             last_modified: row.get("last_modified")?,
             last_position: Duration::from_secs(last_position_u64),
+            play_count: row.get("play_count")?,
+            skip_count: row.get("skip_count")?,
+            last_played: last_played.map(|v| UNIX_EPOCH + Duration::from_secs(v)),
+            // added in migrations/005.sql
+            rating: row.get("rating")?,
+            // added in migrations/006.sql
+            album_artist: row.get("album_artist")?,
+            composer: row.get("composer")?,
+            year: row.get("year")?,
+            track_number: row.get("track_number")?,
+            disc_number: row.get("disc_number")?,
+            performer: row.get("performer")?,
+            comment: row.get("comment")?,
         })
     }
+
+    /// Format the last played date in the local timezone, like `2024-01-31`
+    pub fn last_played_formatted(&self) -> Option<String> {
+        self.last_played
+            .map(|v| DateTime::<Local>::from(v).format("%Y-%m-%d").to_string())
+    }
 }
 
 /// A struct representing a [`Track`](Track) in the database to be inserted
@@ -84,6 +133,14 @@ pub struct TrackDBInsertable<'a> {
     pub directory: &'a str,
     pub last_modified: String,
     pub last_position: Duration,
+    pub rating: u8,
+    pub album_artist: Option<&'a str>,
+    pub composer: Option<&'a str>,
+    pub year: Option<u32>,
+    pub track_number: Option<u32>,
+    pub disc_number: Option<u32>,
+    pub performer: Option<&'a str>,
+    pub comment: Option<&'a str>,
 }
 
 /// Constant strings for Unknown values
@@ -119,6 +176,14 @@ impl<'a> From<&'a Track> for TrackDBInsertable<'a> {
                 .as_secs()
                 .to_string(),
             last_position: Duration::default(),
+            rating: value.rating(),
+            album_artist: value.album_artist(),
+            composer: value.composer(),
+            year: value.year(),
+            track_number: value.track_number(),
+            disc_number: value.disc_number(),
+            performer: value.performer(),
+            comment: value.comment(),
         }
     }
 }
@@ -128,8 +193,8 @@ impl TrackDBInsertable<'_> {
     #[inline]
     pub fn insert_track(&self, con: &Connection) -> Result<usize, rusqlite::Error> {
         con.execute(
-            "INSERT INTO tracks (artist, title, album, genre, file, duration, name, ext, directory, last_modified, last_position) 
-            values (:artist, :title, :album, :genre, :file, :duration, :name, :ext, :directory, :last_modified, :last_position)",
+            "INSERT INTO tracks (artist, title, album, genre, file, duration, name, ext, directory, last_modified, last_position, rating, album_artist, composer, year, track_number, disc_number, performer, comment) 
+            values (:artist, :title, :album, :genre, :file, :duration, :name, :ext, :directory, :last_modified, :last_position, :rating, :album_artist, :composer, :year, :track_number, :disc_number, :performer, :comment)",
             named_params![
                 ":artist": &self.artist,
                 ":title": &self.title,
@@ -142,6 +207,48 @@ impl TrackDBInsertable<'_> {
                 ":directory": &self.directory,
                 ":last_modified": &self.last_modified,
                 ":last_position": &self.last_position.as_secs().to_string(),
+                ":rating": &self.rating,
+                ":album_artist": &self.album_artist,
+                ":composer": &self.composer,
+                ":year": &self.year,
+                ":track_number": &self.track_number,
+                ":disc_number": &self.disc_number,
+                ":performer": &self.performer,
+                ":comment": &self.comment,
+            ],
+        )
+    }
+
+    /// Update the existing track with the same `file` in the `tracks` table, keeping its play statistics and `last_position`
+    ///
+    /// Returns the number of updated rows, `0` if the track is not in the table yet
+    #[inline]
+    pub fn update_track(&self, con: &Connection) -> Result<usize, rusqlite::Error> {
+        con.execute(
+            "UPDATE tracks SET artist = :artist, title = :title, album = :album, genre = :genre, duration = :duration, name = :name,
+            ext = :ext, directory = :directory, last_modified = :last_modified, rating = :rating, album_artist = :album_artist,
+            composer = :composer, year = :year, track_number = :track_number, disc_number = :disc_number, performer = :performer,
+            comment = :comment
+            WHERE file = :file",
+            named_params![
+                ":artist": &self.artist,
+                ":title": &self.title,
+                ":album": &self.album,
+                ":genre": &self.genre,
+                ":file": &self.file,
+                ":duration": &self.duration.as_secs(),
+                ":name": &self.name,
+                ":ext": &self.ext,
+                ":directory": &self.directory,
+                ":last_modified": &self.last_modified,
+                ":rating": &self.rating,
+                ":album_artist": &self.album_artist,
+                ":composer": &self.composer,
+                ":year": &self.year,
+                ":track_number": &self.track_number,
+                ":disc_number": &self.disc_number,
+                ":performer": &self.performer,
+                ":comment": &self.comment,
             ],
         )
     }
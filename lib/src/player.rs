@@ -1,5 +1,12 @@
 #![allow(clippy::module_name_repetitions)]
+use std::num::NonZeroU32;
+
 use anyhow::anyhow;
+use playlist_helpers::{convert_index, PlaylistTrackSource};
+use serde::{Deserialize, Serialize};
+
+use crate::config::v2::server::equalizer::EqualizerSettings;
+use crate::config::v2::server::LoopMode;
 
 // using lower mod to restrict clippy
 #[allow(clippy::pedantic)]
@@ -9,6 +16,10 @@ mod protobuf {
 
 pub use protobuf::*;
 
+pub mod auth;
+pub mod playlist_helpers;
+pub mod radio_history;
+
 // implement transform function for easy use
 impl From<protobuf::Duration> for std::time::Duration {
     fn from(value: protobuf::Duration) -> Self {
@@ -64,6 +75,258 @@ pub struct TrackChangedInfo {
     pub title: Option<String>,
     /// Current progress of the track
     pub progress: Option<PlayerProgress>,
+    /// Metadata of the current track
+    pub track: Option<TrackInfo>,
+}
+
+/// Metadata of a track, for clients that cannot (or do not want to) read the track themself
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackInfo {
+    /// Path or URI of the track, which also determines the media type
+    pub id: PlaylistTrackSource,
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub genre: Option<String>,
+    /// Total duration of the track, if known
+    pub duration: Option<PlayerTimeUnit>,
+    /// Whether there is cover art for the track (embedded or in the same directory)
+    pub has_cover: bool,
+}
+
+impl From<TrackInfo> for protobuf::Track {
+    fn from(value: TrackInfo) -> Self {
+        Self {
+            id: Some(value.id.into()),
+            optional_title: value.title.map(protobuf::track::OptionalTitle::Title),
+            optional_artist: value.artist.map(protobuf::track::OptionalArtist::Artist),
+            optional_album: value.album.map(protobuf::track::OptionalAlbum::Album),
+            optional_genre: value.genre.map(protobuf::track::OptionalGenre::Genre),
+            duration: value.duration.map(Into::into),
+            has_cover: value.has_cover,
+        }
+    }
+}
+
+impl TryFrom<protobuf::Track> for TrackInfo {
+    type Error = anyhow::Error;
+
+    fn try_from(value: protobuf::Track) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: unwrap_msg(value.id, "Track.id")?.try_into()?,
+            title: value.optional_title.map(|v| {
+                let protobuf::track::OptionalTitle::Title(v) = v;
+                v
+            }),
+            artist: value.optional_artist.map(|v| {
+                let protobuf::track::OptionalArtist::Artist(v) = v;
+                v
+            }),
+            album: value.optional_album.map(|v| {
+                let protobuf::track::OptionalAlbum::Album(v) = v;
+                v
+            }),
+            genre: value.optional_genre.map(|v| {
+                let protobuf::track::OptionalGenre::Genre(v) = v;
+                v
+            }),
+            duration: value.duration.map(Into::into),
+            has_cover: value.has_cover,
+        })
+    }
+}
+
+/// What a sleep timer waits for, before fading out and pausing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SleepTimerKind {
+    /// Pause after the given time
+    Duration(PlayerTimeUnit),
+    /// Pause at the end of the current track
+    EndOfTrack,
+    /// Pause after the given amount of tracks have finished, including the current one
+    Tracks(NonZeroU32),
+}
+
+impl From<SleepTimerKind> for protobuf::SleepTimerRequest {
+    fn from(value: SleepTimerKind) -> Self {
+        let kind = match value {
+            SleepTimerKind::Duration(v) => protobuf::sleep_timer_request::Kind::Duration(v.into()),
+            SleepTimerKind::EndOfTrack => {
+                protobuf::sleep_timer_request::Kind::EndOfTrack(protobuf::Empty {})
+            }
+            SleepTimerKind::Tracks(v) => protobuf::sleep_timer_request::Kind::Tracks(v.get()),
+        };
+
+        Self { kind: Some(kind) }
+    }
+}
+
+impl TryFrom<protobuf::SleepTimerRequest> for SleepTimerKind {
+    type Error = anyhow::Error;
+
+    fn try_from(value: protobuf::SleepTimerRequest) -> Result<Self, Self::Error> {
+        Ok(match unwrap_msg(value.kind, "SleepTimerRequest.kind")? {
+            protobuf::sleep_timer_request::Kind::Duration(v) => Self::Duration(v.into()),
+            protobuf::sleep_timer_request::Kind::EndOfTrack(_) => Self::EndOfTrack,
+            protobuf::sleep_timer_request::Kind::Tracks(v) => Self::Tracks(
+                NonZeroU32::new(v).ok_or(anyhow!("SleepTimerRequest.tracks may not be 0"))?,
+            ),
+        })
+    }
+}
+
+/// State of a active sleep timer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SleepTimerInfo {
+    /// Time until the fade-out starts, only for [`SleepTimerKind::Duration`]
+    pub remaining_time: Option<PlayerTimeUnit>,
+    /// Tracks left to finish, including the current one, only for [`SleepTimerKind::EndOfTrack`] and [`SleepTimerKind::Tracks`]
+    pub remaining_tracks: Option<u32>,
+    /// Whether the volume is currently being faded out
+    pub fading: bool,
+}
+
+impl From<Option<SleepTimerInfo>> for protobuf::SleepTimerState {
+    fn from(value: Option<SleepTimerInfo>) -> Self {
+        let Some(value) = value else {
+            return Self::default();
+        };
+
+        Self {
+            active: true,
+            remaining_time: value.remaining_time.map(Into::into),
+            optional_remaining_tracks: value
+                .remaining_tracks
+                .map(protobuf::sleep_timer_state::OptionalRemainingTracks::RemainingTracks),
+            fading: value.fading,
+        }
+    }
+}
+
+impl From<protobuf::SleepTimerState> for Option<SleepTimerInfo> {
+    fn from(value: protobuf::SleepTimerState) -> Self {
+        if !value.active {
+            return None;
+        }
+
+        Some(SleepTimerInfo {
+            remaining_time: value.remaining_time.map(Into::into),
+            remaining_tracks: value.optional_remaining_tracks.map(|v| {
+                let protobuf::sleep_timer_state::OptionalRemainingTracks::RemainingTracks(v) = v;
+                v
+            }),
+            fading: value.fading,
+        })
+    }
+}
+
+/// State of the equalizer
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct EqualizerInfo {
+    /// The active preset, [`None`] if the equalizer is disabled
+    pub preset: Option<String>,
+    /// The names of all available presets
+    pub presets: Vec<String>,
+}
+
+impl From<&EqualizerSettings> for EqualizerInfo {
+    fn from(value: &EqualizerSettings) -> Self {
+        Self {
+            // dont report a preset which does not exist as active
+            preset: value.active_bands().and(value.preset.clone()),
+            presets: value.preset_names(),
+        }
+    }
+}
+
+impl From<EqualizerInfo> for protobuf::EqualizerState {
+    fn from(value: EqualizerInfo) -> Self {
+        Self {
+            optional_preset: value
+                .preset
+                .map(protobuf::equalizer_state::OptionalPreset::Preset),
+            presets: value.presets,
+        }
+    }
+}
+
+impl From<protobuf::EqualizerState> for EqualizerInfo {
+    fn from(value: protobuf::EqualizerState) -> Self {
+        Self {
+            preset: value.optional_preset.map(|v| {
+                let protobuf::equalizer_state::OptionalPreset::Preset(v) = v;
+                v
+            }),
+            presets: value.presets,
+        }
+    }
+}
+
+impl From<Option<String>> for protobuf::SetEqualizerPresetRequest {
+    fn from(value: Option<String>) -> Self {
+        Self {
+            optional_preset: value
+                .map(protobuf::set_equalizer_preset_request::OptionalPreset::Preset),
+        }
+    }
+}
+
+impl From<protobuf::SetEqualizerPresetRequest> for Option<String> {
+    fn from(value: protobuf::SetEqualizerPresetRequest) -> Self {
+        value.optional_preset.map(|v| {
+            let protobuf::set_equalizer_preset_request::OptionalPreset::Preset(v) = v;
+            v
+        })
+    }
+}
+
+/// The selected and the available output devices
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct OutputDeviceInfo {
+    /// The selected device, [`None`] if the default device is selected
+    pub device: Option<String>,
+    /// The names of all available devices
+    pub devices: Vec<String>,
+}
+
+impl From<OutputDeviceInfo> for protobuf::OutputDevices {
+    fn from(value: OutputDeviceInfo) -> Self {
+        Self {
+            optional_device: value
+                .device
+                .map(protobuf::output_devices::OptionalDevice::Device),
+            devices: value.devices,
+        }
+    }
+}
+
+impl From<protobuf::OutputDevices> for OutputDeviceInfo {
+    fn from(value: protobuf::OutputDevices) -> Self {
+        Self {
+            device: value.optional_device.map(|v| {
+                let protobuf::output_devices::OptionalDevice::Device(v) = v;
+                v
+            }),
+            devices: value.devices,
+        }
+    }
+}
+
+impl From<Option<String>> for protobuf::SetOutputDeviceRequest {
+    fn from(value: Option<String>) -> Self {
+        Self {
+            optional_device: value.map(protobuf::set_output_device_request::OptionalDevice::Device),
+        }
+    }
+}
+
+impl From<protobuf::SetOutputDeviceRequest> for Option<String> {
+    fn from(value: protobuf::SetOutputDeviceRequest) -> Self {
+        value.optional_device.map(|v| {
+            let protobuf::set_output_device_request::OptionalDevice::Device(v) = v;
+            v
+        })
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -73,9 +336,49 @@ pub enum UpdateEvents {
     SpeedChanged { speed: i32 },
     PlayStateChanged { playing: u32 },
     TrackChanged(TrackChangedInfo),
+    PlaylistChanged(UpdatePlaylistEvents),
+}
+
+/// Tracks that have been added to the playlist
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlaylistAddTrackInfo {
+    /// The index the tracks have actually been inserted at
+    pub at_index: usize,
+    /// Only the tracks that have actually been added
+    pub tracks: Vec<PlaylistTrackSource>,
+}
+
+/// A track that has been removed from the playlist
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlaylistRemoveTrackInfo {
+    pub at_index: usize,
+    /// The track that has been at `at_index`
+    pub id: PlaylistTrackSource,
+}
+
+/// Two tracks that swapped positions in the playlist
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlaylistSwapInfo {
+    pub index_a: usize,
+    pub index_b: usize,
+}
+
+/// Playlist specific events, with enough information for a client to patch its copy of the playlist
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UpdatePlaylistEvents {
+    PlaylistAddTrack(PlaylistAddTrackInfo),
+    PlaylistRemoveTrack(PlaylistRemoveTrackInfo),
+    PlaylistCleared,
+    PlaylistLoopMode(LoopMode),
+    PlaylistSwapTracks(PlaylistSwapInfo),
+    /// The playlist has been re-ordered, contains the complete new playlist
+    PlaylistShuffled(playlist_helpers::PlaylistTracks),
+    /// The playlist has been replaced, like by reloading it from disk, contains the complete new playlist
+    PlaylistReplaced(playlist_helpers::PlaylistTracks),
 }
 
 type StreamTypes = protobuf::stream_updates::Type;
+type PlaylistTypes = protobuf::update_playlist::Type;
 
 // mainly for server to grpc
 impl From<UpdateEvents> for protobuf::StreamUpdates {
@@ -106,7 +409,54 @@ impl From<UpdateEvents> for protobuf::StreamUpdates {
                     .title
                     .map(protobuf::update_track_changed::OptionalTitle::Title),
                 progress: info.progress.map(Into::into),
+                track: info.track.map(Into::into),
             }),
+            UpdateEvents::PlaylistChanged(ev) => StreamTypes::PlaylistChanged(ev.into()),
+        };
+
+        Self { r#type: Some(val) }
+    }
+}
+
+impl From<UpdatePlaylistEvents> for protobuf::UpdatePlaylist {
+    fn from(value: UpdatePlaylistEvents) -> Self {
+        let val = match value {
+            UpdatePlaylistEvents::PlaylistAddTrack(info) => {
+                PlaylistTypes::AddTrack(UpdatePlaylistAddTrack {
+                    at_index: info.at_index as u64,
+                    tracks: info.tracks.into_iter().map(Into::into).collect(),
+                })
+            }
+            UpdatePlaylistEvents::PlaylistRemoveTrack(info) => {
+                PlaylistTypes::RemoveTrack(UpdatePlaylistRemoveTrack {
+                    at_index: info.at_index as u64,
+                    id: Some(info.id.into()),
+                })
+            }
+            UpdatePlaylistEvents::PlaylistCleared => {
+                PlaylistTypes::Cleared(UpdatePlaylistCleared {})
+            }
+            UpdatePlaylistEvents::PlaylistLoopMode(mode) => {
+                PlaylistTypes::LoopMode(UpdatePlaylistLoopMode {
+                    mode: mode.as_u32(),
+                })
+            }
+            UpdatePlaylistEvents::PlaylistSwapTracks(info) => {
+                PlaylistTypes::SwapTracks(UpdatePlaylistSwapTracks {
+                    index_a: info.index_a as u64,
+                    index_b: info.index_b as u64,
+                })
+            }
+            UpdatePlaylistEvents::PlaylistShuffled(tracks) => {
+                PlaylistTypes::Shuffled(UpdatePlaylistShuffled {
+                    shuffled: Some(tracks.into()),
+                })
+            }
+            UpdatePlaylistEvents::PlaylistReplaced(tracks) => {
+                PlaylistTypes::Replaced(UpdatePlaylistReplaced {
+                    replaced: Some(tracks.into()),
+                })
+            }
         };
 
         Self { r#type: Some(val) }
@@ -141,7 +491,49 @@ impl TryFrom<protobuf::StreamUpdates> for UpdateEvents {
                     v
                 }),
                 progress: ev.progress.map(Into::into),
+                track: ev.track.map(TrackInfo::try_from).transpose()?,
+            }),
+            stream_updates::Type::PlaylistChanged(ev) => Self::PlaylistChanged(ev.try_into()?),
+        };
+
+        Ok(res)
+    }
+}
+
+impl TryFrom<protobuf::UpdatePlaylist> for UpdatePlaylistEvents {
+    type Error = anyhow::Error;
+
+    fn try_from(value: protobuf::UpdatePlaylist) -> Result<Self, Self::Error> {
+        let value = unwrap_msg(value.r#type, "UpdatePlaylist.type")?;
+
+        let res = match value {
+            PlaylistTypes::AddTrack(ev) => Self::PlaylistAddTrack(PlaylistAddTrackInfo {
+                at_index: convert_index(ev.at_index, "UpdatePlaylist.type.add_track.at_index")?,
+                tracks: ev
+                    .tracks
+                    .into_iter()
+                    .map(PlaylistTrackSource::try_from)
+                    .collect::<Result<_, _>>()?,
+            }),
+            PlaylistTypes::RemoveTrack(ev) => Self::PlaylistRemoveTrack(PlaylistRemoveTrackInfo {
+                at_index: convert_index(ev.at_index, "UpdatePlaylist.type.remove_track.at_index")?,
+                id: unwrap_msg(ev.id, "UpdatePlaylist.type.remove_track.id")?.try_into()?,
             }),
+            PlaylistTypes::Cleared(_) => Self::PlaylistCleared,
+            PlaylistTypes::LoopMode(ev) => Self::PlaylistLoopMode(
+                LoopMode::tryfrom_u32(ev.mode)
+                    .ok_or_else(|| anyhow!("Unknown loop mode \"{}\"", ev.mode))?,
+            ),
+            PlaylistTypes::SwapTracks(ev) => Self::PlaylistSwapTracks(PlaylistSwapInfo {
+                index_a: convert_index(ev.index_a, "UpdatePlaylist.type.swap_tracks.index_a")?,
+                index_b: convert_index(ev.index_b, "UpdatePlaylist.type.swap_tracks.index_b")?,
+            }),
+            PlaylistTypes::Shuffled(ev) => Self::PlaylistShuffled(
+                unwrap_msg(ev.shuffled, "UpdatePlaylist.type.shuffled.shuffled")?.try_into()?,
+            ),
+            PlaylistTypes::Replaced(ev) => Self::PlaylistReplaced(
+                unwrap_msg(ev.replaced, "UpdatePlaylist.type.replaced.replaced")?.try_into()?,
+            ),
         };
 
         Ok(res)
@@ -160,3 +552,100 @@ fn unwrap_msg<T>(opt: Option<T>, place: &str) -> Result<T, anyhow::Error> {
 fn clamp_u16(val: u32) -> u16 {
     val.min(u32::from(u16::MAX)) as u16
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_roundtrip_playlist_events() {
+        let source = PlaylistTrackSource::Path("/some/file.mp3".to_string());
+        let events = [
+            UpdatePlaylistEvents::PlaylistAddTrack(PlaylistAddTrackInfo {
+                at_index: 1,
+                tracks: vec![source.clone()],
+            }),
+            UpdatePlaylistEvents::PlaylistRemoveTrack(PlaylistRemoveTrackInfo {
+                at_index: 2,
+                id: source.clone(),
+            }),
+            UpdatePlaylistEvents::PlaylistCleared,
+            UpdatePlaylistEvents::PlaylistLoopMode(LoopMode::Random),
+            UpdatePlaylistEvents::PlaylistSwapTracks(PlaylistSwapInfo {
+                index_a: 0,
+                index_b: 3,
+            }),
+            UpdatePlaylistEvents::PlaylistShuffled(playlist_helpers::PlaylistTracks {
+                current_track_index: 0,
+                tracks: vec![source.clone()],
+            }),
+            UpdatePlaylistEvents::PlaylistReplaced(playlist_helpers::PlaylistTracks {
+                current_track_index: 0,
+                tracks: vec![source],
+            }),
+        ];
+
+        for ev in events {
+            let ev = UpdateEvents::PlaylistChanged(ev);
+            let proto = protobuf::StreamUpdates::from(ev.clone());
+            assert_eq!(UpdateEvents::try_from(proto).unwrap(), ev);
+        }
+    }
+
+    #[test]
+    fn should_roundtrip_track_changed() {
+        let ev = UpdateEvents::TrackChanged(TrackChangedInfo {
+            current_track_index: 1,
+            current_track_updated: true,
+            title: None,
+            progress: None,
+            track: Some(TrackInfo {
+                id: PlaylistTrackSource::Url("https://radio.example/stream".to_string()),
+                title: Some("Title".to_string()),
+                artist: None,
+                album: Some("Album".to_string()),
+                genre: None,
+                duration: Some(PlayerTimeUnit::from_secs(10)),
+                has_cover: true,
+            }),
+        });
+
+        let proto = protobuf::StreamUpdates::from(ev.clone());
+        assert_eq!(UpdateEvents::try_from(proto).unwrap(), ev);
+    }
+
+    #[test]
+    fn should_error_on_unknown_loop_mode() {
+        let proto = protobuf::UpdatePlaylist {
+            r#type: Some(PlaylistTypes::LoopMode(UpdatePlaylistLoopMode { mode: 10 })),
+        };
+        assert!(UpdatePlaylistEvents::try_from(proto).is_err());
+    }
+
+    #[test]
+    fn should_convert_sleep_timer() {
+        for kind in [
+            SleepTimerKind::Duration(PlayerTimeUnit::from_secs(60)),
+            SleepTimerKind::EndOfTrack,
+            SleepTimerKind::Tracks(NonZeroU32::new(3).unwrap()),
+        ] {
+            let proto = protobuf::SleepTimerRequest::from(kind);
+            assert_eq!(SleepTimerKind::try_from(proto).unwrap(), kind);
+        }
+
+        let proto = protobuf::SleepTimerRequest {
+            kind: Some(protobuf::sleep_timer_request::Kind::Tracks(0)),
+        };
+        assert!(SleepTimerKind::try_from(proto).is_err());
+
+        let state = Some(SleepTimerInfo {
+            remaining_time: None,
+            remaining_tracks: Some(2),
+            fading: false,
+        });
+        let proto = protobuf::SleepTimerState::from(state);
+        assert_eq!(Option::<SleepTimerInfo>::from(proto), state);
+        let proto = protobuf::SleepTimerState::from(None);
+        assert_eq!(Option::<SleepTimerInfo>::from(proto), None);
+    }
+}
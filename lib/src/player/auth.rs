@@ -0,0 +1,137 @@
+//! Optional shared-secret authentication between the server and its clients.
+//!
+//! If a token is configured, the client sends it on every request and the server rejects any request without it.
+
+use std::sync::Arc;
+
+use tonic::metadata::{Ascii, MetadataValue};
+use tonic::service::interceptor::InterceptedService;
+use tonic::service::Interceptor;
+use tonic::transport::Channel;
+use tonic::{Request, Status};
+
+use super::music_player_client::MusicPlayerClient;
+
+/// The metadata key the token is sent in
+pub const AUTH_METADATA_KEY: &str = "authorization";
+/// The prefix before the actual token in [`AUTH_METADATA_KEY`]
+const AUTH_PREFIX: &str = "Bearer ";
+
+/// A client which sends the configured token on every request
+pub type AuthedClient = MusicPlayerClient<InterceptedService<Channel, AuthToken>>;
+
+/// Client-side interceptor adding the configured token to each request
+#[derive(Debug, Clone)]
+pub struct AuthToken {
+    value: Option<MetadataValue<Ascii>>,
+}
+
+impl AuthToken {
+    /// Create a new instance, `None` will not send anything.
+    ///
+    /// # Errors
+    ///
+    /// If the token contains characters that are not allowed in metadata (like non-ascii)
+    pub fn new(token: Option<&str>) -> anyhow::Result<Self> {
+        let value = token
+            .map(|token| format!("{AUTH_PREFIX}{token}").parse())
+            .transpose()
+            .map_err(|_| {
+                anyhow::anyhow!("The auth token may only contain visible ascii characters")
+            })?;
+
+        Ok(Self { value })
+    }
+}
+
+impl Interceptor for AuthToken {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        if let Some(value) = &self.value {
+            request
+                .metadata_mut()
+                .insert(AUTH_METADATA_KEY, value.clone());
+        }
+
+        Ok(request)
+    }
+}
+
+/// Server-side interceptor rejecting requests that do not have the configured token
+#[derive(Debug, Clone)]
+pub struct AuthCheck {
+    expected: Option<Arc<str>>,
+}
+
+impl AuthCheck {
+    /// Create a new instance, `None` will allow all requests.
+    pub fn new(token: Option<&str>) -> Self {
+        Self {
+            expected: token.map(|v| Arc::from(format!("{AUTH_PREFIX}{v}"))),
+        }
+    }
+}
+
+impl Interceptor for AuthCheck {
+    fn call(&mut self, request: Request<()>) -> Result<Request<()>, Status> {
+        let Some(expected) = &self.expected else {
+            return Ok(request);
+        };
+
+        let given = request
+            .metadata()
+            .get(AUTH_METADATA_KEY)
+            .map(MetadataValue::as_bytes);
+
+        match given {
+            Some(given) if constant_time_eq(given, expected.as_bytes()) => Ok(request),
+            _ => Err(Status::unauthenticated("Missing or invalid auth token")),
+        }
+    }
+}
+
+/// Compare two byte slices without returning early on the first difference,
+/// to not leak how much of the token was correct through timing
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    a.iter().zip(b).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request_with(token: Option<&str>) -> Request<()> {
+        AuthToken::new(token)
+            .unwrap()
+            .call(Request::new(()))
+            .unwrap()
+    }
+
+    #[test]
+    fn should_allow_everything_without_token() {
+        let mut check = AuthCheck::new(None);
+        assert!(check.call(request_with(None)).is_ok());
+        assert!(check.call(request_with(Some("anything"))).is_ok());
+    }
+
+    #[test]
+    fn should_check_token() {
+        let mut check = AuthCheck::new(Some("secret"));
+        assert!(check.call(request_with(Some("secret"))).is_ok());
+
+        let err = check.call(request_with(None)).unwrap_err();
+        assert_eq!(err.code(), tonic::Code::Unauthenticated);
+        let err = check.call(request_with(Some("secreT"))).unwrap_err();
+        assert_eq!(err.code(), tonic::Code::Unauthenticated);
+        let err = check.call(request_with(Some("secret2"))).unwrap_err();
+        assert_eq!(err.code(), tonic::Code::Unauthenticated);
+    }
+
+    #[test]
+    fn should_reject_invalid_token_chars() {
+        assert!(AuthToken::new(Some("new\nline")).is_err());
+    }
+}
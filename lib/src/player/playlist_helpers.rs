@@ -0,0 +1,265 @@
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use super::protobuf::{self, track_id::Source as TrackIdSource};
+use super::unwrap_msg;
+use crate::track::MediaType;
+
+/// Where a playlist track comes from, which is also everything needed to (re-)create the track
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PlaylistTrackSource {
+    /// A local file, for use with [`MediaType::Music`](crate::track::MediaType::Music)
+    Path(String),
+    /// A remote URI, for use with [`MediaType::LiveRadio`](crate::track::MediaType::LiveRadio)
+    Url(String),
+    /// A remote URI of a podcast episode, for use with [`MediaType::Podcast`](crate::track::MediaType::Podcast)
+    PodcastUrl(String),
+}
+
+impl PlaylistTrackSource {
+    /// Get the inner Path or URI
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Path(v) | Self::Url(v) | Self::PodcastUrl(v) => v,
+        }
+    }
+
+    /// Get the [`MediaType`] a track from this source has
+    pub fn media_type(&self) -> MediaType {
+        match self {
+            Self::Path(_) => MediaType::Music,
+            Self::Url(_) => MediaType::LiveRadio,
+            Self::PodcastUrl(_) => MediaType::Podcast,
+        }
+    }
+}
+
+impl From<PlaylistTrackSource> for protobuf::TrackId {
+    fn from(value: PlaylistTrackSource) -> Self {
+        let source = match value {
+            PlaylistTrackSource::Path(v) => TrackIdSource::Path(v),
+            PlaylistTrackSource::Url(v) => TrackIdSource::Url(v),
+            PlaylistTrackSource::PodcastUrl(v) => TrackIdSource::PodcastUrl(v),
+        };
+
+        Self {
+            source: Some(source),
+        }
+    }
+}
+
+impl TryFrom<protobuf::TrackId> for PlaylistTrackSource {
+    type Error = anyhow::Error;
+
+    fn try_from(value: protobuf::TrackId) -> Result<Self, Self::Error> {
+        let source = unwrap_msg(value.source, "TrackId.source")?;
+
+        Ok(match source {
+            TrackIdSource::Path(v) => Self::Path(v),
+            TrackIdSource::Url(v) => Self::Url(v),
+            TrackIdSource::PodcastUrl(v) => Self::PodcastUrl(v),
+        })
+    }
+}
+
+/// Convert a index from grpc to a `usize`
+pub(super) fn convert_index(index: u64, place: &str) -> Result<usize, anyhow::Error> {
+    usize::try_from(index).with_context(|| format!("Index in \"{place}\" does not fit into usize"))
+}
+
+/// Convert a list of grpc [`TrackId`](protobuf::TrackId)s to [`PlaylistTrackSource`]s
+fn convert_track_ids(
+    tracks: Vec<protobuf::TrackId>,
+) -> Result<Vec<PlaylistTrackSource>, anyhow::Error> {
+    tracks
+        .into_iter()
+        .map(PlaylistTrackSource::try_from)
+        .collect()
+}
+
+/// The full playlist, as sent from the server
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlaylistTracks {
+    pub current_track_index: usize,
+    pub tracks: Vec<PlaylistTrackSource>,
+}
+
+impl From<PlaylistTracks> for protobuf::PlaylistTracks {
+    fn from(value: PlaylistTracks) -> Self {
+        Self {
+            current_track_index: value.current_track_index as u64,
+            tracks: value.tracks.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl TryFrom<protobuf::PlaylistTracks> for PlaylistTracks {
+    type Error = anyhow::Error;
+
+    fn try_from(value: protobuf::PlaylistTracks) -> Result<Self, Self::Error> {
+        Ok(Self {
+            current_track_index: convert_index(
+                value.current_track_index,
+                "PlaylistTracks.current_track_index",
+            )?,
+            tracks: convert_track_ids(value.tracks)?,
+        })
+    }
+}
+
+/// Add the given tracks at `at_index`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlaylistAddTrack {
+    /// The index to insert the tracks at, any index at or above the length of the playlist appends
+    pub at_index: usize,
+    pub tracks: Vec<PlaylistTrackSource>,
+}
+
+impl PlaylistAddTrack {
+    /// Create a new instance which will append the given tracks to the end of the playlist
+    pub fn new_append(tracks: Vec<PlaylistTrackSource>) -> Self {
+        Self {
+            at_index: usize::MAX,
+            tracks,
+        }
+    }
+}
+
+impl From<PlaylistAddTrack> for protobuf::PlaylistTracksToAdd {
+    fn from(value: PlaylistAddTrack) -> Self {
+        Self {
+            at_index: value.at_index as u64,
+            tracks: value.tracks.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl TryFrom<protobuf::PlaylistTracksToAdd> for PlaylistAddTrack {
+    type Error = anyhow::Error;
+
+    fn try_from(value: protobuf::PlaylistTracksToAdd) -> Result<Self, Self::Error> {
+        Ok(Self {
+            // saturate instead of error, as any index past the end means "append"
+            at_index: usize::try_from(value.at_index).unwrap_or(usize::MAX),
+            tracks: convert_track_ids(value.tracks)?,
+        })
+    }
+}
+
+/// Remove the track at `at_index`, if it is `id`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlaylistRemoveTrackIndexed {
+    pub at_index: usize,
+    /// The track expected at `at_index`
+    pub id: PlaylistTrackSource,
+}
+
+impl From<PlaylistRemoveTrackIndexed> for protobuf::PlaylistTracksToRemove {
+    fn from(value: PlaylistRemoveTrackIndexed) -> Self {
+        Self {
+            at_index: value.at_index as u64,
+            id: Some(value.id.into()),
+        }
+    }
+}
+
+impl TryFrom<protobuf::PlaylistTracksToRemove> for PlaylistRemoveTrackIndexed {
+    type Error = anyhow::Error;
+
+    fn try_from(value: protobuf::PlaylistTracksToRemove) -> Result<Self, Self::Error> {
+        Ok(Self {
+            at_index: convert_index(value.at_index, "PlaylistTracksToRemove.at_index")?,
+            id: unwrap_msg(value.id, "PlaylistTracksToRemove.id")?.try_into()?,
+        })
+    }
+}
+
+/// Swap the tracks at `index_a` and `index_b`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlaylistSwapTrack {
+    pub index_a: usize,
+    pub index_b: usize,
+}
+
+impl From<PlaylistSwapTrack> for protobuf::PlaylistTracksToSwap {
+    fn from(value: PlaylistSwapTrack) -> Self {
+        Self {
+            index_a: value.index_a as u64,
+            index_b: value.index_b as u64,
+        }
+    }
+}
+
+impl TryFrom<protobuf::PlaylistTracksToSwap> for PlaylistSwapTrack {
+    type Error = anyhow::Error;
+
+    fn try_from(value: protobuf::PlaylistTracksToSwap) -> Result<Self, Self::Error> {
+        Ok(Self {
+            index_a: convert_index(value.index_a, "PlaylistTracksToSwap.index_a")?,
+            index_b: convert_index(value.index_b, "PlaylistTracksToSwap.index_b")?,
+        })
+    }
+}
+
+/// Skip to and play the track at `track_index`, if it is `id`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlaylistPlaySpecific {
+    pub track_index: usize,
+    /// The track expected at `track_index`
+    pub id: PlaylistTrackSource,
+}
+
+impl From<PlaylistPlaySpecific> for protobuf::PlaylistTrackToPlay {
+    fn from(value: PlaylistPlaySpecific) -> Self {
+        Self {
+            track_index: value.track_index as u64,
+            id: Some(value.id.into()),
+        }
+    }
+}
+
+impl TryFrom<protobuf::PlaylistTrackToPlay> for PlaylistPlaySpecific {
+    type Error = anyhow::Error;
+
+    fn try_from(value: protobuf::PlaylistTrackToPlay) -> Result<Self, Self::Error> {
+        Ok(Self {
+            track_index: convert_index(value.track_index, "PlaylistTrackToPlay.track_index")?,
+            id: unwrap_msg(value.id, "PlaylistTrackToPlay.id")?.try_into()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_roundtrip_add_track() {
+        let info = PlaylistAddTrack {
+            at_index: 3,
+            tracks: vec![
+                PlaylistTrackSource::Path("/some/file.mp3".to_string()),
+                PlaylistTrackSource::Url("https://radio.example/stream".to_string()),
+                PlaylistTrackSource::PodcastUrl("https://podcast.example/ep1.mp3".to_string()),
+            ],
+        };
+
+        let proto = protobuf::PlaylistTracksToAdd::from(info.clone());
+        assert_eq!(PlaylistAddTrack::try_from(proto).unwrap(), info);
+    }
+
+    #[test]
+    fn should_error_on_missing_track_id() {
+        let proto = protobuf::PlaylistTracksToRemove {
+            at_index: 0,
+            id: None,
+        };
+        assert!(PlaylistRemoveTrackIndexed::try_from(proto).is_err());
+
+        let proto = protobuf::PlaylistTracksToAdd {
+            at_index: 0,
+            tracks: vec![protobuf::TrackId { source: None }],
+        };
+        assert!(PlaylistAddTrack::try_from(proto).is_err());
+    }
+}
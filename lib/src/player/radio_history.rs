@@ -0,0 +1,241 @@
+//! History of the titles announced by radio stations (via ICY metadata).
+//!
+//! The server keeps a [`RadioTitleHistory`] for every station played in this session, clients request it per station.
+
+use std::collections::{HashMap, VecDeque};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+
+use super::protobuf;
+use crate::utils::get_app_config_path;
+
+/// The maximal amount of titles kept per station, older titles get dropped
+pub const MAX_TITLES_PER_STATION: usize = 100;
+
+/// The file name of the "heard on radio" list, in the config directory
+const HEARD_ON_RADIO_FILE: &str = "heard_on_radio.txt";
+
+/// A title announced by a radio station
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RadioTitleInfo {
+    pub title: String,
+    /// The time the title was first seen
+    pub started_at: SystemTime,
+}
+
+impl RadioTitleInfo {
+    /// Format the start time in the local timezone, like `14:05`
+    pub fn time_formatted(&self) -> String {
+        DateTime::<Local>::from(self.started_at)
+            .format("%H:%M")
+            .to_string()
+    }
+}
+
+impl From<RadioTitleInfo> for protobuf::RadioTitle {
+    fn from(value: RadioTitleInfo) -> Self {
+        Self {
+            title: value.title,
+            started_at: value
+                .started_at
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+        }
+    }
+}
+
+impl From<protobuf::RadioTitle> for RadioTitleInfo {
+    fn from(value: protobuf::RadioTitle) -> Self {
+        Self {
+            title: value.title,
+            started_at: UNIX_EPOCH + Duration::from_secs(value.started_at),
+        }
+    }
+}
+
+/// The titles of a single station, newest first
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RadioHistoryInfo {
+    /// The URL of the station, empty if there is no station
+    pub url: String,
+    pub titles: Vec<RadioTitleInfo>,
+}
+
+impl From<RadioHistoryInfo> for protobuf::RadioHistory {
+    fn from(value: RadioHistoryInfo) -> Self {
+        Self {
+            url: value.url,
+            titles: value.titles.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<protobuf::RadioHistory> for RadioHistoryInfo {
+    fn from(value: protobuf::RadioHistory) -> Self {
+        Self {
+            url: value.url,
+            titles: value.titles.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<Option<String>> for protobuf::RadioHistoryRequest {
+    fn from(value: Option<String>) -> Self {
+        Self {
+            optional_url: value.map(protobuf::radio_history_request::OptionalUrl::Url),
+        }
+    }
+}
+
+impl From<protobuf::RadioHistoryRequest> for Option<String> {
+    fn from(value: protobuf::RadioHistoryRequest) -> Self {
+        value.optional_url.map(|v| {
+            let protobuf::radio_history_request::OptionalUrl::Url(v) = v;
+            v
+        })
+    }
+}
+
+/// The title history of all stations, by station URL
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RadioTitleHistory {
+    stations: HashMap<String, VecDeque<RadioTitleInfo>>,
+}
+
+impl RadioTitleHistory {
+    /// Add `title` as the current title of the station at `url`.
+    ///
+    /// Empty titles and repeats of the current title are ignored, returns whether the title was added.
+    pub fn push(&mut self, url: &str, title: &str, now: SystemTime) -> bool {
+        let title = title.trim();
+        if title.is_empty() {
+            return false;
+        }
+
+        let titles = self.stations.entry(url.to_string()).or_default();
+        if titles.front().is_some_and(|v| v.title == title) {
+            return false;
+        }
+
+        titles.push_front(RadioTitleInfo {
+            title: title.to_string(),
+            started_at: now,
+        });
+        titles.truncate(MAX_TITLES_PER_STATION);
+
+        true
+    }
+
+    /// Get the history of the station at `url`, empty if nothing was recorded for it
+    pub fn get(&self, url: &str) -> RadioHistoryInfo {
+        RadioHistoryInfo {
+            url: url.to_string(),
+            titles: self
+                .stations
+                .get(url)
+                .map(|v| v.iter().cloned().collect())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Get the path of the "heard on radio" list
+pub fn heard_on_radio_path() -> Result<PathBuf> {
+    Ok(get_app_config_path()?.join(HEARD_ON_RADIO_FILE))
+}
+
+/// Append `title` from the station at `url` to the list at `path`, one title per line
+pub fn save_heard_on_radio(path: &Path, title: &RadioTitleInfo, url: &str) -> Result<()> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| path.display().to_string())?;
+    let date = DateTime::<Local>::from(title.started_at).format("%Y-%m-%d %H:%M");
+    writeln!(file, "{date}\t{}\t{url}", title.title)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    use super::{save_heard_on_radio, RadioTitleHistory, RadioTitleInfo, MAX_TITLES_PER_STATION};
+
+    #[test]
+    fn should_record_changed_titles_per_station() {
+        let mut history = RadioTitleHistory::default();
+        let now = SystemTime::now();
+
+        assert!(history.push("http://a", "First", now));
+        assert!(!history.push("http://a", "First", now));
+        assert!(!history.push("http://a", "  ", now));
+        assert!(history.push("http://b", "Other", now));
+        assert!(history.push("http://a", "Second", now));
+        // a title may come back later
+        assert!(history.push("http://a", "First", now));
+
+        let titles: Vec<_> = history
+            .get("http://a")
+            .titles
+            .into_iter()
+            .map(|v| v.title)
+            .collect();
+        assert_eq!(titles, ["First", "Second", "First"]);
+        assert_eq!(history.get("http://b").titles.len(), 1);
+        assert!(history.get("http://c").titles.is_empty());
+    }
+
+    #[test]
+    fn should_limit_titles_per_station() {
+        let mut history = RadioTitleHistory::default();
+        for i in 0..MAX_TITLES_PER_STATION + 10 {
+            history.push("http://a", &i.to_string(), SystemTime::now());
+        }
+
+        let titles = history.get("http://a").titles;
+        assert_eq!(titles.len(), MAX_TITLES_PER_STATION);
+        assert_eq!(titles[0].title, (MAX_TITLES_PER_STATION + 9).to_string());
+    }
+
+    #[test]
+    fn should_convert_to_protobuf_and_back() {
+        let info = RadioTitleInfo {
+            title: "Title".to_string(),
+            started_at: UNIX_EPOCH + Duration::from_secs(1_700_000_000),
+        };
+
+        let converted: RadioTitleInfo = super::protobuf::RadioTitle::from(info.clone()).into();
+        assert_eq!(converted, info);
+    }
+
+    #[test]
+    fn should_append_to_heard_list() {
+        let path = std::env::temp_dir().join(format!(
+            "termusic-heard-on-radio-{}.txt",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        let title = RadioTitleInfo {
+            title: "Artist - Title".to_string(),
+            started_at: SystemTime::now(),
+        };
+
+        save_heard_on_radio(&path, &title, "http://a").unwrap();
+        save_heard_on_radio(&path, &title, "http://b").unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        let lines: Vec<_> = content.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with("\tArtist - Title\thttp://a"));
+        assert!(lines[1].ends_with("\thttp://b"));
+    }
+}
@@ -0,0 +1,306 @@
+//! Search the community radio station directory at <https://www.radio-browser.info>.
+//!
+//! See <https://de1.api.radio-browser.info/> for the API documentation.
+
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use reqwest::{Client, ClientBuilder, StatusCode};
+use serde::Deserialize;
+
+/// The API endpoint, which resolves to any of the available servers
+pub const DEFAULT_API_URL: &str = "https://all.api.radio-browser.info";
+
+/// The maximal amount of stations requested at once
+const DEFAULT_LIMIT: u32 = 100;
+
+/// A search for stations, all set fields have to match
+///
+/// See [`StationQuery::parse`] for the text format used in the search input.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct StationQuery {
+    /// Part of the station name
+    pub name: Option<String>,
+    /// Part of one of the tags, like `jazz`
+    pub tag: Option<String>,
+    /// Part of the country name, like `Germany`
+    pub country: Option<String>,
+    /// The stream codec, like `MP3` or `AAC`
+    pub codec: Option<String>,
+}
+
+impl StationQuery {
+    /// Parse a search like `jazz tag:smooth country:germany codec:mp3`.
+    ///
+    /// Words with a `tag:`, `country:` or `codec:` prefix set the respective filter (`_` stands for a space),
+    /// all other words are the name.
+    pub fn parse(input: &str) -> Self {
+        let mut query = Self::default();
+        let mut name: Vec<&str> = Vec::new();
+
+        for word in input.split_whitespace() {
+            let (field, value) = match word.split_once(':') {
+                Some(("tag", value)) => (&mut query.tag, value),
+                Some(("country", value)) => (&mut query.country, value),
+                Some(("codec", value)) => (&mut query.codec, value),
+                _ => {
+                    name.push(word);
+                    continue;
+                }
+            };
+            if !value.is_empty() {
+                *field = Some(value.replace('_', " "));
+            }
+        }
+
+        if !name.is_empty() {
+            query.name = Some(name.join(" "));
+        }
+
+        query
+    }
+
+    /// Whether no filter is set
+    pub fn is_empty(&self) -> bool {
+        self.name.is_none() && self.tag.is_none() && self.country.is_none() && self.codec.is_none()
+    }
+
+    /// Get the url query parameters for the station search
+    fn as_query_pairs(&self) -> Vec<(&'static str, String)> {
+        let mut pairs = Vec::new();
+        let fields = [
+            ("name", &self.name),
+            ("tag", &self.tag),
+            ("country", &self.country),
+            ("codec", &self.codec),
+        ];
+        for (key, value) in fields {
+            if let Some(value) = value {
+                pairs.push((key, value.clone()));
+            }
+        }
+
+        pairs.push(("hidebroken", "true".to_string()));
+        pairs.push(("order", "clickcount".to_string()));
+        pairs.push(("reverse", "true".to_string()));
+        pairs.push(("limit", DEFAULT_LIMIT.to_string()));
+
+        pairs
+    }
+}
+
+/// A station as returned by the API, only with the fields used here
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct RadioStation {
+    #[serde(rename = "stationuuid")]
+    pub uuid: String,
+    pub name: String,
+    /// The url as added to the directory, may be a playlist file
+    pub url: String,
+    /// The stream url, with playlist files already resolved
+    #[serde(default)]
+    pub url_resolved: String,
+    /// Comma-separated list of tags
+    #[serde(default)]
+    pub tags: String,
+    #[serde(default)]
+    pub country: String,
+    #[serde(default)]
+    pub codec: String,
+    /// Bitrate in kbit/s, `0` if unknown
+    #[serde(default)]
+    pub bitrate: u32,
+    /// Url of the station icon
+    #[serde(default)]
+    pub favicon: String,
+}
+
+impl RadioStation {
+    /// Get the url to play the station with
+    pub fn stream_url(&self) -> &str {
+        if self.url_resolved.is_empty() {
+            &self.url
+        } else {
+            &self.url_resolved
+        }
+    }
+}
+
+/// Client for the radio-browser API
+#[derive(Debug, Clone)]
+pub struct RadioBrowser {
+    client: Client,
+    api_url: String,
+}
+
+impl RadioBrowser {
+    /// Create a client for the public API at [`DEFAULT_API_URL`]
+    pub fn new() -> Result<Self> {
+        Self::with_api_url(DEFAULT_API_URL)
+    }
+
+    /// Create a client for the API at `api_url`, without a trailing slash
+    pub fn with_api_url(api_url: &str) -> Result<Self> {
+        let client = ClientBuilder::new()
+            .timeout(Duration::from_secs(10))
+            // the api asks for a speaking user agent
+            .user_agent(format!("termusic/{}", crate::VERSION))
+            .build()?;
+
+        Ok(Self {
+            client,
+            api_url: api_url.to_string(),
+        })
+    }
+
+    /// Search for stations matching `query`, the most popular first
+    pub async fn search(&self, query: &StationQuery) -> Result<Vec<RadioStation>> {
+        if query.is_empty() {
+            bail!("Empty station search");
+        }
+
+        let url = format!("{}/json/stations/search", self.api_url);
+        let response = self
+            .client
+            .get(&url)
+            .query(&query.as_query_pairs())
+            .send()
+            .await
+            .with_context(|| url.clone())?;
+
+        match response.status() {
+            StatusCode::OK => Ok(response
+                .json()
+                .await
+                .context("parsing station search result")?),
+            status => bail!("Station search failed with status {status}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::thread::JoinHandle;
+
+    use super::{RadioBrowser, StationQuery};
+
+    /// Serve a single request with `status` and `body`, returning the request line
+    fn mock_server(status: &'static str, body: &'static str) -> (String, JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        let handle = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            // skip the headers
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+
+            let mut stream = reader.into_inner();
+            write!(
+                stream,
+                "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            )
+            .unwrap();
+
+            request_line.trim_end().to_string()
+        });
+
+        (url, handle)
+    }
+
+    const SEARCH_RESULT: &str = r#"[
+        {
+            "changeuuid": "c1",
+            "stationuuid": "s1",
+            "name": "Jazz Radio",
+            "url": "http://example.com/jazz.pls",
+            "url_resolved": "http://example.com/jazz.mp3",
+            "homepage": "http://example.com",
+            "favicon": "http://example.com/icon.png",
+            "tags": "jazz,smooth jazz",
+            "country": "Germany",
+            "codec": "MP3",
+            "bitrate": 128,
+            "votes": 10
+        },
+        {
+            "stationuuid": "s2",
+            "name": "Other",
+            "url": "http://example.com/other",
+            "url_resolved": "",
+            "bitrate": 0
+        }
+    ]"#;
+
+    #[test]
+    fn should_parse_query() {
+        assert_eq!(
+            StationQuery::parse("smooth  jazz tag:lounge country:united_kingdom codec:aac"),
+            StationQuery {
+                name: Some("smooth jazz".to_string()),
+                tag: Some("lounge".to_string()),
+                country: Some("united kingdom".to_string()),
+                codec: Some("aac".to_string()),
+            }
+        );
+        assert_eq!(
+            StationQuery::parse("tag:jazz"),
+            StationQuery {
+                tag: Some("jazz".to_string()),
+                ..Default::default()
+            }
+        );
+        assert!(StationQuery::parse("  tag: ").is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_search_stations() {
+        let (url, server) = mock_server("200 OK", SEARCH_RESULT);
+        let browser = RadioBrowser::with_api_url(&url).unwrap();
+
+        let stations = browser
+            .search(&StationQuery::parse("jazz country:germany"))
+            .await
+            .unwrap();
+
+        let request_line = server.join().unwrap();
+        assert!(
+            request_line.starts_with("GET /json/stations/search?name=jazz&country=germany&"),
+            "{request_line}"
+        );
+        assert!(request_line.contains("hidebroken=true"));
+
+        assert_eq!(stations.len(), 2);
+        assert_eq!(stations[0].name, "Jazz Radio");
+        assert_eq!(stations[0].bitrate, 128);
+        assert_eq!(stations[0].stream_url(), "http://example.com/jazz.mp3");
+        // fallback if the url could not be resolved
+        assert_eq!(stations[1].stream_url(), "http://example.com/other");
+    }
+
+    #[tokio::test]
+    async fn should_fail_on_error_status() {
+        let (url, server) = mock_server("503 Service Unavailable", "");
+        let browser = RadioBrowser::with_api_url(&url).unwrap();
+
+        let res = browser.search(&StationQuery::parse("jazz")).await;
+        server.join().unwrap();
+
+        assert!(res.is_err());
+    }
+
+    #[tokio::test]
+    async fn should_not_search_without_filter() {
+        let browser = RadioBrowser::with_api_url("http://127.0.0.1:1").unwrap();
+
+        assert!(browser.search(&StationQuery::default()).await.is_err());
+    }
+}
@@ -1,4 +1,9 @@
-use crate::library_db::const_unknown::{UNKNOWN_ARTIST, UNKNOWN_TITLE};
+use crate::config::v2::server::ReplayGainMode;
+use crate::library_db::const_unknown::{
+    UNKNOWN_ALBUM, UNKNOWN_ARTIST, UNKNOWN_GENRE, UNKNOWN_TITLE,
+};
+use crate::player::playlist_helpers::PlaylistTrackSource;
+use crate::player::TrackInfo;
 use crate::podcast::episode::Episode;
 /**
  * MIT License
@@ -25,16 +30,24 @@ use crate::podcast::episode::Episode;
  */
 use crate::songtag::lrc::Lyric;
 use crate::utils::get_parent_folder;
-use anyhow::{bail, Result};
+use anyhow::{bail, Context, Result};
 use id3::frame::Lyrics as Id3Lyrics;
-use lofty::config::WriteOptions;
+use lofty::aac::AacFile;
+use lofty::config::{ParseOptions, WriteOptions};
+use lofty::file::{FileType, TaggedFile};
+use lofty::id3::v2::{Frame, FrameFlags, FrameId, Id3v2Tag, PopularimeterFrame};
+use lofty::iff::aiff::AiffFile;
+use lofty::iff::wav::WavFile;
+use lofty::mpeg::MpegFile;
 use lofty::picture::{Picture, PictureType};
 use lofty::prelude::{Accessor, AudioFile, ItemKey, TagExt, TaggedFileExt};
-use lofty::tag::{ItemValue, Tag as LoftyTag, TagItem};
-use lofty::{file::FileType, probe::Probe};
+use lofty::probe::Probe;
+use lofty::tag::{ItemValue, MergeTag, SplitTag, Tag as LoftyTag, TagItem, TagType};
+use std::borrow::Cow;
 use std::convert::From;
 use std::ffi::OsStr;
-use std::fs::rename;
+use std::fs::{create_dir_all, rename, File};
+use std::io::BufReader;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::time::{Duration, SystemTime};
@@ -46,6 +59,14 @@ use std::time::{Duration, SystemTime};
 #[allow(unused)]
 pub const UNSUPPORTED: &str = "Unsupported?";
 
+/// The highest rating a track can have, in stars
+pub const MAX_RATING: u8 = 5;
+
+/// The email written into `ID3v2` `POPM` frames, which identifies the application that set the rating
+const POPM_EMAIL: &str = "termusic";
+/// The `ID3v2` frame id for ratings
+const POPM_ID: FrameId<'static> = FrameId::Valid(Cow::Borrowed("POPM"));
+
 /// Location types for a Track, could be a local file with [`LocationType::Path`] or a remote URI with [`LocationType::Uri`]
 #[derive(Clone, Debug, PartialEq)]
 pub enum LocationType {
@@ -83,14 +104,27 @@ pub struct Track {
     picture: Option<Picture>,
     album_photo: Option<String>,
     file_type: Option<FileType>,
-    // Date
-    // Track
+    /// Release year of the song
+    year: Option<u32>,
+    /// Position of the song on its disc
+    number: Option<u32>,
     genre: Option<String>,
-    // Composer
-    // Performer
-    // Disc
-    // Comment
+    /// Rating in stars from `0` to [`MAX_RATING`], `0` meaning unrated
+    rating: u8,
+    /// Rating as it was last read from or written to the file, to only write it when it changed
+    saved_rating: u8,
+    /// Artist of the whole album, may differ from the song artist on compilations
+    album_artist: Option<String>,
+    /// Composer of the song
+    composer: Option<String>,
+    /// Performer of the song, like the conductor or soloist of a classical recording
+    performer: Option<String>,
+    /// Disc of the album the song is on
+    disc_number: Option<u32>,
+    /// Free-form comment of the song
+    comment: Option<String>,
     pub podcast_localfile: Option<String>,
+    replay_gain: ReplayGain,
 }
 
 impl PartialEq for Track {
@@ -131,9 +165,19 @@ impl Track {
             picture: None,
             album_photo: ep.image_url.clone(),
             file_type: None,
+            year: None,
+            number: None,
             genre: None,
+            rating: 0,
+            saved_rating: 0,
+            album_artist: None,
+            composer: None,
+            performer: None,
+            disc_number: None,
+            comment: None,
             media_type: MediaType::Podcast,
             podcast_localfile,
+            replay_gain: ReplayGain::default(),
         }
     }
 
@@ -144,7 +188,7 @@ impl Track {
         let probe = Probe::open(path)?;
 
         let mut song = Self::new(LocationType::Path(path.to_path_buf()), MediaType::Music);
-        let tagged_file = match probe.read() {
+        let tagged_file = match read_tagged_file(probe) {
             Ok(v) => Some(v),
             Err(err) => {
                 warn!(
@@ -156,7 +200,7 @@ impl Track {
             }
         };
 
-        if let Some(mut tagged_file) = tagged_file {
+        if let Some((mut tagged_file, id3v2_rating)) = tagged_file {
             // We can at most get the duration and file type at this point
             let properties = tagged_file.properties();
             song.duration = properties.duration();
@@ -169,6 +213,11 @@ impl Track {
             } else {
                 warn!("File \"{}\" does not have any tags!", path.display());
             }
+
+            if let Some(rating) = id3v2_rating {
+                song.rating = rating;
+            }
+            song.saved_rating = song.rating;
         }
 
         // exit early if its for db only as no cover is needed there
@@ -203,6 +252,21 @@ impl Track {
         track.album = tag.album().map(std::borrow::Cow::into_owned);
         track.title = tag.title().map(std::borrow::Cow::into_owned);
         track.genre = tag.genre().map(std::borrow::Cow::into_owned);
+        track.year = tag.year();
+        track.number = tag.track();
+        track.disc_number = tag.disk();
+        track.album_artist = tag
+            .get_string(&ItemKey::AlbumArtist)
+            .map(ToString::to_string);
+        track.composer = tag.get_string(&ItemKey::Composer).map(ToString::to_string);
+        track.performer = tag
+            .get_string(&performer_key(tag.tag_type()))
+            .map(ToString::to_string);
+        track.comment = tag.comment().map(std::borrow::Cow::into_owned);
+        track.rating = tag
+            .get(&ItemKey::Popularimeter)
+            .and_then(|item| rating_from_value(item.value()))
+            .unwrap_or_default();
         track.media_type = MediaType::Music;
 
         if for_db {
@@ -228,6 +292,8 @@ impl Track {
 
         track.picture = picture;
 
+        track.replay_gain = ReplayGain::from_tag(tag);
+
         Ok(())
     }
 
@@ -240,6 +306,21 @@ impl Track {
         track
     }
 
+    /// Create a new track from only the given `source`, without reading any extra information.
+    ///
+    /// For use when the actual source cannot be read, but a track still needs to be represented.
+    pub fn new_from_source(source: &PlaylistTrackSource) -> Self {
+        match source {
+            PlaylistTrackSource::Path(path) => {
+                Self::new(LocationType::Path(PathBuf::from(path)), MediaType::Music)
+            }
+            PlaylistTrackSource::Url(url) => Self::new_radio(url),
+            PlaylistTrackSource::PodcastUrl(url) => {
+                Self::new(LocationType::Uri(url.clone()), MediaType::Podcast)
+            }
+        }
+    }
+
     fn new(location: LocationType, media_type: MediaType) -> Self {
         let duration = Duration::from_secs(0);
         let lyric_frames: Vec<Id3Lyrics> = Vec::new();
@@ -269,9 +350,19 @@ impl Track {
             picture: None,
             album_photo: None,
             last_modified,
+            year: None,
+            number: None,
             genre: None,
+            rating: 0,
+            saved_rating: 0,
+            album_artist: None,
+            composer: None,
+            performer: None,
+            disc_number: None,
+            comment: None,
             media_type,
             podcast_localfile: None,
+            replay_gain: ReplayGain::default(),
         }
     }
 
@@ -384,11 +475,84 @@ impl Track {
         self.genre.as_deref()
     }
 
+    /// Get the artist of the whole album, if set
+    pub fn album_artist(&self) -> Option<&str> {
+        self.album_artist.as_deref()
+    }
+
+    pub fn set_album_artist(&mut self, album_artist: &str) {
+        self.album_artist = Some(album_artist.to_string());
+    }
+
+    pub fn composer(&self) -> Option<&str> {
+        self.composer.as_deref()
+    }
+
+    pub fn set_composer(&mut self, composer: &str) {
+        self.composer = Some(composer.to_string());
+    }
+
+    pub fn performer(&self) -> Option<&str> {
+        self.performer.as_deref()
+    }
+
+    pub fn set_performer(&mut self, performer: &str) {
+        self.performer = Some(performer.to_string());
+    }
+
+    pub fn comment(&self) -> Option<&str> {
+        self.comment.as_deref()
+    }
+
+    pub fn set_comment(&mut self, comment: &str) {
+        self.comment = Some(comment.to_string());
+    }
+
+    pub const fn year(&self) -> Option<u32> {
+        self.year
+    }
+
+    pub fn set_year(&mut self, year: Option<u32>) {
+        self.year = year;
+    }
+
+    /// Get the position of the song on its disc
+    pub const fn track_number(&self) -> Option<u32> {
+        self.number
+    }
+
+    pub fn set_track_number(&mut self, track_number: Option<u32>) {
+        self.number = track_number;
+    }
+
+    pub const fn disc_number(&self) -> Option<u32> {
+        self.disc_number
+    }
+
+    pub fn set_disc_number(&mut self, disc_number: Option<u32>) {
+        self.disc_number = disc_number;
+    }
+
+    /// Get the replay gain values read from the tag, only available if not read `for_db`
+    pub fn replay_gain(&self) -> &ReplayGain {
+        &self.replay_gain
+    }
+
     #[allow(unused)]
     pub fn set_genre(&mut self, genre: &str) {
         self.genre = Some(genre.to_string());
     }
 
+    /// Get the rating in stars, `0` if unrated
+    pub const fn rating(&self) -> u8 {
+        self.rating
+    }
+
+    /// Set the rating in stars, limited to [`MAX_RATING`]
+    pub fn set_rating(&mut self, rating: u8) {
+        self.rating = rating.min(MAX_RATING);
+    }
+
     /// Optionally return the title of the song
     /// If `None` it wasn't able to read the tags
     pub fn title(&self) -> Option<&str> {
@@ -407,6 +571,40 @@ impl Track {
         }
     }
 
+    /// Get the [`PlaylistTrackSource`] to identify / re-create this track with.
+    ///
+    /// Paths which cannot be represented as UTF-8 are converted lossily, so that every track has a source.
+    pub fn as_track_source(&self) -> PlaylistTrackSource {
+        let file = match &self.location {
+            LocationType::Path(path_buf) => path_buf.to_string_lossy().to_string(),
+            LocationType::Uri(uri) => uri.clone(),
+        };
+
+        match self.media_type {
+            MediaType::Music => PlaylistTrackSource::Path(file),
+            MediaType::Podcast => PlaylistTrackSource::PodcastUrl(file),
+            MediaType::LiveRadio => PlaylistTrackSource::Url(file),
+        }
+    }
+
+    /// Get all the metadata a client may need to display this track, without having to read it itself.
+    pub fn as_track_info(&self) -> TrackInfo {
+        TrackInfo {
+            id: self.as_track_source(),
+            title: self.title().map(ToString::to_string),
+            artist: self.artist().map(ToString::to_string),
+            album: self.album().map(ToString::to_string),
+            genre: self.genre().map(ToString::to_string),
+            duration: Some(self.duration).filter(|v| !v.is_zero()),
+            has_cover: self.has_cover(),
+        }
+    }
+
+    /// Get whether there is cover art for this track, either embedded or as a file in the same directory
+    pub fn has_cover(&self) -> bool {
+        self.picture.is_some() || self.album_photo.is_some()
+    }
+
     /// Get the directory the track is in, if its a local file
     pub fn directory(&self) -> Option<&str> {
         if let LocationType::Path(path) = &self.location {
@@ -455,56 +653,201 @@ impl Track {
         }
     }
 
+    /// Write the tag values of this track to the file
+    ///
+    /// Only the items a track knows about are changed, all other items (like replay gain values or release ids)
+    /// and pictures of other types are kept as they are in the file.
     pub fn save_tag(&mut self) -> Result<()> {
-        if let Some(file_path) = self.file() {
-            let tag_type = match self.file_type {
-                Some(file_type) => file_type.primary_tag_type(),
-                None => return Ok(()),
-            };
+        let (LocationType::Path(path), Some(file_type)) = (&self.location, self.file_type) else {
+            return Ok(());
+        };
 
-            let mut tag = LoftyTag::new(tag_type);
+        if file_type.primary_tag_type() == TagType::Id3v2 {
+            // edit the ID3v2 tag directly, as the generic tag would drop all "POPM" and unsupported frames
+            let (remainder, mut tag) = read_id3v2(path, file_type)?.unwrap_or_default().split_tag();
             self.update_tag(&mut tag);
-
-            if !self.lyric_frames_is_empty() {
-                if let Some(lyric_frames) = self.lyric_frames() {
-                    for l in lyric_frames {
-                        tag.push(TagItem::new(ItemKey::Lyrics, ItemValue::Text(l.text)));
-                    }
+            let mut tag = remainder.merge_tag(tag);
+            if self.rating != self.saved_rating {
+                set_id3v2_rating(&mut tag, self.rating);
+            }
+            tag.save_to_path(path, WriteOptions::new())?;
+        } else {
+            let mut tagged_file = Probe::open(path)?.read()?;
+            let tag_type = tagged_file.primary_tag_type();
+            if tagged_file.primary_tag().is_none() {
+                tagged_file.insert_tag(LoftyTag::new(tag_type));
+            }
+            let Some(tag) = tagged_file.primary_tag_mut() else {
+                bail!("could not create a tag");
+            };
+            self.update_tag(tag);
+            if self.rating != self.saved_rating {
+                tag.remove_key(&ItemKey::Popularimeter);
+                if let Some(item) = rating_to_item(tag_type, self.rating)? {
+                    tag.push(item);
                 }
             }
+            tag.save_to_path(path, WriteOptions::new())?;
+        }
+        self.saved_rating = self.rating;
 
-            if let Some(any_picture) = self.picture().cloned() {
-                tag.push_picture(any_picture);
-            }
+        Ok(())
+    }
+
+    /// Set the rating and write only the rating to the file, leaving all other tag items as they are
+    ///
+    /// Does nothing but setting the value if this is not a local file
+    pub fn save_rating(&mut self, rating: u8) -> Result<()> {
+        self.set_rating(rating);
+        let LocationType::Path(path) = &self.location else {
+            return Ok(());
+        };
+
+        let probe = Probe::open(path)?;
+        if let Some(file_type) = probe
+            .file_type()
+            .filter(|file_type| file_type.primary_tag_type() == TagType::Id3v2)
+        {
+            // edit the ID3v2 tag directly, as the generic tag would drop all other "POPM" and unsupported frames
+            let mut tag = read_id3v2(path, file_type)?.unwrap_or_default();
+            set_id3v2_rating(&mut tag, self.rating);
+            tag.save_to_path(path, WriteOptions::new())?;
+            self.saved_rating = self.rating;
+            return Ok(());
+        }
 
-            tag.save_to_path(file_path, WriteOptions::new())?;
+        let mut tagged_file = probe.read()?;
+        let tag_type = tagged_file.primary_tag_type();
+        if tagged_file.primary_tag().is_none() {
+            tagged_file.insert_tag(LoftyTag::new(tag_type));
         }
+        let Some(tag) = tagged_file.primary_tag_mut() else {
+            bail!("could not create a tag");
+        };
+        tag.remove_key(&ItemKey::Popularimeter);
+        if let Some(item) = rating_to_item(tag_type, self.rating)? {
+            tag.push(item);
+        }
+        tag.save_to_path(path, WriteOptions::new())?;
+        self.saved_rating = self.rating;
 
-        self.rename_by_tag()?;
         Ok(())
     }
 
-    fn rename_by_tag(&mut self) -> Result<()> {
-        if let Some(ext) = self.ext() {
-            let new_name = format!(
-                "{}-{}.{}",
-                self.artist().unwrap_or(UNKNOWN_ARTIST),
-                self.title().unwrap_or(UNKNOWN_TITLE),
-                ext,
-            );
+    /// Move the file to the path given by `template`, creating missing directories
+    ///
+    /// A template without a `/` only renames the file in its current directory, otherwise the path is
+    /// relative to `base_dir` (usually the music directory the file is in). The file extension is kept.
+    ///
+    /// Placeholders are `{artist}`, `{albumartist}`, `{album}`, `{title}`, `{genre}`, `{composer}`,
+    /// `{year}`, `{track}` and `{disc}`, numbers can be padded with zeros like `{track:02}`.
+    ///
+    /// Returns the previous path if the file has been moved.
+    pub fn rename_by_template(
+        &mut self,
+        template: &str,
+        base_dir: &Path,
+    ) -> Result<Option<PathBuf>> {
+        let LocationType::Path(old_path) = &self.location else {
+            return Ok(None);
+        };
 
-            let new_name_path: &Path = Path::new(new_name.as_str());
-            if let Some(file) = self.file() {
-                let p_old: &Path = Path::new(file);
-                if let Some(p_prefix) = p_old.parent() {
-                    let p_new = p_prefix.join(new_name_path);
-                    rename(p_old, &p_new)?;
-                    self.location = LocationType::Path(p_new);
-                }
+        let mut new_name = self.format_template(template)?.into_os_string();
+        if let Some(ext) = old_path.extension() {
+            new_name.push(".");
+            new_name.push(ext);
+        }
+        let new_path = if template.contains('/') {
+            base_dir.join(new_name)
+        } else {
+            old_path.with_file_name(new_name)
+        };
+
+        if &new_path == old_path {
+            return Ok(None);
+        }
+        // on case-insensitive filesystems a rename that only changes the case "exists" already as the same file
+        if new_path.exists() && new_path.canonicalize().ok() != old_path.canonicalize().ok() {
+            bail!("\"{}\" already exists", new_path.display());
+        }
+        if let Some(parent) = new_path.parent() {
+            create_dir_all(parent)
+                .with_context(|| format!("create directory \"{}\"", parent.display()))?;
+        }
+        rename(old_path, &new_path)?;
+
+        match std::mem::replace(&mut self.location, LocationType::Path(new_path)) {
+            LocationType::Path(old_path) => Ok(Some(old_path)),
+            LocationType::Uri(_) => Ok(None),
+        }
+    }
+
+    /// Fill in the placeholders of the rename `template`, without the file extension
+    fn format_template(&self, template: &str) -> Result<PathBuf> {
+        let mut path = PathBuf::new();
+
+        for component in template.split('/') {
+            let mut name = String::new();
+            let mut rest = component;
+            while let Some(start) = rest.find('{') {
+                name.push_str(&rest[..start]);
+                let Some(len) = rest[start..].find('}') else {
+                    bail!("unclosed \"{{\" in rename template \"{template}\"");
+                };
+                name.push_str(&self.template_value(&rest[start + 1..start + len])?);
+                rest = &rest[start + len + 1..];
+            }
+            name.push_str(rest);
+
+            // skip components which are empty because of missing values, or which would leave the directory
+            let name = name.trim();
+            if !name.is_empty() && name != "." && name != ".." {
+                path.push(name);
             }
         }
 
-        Ok(())
+        if path.as_os_str().is_empty() {
+            bail!("rename template \"{template}\" results in a empty file name");
+        }
+
+        Ok(path)
+    }
+
+    /// Get the value for a `placeholder` (without braces) of a rename template
+    fn template_value(&self, placeholder: &str) -> Result<String> {
+        let (key, width) = match placeholder.split_once(':') {
+            Some((key, width)) => (
+                key,
+                Some(width.parse::<usize>().with_context(|| {
+                    format!("invalid width in rename template placeholder \"{{{placeholder}}}\"")
+                })?),
+            ),
+            None => (placeholder, None),
+        };
+
+        let non_empty = |v: Option<&str>| v.filter(|v| !v.is_empty()).map(ToString::to_string);
+        let value = match key {
+            "artist" => non_empty(self.artist()).unwrap_or_else(|| UNKNOWN_ARTIST.to_string()),
+            "albumartist" => non_empty(self.album_artist())
+                .or_else(|| non_empty(self.artist()))
+                .unwrap_or_else(|| UNKNOWN_ARTIST.to_string()),
+            "album" => non_empty(self.album()).unwrap_or_else(|| UNKNOWN_ALBUM.to_string()),
+            "title" => non_empty(self.title()).unwrap_or_else(|| UNKNOWN_TITLE.to_string()),
+            "genre" => non_empty(self.genre()).unwrap_or_else(|| UNKNOWN_GENRE.to_string()),
+            "composer" => non_empty(self.composer()).unwrap_or_default(),
+            "year" => self.year.map(|v| v.to_string()).unwrap_or_default(),
+            "track" => self.number.map(|v| v.to_string()).unwrap_or_default(),
+            "disc" => self.disc_number.map(|v| v.to_string()).unwrap_or_default(),
+            _ => bail!("unknown rename template placeholder \"{{{key}}}\""),
+        };
+
+        let value = match width {
+            Some(width) if !value.is_empty() => format!("{value:0>width$}"),
+            _ => value,
+        };
+
+        // values must not create directories on their own
+        Ok(value.replace(['/', '\\', '\0'], "_"))
     }
 
     pub fn set_lyric(&mut self, lyric_str: &str, lang_ext: &str) {
@@ -532,7 +875,8 @@ impl Track {
         self.picture = Some(picture);
     }
 
-    fn update_tag<T: Accessor>(&self, tag: &mut T) {
+    /// Change the items of `tag` this track knows about, keeping all others
+    fn update_tag(&self, tag: &mut LoftyTag) {
         tag.set_artist(
             self.artist()
                 .map_or_else(|| String::from(UNKNOWN_ARTIST), str::to_string),
@@ -543,11 +887,302 @@ impl Track {
                 .map_or_else(|| String::from(UNKNOWN_TITLE), str::to_string),
         );
 
-        tag.set_album(self.album().map_or_else(String::new, str::to_string));
-        tag.set_genre(self.genre().map_or_else(String::new, str::to_string));
+        match self.album().filter(|v| !v.is_empty()) {
+            Some(album) => tag.set_album(album.to_string()),
+            None => tag.remove_album(),
+        }
+        match self.genre().filter(|v| !v.is_empty()) {
+            Some(genre) => tag.set_genre(genre.to_string()),
+            None => tag.remove_genre(),
+        }
+        match self.comment().filter(|v| !v.is_empty()) {
+            Some(comment) => tag.set_comment(comment.to_string()),
+            None => tag.remove_comment(),
+        }
+
+        match self.year {
+            Some(year) => tag.set_year(year),
+            None => tag.remove_year(),
+        }
+        match self.number {
+            Some(track_number) => tag.set_track(track_number),
+            None => tag.remove_track(),
+        }
+        match self.disc_number {
+            Some(disc_number) => tag.set_disk(disc_number),
+            None => tag.remove_disk(),
+        }
+
+        for (key, value) in [
+            (ItemKey::AlbumArtist, self.album_artist()),
+            (ItemKey::Composer, self.composer()),
+            (performer_key(tag.tag_type()), self.performer()),
+        ] {
+            tag.remove_key(&key);
+            if let Some(value) = value.filter(|v| !v.is_empty()) {
+                // "insert_text" refuses keys without a generic mapping, like the performer in ID3v2
+                tag.push_unchecked(TagItem::new(key, ItemValue::Text(value.to_string())));
+            }
+        }
+
+        tag.remove_key(&ItemKey::Lyrics);
+        for lyric_frame in &self.lyric_frames {
+            let mut item = TagItem::new(ItemKey::Lyrics, ItemValue::Text(lyric_frame.text.clone()));
+            if let Ok(lang) = lyric_frame.lang.as_bytes().try_into() {
+                item.set_lang(lang);
+            }
+            item.set_description(lyric_frame.description.clone());
+            tag.push(item);
+        }
+
+        // only replace the picture of the same type if it was changed, other pictures (like the back cover) are kept
+        if let Some(picture) = self.picture().cloned() {
+            if !tag.pictures().contains(&picture) {
+                tag.remove_picture_type(picture.pic_type());
+                tag.push_picture(picture);
+            }
+        }
+    }
+}
+
+/// Replay gain values for one scope, like a track or a album
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GainValues {
+    /// Gain in dB
+    pub gain: f32,
+    /// Peak sample value, where `1.0` is full scale
+    pub peak: Option<f32>,
+}
+
+impl GainValues {
+    /// Get the linear factor to multiply samples with.
+    ///
+    /// If a peak is known, the factor is lowered so that the peak does not clip.
+    pub fn factor(&self) -> f32 {
+        let factor = 10f32.powf(self.gain / 20.0);
+
+        match self.peak {
+            Some(peak) if factor * peak > 1.0 => 1.0 / peak,
+            _ => factor,
+        }
+    }
+}
+
+/// Replay gain values of a track
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub struct ReplayGain {
+    /// Values for only this track
+    pub track: Option<GainValues>,
+    /// Values for the whole album this track is part of
+    pub album: Option<GainValues>,
+}
+
+impl ReplayGain {
+    /// Read the replay gain values from the given tag, ignoring invalid values
+    fn from_tag(tag: &LoftyTag) -> Self {
+        let values = |gain_key: &ItemKey, peak_key: &ItemKey| {
+            let gain = tag.get_string(gain_key).and_then(parse_gain)?;
+            let peak = tag.get_string(peak_key).and_then(parse_peak);
+
+            Some(GainValues { gain, peak })
+        };
+
+        Self {
+            track: values(&ItemKey::ReplayGainTrackGain, &ItemKey::ReplayGainTrackPeak),
+            album: values(&ItemKey::ReplayGainAlbumGain, &ItemKey::ReplayGainAlbumPeak),
+        }
+    }
+
+    /// Get the linear factor to apply for the given `mode`.
+    ///
+    /// Falls back to the other scope if the preferred one is not available, and to `1.0` if none is.
+    pub fn factor(&self, mode: ReplayGainMode) -> f32 {
+        let values = match mode {
+            ReplayGainMode::Off => None,
+            ReplayGainMode::Track => self.track.or(self.album),
+            ReplayGainMode::Album => self.album.or(self.track),
+        };
+
+        values.map_or(1.0, |v| v.factor())
+    }
+}
+
+/// Parse a gain value like `-6.54 dB`
+fn parse_gain(value: &str) -> Option<f32> {
+    let value = value.trim();
+    let value = value
+        .strip_suffix("dB")
+        .or_else(|| value.strip_suffix("db"))
+        .or_else(|| value.strip_suffix("DB"))
+        .unwrap_or(value);
+
+    value.trim().parse::<f32>().ok().filter(|v| v.is_finite())
+}
+
+/// Parse a peak value like `0.988547`
+fn parse_peak(value: &str) -> Option<f32> {
+    value
+        .trim()
+        .parse::<f32>()
+        .ok()
+        .filter(|v| v.is_finite() && *v > 0.0)
+}
+
+/// Get the key of the performer item, which `ID3v2` only has as a user defined `TXXX` frame
+fn performer_key(tag_type: TagType) -> ItemKey {
+    match tag_type {
+        TagType::Id3v2 => ItemKey::Unknown("PERFORMER".to_string()),
+        _ => ItemKey::Performer,
+    }
+}
+
+/// Read only the `ID3v2` tag of the file at `path`, for file types which have it as their primary tag
+fn read_id3v2(path: &Path, file_type: FileType) -> Result<Option<Id3v2Tag>> {
+    let mut file = File::open(path)?;
+    let options = ParseOptions::new().read_properties(false);
+
+    let tag = match file_type {
+        FileType::Mpeg => MpegFile::read_from(&mut file, options)?.id3v2().cloned(),
+        FileType::Wav => WavFile::read_from(&mut file, options)?.id3v2().cloned(),
+        FileType::Aiff => AiffFile::read_from(&mut file, options)?.id3v2().cloned(),
+        FileType::Aac => AacFile::read_from(&mut file, options)?.id3v2().cloned(),
+        _ => None,
+    };
+
+    Ok(tag)
+}
+
+/// Read the file like [`Probe::read`], but also get the rating from the `ID3v2` tag for file types which have it as their primary tag
+///
+/// lofty does not convert "POPM" frames into generic items, so they have to be read from the `ID3v2` tag itself.
+fn read_tagged_file(probe: Probe<BufReader<File>>) -> Result<(TaggedFile, Option<u8>)> {
+    let options = ParseOptions::new();
+    let (tagged_file, rating) = match probe.file_type() {
+        Some(FileType::Mpeg) => {
+            let file = MpegFile::read_from(&mut probe.into_inner(), options)?;
+            let rating = file.id3v2().and_then(rating_from_id3v2);
+            (file.into(), rating)
+        }
+        Some(FileType::Wav) => {
+            let file = WavFile::read_from(&mut probe.into_inner(), options)?;
+            let rating = file.id3v2().and_then(rating_from_id3v2);
+            (file.into(), rating)
+        }
+        Some(FileType::Aiff) => {
+            let file = AiffFile::read_from(&mut probe.into_inner(), options)?;
+            let rating = file.id3v2().and_then(rating_from_id3v2);
+            (file.into(), rating)
+        }
+        Some(FileType::Aac) => {
+            let file = AacFile::read_from(&mut probe.into_inner(), options)?;
+            let rating = file.id3v2().and_then(rating_from_id3v2);
+            (file.into(), rating)
+        }
+        _ => (probe.read()?, None),
+    };
+
+    Ok((tagged_file, rating))
+}
+
+/// Replace the "POPM" frame written by termusic with one for the given rating, or remove it if unrated
+///
+/// "POPM" frames of other applications are kept as they are.
+fn set_id3v2_rating(tag: &mut Id3v2Tag, stars: u8) {
+    tag.retain(|frame| !is_own_popm(frame));
+    if stars > 0 {
+        tag.insert(Frame::Popularimeter(PopularimeterFrame::new(
+            POPM_EMAIL.to_string(),
+            stars_to_popm(stars),
+            0,
+        )));
+    }
+}
+
+/// Check whether the frame is a "POPM" frame written by termusic
+fn is_own_popm(frame: &Frame<'_>) -> bool {
+    matches!(frame, Frame::Popularimeter(popm) if popm.email == POPM_EMAIL)
+}
+
+/// Get the rating in stars from the `ID3v2` tag, preferring the "POPM" frame written by termusic over the ones of other applications
+fn rating_from_id3v2(tag: &Id3v2Tag) -> Option<u8> {
+    let own = tag.into_iter().find(|frame| is_own_popm(frame));
+    match own.or_else(|| tag.get(&POPM_ID))? {
+        Frame::Popularimeter(popm) => Some(stars_from_popm(popm.rating)),
+        _ => None,
+    }
+}
+
+/// Get the rating in stars from a generic [`ItemKey::Popularimeter`] value
+///
+/// Text values may either be in stars or in percent, binary values are `ID3v2` "POPM" frames.
+fn rating_from_value(value: &ItemValue) -> Option<u8> {
+    match value {
+        ItemValue::Text(text) => stars_from_text(text),
+        ItemValue::Binary(bytes) => {
+            PopularimeterFrame::parse(&mut &bytes[..], FrameFlags::default())
+                .ok()
+                .map(|popm| stars_from_popm(popm.rating))
+        }
+        ItemValue::Locator(_) => None,
+    }
+}
+
+/// Create the [`ItemKey::Popularimeter`] item for the given tag type, `None` if the track is unrated
+fn rating_to_item(tag_type: TagType, stars: u8) -> Result<Option<TagItem>> {
+    if stars == 0 {
+        return Ok(None);
+    }
+
+    let value = if tag_type == TagType::Id3v2 {
+        let popm = PopularimeterFrame::new(POPM_EMAIL.to_string(), stars_to_popm(stars), 0);
+        ItemValue::Binary(popm.as_bytes()?)
+    } else {
+        ItemValue::Text(stars_to_text(stars))
+    };
+
+    Ok(Some(TagItem::new(ItemKey::Popularimeter, value)))
+}
+
+/// Convert a "POPM" rating (`1`-`255`, `0` unknown) to stars, using the common Windows Media Player ranges
+fn stars_from_popm(value: u8) -> u8 {
+    match value {
+        0 => 0,
+        1..=31 => 1,
+        32..=95 => 2,
+        96..=159 => 3,
+        160..=223 => 4,
+        224..=255 => 5,
+    }
+}
+
+/// Convert stars to the "POPM" rating other players write
+fn stars_to_popm(stars: u8) -> u8 {
+    match stars {
+        0 => 0,
+        1 => 1,
+        2 => 64,
+        3 => 128,
+        4 => 196,
+        _ => 255,
+    }
+}
+
+/// Parse a text rating, either in stars (`0`-`5`) or in percent (`0`-`100`)
+fn stars_from_text(text: &str) -> Option<u8> {
+    let value = text.trim().parse::<u8>().ok()?;
+
+    match value {
+        0..=MAX_RATING => Some(value),
+        6..=100 => Some((value + 10) / 20),
+        _ => None,
     }
 }
 
+/// Convert stars to a text rating in percent, which is what vorbis comments and mp4 use
+fn stars_to_text(stars: u8) -> String {
+    (u16::from(stars.min(MAX_RATING)) * 20).to_string()
+}
+
 fn create_lyrics(tag: &mut LoftyTag, lyric_frames: &mut Vec<Id3Lyrics>) {
     let lyrics = tag.take(&ItemKey::Lyrics);
     for lyric in lyrics {
@@ -560,3 +1195,523 @@ fn create_lyrics(tag: &mut LoftyTag, lyric_frames: &mut Vec<Id3Lyrics>) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        parse_gain, parse_peak, rating_from_value, rating_to_item, stars_from_popm,
+        stars_from_text, stars_to_popm, GainValues, ReplayGain,
+    };
+    use super::{read_id3v2, Track};
+    use crate::config::v2::server::ReplayGainMode;
+    use lofty::config::WriteOptions;
+    use lofty::file::FileType;
+    use lofty::id3::v2::{Frame, FrameId, Id3v2Tag, PopularimeterFrame, PrivateFrame};
+    use lofty::picture::{MimeType, Picture, PictureType};
+    use lofty::prelude::{Accessor, ItemKey, TagExt, TaggedFileExt};
+    use lofty::probe::Probe;
+    use lofty::tag::{ItemValue, Tag as LoftyTag, TagItem, TagType};
+    use std::borrow::Cow;
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn should_parse_replaygain_values() {
+        assert_eq!(parse_gain("-6.54 dB"), Some(-6.54));
+        assert_eq!(parse_gain("+2.10 dB"), Some(2.1));
+        assert_eq!(parse_gain("3"), Some(3.0));
+        assert_eq!(parse_gain("loud"), None);
+
+        assert_eq!(parse_peak("0.988547"), Some(0.988_547));
+        assert_eq!(parse_peak("0"), None);
+        assert_eq!(parse_peak("-1"), None);
+    }
+
+    #[test]
+    fn should_compute_replaygain_factor() {
+        let track = GainValues {
+            gain: -6.0,
+            peak: Some(0.9),
+        };
+        let album = GainValues {
+            gain: 6.0,
+            peak: Some(0.9),
+        };
+        let rg = ReplayGain {
+            track: Some(track),
+            album: Some(album),
+        };
+
+        assert!((rg.factor(ReplayGainMode::Off) - 1.0).abs() < f32::EPSILON);
+        assert!((rg.factor(ReplayGainMode::Track) - 0.501).abs() < 0.001);
+        // would be ~1.995, but is limited by the peak
+        assert!((rg.factor(ReplayGainMode::Album) - 1.0 / 0.9).abs() < f32::EPSILON);
+
+        // falls back to the track values
+        let rg = ReplayGain {
+            track: Some(track),
+            album: None,
+        };
+        assert!((rg.factor(ReplayGainMode::Album) - 0.501).abs() < 0.001);
+
+        assert!((ReplayGain::default().factor(ReplayGainMode::Album) - 1.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn should_convert_ratings() {
+        for stars in 0..=5 {
+            assert_eq!(stars_from_popm(stars_to_popm(stars)), stars);
+        }
+        assert_eq!(stars_from_popm(0), 0);
+        assert_eq!(stars_from_popm(200), 4);
+
+        assert_eq!(stars_from_text("3"), Some(3));
+        assert_eq!(stars_from_text("60"), Some(3));
+        assert_eq!(stars_from_text("100"), Some(5));
+        assert_eq!(stars_from_text("255"), None);
+        assert_eq!(stars_from_text("great"), None);
+    }
+
+    #[test]
+    fn should_roundtrip_rating_items() {
+        assert!(rating_to_item(TagType::VorbisComments, 0)
+            .unwrap()
+            .is_none());
+
+        for tag_type in [TagType::Id3v2, TagType::VorbisComments, TagType::Mp4Ilst] {
+            let item = rating_to_item(tag_type, 4).unwrap().unwrap();
+            assert_eq!(rating_from_value(item.value()), Some(4));
+        }
+
+        assert_eq!(
+            rating_from_value(&ItemValue::Text("80".to_string())),
+            Some(4)
+        );
+    }
+
+    /// Create an empty directory for sample files of the test `name`
+    fn sample_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("termusic-save-tag-{}-{name}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// A short mono 16-bit PCM WAV file with silence
+    fn sample_wav() -> Vec<u8> {
+        let data_len: u32 = 1600;
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(b"RIFF");
+        bytes.extend_from_slice(&(36 + data_len).to_le_bytes());
+        bytes.extend_from_slice(b"WAVEfmt ");
+        bytes.extend_from_slice(&16u32.to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes()); // PCM
+        bytes.extend_from_slice(&1u16.to_le_bytes()); // channels
+        bytes.extend_from_slice(&8000u32.to_le_bytes()); // sample rate
+        bytes.extend_from_slice(&16000u32.to_le_bytes()); // byte rate
+        bytes.extend_from_slice(&2u16.to_le_bytes()); // block align
+        bytes.extend_from_slice(&16u16.to_le_bytes()); // bits per sample
+        bytes.extend_from_slice(b"data");
+        bytes.extend_from_slice(&data_len.to_le_bytes());
+        bytes.resize(bytes.len() + data_len as usize, 0);
+        bytes
+    }
+
+    /// A FLAC file with only a "STREAMINFO" and a "PADDING" block and no audio frames
+    fn sample_flac() -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(b"fLaC");
+        // metadata block of type STREAMINFO, 34 bytes long
+        bytes.extend_from_slice(&[0, 0, 0, 34]);
+        // min block size
+        bytes.extend_from_slice(&4096u16.to_be_bytes());
+        // max block size
+        bytes.extend_from_slice(&4096u16.to_be_bytes());
+        // min and max frame size
+        bytes.extend_from_slice(&[0; 6]);
+        // 44100 Hz (20 bits), 2 channels (3 bits), 16 bits per sample (5 bits), 0 samples (36 bits)
+        bytes.extend_from_slice(&[0x0A, 0xC4, 0x42, 0xF0, 0, 0, 0, 0]);
+        // md5
+        bytes.extend_from_slice(&[0; 16]);
+        // last metadata block of type PADDING, 16 bytes long
+        bytes.extend_from_slice(&[0x81, 0, 0, 16]);
+        bytes.extend_from_slice(&[0; 16]);
+        bytes
+    }
+
+    /// An MP3 file of silent MPEG-1 Layer III frames (128 kbit/s, 44100 Hz)
+    fn sample_mp3() -> Vec<u8> {
+        let mut frame = vec![0xFF, 0xFB, 0x90, 0x00];
+        frame.resize(417, 0);
+        frame.repeat(10)
+    }
+
+    /// Create a tag with items termusic does not edit, and a back cover which should not be touched
+    fn sample_tag(tag_type: TagType) -> LoftyTag {
+        let mut tag = LoftyTag::new(tag_type);
+        tag.set_artist("Artist".to_string());
+        tag.set_title("Title".to_string());
+        tag.set_album("Album".to_string());
+        tag.set_year(1999);
+        tag.set_track(3);
+        tag.set_comment("Comment".to_string());
+        tag.insert_text(ItemKey::ReplayGainTrackGain, "-6.54 dB".to_string());
+        tag.insert_text(
+            ItemKey::MusicBrainzReleaseId,
+            "0d3cf5a0-7f4b-4a6e-9f1a-3a6f0a6c2b11".to_string(),
+        );
+        tag.push_picture(Picture::new_unchecked(
+            PictureType::CoverBack,
+            Some(MimeType::Png),
+            Some("Back".to_string()),
+            vec![1, 2, 3],
+        ));
+        // a second picture of the same type, which a track cannot represent
+        tag.push_picture(Picture::new_unchecked(
+            PictureType::CoverBack,
+            Some(MimeType::Png),
+            Some("Inlay".to_string()),
+            vec![10, 11, 12],
+        ));
+        if tag_type == TagType::Id3v2 {
+            // the rating and play counter of another application
+            tag.push(TagItem::new(
+                ItemKey::Popularimeter,
+                ItemValue::Binary(foreign_popm().as_bytes().unwrap()),
+            ));
+        }
+        tag
+    }
+
+    /// The "POPM" frame another application wrote into [`sample_tag`]
+    fn foreign_popm() -> PopularimeterFrame<'static> {
+        PopularimeterFrame::new("other@example.com".to_string(), 196, 42)
+    }
+
+    /// Change the title and front cover of the track at `path` with [`Track::save_tag`]
+    fn edit_title(path: &Path) {
+        let mut track = Track::read_from_path(path, false).unwrap();
+        track.set_title("Edited");
+        track.set_photo(Picture::new_unchecked(
+            PictureType::CoverFront,
+            Some(MimeType::Jpeg),
+            None,
+            vec![4, 5, 6],
+        ));
+        track.save_tag().unwrap();
+        assert_eq!(track.file(), path.to_str());
+    }
+
+    /// Check that the edit was saved and all other items are still the ones from [`sample_tag`]
+    fn assert_kept_items(path: &Path) {
+        let tagged_file = Probe::open(path).unwrap().read().unwrap();
+        let tag = tagged_file.primary_tag().unwrap();
+
+        assert_eq!(tag.title(), Some(Cow::Borrowed("Edited")));
+        assert_eq!(tag.artist(), Some(Cow::Borrowed("Artist")));
+        assert_eq!(tag.album(), Some(Cow::Borrowed("Album")));
+        assert_eq!(tag.year(), Some(1999));
+        assert_eq!(tag.track(), Some(3));
+        assert_eq!(tag.comment(), Some(Cow::Borrowed("Comment")));
+        assert_eq!(
+            tag.get_string(&ItemKey::ReplayGainTrackGain),
+            Some("-6.54 dB")
+        );
+        assert_eq!(
+            tag.get_string(&ItemKey::MusicBrainzReleaseId),
+            Some("0d3cf5a0-7f4b-4a6e-9f1a-3a6f0a6c2b11")
+        );
+
+        let mut pictures: Vec<_> = tag
+            .pictures()
+            .iter()
+            .map(|pic| (pic.pic_type(), pic.data().to_vec()))
+            .collect();
+        pictures.sort_by_key(|(pic_type, data)| (pic_type.as_u8(), data.clone()));
+        assert_eq!(
+            pictures,
+            vec![
+                (PictureType::CoverFront, vec![4, 5, 6]),
+                (PictureType::CoverBack, vec![1, 2, 3]),
+                (PictureType::CoverBack, vec![10, 11, 12])
+            ]
+        );
+
+        let file_type = tagged_file.file_type();
+        if file_type.primary_tag_type() == TagType::Id3v2 {
+            let id3v2 = read_id3v2(path, file_type).unwrap().unwrap();
+            let foreign = foreign_popm();
+            assert!(
+                id3v2
+                    .into_iter()
+                    .any(|frame| matches!(frame, Frame::Popularimeter(popm) if popm == foreign)),
+                "expected the foreign \"POPM\" frame to be kept"
+            );
+        }
+    }
+
+    #[test]
+    fn should_keep_unedited_items_in_flac() {
+        let dir = sample_dir("flac");
+        let path = dir.join("sample.flac");
+        std::fs::write(&path, sample_flac()).unwrap();
+        sample_tag(TagType::VorbisComments)
+            .save_to_path(&path, WriteOptions::new())
+            .unwrap();
+
+        edit_title(&path);
+        assert_kept_items(&path);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn should_keep_unedited_items_in_wav() {
+        let dir = sample_dir("wav");
+        let path = dir.join("sample.wav");
+        std::fs::write(&path, sample_wav()).unwrap();
+        sample_tag(TagType::Id3v2)
+            .save_to_path(&path, WriteOptions::new())
+            .unwrap();
+
+        edit_title(&path);
+        assert_kept_items(&path);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn should_keep_unsupported_frames_in_mp3() {
+        let dir = sample_dir("mp3");
+        let path = dir.join("sample.mp3");
+        std::fs::write(&path, sample_mp3()).unwrap();
+        sample_tag(TagType::Id3v2)
+            .save_to_path(&path, WriteOptions::new())
+            .unwrap();
+        // "PRIV" frames cannot be represented in a generic tag
+        let mut id3v2 = read_id3v2(&path, FileType::Mpeg).unwrap().unwrap();
+        id3v2.insert(Frame::Private(PrivateFrame::new(
+            "termusic-test".to_string(),
+            vec![7, 8, 9],
+        )));
+        id3v2.save_to_path(&path, WriteOptions::new()).unwrap();
+
+        let mut track = Track::read_from_path(&path, true).unwrap();
+        track.save_rating(3).unwrap();
+        edit_title(&path);
+        assert_kept_items(&path);
+
+        let id3v2 = read_id3v2(&path, FileType::Mpeg).unwrap().unwrap();
+        match id3v2.get(&FrameId::Valid(Cow::Borrowed("PRIV"))) {
+            Some(Frame::Private(frame)) => assert_eq!(frame.private_data, vec![7, 8, 9]),
+            frame => panic!("expected the private frame to be kept, got {frame:?}"),
+        }
+        assert_eq!(Track::read_from_path(&path, true).unwrap().rating(), 3);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn should_only_replace_own_popm_frame() {
+        let dir = sample_dir("popm");
+        let path = dir.join("sample.mp3");
+        std::fs::write(&path, sample_mp3()).unwrap();
+        let foreign = PopularimeterFrame::new("other@example.com".to_string(), 196, 42);
+        let mut id3v2 = Id3v2Tag::new();
+        id3v2.insert(Frame::Popularimeter(foreign.clone()));
+        id3v2.save_to_path(&path, WriteOptions::new()).unwrap();
+        let popm_frames = |path: &Path| -> Vec<PopularimeterFrame<'static>> {
+            read_id3v2(path, FileType::Mpeg)
+                .unwrap()
+                .unwrap()
+                .into_iter()
+                .filter_map(|frame| match frame {
+                    Frame::Popularimeter(popm) => Some(popm.clone()),
+                    _ => None,
+                })
+                .collect()
+        };
+
+        // the rating of other applications is used, but not written back if unchanged
+        let mut track = Track::read_from_path(&path, true).unwrap();
+        assert_eq!(track.rating(), 4);
+        track.set_title("Edited");
+        track.save_tag().unwrap();
+        assert_eq!(popm_frames(&path), vec![foreign.clone()]);
+
+        track.save_rating(2).unwrap();
+        let own = PopularimeterFrame::new("termusic".to_string(), stars_to_popm(2), 0);
+        assert_eq!(popm_frames(&path), vec![foreign.clone(), own]);
+        assert_eq!(Track::read_from_path(&path, true).unwrap().rating(), 2);
+
+        track.save_rating(0).unwrap();
+        assert_eq!(popm_frames(&path), vec![foreign]);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn should_keep_unedited_pictures() {
+        let dir = sample_dir("pictures");
+        let path = dir.join("sample.flac");
+        std::fs::write(&path, sample_flac()).unwrap();
+        let mut tag = sample_tag(TagType::VorbisComments);
+        for data in [vec![4, 5, 6], vec![7, 8, 9]] {
+            tag.push_picture(Picture::new_unchecked(
+                PictureType::CoverFront,
+                Some(MimeType::Png),
+                None,
+                data,
+            ));
+        }
+        tag.save_to_path(&path, WriteOptions::new()).unwrap();
+
+        let mut track = Track::read_from_path(&path, false).unwrap();
+        track.set_title("Edited");
+        track.save_tag().unwrap();
+
+        let tagged_file = Probe::open(&path).unwrap().read().unwrap();
+        let tag = tagged_file.primary_tag().unwrap();
+        assert_eq!(tag.title(), Some(Cow::Borrowed("Edited")));
+        let front_covers: Vec<_> = tag
+            .pictures()
+            .iter()
+            .filter(|pic| pic.pic_type() == PictureType::CoverFront)
+            .map(|pic| pic.data().to_vec())
+            .collect();
+        assert_eq!(front_covers, vec![vec![4, 5, 6], vec![7, 8, 9]]);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn should_remove_cleared_items() {
+        let dir = sample_dir("cleared");
+        let path = dir.join("sample.flac");
+        std::fs::write(&path, sample_flac()).unwrap();
+        sample_tag(TagType::VorbisComments)
+            .save_to_path(&path, WriteOptions::new())
+            .unwrap();
+
+        let mut track = Track::read_from_path(&path, false).unwrap();
+        track.set_album("");
+        track.set_year(None);
+        track.save_tag().unwrap();
+
+        let tagged_file = Probe::open(&path).unwrap().read().unwrap();
+        let tag = tagged_file.primary_tag().unwrap();
+        assert_eq!(tag.album(), None);
+        assert_eq!(tag.year(), None);
+        assert_eq!(tag.track(), Some(3));
+        assert_eq!(tag.comment(), Some(Cow::Borrowed("Comment")));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn should_save_performer_and_comment() {
+        let dir = sample_dir("performer");
+        let path = dir.join("sample.mp3");
+        std::fs::write(&path, sample_mp3()).unwrap();
+        sample_tag(TagType::Id3v2)
+            .save_to_path(&path, WriteOptions::new())
+            .unwrap();
+
+        let mut track = Track::read_from_path(&path, false).unwrap();
+        assert_eq!(track.comment(), Some("Comment"));
+        assert_eq!(track.performer(), None);
+        track.set_performer("Orchestra");
+        track.set_comment("Live");
+        track.save_tag().unwrap();
+
+        let track = Track::read_from_path(&path, false).unwrap();
+        assert_eq!(track.performer(), Some("Orchestra"));
+        assert_eq!(track.comment(), Some("Live"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn should_have_a_track_source_for_non_utf8_paths() {
+        use crate::player::playlist_helpers::PlaylistTrackSource;
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let track = Track::new(
+            super::LocationType::Path(PathBuf::from(OsStr::from_bytes(b"/music/a\xff.mp3"))),
+            super::MediaType::Music,
+        );
+
+        assert_eq!(track.file(), None);
+        assert_eq!(
+            track.as_track_source(),
+            PlaylistTrackSource::Path("/music/a\u{FFFD}.mp3".to_string())
+        );
+    }
+
+    #[test]
+    fn should_rename_by_template() {
+        let dir = sample_dir("rename");
+        let path = dir.join("sample.flac");
+        std::fs::write(&path, sample_flac()).unwrap();
+        sample_tag(TagType::VorbisComments)
+            .save_to_path(&path, WriteOptions::new())
+            .unwrap();
+
+        let mut track = Track::read_from_path(&path, true).unwrap();
+        track.set_artist("AC/DC");
+        assert_eq!(
+            track.rename_by_template("{artist}-{title}", &dir).unwrap(),
+            Some(path.clone())
+        );
+        let renamed = dir.join("AC_DC-Title.flac");
+        assert_eq!(track.file(), renamed.to_str());
+        assert!(renamed.exists());
+        assert!(!path.exists());
+
+        // renaming to the same path does nothing
+        assert_eq!(
+            track.rename_by_template("{artist}-{title}", &dir).unwrap(),
+            None
+        );
+
+        track.set_album_artist("Various");
+        let template = "{albumartist}/{year} - {album}/{disc}{track:02} {title}";
+        assert_eq!(
+            track.rename_by_template(template, &dir).unwrap(),
+            Some(renamed.clone())
+        );
+        let moved = dir
+            .join("Various")
+            .join("1999 - Album")
+            .join("03 Title.flac");
+        assert_eq!(track.file(), moved.to_str());
+        assert!(moved.exists());
+        assert!(!renamed.exists());
+
+        assert!(track.rename_by_template("{artist", &dir).is_err());
+        assert!(track.rename_by_template("{bpm}", &dir).is_err());
+        assert!(track.rename_by_template("{track:x}", &dir).is_err());
+        assert!(track.rename_by_template("{composer}/..", &dir).is_err());
+        assert!(moved.exists());
+
+        // only changing the case works, even on case-insensitive filesystems
+        track.set_title("title");
+        assert_eq!(
+            track.rename_by_template(template, &dir).unwrap(),
+            Some(moved.clone())
+        );
+        let lowercase = moved.with_file_name("03 title.flac");
+        assert_eq!(track.file(), lowercase.to_str());
+        assert!(lowercase.exists());
+
+        // another existing file is not overwritten
+        std::fs::write(moved.with_file_name("03 Other.flac"), []).unwrap();
+        track.set_title("Other");
+        assert!(track.rename_by_template(template, &dir).is_err());
+        assert!(lowercase.exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -3,6 +3,7 @@ use std::sync::Arc;
 use crate::config::v2::tui::{keys::KeyBinding, theme::styles::ColorTermusic};
 use crate::invidious::{Instance, YoutubeVideo};
 use crate::podcast::{EpData, PodcastFeed, PodcastNoId};
+use crate::radio_browser::RadioStation;
 use crate::songtag::SongTag;
 use anyhow::{anyhow, Result};
 use image::DynamicImage;
@@ -23,6 +24,7 @@ pub enum Msg {
     LayoutTreeView,
     LayoutDataBase,
     LayoutPodCast,
+    LayoutRadio,
     Library(LIMsg),
     LyricMessage(LyricMsg),
     LyricCycle,
@@ -40,6 +42,8 @@ pub enum Msg {
     QuitPopupCloseCancel,
     QuitPopupCloseOk,
     QuitPopupShow,
+    RadioSearch(RSMsg),
+    RadioStation(RLMsg),
     SavePlaylistPopupShow,
     SavePlaylistPopupCloseCancel,
     SavePlaylistPopupUpdate(String),
@@ -119,6 +123,12 @@ pub enum DLMsg {
 pub enum LyricMsg {
     LyricTextAreaBlurUp,
     LyricTextAreaBlurDown,
+    /// Copy the radio title at the given line to the clipboard
+    /// `(line)`
+    RadioTitleCopy(usize),
+    /// Save the radio title at the given line to the "heard on radio" list
+    /// `(line)`
+    RadioTitleSave(usize),
 }
 
 #[derive(Clone, PartialEq, Eq, Debug)]
@@ -208,6 +218,12 @@ pub enum ConfigEditorMsg {
     PlayerUseDiscordBlurUp,
     PlayerPortBlurDown,
     PlayerPortBlurUp,
+    EqualizerPresetBlurDown,
+    EqualizerPresetBlurUp,
+    /// The selected equalizer preset changed, the band editor needs to be updated
+    EqualizerPresetChanged,
+    EqualizerBandsBlurDown,
+    EqualizerBandsBlurUp,
 
     CurrentlyPlayingTrackSymbolBlurDown,
     CurrentlyPlayingTrackSymbolBlurUp,
@@ -385,6 +401,10 @@ pub enum DBMsg {
     SearchTrack(usize),
     SearchTracksBlurDown,
     SearchTracksBlurUp,
+    RatingUp(usize),
+    RatingDown(usize),
+    /// Open the batch tag editor on all tracks in the Database view "Tracks" section
+    OpenTagEditor,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -457,6 +477,10 @@ pub enum PLMsg {
     AddRandomAlbum,
     /// Start choosing random tracks to be added to the playlist
     AddRandomTracks,
+    /// Rate the track at INDEX one star higher
+    RatingUp(usize),
+    /// Rate the track at INDEX one star lower
+    RatingDown(usize),
 }
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum GSMsg {
@@ -500,6 +524,53 @@ pub enum YSMsg {
     /// `(ErrorAsString)`
     YoutubeSearchFail(String),
 }
+/// Messages for the radio station search
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RSMsg {
+    InputPopupShow,
+    InputPopupCloseCancel,
+    InputPopupCloseOk(String),
+    TablePopupCloseCancel,
+    /// Add the station at the index to the playlist
+    TablePopupCloseOk(usize),
+    /// Save the station at the index to the radio station library
+    TablePopupSave(usize),
+
+    /// The station search was a success, with all found stations.
+    SearchSuccess(Vec<RadioStation>),
+    /// Indicates that the station search has failed, with error message.
+    ///
+    /// `(ErrorAsString)`
+    SearchFail(String),
+}
+
+/// Messages for the saved radio stations (radio library)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RLMsg {
+    StationsBlur,
+    /// Add the station at the index to the playlist
+    AddPlaylist(usize),
+    /// Open the form to add a new station
+    AddPopupShow,
+    /// Open the form to edit the station at the index
+    EditPopupShow(usize),
+    EditPopupCloseCancel,
+    EditPopupCloseOk(RadioStationFields),
+    /// Ask to delete the station at the index
+    DeleteShow(usize),
+    DeleteCloseCancel,
+    DeleteCloseOk,
+}
+
+/// The user-editable values of a saved radio station
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct RadioStationFields {
+    pub name: String,
+    pub url: String,
+    pub genre: String,
+    pub favicon: String,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum TEMsg {
     TagEditorRun(String),
@@ -511,6 +582,8 @@ pub enum TEMsg {
     TERename,
     TESearch,
     TESelectLyricOk(usize),
+    /// Number the tracks of the batch tag editor in the listed order
+    TEBatchNumber,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -525,10 +598,26 @@ pub enum TFMsg {
     InputAlbumBlurUp,
     InputGenreBlurDown,
     InputGenreBlurUp,
+    InputAlbumArtistBlurDown,
+    InputAlbumArtistBlurUp,
+    InputYearBlurDown,
+    InputYearBlurUp,
+    InputTrackNumberBlurDown,
+    InputTrackNumberBlurUp,
+    InputDiscNumberBlurDown,
+    InputDiscNumberBlurUp,
+    InputComposerBlurDown,
+    InputComposerBlurUp,
+    InputPerformerBlurDown,
+    InputPerformerBlurUp,
+    InputCommentBlurDown,
+    InputCommentBlurUp,
     SelectLyricBlurDown,
     SelectLyricBlurUp,
     TableLyricOptionsBlurDown,
     TableLyricOptionsBlurUp,
+    TableBatchBlurDown,
+    TableBatchBlurUp,
     TextareaLyricBlurDown,
     TextareaLyricBlurUp,
 }
@@ -560,6 +649,11 @@ pub enum Id {
     FeedDeleteConfirmInputPopup,
     Progress,
     QuitPopup,
+    RadioSearchInputPopup,
+    RadioSearchTablePopup,
+    RadioStationDeleteConfirmPopup,
+    RadioStationEditPopup,
+    RadioStations,
     SavePlaylistPopup,
     SavePlaylistLabel,
     SavePlaylistConfirm,
@@ -576,9 +670,18 @@ pub enum IdTagEditor {
     InputTitle,
     InputAlbum,
     InputGenre,
+    InputAlbumArtist,
+    InputYear,
+    InputTrackNumber,
+    InputDiscNumber,
+    InputComposer,
+    InputPerformer,
+    InputComment,
     SelectLyric,
     TableLyricOptions,
     TextareaLyric,
+    /// Tracks of the batch tag editor
+    TableBatch,
 }
 
 #[derive(Debug, Eq, PartialEq, Clone, Copy, Hash)]
@@ -605,6 +708,8 @@ pub enum IdConfigEditor {
     LyricLabel,
 
     MusicDir,
+    EqualizerBands,
+    EqualizerPreset,
     PlayerPort,
     PlayerUseDiscord,
     PlayerUseMpris,
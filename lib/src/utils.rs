@@ -2,6 +2,7 @@ use anyhow::{anyhow, Context, Result};
 use pinyin::ToPinyin;
 use rand::Rng;
 use std::borrow::Cow;
+use std::cmp::Ordering;
 use std::path::{Path, PathBuf};
 use std::process::Stdio;
 use std::{
@@ -86,6 +87,17 @@ pub fn get_app_config_path() -> Result<PathBuf> {
     Ok(path)
 }
 
+/// Get the cache directory of the app and create it if not existing
+pub fn get_app_cache_path() -> Result<PathBuf> {
+    let mut path = dirs::cache_dir().ok_or_else(|| anyhow!("failed to find os cache dir."))?;
+    path.push("termusic");
+
+    if !path.exists() {
+        std::fs::create_dir_all(&path)?;
+    }
+    Ok(path)
+}
+
 /// Get the podcast directoy resolved and created
 fn get_podcast_save_path(config: &ServerOverlay) -> Result<PathBuf> {
     let full_path = shellexpand::path::tilde(&config.settings.podcast.download_dir);
@@ -256,6 +268,50 @@ pub fn random_ascii(len: usize) -> String {
 ///
 /// Source: <https://internals.rust-lang.org/t/suggestion-for-helper-for-writing-fmt-debug-impls-with-nested-structure/19477/2>
 ///
+/// Compare `a` and `b` with runs of ascii digits compared by their numeric value, so that `"2 x"` is before `"10 x"`
+#[must_use]
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let mut a = a.chars().peekable();
+    let mut b = b.chars().peekable();
+    loop {
+        let (Some(&char_a), Some(&char_b)) = (a.peek(), b.peek()) else {
+            return a.peek().is_some().cmp(&b.peek().is_some());
+        };
+        if char_a.is_ascii_digit() && char_b.is_ascii_digit() {
+            let run_a = take_digits(&mut a);
+            let run_b = take_digits(&mut b);
+            let ordering = run_a
+                .trim_start_matches('0')
+                .len()
+                .cmp(&run_b.trim_start_matches('0').len())
+                .then_with(|| {
+                    run_a
+                        .trim_start_matches('0')
+                        .cmp(run_b.trim_start_matches('0'))
+                })
+                .then_with(|| run_a.len().cmp(&run_b.len()));
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+        } else {
+            if char_a != char_b {
+                return char_a.cmp(&char_b);
+            }
+            a.next();
+            b.next();
+        }
+    }
+}
+
+/// Consume the run of ascii digits at the start of `chars`
+fn take_digits(chars: &mut std::iter::Peekable<std::str::Chars<'_>>) -> String {
+    let mut digits = String::new();
+    while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+        digits.push(digit);
+    }
+    digits
+}
+
 /// Example:
 /// ```
 /// # use std::fmt::Display;
@@ -319,6 +375,20 @@ mod tests {
         assert_eq!("陈一发儿".substr(3, 1), "儿");
     }
 
+    #[test]
+    fn should_compare_naturally() {
+        assert_eq!(natural_cmp("2 x", "10 x"), Ordering::Less);
+        assert_eq!(natural_cmp("10 x", "9 x"), Ordering::Greater);
+        assert_eq!(natural_cmp("track 02", "track 2"), Ordering::Greater);
+        assert_eq!(natural_cmp("a", "a1"), Ordering::Less);
+        assert_eq!(natural_cmp("b1", "a2"), Ordering::Greater);
+        assert_eq!(natural_cmp("a10b", "a10b"), Ordering::Equal);
+
+        let mut files = vec!["10 x", "2 x", "1 x", "disc2", "disc10"];
+        files.sort_by(|a, b| natural_cmp(a, b));
+        assert_eq!(files, ["1 x", "2 x", "10 x", "disc2", "disc10"]);
+    }
+
     #[test]
     fn display_with_to_string() {
         fn nested() -> impl Display {
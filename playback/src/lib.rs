@@ -40,19 +40,26 @@ pub mod playlist;
 
 use anyhow::{Context, Result};
 use async_trait::async_trait;
-pub use playlist::{Playlist, Status};
-use serde::{Deserialize, Serialize};
-use std::time::Duration;
+use parking_lot::{Mutex, RwLock};
+pub use playlist::{Playlist, PlaylistEditError, Status};
+pub use rusty_backend::output_device_names;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 use termusiclib::config::v2::server::config_extra::ServerConfigVersionedDefaulted;
 use termusiclib::config::{new_shared_server_settings, ServerOverlay, SharedServerSettings};
 use termusiclib::library_db::DataBase;
-use termusiclib::player::{PlayerProgress, PlayerTimeUnit, TrackChangedInfo, UpdateEvents};
+use termusiclib::player::playlist_helpers::{
+    PlaylistAddTrack, PlaylistPlaySpecific, PlaylistRemoveTrackIndexed, PlaylistSwapTrack,
+};
+use termusiclib::player::{
+    PlayerProgress, PlayerTimeUnit, SleepTimerKind, TrackChangedInfo, UpdateEvents,
+};
 use termusiclib::podcast::db::Database as DBPod;
 use termusiclib::track::{MediaType, Track};
 use termusiclib::utils::get_app_config_path;
 use tokio::runtime::Handle;
-use tokio::sync::broadcast;
 use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
+use tokio::sync::{broadcast, oneshot};
 
 #[macro_use]
 extern crate log;
@@ -153,7 +160,7 @@ impl Backend {
     }
 }
 
-#[derive(Clone, Serialize, Deserialize, Debug)]
+#[derive(Clone, Debug)]
 pub enum PlayerCmd {
     AboutToFinish,
     CycleLoop,
@@ -163,13 +170,33 @@ pub enum PlayerCmd {
     SkipPrevious,
     Pause,
     Play,
+    PlaylistAddTrack(PlaylistAddTrack, PlaylistEditReply),
+    PlaylistClear,
+    PlaylistPlaySpecific(PlaylistPlaySpecific, PlaylistEditReply),
+    PlaylistRemoveDeletedTracks,
+    PlaylistRemoveTrack(PlaylistRemoveTrackIndexed, PlaylistEditReply),
+    PlaylistShuffle,
+    PlaylistSwapTrack(PlaylistSwapTrack, PlaylistEditReply),
     ProcessID,
     Quit,
     ReloadConfig,
     ReloadPlaylist,
     SeekBackward,
     SeekForward,
+    SeekTo(Duration),
+    /// Switch the equalizer to the given preset, [`None`] to disable it
+    SetEqualizerPreset(Option<String>),
+    /// Switch to the output device with the given name, [`None`] for the default device.
+    ///
+    /// Replies with the device actually opened, see [`GeneralPlayer::output_device`]
+    SetOutputDevice(Option<String>, PlayerCmdReply<Option<String>>),
+    /// The backend opened a output device, [`None`] for the default device
+    OutputDeviceOpened(Option<String>),
+    SetSpeed(Speed),
+    SetVolume(Volume),
     SkipNext,
+    SleepTimerCancel,
+    SleepTimerSet(SleepTimerKind),
     SpeedDown,
     SpeedUp,
     Tick,
@@ -179,12 +206,53 @@ pub enum PlayerCmd {
     VolumeUp,
 }
 
+/// Send the outcome of a [`PlayerCmd`] back to whoever requested it, if anyone is waiting for it.
+///
+/// Clones share the same channel, only the first reply is delivered.
+#[derive(Debug)]
+pub struct PlayerCmdReply<T>(Option<Arc<Mutex<Option<oneshot::Sender<T>>>>>);
+
+impl<T> PlayerCmdReply<T> {
+    /// Create a new reply channel, the receiver gets the reply once the command is handled
+    #[must_use]
+    pub fn new() -> (Self, oneshot::Receiver<T>) {
+        let (tx, rx) = oneshot::channel();
+        (Self(Some(Arc::new(Mutex::new(Some(tx))))), rx)
+    }
+
+    /// Send the reply, does nothing if no one is waiting or a reply has already been sent
+    pub fn send(&self, value: T) {
+        if let Some(tx) = self.0.as_ref().and_then(|v| v.lock().take()) {
+            let _ = tx.send(value);
+        }
+    }
+}
+
+/// No one is waiting for the reply
+impl<T> Default for PlayerCmdReply<T> {
+    fn default() -> Self {
+        Self(None)
+    }
+}
+
+// not derived, as that would require "T: Clone"
+impl<T> Clone for PlayerCmdReply<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+/// Reply to a playlist edit, whether it has been applied
+pub type PlaylistEditReply = PlayerCmdReply<Result<(), PlaylistEditError>>;
+
 pub type StreamTX = broadcast::Sender<UpdateEvents>;
+/// The [`Playlist`], shared between the player and anything else that needs to read it (like the grpc service)
+pub type SharedPlaylist = Arc<RwLock<Playlist>>;
 
 #[allow(clippy::module_name_repetitions)]
 pub struct GeneralPlayer {
     pub backend: Backend,
-    pub playlist: Playlist,
+    pub playlist: SharedPlaylist,
     pub config: SharedServerSettings,
     pub current_track_updated: bool,
     pub mpris: Option<mpris::Mpris>,
@@ -213,10 +281,15 @@ impl GeneralPlayer {
         let db_path = get_app_config_path().with_context(|| "failed to get podcast db path.")?;
 
         let db_podcast = DBPod::new(&db_path).with_context(|| "error connecting to podcast db.")?;
-        let db = DataBase::new(&config)?;
+        let mut db = DataBase::new(&config)?;
 
         let config = new_shared_server_settings(config);
-        let playlist = Playlist::new(&config).unwrap_or_default();
+        let playlist =
+            Playlist::new(&config, Some(stream_tx.clone()), &mut db).unwrap_or_else(|err| {
+                error!("Error loading the playlist, using a empty one: {err:#}");
+                Playlist::new_empty(&config, Some(stream_tx.clone()))
+            });
+        let playlist = Arc::new(RwLock::new(playlist));
         let mpris = if config.read().settings.player.use_mediacontrols {
             Some(mpris::Mpris::new(cmd_tx.clone()))
         } else {
@@ -271,7 +344,7 @@ impl GeneralPlayer {
             // start mpris if new config has it enabled, but is not active yet
             let mut mpris = mpris::Mpris::new(self.cmd_tx.clone());
             // actually set the metadata of the currently playing track, otherwise the controls will work but no title or coverart will be set until next track
-            if let Some(track) = self.playlist.current_track() {
+            if let Some(track) = self.playlist.read().current_track() {
                 mpris.add_and_play(track);
             }
             // the same for volume
@@ -287,7 +360,7 @@ impl GeneralPlayer {
             let mut discord = discord::Rpc::default();
 
             // actually set the metadata of the currently playing track, otherwise the controls will work but no title or coverart will be set until next track
-            if let Some(track) = self.playlist.current_track() {
+            if let Some(track) = self.playlist.read().current_track() {
                 discord.update(track);
             }
 
@@ -297,11 +370,77 @@ impl GeneralPlayer {
             self.discord.take();
         }
 
+        #[allow(irrefutable_let_patterns)]
+        if let Backend::Rusty(ref mut backend) = self.backend {
+            backend.set_replaygain(config.settings.player.replaygain);
+            backend.set_crossfade(config.settings.player.crossfade_ms);
+            backend.set_stream_cache_max_size(config.settings.podcast.stream_cache_max_mib);
+            backend.set_time_stretch(config.settings.player.time_stretch);
+            backend.set_equalizer(config.settings.player.equalizer.active_bands());
+            backend.set_output_device(
+                config.settings.player.output_device.clone(),
+                PlayerCmdReply::default(),
+            );
+        }
+
         info!("Config Reloaded");
 
         Ok(())
     }
 
+    /// Switch the equalizer to the given preset, [`None`] to disable it.
+    ///
+    /// The preset is not checked to exist, a unknown preset disables the equalizer.
+    pub fn set_equalizer_preset(&mut self, preset: Option<String>) {
+        let mut config = self.config.write();
+        config.settings.player.equalizer.preset = preset;
+
+        #[allow(irrefutable_let_patterns)]
+        if let Backend::Rusty(ref mut backend) = self.backend {
+            backend.set_equalizer(config.settings.player.equalizer.active_bands());
+        }
+    }
+
+    /// Switch to the output device with the given name, [`None`] for the default device.
+    ///
+    /// The device is not checked to exist, a unknown device falls back to the default device.
+    /// This does not wait for the device to be opened, `reply` gets the device actually opened once that is done.
+    pub fn set_output_device(
+        &mut self,
+        device: Option<String>,
+        reply: PlayerCmdReply<Option<String>>,
+    ) {
+        self.config
+            .write()
+            .settings
+            .player
+            .output_device
+            .clone_from(&device);
+
+        #[allow(irrefutable_let_patterns)]
+        if let Backend::Rusty(ref mut backend) = self.backend {
+            backend.set_output_device(device, reply);
+            return;
+        }
+
+        // the other backends always use the default device
+        reply.send(None);
+    }
+
+    /// Get the name of the output device actually in use, [`None`] for the default device.
+    ///
+    /// This differs from the configured device if it could not be opened or disappeared while playing.
+    #[must_use]
+    pub fn output_device(&self) -> Option<String> {
+        #[allow(irrefutable_let_patterns)]
+        if let Backend::Rusty(ref backend) = self.backend {
+            return backend.opened_output_device();
+        }
+
+        // the other backends always use the default device
+        None
+    }
+
     fn get_player(&self) -> &dyn PlayerTrait {
         self.backend.as_player()
     }
@@ -323,19 +462,26 @@ impl GeneralPlayer {
     ///
     /// if `current_track_index` in playlist is above u32
     pub fn start_play(&mut self) {
-        if self.playlist.is_stopped() | self.playlist.is_paused() {
-            self.playlist.set_status(Status::Running);
+        let mut playlist = self.playlist.write();
+        if playlist.is_stopped() | playlist.is_paused() {
+            playlist.set_status(Status::Running);
         }
 
-        self.playlist.proceed();
+        playlist.proceed();
 
-        if let Some(track) = self.playlist.current_track() {
-            let track = track.clone();
+        let track = playlist.current_track().cloned();
+        let has_next_track = playlist.has_next_track();
+        if has_next_track {
+            playlist.set_next_track(None);
+        }
+        let current_track_index = playlist.get_current_track_index();
+        drop(playlist);
 
+        if let Some(track) = track {
             info!("Starting Track {:#?}", track);
+            self.radio_station_played(&track);
 
-            if self.playlist.has_next_track() {
-                self.playlist.set_next_track(None);
+            if has_next_track {
                 self.current_track_updated = true;
                 info!("gapless next track played");
                 #[allow(irrefutable_let_patterns)]
@@ -360,17 +506,68 @@ impl GeneralPlayer {
             }
 
             self.send_stream_ev(UpdateEvents::TrackChanged(TrackChangedInfo {
-                current_track_index: u32::try_from(self.playlist.get_current_track_index())
-                    .unwrap(),
+                current_track_index: u32::try_from(current_track_index).unwrap(),
                 current_track_updated: self.current_track_updated,
                 title: self.media_info().media_title,
                 progress: self.get_progress(),
+                track: Some(track.as_track_info()),
             }));
         }
     }
 
+    /// Update the last played time if `track` is a saved radio station
+    fn radio_station_played(&mut self, track: &Track) {
+        if track.media_type != MediaType::LiveRadio {
+            return;
+        }
+        let Some(url) = track.file() else {
+            return;
+        };
+        if let Err(err) = self
+            .db
+            .set_radio_station_last_played(url, SystemTime::now())
+        {
+            error!(
+                "Saving last_played for radio station failed, Error: {:#?}",
+                err
+            );
+        }
+    }
+
+    /// Update the library play statistics of the current track, if it is music
+    ///
+    /// A `finished` track always counts as played, otherwise it only counts as played once the position is past
+    /// [`play_count_threshold`](termusiclib::config::v2::server::PlayerSettings::play_count_threshold)
+    /// and as skipped before that.
+    /// Unless it finished, nothing is updated if the duration of the track is unknown.
+    pub fn player_update_play_statistics(&mut self, finished: bool) {
+        let Some(track) = self.playlist.read().current_track().cloned() else {
+            return;
+        };
+        if track.media_type != MediaType::Music {
+            return;
+        }
+        let Some(file) = track.file() else {
+            return;
+        };
+        let threshold = self.config.read().settings.player.play_count_threshold;
+        let position = self.position().unwrap_or_default();
+        let Some(played) = counts_as_played(finished, position, track.duration(), threshold) else {
+            return;
+        };
+
+        let res = if played {
+            self.db.add_track_played(file, SystemTime::now())
+        } else {
+            self.db.add_track_skipped(file)
+        };
+        if let Err(err) = res {
+            error!("Saving play statistics for music failed, Error: {:#?}", err);
+        }
+    }
+
     fn add_and_play_mpris_discord(&mut self) {
-        if let Some(track) = self.playlist.current_track() {
+        if let Some(track) = self.playlist.read().current_track() {
             if let Some(ref mut mpris) = self.mpris {
                 mpris.add_and_play(track);
             }
@@ -381,13 +578,16 @@ impl GeneralPlayer {
         }
     }
     pub fn enqueue_next_from_playlist(&mut self) {
-        if self.playlist.has_next_track() {
-            return;
-        }
+        let track = {
+            let mut playlist = self.playlist.write();
+            if playlist.has_next_track() {
+                return;
+            }
 
-        let track = match self.playlist.fetch_next_track() {
-            Some(t) => t.clone(),
-            None => return,
+            match playlist.fetch_next_track() {
+                Some(t) => t.clone(),
+                None => return,
+            }
         };
 
         self.enqueue_next(&track);
@@ -396,9 +596,10 @@ impl GeneralPlayer {
     }
 
     pub fn next(&mut self) {
-        if self.playlist.current_track().is_some() {
+        let has_current_track = self.playlist.read().current_track().is_some();
+        if has_current_track {
             info!("skip route 1 which is in most cases.");
-            self.playlist.set_next_track(None);
+            self.playlist.write().set_next_track(None);
             self.skip_one();
         } else {
             info!("skip route 2 cause no current track.");
@@ -409,14 +610,18 @@ impl GeneralPlayer {
         }
     }
     pub fn previous(&mut self) {
-        self.playlist.previous();
-        self.playlist.proceed_false();
+        {
+            let mut playlist = self.playlist.write();
+            playlist.previous();
+            playlist.proceed_false();
+        }
         self.next();
     }
 
     /// Resume playback if paused, pause playback if running
     pub fn toggle_pause(&mut self) {
-        match self.playlist.status() {
+        let status = self.playlist.read().status();
+        match status {
             Status::Running => {
                 <Self as PlayerTrait>::pause(self);
             }
@@ -429,7 +634,8 @@ impl GeneralPlayer {
 
     /// Pause playback if running
     pub fn pause(&mut self) {
-        match self.playlist.status() {
+        let status = self.playlist.read().status();
+        match status {
             Status::Running => {
                 <Self as PlayerTrait>::pause(self);
             }
@@ -439,7 +645,8 @@ impl GeneralPlayer {
 
     /// Resume playback if paused
     pub fn play(&mut self) {
-        match self.playlist.status() {
+        let status = self.playlist.read().status();
+        match status {
             Status::Running | Status::Stopped => {}
             Status::Paused => {
                 <Self as PlayerTrait>::resume(self);
@@ -450,7 +657,7 @@ impl GeneralPlayer {
     ///
     /// if the underlying "seek" returns a error (which current never happens)
     pub fn seek_relative(&mut self, forward: bool) {
-        let track_len = if let Some(track) = self.playlist.current_track() {
+        let track_len = if let Some(track) = self.playlist.read().current_track() {
             track.duration().as_secs()
         } else {
             // fallback to 5 instead of not seeking at all
@@ -473,7 +680,7 @@ impl GeneralPlayer {
 
     #[allow(clippy::cast_sign_loss)]
     pub fn player_save_last_position(&mut self) {
-        let Some(track) = self.playlist.current_track() else {
+        let Some(track) = self.playlist.read().current_track().cloned() else {
             info!("Not saving Last position as there is no current track");
             return;
         };
@@ -500,12 +707,12 @@ impl GeneralPlayer {
         if time_before_save < position.as_secs() {
             match track.media_type {
                 MediaType::Music => {
-                    if let Err(err) = self.db.set_last_position(track, position) {
+                    if let Err(err) = self.db.set_last_position(&track, position) {
                         error!("Saving last_position for music failed, Error: {:#?}", err);
                     }
                 }
                 MediaType::Podcast => {
-                    if let Err(err) = self.db_podcast.set_last_position(track, position) {
+                    if let Err(err) = self.db_podcast.set_last_position(&track, position) {
                         error!("Saving last_position for podcast failed, Error: {:#?}", err);
                     }
                 }
@@ -517,7 +724,7 @@ impl GeneralPlayer {
     }
 
     pub fn player_restore_last_position(&mut self) {
-        let Some(track) = self.playlist.current_track() else {
+        let Some(track) = self.playlist.read().current_track().cloned() else {
             info!("Not restoring Last position as there is no current track");
             return;
         };
@@ -534,13 +741,13 @@ impl GeneralPlayer {
         {
             match track.media_type {
                 MediaType::Music => {
-                    if let Ok(last_pos) = self.db.get_last_position(track) {
+                    if let Ok(last_pos) = self.db.get_last_position(&track) {
                         self.seek_to(last_pos);
                         restored = true;
                     }
                 }
                 MediaType::Podcast => {
-                    if let Ok(last_pos) = self.db_podcast.get_last_position(track) {
+                    if let Ok(last_pos) = self.db_podcast.get_last_position(&track) {
                         self.seek_to(last_pos);
                         restored = true;
                     }
@@ -555,10 +762,8 @@ impl GeneralPlayer {
         }
 
         if restored {
-            if let Some(track) = self.playlist.current_track() {
-                if let Err(err) = self.db.set_last_position(track, Duration::from_secs(0)) {
-                    error!("Resetting last_position failed, Error: {:#?}", err);
-                }
+            if let Err(err) = self.db.set_last_position(&track, Duration::from_secs(0)) {
+                error!("Resetting last_position failed, Error: {:#?}", err);
             }
         }
     }
@@ -594,7 +799,7 @@ impl PlayerTrait for GeneralPlayer {
     }
     /// This function should not be used directly, use GeneralPlayer::pause
     fn pause(&mut self) {
-        self.playlist.set_status(Status::Paused);
+        self.playlist.write().set_status(Status::Paused);
         self.get_player_mut().pause();
         if let Some(ref mut mpris) = self.mpris {
             mpris.pause();
@@ -609,7 +814,7 @@ impl PlayerTrait for GeneralPlayer {
     }
     /// This function should not be used directly, use GeneralPlayer::play
     fn resume(&mut self) {
-        self.playlist.set_status(Status::Running);
+        self.playlist.write().set_status(Status::Running);
         self.get_player_mut().resume();
         if let Some(ref mut mpris) = self.mpris {
             mpris.resume();
@@ -652,9 +857,12 @@ impl PlayerTrait for GeneralPlayer {
     }
 
     fn stop(&mut self) {
-        self.playlist.set_status(Status::Stopped);
-        self.playlist.set_next_track(None);
-        self.playlist.clear_current_track();
+        {
+            let mut playlist = self.playlist.write();
+            playlist.set_status(Status::Stopped);
+            playlist.set_next_track(None);
+            playlist.clear_current_track();
+        }
         self.get_player_mut().stop();
     }
 
@@ -706,6 +914,26 @@ pub type SpeedSigned = Speed;
 pub const MIN_SPEED: Speed = 1;
 pub const MAX_SPEED: Speed = 30;
 
+/// Whether a track stopped at `position` counts as played, see [`GeneralPlayer::player_update_play_statistics`].
+///
+/// Returns [`None`] if it counts as neither played nor skipped, because the `duration` is unknown.
+fn counts_as_played(
+    finished: bool,
+    position: Duration,
+    duration: Duration,
+    threshold: u8,
+) -> Option<bool> {
+    if finished {
+        return Some(true);
+    }
+    // without a duration, the threshold would count every skip as played
+    if duration.is_zero() {
+        return None;
+    }
+
+    Some(position.as_millis() * 100 >= duration.as_millis() * u128::from(threshold))
+}
+
 #[allow(clippy::module_name_repetitions)]
 #[async_trait]
 pub trait PlayerTrait {
@@ -767,3 +995,25 @@ pub trait PlayerTrait {
     /// Get info of the current media
     fn media_info(&self) -> MediaInfo;
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::counts_as_played;
+
+    #[test]
+    fn should_count_as_played() {
+        let secs = Duration::from_secs;
+
+        assert_eq!(counts_as_played(false, secs(50), secs(100), 50), Some(true));
+        assert_eq!(
+            counts_as_played(false, secs(49), secs(100), 50),
+            Some(false)
+        );
+        assert_eq!(counts_as_played(true, secs(1), secs(100), 50), Some(true));
+        // finished tracks always count, even without a known duration
+        assert_eq!(counts_as_played(true, secs(0), secs(0), 50), Some(true));
+        assert_eq!(counts_as_played(false, secs(10), secs(0), 50), None);
+    }
+}
@@ -11,6 +11,15 @@ use std::io::{BufRead, BufReader, BufWriter, Write};
 use std::path::{Path, PathBuf};
 use termusiclib::config::v2::server::LoopMode;
 use termusiclib::config::SharedServerSettings;
+use termusiclib::library_db::DataBase;
+use termusiclib::player::playlist_helpers::{
+    PlaylistAddTrack, PlaylistPlaySpecific, PlaylistRemoveTrackIndexed, PlaylistSwapTrack,
+    PlaylistTrackSource, PlaylistTracks,
+};
+use termusiclib::player::{
+    PlaylistAddTrackInfo, PlaylistRemoveTrackInfo, PlaylistSwapInfo, UpdateEvents,
+    UpdatePlaylistEvents,
+};
 use termusiclib::podcast::{db::Database as DBPod, episode::Episode};
 use termusiclib::track::MediaType;
 use termusiclib::{
@@ -18,6 +27,8 @@ use termusiclib::{
     utils::{filetype_supported, get_app_config_path, get_parent_folder},
 };
 
+use crate::StreamTX;
+
 #[derive(Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Debug)]
 pub enum Status {
     #[default]
@@ -74,27 +85,49 @@ pub struct Playlist {
     played_index: Vec<usize>,
     /// Indicator if the playlist should advance the `current_*` and `next_*` values
     need_proceed_to_next: bool,
+    /// Where to send playlist change events to, if any
+    stream_tx: Option<StreamTX>,
 }
 
 impl Playlist {
+    /// Create a new playlist with the tracks loaded from the playlist log
+    ///
+    /// `stream_tx` is where playlist change events are sent to, if any.
+    ///
     /// # Errors
     /// errors could happen when reading files
-    pub fn new(config: &SharedServerSettings) -> Result<Self> {
-        let (current_track_index, tracks) = Self::load()?;
+    pub fn new(
+        config: &SharedServerSettings,
+        stream_tx: Option<StreamTX>,
+        db: &mut DataBase,
+    ) -> Result<Self> {
+        let (current_track_index, tracks) = Self::load(db)?;
+        let mut playlist = Self::new_empty(config, stream_tx);
+        playlist.tracks = tracks;
+        playlist.current_track_index = current_track_index;
+
+        Ok(playlist)
+    }
+
+    /// Create a new playlist without any tracks
+    ///
+    /// `stream_tx` is where playlist change events are sent to, if any.
+    #[must_use]
+    pub fn new_empty(config: &SharedServerSettings, stream_tx: Option<StreamTX>) -> Self {
         // TODO: shouldnt "loop_mode" be combined with the config ones?
         let loop_mode = config.read().settings.player.loop_mode;
-        let current_track = None;
 
-        Ok(Self {
-            tracks,
+        Self {
+            tracks: Vec::new(),
             status: Status::Stopped,
             loop_mode,
-            current_track_index,
-            current_track,
+            current_track_index: 0,
+            current_track: None,
             played_index: Vec::new(),
             next_track_index: None,
             need_proceed_to_next: false,
-        })
+            stream_tx,
+        }
     }
 
     /// Advance the playlist to the next track.
@@ -121,7 +154,7 @@ impl Playlist {
     /// # Errors
     /// - When the playlist path is not write-able
     /// - When podcasts cannot be loaded
-    pub fn load() -> Result<(usize, Vec<Track>)> {
+    pub fn load(db: &mut DataBase) -> Result<(usize, Vec<Track>)> {
         let path = get_playlist_path()?;
 
         let Ok(file) = File::open(&path) else {
@@ -166,7 +199,7 @@ impl Playlist {
                     }
                 }
                 if !is_podcast {
-                    let track = Track::new_radio(&line);
+                    let track = Self::radio_track(&line, db);
                     playlist_items.push(track);
                 }
                 continue;
@@ -184,20 +217,44 @@ impl Playlist {
     ///
     /// # Errors
     /// See [`Self::load`]
-    pub fn reload_tracks(&mut self) -> Result<()> {
-        let (current_track_index, tracks) = Self::load()?;
+    pub fn reload_tracks(&mut self, db: &mut DataBase) -> Result<()> {
+        let (current_track_index, tracks) = Self::load(db)?;
         self.tracks = tracks;
         self.current_track_index = current_track_index;
+        self.played_index.clear();
+        self.next_track_index = None;
+        self.send_stream_ev_pl(UpdatePlaylistEvents::PlaylistReplaced(
+            self.as_grpc_playlist_tracks(),
+        ));
         Ok(())
     }
 
+    /// Replace the current tracks and index with the given playlist from the server.
+    ///
+    /// Tracks that cannot be read locally are still added (without metadata), to keep the indexes in sync with the server.
+    pub fn load_from_grpc(&mut self, info: &PlaylistTracks, db_podcast: &DBPod, db: &mut DataBase) {
+        self.tracks = Self::sources_to_tracks(&info.tracks, db_podcast, db);
+        self.current_track_index = info.current_track_index;
+        self.played_index.clear();
+        self.next_track_index = None;
+    }
+
+    /// Get the current tracks and index in a form that can be send to the clients
+    #[must_use]
+    pub fn as_grpc_playlist_tracks(&self) -> PlaylistTracks {
+        PlaylistTracks {
+            current_track_index: self.current_track_index,
+            tracks: self.tracks.iter().map(Track::as_track_source).collect(),
+        }
+    }
+
     /// Save the current playlist and playing index to the playlist log
     ///
     /// Path in `$config$/playlist.log`
     ///
     /// # Errors
     /// Errors could happen when writing files
-    pub fn save(&mut self) -> Result<()> {
+    pub fn save(&self) -> Result<()> {
         let path = get_playlist_path()?;
 
         let file = File::create(&path)?;
@@ -290,27 +347,59 @@ impl Playlist {
     /// Swap the `index` with the one below(+1) it, if there is one.
     pub fn swap_down(&mut self, index: usize) {
         if index < self.len().saturating_sub(1) {
-            self.tracks.swap(index, index + 1);
-            // handle index
-            if index == self.current_track_index {
-                self.current_track_index += 1;
-            } else if index == self.current_track_index - 1 {
-                self.current_track_index -= 1;
-            }
+            let _ = self.swap(index, index + 1);
         }
     }
 
     /// Swap the `index` with the one above(-1) it, if there is one.
     pub fn swap_up(&mut self, index: usize) {
         if index > 0 {
-            self.tracks.swap(index, index - 1);
-            // handle index
-            if index == self.current_track_index {
-                self.current_track_index -= 1;
-            } else if index == self.current_track_index + 1 {
-                self.current_track_index += 1;
+            let _ = self.swap(index, index - 1);
+        }
+    }
+
+    /// Swap the tracks at `index_a` and `index_b`, keeping the current track index on the same track.
+    ///
+    /// # Errors
+    /// - When either index is out-of-bounds
+    pub fn swap(&mut self, index_a: usize, index_b: usize) -> Result<(), PlaylistEditError> {
+        for index in [index_a, index_b] {
+            if index >= self.len() {
+                return Err(PlaylistEditError::OutOfBounds {
+                    index,
+                    len: self.len(),
+                });
             }
         }
+
+        self.tracks.swap(index_a, index_b);
+        // handle index
+        if index_a == self.current_track_index {
+            self.current_track_index = index_b;
+        } else if index_b == self.current_track_index {
+            self.current_track_index = index_a;
+        }
+        // and also keep the already enqueued next track
+        if self.next_track_index == Some(index_a) {
+            self.next_track_index = Some(index_b);
+        } else if self.next_track_index == Some(index_b) {
+            self.next_track_index = Some(index_a);
+        }
+
+        self.send_stream_ev_pl(UpdatePlaylistEvents::PlaylistSwapTracks(PlaylistSwapInfo {
+            index_a,
+            index_b,
+        }));
+
+        Ok(())
+    }
+
+    /// Swap the tracks as described by `info`.
+    ///
+    /// # Errors
+    /// See [`Self::swap`]
+    pub fn swap_tracks(&mut self, info: &PlaylistSwapTrack) -> Result<(), PlaylistEditError> {
+        self.swap(info.index_a, info.index_b)
     }
 
     /// Get the current track's Path/Url.
@@ -383,9 +472,16 @@ impl Playlist {
                 self.loop_mode = LoopMode::Random;
             }
         };
+        self.send_stream_ev_pl(UpdatePlaylistEvents::PlaylistLoopMode(self.loop_mode));
         self.loop_mode
     }
 
+    /// Set the loop mode to `mode`.
+    pub fn set_loop_mode(&mut self, mode: LoopMode) {
+        self.loop_mode = mode;
+        self.send_stream_ev_pl(UpdatePlaylistEvents::PlaylistLoopMode(self.loop_mode));
+    }
+
     /// Export the current playlist to a `.m3u` playlist file.
     ///
     /// Might be confused with [save](Self::save).
@@ -429,6 +525,148 @@ impl Playlist {
         self.tracks.push(track);
     }
 
+    /// Create a [`Track`] from the given [`PlaylistTrackSource`].
+    ///
+    /// # Errors
+    /// - When invalid inputs are given (non-existing path, unsupported file types, etc)
+    /// - When a podcast episode cannot be found in the database
+    pub fn source_to_track(
+        source: &PlaylistTrackSource,
+        db_podcast: &DBPod,
+        db: &mut DataBase,
+    ) -> Result<Track, PlaylistAddError> {
+        match source {
+            PlaylistTrackSource::Path(path) => Self::track_from_path(path),
+            PlaylistTrackSource::Url(url) => Ok(Self::radio_track(url, db)),
+            PlaylistTrackSource::PodcastUrl(url) => {
+                let episode = db_podcast
+                    .get_episode_by_url(url)
+                    .map_err(|err| PlaylistAddError::PodcastEpisodeNotFound(err, url.clone()))?;
+
+                Ok(Track::from_episode(&episode))
+            }
+        }
+    }
+
+    /// Create a radio track for `url`, named like the saved radio station with that url, if any
+    fn radio_track(url: &str, db: &mut DataBase) -> Track {
+        let mut track = Track::new_radio(url);
+        match db.get_radio_station_by_url(url) {
+            Ok(Some(station)) => track.set_title(&station.name),
+            Ok(None) => (),
+            Err(err) => warn!("Could not look up radio station {url:?}: {err}"),
+        }
+
+        track
+    }
+
+    /// Create [`Track`]s for all `sources`, falling back to a track without metadata if one cannot be read.
+    fn sources_to_tracks(
+        sources: &[PlaylistTrackSource],
+        db_podcast: &DBPod,
+        db: &mut DataBase,
+    ) -> Vec<Track> {
+        sources
+            .iter()
+            .map(|source| {
+                Self::source_to_track(source, db_podcast, db).unwrap_or_else(|err| {
+                    warn!("Could not read track, using it without metadata: {err}");
+                    Track::new_from_source(source)
+                })
+            })
+            .collect()
+    }
+
+    /// Insert all tracks from `info` at the requested index, or append them if the index is past the end.
+    ///
+    /// The current track index is moved along if tracks are inserted before it.
+    ///
+    /// Returns the index the tracks have actually been inserted at.
+    ///
+    /// # Errors
+    /// - When any of the tracks could not be added, all other tracks will still be added
+    pub fn add_tracks(
+        &mut self,
+        info: &PlaylistAddTrack,
+        db_podcast: &DBPod,
+        db: &mut DataBase,
+    ) -> Result<usize, PlaylistAddErrorVec> {
+        let at_index = info.at_index.min(self.len());
+        let mut errors = PlaylistAddErrorVec::default();
+        let mut new_tracks = Vec::with_capacity(info.tracks.len());
+        let mut added_sources = Vec::with_capacity(info.tracks.len());
+
+        for source in &info.tracks {
+            match Self::source_to_track(source, db_podcast, db) {
+                Ok(track) => {
+                    new_tracks.push(track);
+                    added_sources.push(source.clone());
+                }
+                Err(err) => errors.push(err),
+            }
+        }
+
+        self.insert_tracks(at_index, new_tracks, added_sources);
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        Ok(at_index)
+    }
+
+    /// Like [`Self::add_tracks`], but tracks that cannot be read are still added without metadata.
+    ///
+    /// For use when the tracks are known to exist elsewhere (like on the server) and the indexes need to stay in sync.
+    ///
+    /// Returns the index the tracks have actually been inserted at.
+    pub fn add_tracks_lenient(
+        &mut self,
+        info: &PlaylistAddTrack,
+        db_podcast: &DBPod,
+        db: &mut DataBase,
+    ) -> usize {
+        let at_index = info.at_index.min(self.len());
+        let new_tracks = Self::sources_to_tracks(&info.tracks, db_podcast, db);
+        self.insert_tracks(at_index, new_tracks, info.tracks.clone());
+
+        at_index
+    }
+
+    /// Insert `new_tracks` at `at_index`, moving the current track index along and sending the change event.
+    ///
+    /// `sources` are the [`PlaylistTrackSource`]s of `new_tracks`.
+    fn insert_tracks(
+        &mut self,
+        at_index: usize,
+        new_tracks: Vec<Track>,
+        sources: Vec<PlaylistTrackSource>,
+    ) {
+        let was_empty = self.is_empty();
+        let amount = new_tracks.len();
+        self.tracks.splice(at_index..at_index, new_tracks);
+
+        // keep the current track index on the same track, if tracks got inserted before it
+        if !was_empty && at_index <= self.current_track_index {
+            self.current_track_index += amount;
+        }
+        // same for the already enqueued next track
+        if let Some(next_index) = self.next_track_index.as_mut() {
+            if at_index <= *next_index {
+                *next_index += amount;
+            }
+        }
+
+        if amount > 0 {
+            self.send_stream_ev_pl(UpdatePlaylistEvents::PlaylistAddTrack(
+                PlaylistAddTrackInfo {
+                    at_index,
+                    tracks: sources,
+                },
+            ));
+        }
+    }
+
     /// Add many Paths/Urls to the playlist.
     ///
     /// # Errors
@@ -461,6 +699,19 @@ impl Playlist {
             self.tracks.push(track);
             return Ok(());
         }
+
+        let track = Self::track_from_path(track)?;
+
+        self.tracks.push(track);
+
+        Ok(())
+    }
+
+    /// Check and read a local file into a [`Track`]
+    ///
+    /// # Errors
+    /// - When invalid inputs are given (non-existing path, unsupported file types, etc)
+    fn track_from_path(track: &str) -> Result<Track, PlaylistAddError> {
         let path = Path::new(track);
         if !filetype_supported(track) {
             error!("unsupported filetype: {:#?}", track);
@@ -472,12 +723,48 @@ impl Playlist {
             return Err(PlaylistAddError::PathDoesNotExist(path.to_path_buf()));
         }
 
-        let track = Track::read_from_path(track, false)
-            .map_err(|err| PlaylistAddError::ReadError(err, path.to_path_buf()))?;
+        Track::read_from_path(track, false)
+            .map_err(|err| PlaylistAddError::ReadError(err, path.to_path_buf()))
+    }
 
-        self.tracks.push(track);
+    /// Get the [`PlaylistTrackSource`] for every track that would be added by `vec`.
+    ///
+    /// This applies the same checks as [`Self::add_playlist`], without reading the tracks.
+    ///
+    /// # Errors
+    /// - When invalid inputs are given (non-existing path, unsupported file types, etc), all valid sources are still returned
+    pub fn check_sources<T: AsRef<str>>(
+        vec: &[T],
+    ) -> (Vec<PlaylistTrackSource>, Result<(), PlaylistAddErrorVec>) {
+        let mut errors = PlaylistAddErrorVec::default();
+        let mut sources = Vec::with_capacity(vec.len());
+        for item in vec {
+            let item = item.as_ref();
+            if item.starts_with("http") {
+                sources.push(PlaylistTrackSource::Url(item.to_string()));
+                continue;
+            }
 
-        Ok(())
+            let path = Path::new(item);
+            if !filetype_supported(item) {
+                let p = path.to_path_buf();
+                let ext = p.extension().map(|v| v.to_string_lossy().to_string());
+                errors.push(PlaylistAddError::UnsupportedFileType(ext, p));
+                continue;
+            }
+            if !path.exists() {
+                errors.push(PlaylistAddError::PathDoesNotExist(path.to_path_buf()));
+                continue;
+            }
+
+            sources.push(PlaylistTrackSource::Path(item.to_string()));
+        }
+
+        if !errors.is_empty() {
+            return (sources, Err(errors));
+        }
+
+        (sources, Ok(()))
     }
 
     #[must_use]
@@ -495,6 +782,70 @@ impl Playlist {
                 self.current_track_index -= 1;
             }
         }
+        // the enqueued next track is not in the playlist anymore if it was the removed one
+        match self.next_track_index {
+            Some(next_index) if index < next_index => self.next_track_index = Some(next_index - 1),
+            Some(next_index) if index == next_index => self.next_track_index = None,
+            _ => (),
+        }
+    }
+
+    /// Remove the track as described by `info`, but only if the track at the index is the expected one.
+    ///
+    /// # Errors
+    /// - When the index is out-of-bounds
+    /// - When the track at the index is not the expected track
+    pub fn remove_indexed(
+        &mut self,
+        info: &PlaylistRemoveTrackIndexed,
+    ) -> Result<(), PlaylistEditError> {
+        self.check_track_at(info.at_index, &info.id)?;
+        self.remove(info.at_index);
+        self.send_stream_ev_pl(UpdatePlaylistEvents::PlaylistRemoveTrack(
+            PlaylistRemoveTrackInfo {
+                at_index: info.at_index,
+                id: info.id.clone(),
+            },
+        ));
+
+        Ok(())
+    }
+
+    /// Set the current track index as described by `info`, but only if the track at the index is the expected one.
+    ///
+    /// This does not start playback by itself.
+    ///
+    /// # Errors
+    /// - When the index is out-of-bounds
+    /// - When the track at the index is not the expected track
+    pub fn play_specific(&mut self, info: &PlaylistPlaySpecific) -> Result<(), PlaylistEditError> {
+        self.check_track_at(info.track_index, &info.id)?;
+        self.current_track_index = info.track_index;
+
+        Ok(())
+    }
+
+    /// Check that the track at `index` exists and is `id`.
+    fn check_track_at(
+        &self,
+        index: usize,
+        id: &PlaylistTrackSource,
+    ) -> Result<(), PlaylistEditError> {
+        let Some(track) = self.tracks.get(index) else {
+            return Err(PlaylistEditError::OutOfBounds {
+                index,
+                len: self.len(),
+            });
+        };
+
+        if track.as_track_source() != *id {
+            return Err(PlaylistEditError::TrackMismatch {
+                index,
+                expected: id.clone(),
+            });
+        }
+
+        Ok(())
     }
 
     /// Clear the current playlist.
@@ -505,6 +856,7 @@ impl Playlist {
         self.next_track_index.take();
         self.current_track_index = 0;
         self.need_proceed_to_next = false;
+        self.send_stream_ev_pl(UpdatePlaylistEvents::PlaylistCleared);
     }
 
     /// Shuffle the playlist
@@ -515,6 +867,9 @@ impl Playlist {
             if let Some(index) = self.find_index_from_file(&current_track_file) {
                 self.current_track_index = index;
             }
+            self.send_stream_ev_pl(UpdatePlaylistEvents::PlaylistShuffled(
+                self.as_grpc_playlist_tracks(),
+            ));
         }
     }
 
@@ -549,17 +904,38 @@ impl Playlist {
 
     /// Remove all tracks from the playlist that dont exist on the disk.
     pub fn remove_deleted_items(&mut self) {
-        if let Some(current_track_file) = self.get_current_track() {
-            // TODO: dosnt this remove radio and podcast episodes?
-            self.tracks
-                .retain(|x| x.file().is_some_and(|p| Path::new(p).exists()));
-            match self.find_index_from_file(&current_track_file) {
-                Some(new_index) => self.current_track_index = new_index,
-                None => self.current_track_index = 0,
+        if self.get_current_track().is_some() {
+            // iterate in reverse, so that the indexes of the not yet visited tracks do not change
+            for index in (0..self.tracks.len()).rev() {
+                // TODO: dosnt this remove radio and podcast episodes?
+                let track = &self.tracks[index];
+                if track.file().is_some_and(|p| Path::new(p).exists()) {
+                    continue;
+                }
+
+                let id = track.as_track_source();
+                self.remove(index);
+                self.send_stream_ev_pl(UpdatePlaylistEvents::PlaylistRemoveTrack(
+                    PlaylistRemoveTrackInfo {
+                        at_index: index,
+                        id,
+                    },
+                ));
             }
         }
     }
 
+    /// Send a playlist event to all stream listeners, if this playlist has a stream
+    fn send_stream_ev_pl(&self, ev: UpdatePlaylistEvents) {
+        let Some(stream_tx) = &self.stream_tx else {
+            return;
+        };
+        // there is only one error case: no receivers
+        if stream_tx.send(UpdateEvents::PlaylistChanged(ev)).is_err() {
+            debug!("Stream Event not send: No Receivers");
+        }
+    }
+
     #[must_use]
     pub fn current_track(&self) -> Option<&Track> {
         if self.current_track.is_some() {
@@ -610,6 +986,38 @@ fn get_playlist_path() -> Result<PathBuf> {
     Ok(path)
 }
 
+/// Error for when a edit of the playlist by index is rejected
+#[derive(Debug)]
+pub enum PlaylistEditError {
+    /// The index is out-of-bounds for the playlist's length
+    OutOfBounds { index: usize, len: usize },
+    /// The track at the index is not the expected track, likely because the playlist changed in the meantime
+    TrackMismatch {
+        index: usize,
+        expected: PlaylistTrackSource,
+    },
+    /// Some tracks could not be added, all others have been added
+    AddTracks(PlaylistAddErrorVec),
+}
+
+impl Display for PlaylistEditError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OutOfBounds { index, len } => {
+                write!(f, "Index {index} out of bounds for length {len}")
+            }
+            Self::TrackMismatch { index, expected } => write!(
+                f,
+                "Track at index {index} is not the expected track \"{}\"",
+                expected.as_str()
+            ),
+            Self::AddTracks(errors) => Display::fmt(errors, f),
+        }
+    }
+}
+
+impl Error for PlaylistEditError {}
+
 // TODO: consider upgrading this with "thiserror"
 /// Error for when [`Playlist::add_track`] fails
 #[derive(Debug)]
@@ -621,6 +1029,9 @@ pub enum PlaylistAddError {
     /// Generic Error for when reading the track fails
     /// `(OriginalError, Path)`
     ReadError(anyhow::Error, PathBuf),
+    /// Error for when a podcast episode could not be found in the database
+    /// `(OriginalError, Url)`
+    PodcastEpisodeNotFound(anyhow::Error, String),
 }
 
 impl Display for PlaylistAddError {
@@ -643,6 +1054,9 @@ impl Display for PlaylistAddError {
                 Self::ReadError(err, path) => {
                     format!("{err} at \"{}\"", path.display())
                 }
+                Self::PodcastEpisodeNotFound(err, url) => {
+                    format!("{err} for podcast episode \"{url}\"")
+                }
             }
         )
     }
@@ -678,3 +1092,99 @@ impl Display for PlaylistAddErrorVec {
 }
 
 impl Error for PlaylistAddErrorVec {}
+
+#[cfg(test)]
+mod tests {
+    use termusiclib::config::{new_shared_server_settings, ServerOverlay};
+    use termusiclib::player::playlist_helpers::{PlaylistRemoveTrackIndexed, PlaylistTrackSource};
+    use termusiclib::track::Track;
+
+    use super::{Playlist, PlaylistEditError};
+
+    /// Create a empty playlist without a stream
+    fn playlist() -> Playlist {
+        Playlist::new_empty(&new_shared_server_settings(ServerOverlay::default()), None)
+    }
+
+    /// Insert radio tracks for all `urls` at `at_index`
+    fn insert_radios(playlist: &mut Playlist, at_index: usize, urls: &[&str]) {
+        let sources: Vec<_> = urls
+            .iter()
+            .map(|v| PlaylistTrackSource::Url((*v).to_string()))
+            .collect();
+        let tracks = sources.iter().map(Track::new_from_source).collect();
+        playlist.insert_tracks(at_index, tracks, sources);
+    }
+
+    fn source(url: &str) -> PlaylistTrackSource {
+        PlaylistTrackSource::Url(url.to_string())
+    }
+
+    #[test]
+    fn should_keep_next_track_on_insert_before_current() {
+        let mut playlist = playlist();
+        insert_radios(&mut playlist, 0, &["a", "b", "c"]);
+        playlist.set_current_track_index(1);
+        playlist.set_next_track(Some(2));
+
+        insert_radios(&mut playlist, 0, &["d"]);
+
+        assert_eq!(playlist.get_current_track_index(), 2);
+        assert_eq!(
+            playlist.next_track().map(Track::as_track_source),
+            Some(source("c"))
+        );
+
+        playlist.next();
+        assert_eq!(
+            playlist.current_track().map(Track::as_track_source),
+            Some(source("c"))
+        );
+    }
+
+    #[test]
+    fn should_keep_next_track_on_remove_and_swap() {
+        let mut playlist = playlist();
+        insert_radios(&mut playlist, 0, &["a", "b", "c", "d"]);
+        playlist.set_current_track_index(1);
+        playlist.set_next_track(Some(2));
+
+        playlist.remove(0);
+        assert_eq!(
+            playlist.next_track().map(Track::as_track_source),
+            Some(source("c"))
+        );
+
+        playlist.swap(1, 2).unwrap();
+        assert_eq!(
+            playlist.next_track().map(Track::as_track_source),
+            Some(source("c"))
+        );
+
+        // the enqueued track itself got removed
+        playlist.remove(2);
+        assert!(!playlist.has_next_track());
+    }
+
+    #[test]
+    fn should_reject_edits_of_other_tracks() {
+        let mut playlist = playlist();
+        insert_radios(&mut playlist, 0, &["a", "b"]);
+
+        let res = playlist.remove_indexed(&PlaylistRemoveTrackIndexed {
+            at_index: 1,
+            id: source("a"),
+        });
+        assert!(matches!(
+            res,
+            Err(PlaylistEditError::TrackMismatch { index: 1, .. })
+        ));
+
+        let res = playlist.swap(0, 2);
+        assert!(matches!(
+            res,
+            Err(PlaylistEditError::OutOfBounds { index: 2, len: 2 })
+        ));
+        assert_eq!(playlist.len(), 2);
+    }
+}
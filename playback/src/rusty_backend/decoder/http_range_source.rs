@@ -0,0 +1,722 @@
+//! Read a remote file via HTTP range requests, fetching only the parts that are actually read.
+//!
+//! Fetched chunks are cached on disk, so that resuming a episode does not need to fetch them again.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use anyhow::{bail, Context, Result};
+use parking_lot::{Condvar, Mutex};
+use reqwest::{header, Client, StatusCode};
+use tokio::sync::Notify;
+
+/// The size of a single range request and cache unit
+const CHUNK_SIZE: u64 = 256 * 1024;
+/// How many chunks to fetch ahead of the current read position
+const READ_AHEAD_CHUNKS: usize = 8;
+/// How often to try a chunk before reporting a error to the reader
+const MAX_RETRIES: usize = 3;
+/// Timeout for a single range request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+/// Cache entries that have not been used for this long get removed
+const CACHE_MAX_AGE: Duration = Duration::from_secs(60 * 60 * 24 * 30);
+
+/// The cache entries currently opened by a [`HttpRangeSource`], which must not be pruned
+static OPEN_ENTRIES: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// The name of the file containing the actual data, in a cache entry
+const DATA_FILE: &str = "data";
+/// The name of the file containing the [`ChunkMap`], in a cache entry
+const MAP_FILE: &str = "chunks";
+
+/// Which chunks of a file are cached
+#[derive(Debug, Clone, PartialEq)]
+struct ChunkMap {
+    /// The length of the whole file in bytes
+    len: u64,
+    present: Vec<bool>,
+}
+
+impl ChunkMap {
+    fn new(len: u64) -> Self {
+        let chunks = usize::try_from(len.div_ceil(CHUNK_SIZE)).unwrap_or(usize::MAX);
+
+        Self {
+            len,
+            present: vec![false; chunks],
+        }
+    }
+
+    /// Load the map from `path`, returns [`None`] if it does not exist or is for a file of a different length
+    fn load(path: &Path, len: u64) -> Option<Self> {
+        let data = std::fs::read(path).ok()?;
+        let (stored_len, present) = data.split_first_chunk::<8>()?;
+        if u64::from_le_bytes(*stored_len) != len {
+            return None;
+        }
+        let map = Self {
+            len,
+            present: present.iter().map(|v| *v != 0).collect(),
+        };
+        if map.present.len() != Self::new(len).present.len() {
+            return None;
+        }
+
+        Some(map)
+    }
+
+    /// Store the map at `path`, in the form of the file length followed by one byte per chunk
+    fn save(&self, path: &Path) -> io::Result<()> {
+        let mut data = Vec::with_capacity(8 + self.present.len());
+        data.extend_from_slice(&self.len.to_le_bytes());
+        data.extend(self.present.iter().map(|v| u8::from(*v)));
+
+        std::fs::write(path, data)
+    }
+
+    /// Get the byte range of the given chunk
+    fn range(&self, chunk: usize) -> (u64, u64) {
+        let start = chunk as u64 * CHUNK_SIZE;
+        (start, (start + CHUNK_SIZE).min(self.len))
+    }
+
+    fn is_complete(&self) -> bool {
+        self.present.iter().all(|v| *v)
+    }
+}
+
+/// State shared between the reader and the fetcher
+struct State {
+    map: ChunkMap,
+    /// The chunk the reader currently reads, the fetcher fetches the missing chunks starting at this one
+    wanted: usize,
+    /// The last fetch error, taken by the reader
+    error: Option<String>,
+    /// Set once the reader is dropped, to stop the fetcher
+    closed: bool,
+}
+
+struct Shared {
+    state: Mutex<State>,
+    /// Notifies the reader about newly fetched chunks and errors
+    fetched: Condvar,
+    /// Notifies the fetcher about a changed wanted chunk
+    wanted: Notify,
+}
+
+/// Marks a cache entry as open in [`OPEN_ENTRIES`] while alive
+struct OpenEntry(PathBuf);
+
+impl OpenEntry {
+    fn new(entry_dir: PathBuf) -> Self {
+        OPEN_ENTRIES.lock().push(entry_dir.clone());
+        Self(entry_dir)
+    }
+}
+
+impl Drop for OpenEntry {
+    fn drop(&mut self) {
+        let mut open = OPEN_ENTRIES.lock();
+        if let Some(index) = open.iter().position(|v| *v == self.0) {
+            open.swap_remove(index);
+        }
+    }
+}
+
+/// A [`Read`] + [`Seek`] for a remote file, which blocks until the requested data is fetched
+pub struct HttpRangeSource {
+    file: File,
+    pos: u64,
+    len: u64,
+    shared: Arc<Shared>,
+    _entry: OpenEntry,
+}
+
+impl HttpRangeSource {
+    /// Open `url` with range requests, caching the chunks in `cache_dir`.
+    ///
+    /// The cache is pruned to `max_cache_size` bytes beforehand, `0` for no limit.
+    /// Returns [`None`] if the server does not support range requests.
+    /// The chunks are fetched by a task spawned on the current tokio runtime.
+    pub async fn open(
+        client: Client,
+        url: &str,
+        cache_dir: &Path,
+        max_cache_size: u64,
+    ) -> Result<Option<Self>> {
+        let entry_dir = cache_dir.join(format!("{:x}", md5::compute(url)));
+        let entry = OpenEntry::new(entry_dir.clone());
+        prune_cache(cache_dir, max_cache_size);
+
+        let map_path = entry_dir.join(MAP_FILE);
+        let data_path = entry_dir.join(DATA_FILE);
+
+        // a complete cache does not need the server at all
+        if let Some(map) = load_complete_map(&map_path, &data_path) {
+            // mark the entry as used, so that it is not pruned
+            if let Err(err) = map.save(&map_path) {
+                warn!("Failed to save the stream cache map: {err:#}");
+            }
+            return Self::new(client, url, map, &data_path, map_path, entry).map(Some);
+        }
+
+        let Some((len, first_chunk)) = probe(&client, url).await? else {
+            return Ok(None);
+        };
+
+        std::fs::create_dir_all(&entry_dir)
+            .with_context(|| format!("Failed to create cache dir {}", entry_dir.display()))?;
+
+        let mut map = ChunkMap::load(&map_path, len).unwrap_or_else(|| ChunkMap::new(len));
+        let mut data = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&data_path)
+            .with_context(|| format!("Failed to open cache file {}", data_path.display()))?;
+        data.set_len(len)?;
+        if !map.present.is_empty() && first_chunk.len() as u64 == map.range(0).1 {
+            data.write_all(&first_chunk)?;
+            map.present[0] = true;
+        }
+        map.save(&map_path)?;
+
+        Self::new(client, url, map, &data_path, map_path, entry).map(Some)
+    }
+
+    /// Create the reader and spawn the fetcher
+    fn new(
+        client: Client,
+        url: &str,
+        map: ChunkMap,
+        data_path: &Path,
+        map_path: PathBuf,
+        entry: OpenEntry,
+    ) -> Result<Self> {
+        let len = map.len;
+        let file = File::open(data_path)
+            .with_context(|| format!("Failed to open cache file {}", data_path.display()))?;
+        let writer = OpenOptions::new().write(true).open(data_path)?;
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State {
+                map,
+                wanted: 0,
+                error: None,
+                closed: false,
+            }),
+            fetched: Condvar::new(),
+            wanted: Notify::new(),
+        });
+
+        if !shared.state.lock().map.is_complete() {
+            tokio::spawn(fetcher(
+                client,
+                url.to_string(),
+                writer,
+                map_path,
+                shared.clone(),
+            ));
+        }
+
+        Ok(Self {
+            file,
+            pos: 0,
+            len,
+            shared,
+            _entry: entry,
+        })
+    }
+
+    /// Get the length of the remote file in bytes
+    pub fn byte_len(&self) -> u64 {
+        self.len
+    }
+
+    /// Block until the given chunk is cached
+    fn wait_for(&self, chunk: usize) -> io::Result<()> {
+        let mut state = self.shared.state.lock();
+        if state.map.present[chunk] {
+            if state.wanted != chunk {
+                // keep the read-ahead going
+                state.wanted = chunk;
+                self.shared.wanted.notify_one();
+            }
+            return Ok(());
+        }
+
+        state.wanted = chunk;
+        self.shared.wanted.notify_one();
+        while !state.map.present[chunk] {
+            if let Some(err) = state.error.take() {
+                return Err(io::Error::other(err));
+            }
+            self.shared.fetched.wait(&mut state);
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for HttpRangeSource {
+    fn drop(&mut self) {
+        self.shared.state.lock().closed = true;
+        self.shared.wanted.notify_one();
+    }
+}
+
+impl Read for HttpRangeSource {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pos >= self.len || buf.is_empty() {
+            return Ok(0);
+        }
+
+        let chunk = usize::try_from(self.pos / CHUNK_SIZE).map_err(io::Error::other)?;
+        self.wait_for(chunk)?;
+
+        // dont read past the chunk, the next one may not be fetched yet
+        let chunk_end = ((chunk as u64 + 1) * CHUNK_SIZE).min(self.len);
+        let len = usize::try_from(chunk_end - self.pos)
+            .unwrap_or(usize::MAX)
+            .min(buf.len());
+        self.file.seek(SeekFrom::Start(self.pos))?;
+        self.file.read_exact(&mut buf[..len])?;
+        self.pos += len as u64;
+
+        Ok(len)
+    }
+}
+
+impl Seek for HttpRangeSource {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let new_pos = match pos {
+            SeekFrom::Start(v) => Some(v),
+            SeekFrom::End(v) => self.len.checked_add_signed(v),
+            SeekFrom::Current(v) => self.pos.checked_add_signed(v),
+        };
+        let Some(new_pos) = new_pos else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            ));
+        };
+        self.pos = new_pos;
+
+        Ok(new_pos)
+    }
+}
+
+/// Load the map at `map_path` if all chunks are cached
+fn load_complete_map(map_path: &Path, data_path: &Path) -> Option<ChunkMap> {
+    let len = std::fs::metadata(data_path).ok()?.len();
+    let map = ChunkMap::load(map_path, len)?;
+
+    map.is_complete().then_some(map)
+}
+
+/// Request the first chunk, returns the length of the file and the first chunk,
+/// or [`None`] if the server does not support range requests
+async fn probe(client: &Client, url: &str) -> Result<Option<(u64, Vec<u8>)>> {
+    let res = client
+        .get(url)
+        .header(header::RANGE, format!("bytes=0-{}", CHUNK_SIZE - 1))
+        .timeout(REQUEST_TIMEOUT)
+        .send()
+        .await?
+        .error_for_status()?;
+
+    if res.status() != StatusCode::PARTIAL_CONTENT {
+        return Ok(None);
+    }
+    let Some(len) = res
+        .headers()
+        .get(header::CONTENT_RANGE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(parse_content_range_len)
+    else {
+        return Ok(None);
+    };
+
+    Ok(Some((len, res.bytes().await?.to_vec())))
+}
+
+/// Get the complete length from a `Content-Range` header value like `bytes 0-1023/4096`
+fn parse_content_range_len(value: &str) -> Option<u64> {
+    let (unit, range) = value.trim().split_once(' ')?;
+    if unit != "bytes" {
+        return None;
+    }
+    let (_, len) = range.split_once('/')?;
+
+    len.parse().ok()
+}
+
+/// Fetch the wanted and read-ahead chunks until the reader is dropped
+async fn fetcher(
+    client: Client,
+    url: String,
+    mut writer: File,
+    map_path: PathBuf,
+    shared: Arc<Shared>,
+) {
+    let mut failures = 0;
+    loop {
+        let next = {
+            let state = shared.state.lock();
+            if state.closed {
+                break;
+            }
+            let end = (state.wanted + READ_AHEAD_CHUNKS).min(state.map.present.len());
+            (state.wanted..end)
+                .find(|chunk| !state.map.present[*chunk])
+                .map(|chunk| (chunk, state.map.range(chunk)))
+        };
+        let Some((chunk, (start, end))) = next else {
+            shared.wanted.notified().await;
+            continue;
+        };
+
+        let result = fetch_chunk(&client, &url, start, end)
+            .await
+            .and_then(|data| {
+                writer.seek(SeekFrom::Start(start))?;
+                writer.write_all(&data)?;
+                Ok(())
+            });
+
+        let give_up = {
+            let mut state = shared.state.lock();
+            match result {
+                Ok(()) => {
+                    failures = 0;
+                    state.map.present[chunk] = true;
+                    if let Err(err) = state.map.save(&map_path) {
+                        warn!("Failed to save the stream cache map: {err:#}");
+                    }
+                    false
+                }
+                Err(err) => {
+                    failures += 1;
+                    warn!("Failed to fetch bytes {start}-{end} of {url}: {err:#}");
+                    if failures >= MAX_RETRIES {
+                        failures = 0;
+                        state.error = Some(format!("{err:#}"));
+                        true
+                    } else {
+                        false
+                    }
+                }
+            }
+        };
+        shared.fetched.notify_all();
+
+        if give_up {
+            // wait for the reader to want something again
+            shared.wanted.notified().await;
+        }
+    }
+}
+
+/// Fetch the bytes from `start` to `end`(exclusive)
+async fn fetch_chunk(client: &Client, url: &str, start: u64, end: u64) -> Result<Vec<u8>> {
+    let res = client
+        .get(url)
+        .header(header::RANGE, format!("bytes={start}-{}", end - 1))
+        .timeout(REQUEST_TIMEOUT)
+        .send()
+        .await?
+        .error_for_status()?;
+    if res.status() != StatusCode::PARTIAL_CONTENT {
+        bail!("Server did not respond with a range, but {}", res.status());
+    }
+    let data = res.bytes().await?;
+    if data.len() as u64 != end - start {
+        bail!("Expected {} bytes, got {}", end - start, data.len());
+    }
+
+    Ok(data.to_vec())
+}
+
+/// Remove cache entries that have not been used for [`CACHE_MAX_AGE`], then remove the least recently used entries
+/// until all entries together are at most `max_size` bytes, `0` for no limit.
+///
+/// Entries without a map are skipped, as they may be in the process of being created,
+/// as are entries currently opened, see [`OPEN_ENTRIES`].
+fn prune_cache(cache_dir: &Path, max_size: u64) {
+    let Ok(entries) = std::fs::read_dir(cache_dir) else {
+        return;
+    };
+    let now = SystemTime::now();
+    let open = OPEN_ENTRIES.lock().clone();
+    let mut total_size = 0;
+    // (last used, size, path) of the entries which may be removed
+    let mut removable = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let size = std::fs::metadata(path.join(DATA_FILE)).map_or(0, |v| v.len());
+        let last_used = std::fs::metadata(path.join(MAP_FILE)).and_then(|v| v.modified());
+        let Ok(last_used) = last_used else {
+            total_size += size;
+            continue;
+        };
+        if open.contains(&path) {
+            total_size += size;
+            continue;
+        }
+
+        let unused = now
+            .duration_since(last_used)
+            .is_ok_and(|age| age > CACHE_MAX_AGE);
+        if unused {
+            remove_cache_entry(&path);
+            continue;
+        }
+        total_size += size;
+        removable.push((last_used, size, path));
+    }
+
+    if max_size == 0 {
+        return;
+    }
+    removable.sort_by_key(|(last_used, ..)| *last_used);
+    for (_, size, path) in removable {
+        if total_size <= max_size {
+            break;
+        }
+        remove_cache_entry(&path);
+        total_size -= size;
+    }
+}
+
+fn remove_cache_entry(path: &Path) {
+    if let Err(err) = std::fs::remove_dir_all(path) {
+        warn!("Failed to remove stream cache {}: {err:#}", path.display());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+    use std::net::TcpListener;
+    use std::path::{Path, PathBuf};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::{Duration, SystemTime};
+
+    use reqwest::Client;
+
+    use super::{
+        parse_content_range_len, prune_cache, ChunkMap, HttpRangeSource, OpenEntry, CACHE_MAX_AGE,
+        CHUNK_SIZE, DATA_FILE, MAP_FILE,
+    };
+
+    /// Get a empty directory unique to this process and `name`
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("termusic-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        dir
+    }
+
+    /// Serve `data` with range requests on a local port, returns the url and the amount of handled requests
+    fn serve_ranges(data: Arc<Vec<u8>>) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/episode.mp3", listener.local_addr().unwrap());
+        let requests = Arc::new(AtomicUsize::new(0));
+        let requests_local = requests.clone();
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else {
+                    continue;
+                };
+                requests_local.fetch_add(1, Ordering::SeqCst);
+                let data = data.clone();
+                std::thread::spawn(move || {
+                    let mut range = None;
+                    for line in BufReader::new(&stream).lines() {
+                        let line = line.unwrap().to_ascii_lowercase();
+                        if line.is_empty() {
+                            break;
+                        }
+                        if let Some(value) = line.strip_prefix("range: bytes=") {
+                            let (start, end) = value.split_once('-').unwrap();
+                            range = Some((
+                                start.parse::<usize>().unwrap(),
+                                end.parse::<usize>().unwrap(),
+                            ));
+                        }
+                    }
+                    let (start, end) = range.expect("only range requests are expected");
+                    let end = end.min(data.len() - 1);
+                    let body = &data[start..=end];
+                    write!(
+                        stream,
+                        "HTTP/1.1 206 Partial Content\r\nContent-Range: bytes {start}-{end}/{}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                        data.len(),
+                        body.len()
+                    )
+                    .unwrap();
+                    stream.write_all(body).unwrap();
+                });
+            }
+        });
+
+        (url, requests)
+    }
+
+    /// Open `url` and do the given reads of (position, length)
+    fn read_at(
+        runtime: &tokio::runtime::Runtime,
+        url: &str,
+        cache_dir: &Path,
+        reads: &[(u64, usize)],
+    ) -> Vec<Vec<u8>> {
+        let client = Client::builder().no_proxy().build().unwrap();
+        let mut source = runtime
+            .block_on(HttpRangeSource::open(client, url, cache_dir, 0))
+            .unwrap()
+            .expect("the server supports range requests");
+
+        reads
+            .iter()
+            .map(|(pos, len)| {
+                let mut buf = vec![0; *len];
+                source.seek(SeekFrom::Start(*pos)).unwrap();
+                source.read_exact(&mut buf).unwrap();
+                buf
+            })
+            .collect()
+    }
+
+    #[test]
+    fn should_parse_content_range() {
+        assert_eq!(parse_content_range_len("bytes 0-1023/4096"), Some(4096));
+        assert_eq!(parse_content_range_len("bytes 0-1023/*"), None);
+        assert_eq!(parse_content_range_len("items 0-1/2"), None);
+    }
+
+    #[test]
+    fn should_save_and_load_chunk_map() {
+        let path = std::env::temp_dir().join(format!("termusic-chunk-map-{}", std::process::id()));
+
+        let mut map = ChunkMap::new(CHUNK_SIZE * 2 + 1);
+        assert_eq!(map.present.len(), 3);
+        assert_eq!(map.range(2), (CHUNK_SIZE * 2, CHUNK_SIZE * 2 + 1));
+        map.present[1] = true;
+        map.save(&path).unwrap();
+
+        assert_eq!(ChunkMap::load(&path, CHUNK_SIZE * 2 + 1), Some(map));
+        // a changed file is not reused
+        assert_eq!(ChunkMap::load(&path, CHUNK_SIZE * 2), None);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn should_fetch_and_seek() {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let cache_dir = test_dir("range-source");
+        let len = usize::try_from(CHUNK_SIZE).unwrap() * 3 + 100;
+        let data: Arc<Vec<u8>> =
+            Arc::new((0..len).map(|v| u8::try_from(v % 251).unwrap()).collect());
+        let (url, requests) = serve_ranges(data.clone());
+
+        let third = usize::try_from(CHUNK_SIZE).unwrap() * 2 + 10;
+        let reads = read_at(
+            &runtime,
+            &url,
+            &cache_dir,
+            &[(third as u64, 500), (10, 100), (0, len)],
+        );
+        assert_eq!(reads[0], data[third..third + 500]);
+        assert_eq!(reads[1], data[10..110]);
+        assert_eq!(reads[2], *data);
+
+        // a complete cache is used without the server, and marked as used
+        let entry = std::fs::read_dir(&cache_dir)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .path();
+        let old = SystemTime::now() - Duration::from_secs(60 * 60);
+        std::fs::File::options()
+            .write(true)
+            .open(entry.join(MAP_FILE))
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+        let handled = requests.load(Ordering::SeqCst);
+        let reads = read_at(&runtime, &url, &cache_dir, &[(0, len)]);
+        assert_eq!(reads[0], *data);
+        assert_eq!(requests.load(Ordering::SeqCst), handled);
+        let modified = std::fs::metadata(entry.join(MAP_FILE))
+            .unwrap()
+            .modified()
+            .unwrap();
+        assert!(modified > old);
+
+        std::fs::remove_dir_all(&cache_dir).unwrap();
+    }
+
+    #[test]
+    fn should_prune_only_old_entries() {
+        let cache_dir = test_dir("range-prune");
+        let old = cache_dir.join("old");
+        let recent = cache_dir.join("recent");
+        let creating = cache_dir.join("creating");
+        for dir in [&old, &recent, &creating] {
+            std::fs::create_dir(dir).unwrap();
+        }
+        std::fs::write(recent.join(MAP_FILE), []).unwrap();
+        let map = std::fs::File::create(old.join(MAP_FILE)).unwrap();
+        map.set_modified(SystemTime::now() - CACHE_MAX_AGE - Duration::from_secs(60))
+            .unwrap();
+
+        prune_cache(&cache_dir, 0);
+
+        assert!(!old.exists());
+        assert!(recent.exists());
+        assert!(creating.exists());
+        std::fs::remove_dir_all(&cache_dir).unwrap();
+    }
+
+    #[test]
+    fn should_prune_least_recently_used_entries_over_size() {
+        let cache_dir = test_dir("range-prune-size");
+        let now = SystemTime::now();
+        let hours = |v: u64| Duration::from_secs(60 * 60 * v);
+        let mut entries = Vec::new();
+        for (name, age) in [("open", 4), ("a", 3), ("b", 2), ("c", 1)] {
+            let dir = cache_dir.join(name);
+            std::fs::create_dir(&dir).unwrap();
+            std::fs::write(dir.join(DATA_FILE), [0; 100]).unwrap();
+            let map = std::fs::File::create(dir.join(MAP_FILE)).unwrap();
+            map.set_modified(now - hours(age)).unwrap();
+            entries.push(dir);
+        }
+        let [open, a, b, c] = entries.try_into().unwrap();
+        let entry = OpenEntry::new(open.clone());
+
+        prune_cache(&cache_dir, 200);
+
+        assert!(open.exists());
+        assert!(!a.exists());
+        assert!(!b.exists());
+        assert!(c.exists());
+
+        // not open anymore, so the oldest entry is removed
+        drop(entry);
+        prune_cache(&cache_dir, 100);
+        assert!(!open.exists());
+        assert!(c.exists());
+
+        std::fs::remove_dir_all(&cache_dir).unwrap();
+    }
+}
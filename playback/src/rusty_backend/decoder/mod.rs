@@ -1,4 +1,5 @@
 pub mod buffered_source;
+pub mod http_range_source;
 pub mod read_seek_source;
 
 use super::Source;
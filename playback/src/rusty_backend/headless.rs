@@ -0,0 +1,355 @@
+//! Outputs which do not need a sound card, to run the playback pipeline in tests and to record what the player produced.
+
+use std::fs::File;
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use rodio::source::UniformSourceIterator;
+use rodio::Source;
+use termusiclib::config::v2::server::AudioOutput;
+
+use super::sink::SinkStatus;
+
+/// The channels all audio gets converted to
+const CHANNELS: u16 = 2;
+/// The sample rate all audio gets converted to
+const SAMPLE_RATE: u32 = 44100;
+/// The amount of samples processed at once, 10ms
+const BLOCK_SAMPLES: usize = (SAMPLE_RATE / 100) as usize * CHANNELS as usize;
+/// How often the WAV header gets updated, so that the file is valid even if the process does not exit cleanly
+const HEADER_UPDATE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Where a [`HeadlessOutput`] writes the audio to
+#[derive(Debug, Clone, PartialEq)]
+enum Target {
+    Null,
+    Wav(PathBuf),
+}
+
+/// A output which is not a audio device, see [`AudioOutput`]
+#[derive(Debug)]
+pub struct HeadlessOutput {
+    target: Target,
+    /// Process as fast as possible instead of at real time
+    fast: bool,
+    /// The thread processing the audio, with its stop flag
+    worker: Option<(Arc<AtomicBool>, JoinHandle<()>)>,
+}
+
+impl HeadlessOutput {
+    /// Create a new headless output, returns [`None`] for [`AudioOutput::Device`]
+    pub fn new(output: &AudioOutput) -> Option<Self> {
+        let (target, fast) = match output {
+            AudioOutput::Device => return None,
+            AudioOutput::Null { fast } => (Target::Null, *fast),
+            AudioOutput::Wav { path, fast } => (Target::Wav(path.clone()), *fast),
+        };
+
+        Some(Self {
+            target,
+            fast,
+            worker: None,
+        })
+    }
+
+    /// Start processing `source`, replacing any previous source.
+    ///
+    /// `status` is used to not produce endless silence while there is nothing to play.
+    pub fn play<S>(&mut self, source: S, status: SinkStatus) -> Result<()>
+    where
+        S: Source<Item = f32> + Send + 'static,
+    {
+        self.stop();
+
+        let mut writer = match &self.target {
+            Target::Null => SampleWriter::Null,
+            Target::Wav(path) => SampleWriter::Wav(WavWriter::create(path)?),
+        };
+        let fast = self.fast;
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_local = stop.clone();
+
+        let handle = std::thread::Builder::new()
+            .name("headless output".into())
+            .spawn(move || {
+                let source = UniformSourceIterator::new(source, CHANNELS, SAMPLE_RATE);
+                if let Err(err) = pump(source, &mut writer, fast, &status, &stop_local) {
+                    error!("Headless output failed: {err:#}");
+                }
+                if let Err(err) = writer.finish() {
+                    error!("Failed to finish headless output: {err:#}");
+                }
+            })
+            .context("Failed to spawn headless output thread")?;
+        self.worker = Some((stop, handle));
+
+        Ok(())
+    }
+
+    /// Stop processing and wait until all output is written
+    fn stop(&mut self) {
+        if let Some((stop, handle)) = self.worker.take() {
+            stop.store(true, Ordering::SeqCst);
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for HeadlessOutput {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Pull samples from `source` into `writer` until the source ends or `stop` is set.
+///
+/// Nothing is pulled while the sink is empty, and nothing is written while the sink is paused.
+/// Unless `fast` is set, this is throttled to real time.
+#[allow(clippy::cast_precision_loss)]
+fn pump<S, W>(
+    mut source: S,
+    writer: &mut SampleWriter<W>,
+    fast: bool,
+    status: &SinkStatus,
+    stop: &AtomicBool,
+) -> Result<()>
+where
+    S: Source<Item = f32>,
+    W: Write + Seek,
+{
+    let mut block = Vec::with_capacity(BLOCK_SAMPLES);
+    // the time the current run of uninterrupted playback started and the samples processed since
+    let mut clock: Option<(Instant, u64)> = None;
+
+    while !stop.load(Ordering::SeqCst) {
+        if status.is_empty() {
+            clock = None;
+            std::thread::sleep(Duration::from_millis(10));
+            continue;
+        }
+
+        block.clear();
+        block.extend(source.by_ref().take(BLOCK_SAMPLES));
+        if block.is_empty() {
+            break;
+        }
+
+        let paused = status.is_paused();
+        if !paused {
+            writer.write(&block)?;
+        }
+
+        // still throttle while paused in fast mode, otherwise this would spin
+        if fast && !paused {
+            continue;
+        }
+        let (start, processed) = clock.get_or_insert_with(|| (Instant::now(), 0));
+        *processed += block.len() as u64;
+        let target = Duration::from_secs_f64(
+            *processed as f64 / (f64::from(SAMPLE_RATE) * f64::from(CHANNELS)),
+        );
+        if let Some(wait) = target.checked_sub(start.elapsed()) {
+            std::thread::sleep(wait);
+        }
+    }
+
+    Ok(())
+}
+
+/// The actual destination of the samples
+enum SampleWriter<W: Write + Seek> {
+    Null,
+    Wav(WavWriter<W>),
+}
+
+impl<W: Write + Seek> SampleWriter<W> {
+    fn write(&mut self, samples: &[f32]) -> Result<()> {
+        match self {
+            SampleWriter::Null => Ok(()),
+            SampleWriter::Wav(wav) => wav.write(samples),
+        }
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        match self {
+            SampleWriter::Null => Ok(()),
+            SampleWriter::Wav(wav) => wav.update_header(),
+        }
+    }
+}
+
+/// Minimal writer for 32-bit float WAV files
+struct WavWriter<W: Write + Seek> {
+    writer: W,
+    /// The amount of sample bytes written
+    data_len: u64,
+    last_header_update: Instant,
+}
+
+impl WavWriter<BufWriter<File>> {
+    /// Create the file at `path`, overwriting it if it exists
+    fn create(path: &Path) -> Result<Self> {
+        let file = File::create(path)
+            .with_context(|| format!("Failed to create WAV file {}", path.display()))?;
+
+        Self::new(BufWriter::new(file))
+    }
+}
+
+impl<W: Write + Seek> WavWriter<W> {
+    fn new(mut writer: W) -> Result<Self> {
+        let block_align = CHANNELS * 4;
+
+        writer.write_all(b"RIFF")?;
+        // sizes are filled in by "update_header"
+        writer.write_all(&36u32.to_le_bytes())?;
+        writer.write_all(b"WAVE")?;
+        writer.write_all(b"fmt ")?;
+        writer.write_all(&16u32.to_le_bytes())?;
+        // WAVE_FORMAT_IEEE_FLOAT
+        writer.write_all(&3u16.to_le_bytes())?;
+        writer.write_all(&CHANNELS.to_le_bytes())?;
+        writer.write_all(&SAMPLE_RATE.to_le_bytes())?;
+        writer.write_all(&(SAMPLE_RATE * u32::from(block_align)).to_le_bytes())?;
+        writer.write_all(&block_align.to_le_bytes())?;
+        writer.write_all(&32u16.to_le_bytes())?;
+        writer.write_all(b"data")?;
+        writer.write_all(&0u32.to_le_bytes())?;
+
+        Ok(Self {
+            writer,
+            data_len: 0,
+            last_header_update: Instant::now(),
+        })
+    }
+
+    fn write(&mut self, samples: &[f32]) -> Result<()> {
+        for sample in samples {
+            self.writer.write_all(&sample.to_le_bytes())?;
+        }
+        self.data_len += samples.len() as u64 * 4;
+
+        if self.last_header_update.elapsed() >= HEADER_UPDATE_INTERVAL {
+            self.update_header()?;
+        }
+
+        Ok(())
+    }
+
+    /// Write the current sizes into the header and flush everything
+    fn update_header(&mut self) -> Result<()> {
+        // files above 4GiB cannot be represented, readers will just stop at the limit
+        let data_len = u32::try_from(self.data_len)
+            .unwrap_or(u32::MAX)
+            .min(u32::MAX - 36);
+
+        self.writer.seek(SeekFrom::Start(4))?;
+        self.writer.write_all(&(36 + data_len).to_le_bytes())?;
+        self.writer.seek(SeekFrom::Start(40))?;
+        self.writer.write_all(&data_len.to_le_bytes())?;
+        self.writer.seek(SeekFrom::End(0))?;
+        self.writer.flush()?;
+        self.last_header_update = Instant::now();
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    use rodio::buffer::SamplesBuffer;
+
+    use super::{pump, SampleWriter, WavWriter, BLOCK_SAMPLES, CHANNELS, SAMPLE_RATE};
+    use crate::rusty_backend::sink::Sink;
+
+    #[test]
+    fn should_write_wav_header() {
+        let mut wav = WavWriter::new(Cursor::new(Vec::new())).unwrap();
+        wav.write(&[0.5, -0.5, 1.0, -1.0]).unwrap();
+        wav.update_header().unwrap();
+
+        let data = wav.writer.into_inner();
+        assert_eq!(data.len(), 44 + 16);
+        assert_eq!(&data[0..4], b"RIFF");
+        assert_eq!(u32::from_le_bytes(data[4..8].try_into().unwrap()), 36 + 16);
+        assert_eq!(&data[8..16], b"WAVEfmt ");
+        assert_eq!(
+            u16::from_le_bytes(data[22..24].try_into().unwrap()),
+            CHANNELS
+        );
+        assert_eq!(
+            u32::from_le_bytes(data[24..28].try_into().unwrap()),
+            SAMPLE_RATE
+        );
+        assert_eq!(&data[36..40], b"data");
+        assert_eq!(u32::from_le_bytes(data[40..44].try_into().unwrap()), 16);
+        assert_eq!(&data[44..48], 0.5f32.to_le_bytes());
+    }
+
+    #[test]
+    fn should_clamp_wav_header_sizes() {
+        let mut wav = WavWriter::new(Cursor::new(Vec::new())).unwrap();
+        // fits into a u32, but not together with the rest of the header
+        wav.data_len = u64::from(u32::MAX - 10);
+        wav.update_header().unwrap();
+
+        let data = wav.writer.into_inner();
+        assert_eq!(u32::from_le_bytes(data[4..8].try_into().unwrap()), u32::MAX);
+        assert_eq!(
+            u32::from_le_bytes(data[40..44].try_into().unwrap()),
+            u32::MAX - 36
+        );
+    }
+
+    #[test]
+    fn should_pump_while_not_empty() {
+        let (picmd_tx, _picmd_rx) = std::sync::mpsc::channel();
+        let (pcmd_tx, _pcmd_rx) = tokio::sync::mpsc::unbounded_channel();
+        let (sink, queue_rx) = Sink::new_idle(picmd_tx, pcmd_tx);
+        // one second of audio
+        let samples = SAMPLE_RATE as usize * usize::from(CHANNELS);
+        sink.append(SamplesBuffer::new(
+            CHANNELS,
+            SAMPLE_RATE,
+            vec![0.25f32; samples],
+        ));
+        let status = sink.status();
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let stop_local = stop.clone();
+        let pump_status = status.clone();
+        let handle = std::thread::spawn(move || {
+            let mut writer = SampleWriter::Wav(WavWriter::new(Cursor::new(Vec::new())).unwrap());
+            pump(queue_rx, &mut writer, true, &pump_status, &stop_local).unwrap();
+            writer
+        });
+
+        // only guard against hanging, the result is checked by the amount of samples written
+        let start = Instant::now();
+        while !status.is_empty() {
+            assert!(start.elapsed() < Duration::from_secs(30));
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        stop.store(true, Ordering::SeqCst);
+        let SampleWriter::Wav(wav) = handle.join().unwrap() else {
+            unreachable!();
+        };
+
+        // the block in which the source ended is padded with silence
+        let written = usize::try_from(wav.data_len).unwrap() / 4;
+        assert!((samples..=samples + BLOCK_SAMPLES).contains(&written));
+        let data = wav.writer.into_inner();
+        assert_eq!(&data[44..48], 0.25f32.to_le_bytes());
+        drop(sink);
+    }
+}
@@ -1,34 +1,42 @@
 #![cfg_attr(test, deny(missing_docs))]
 
 mod decoder;
+mod headless;
 mod icy_metadata;
 mod sink;
 mod source;
 
 use async_trait::async_trait;
 use decoder::Symphonia;
-use rodio::OutputStream;
+use headless::HeadlessOutput;
+use rodio::cpal::traits::{HostTrait, StreamTrait};
+use rodio::dynamic_mixer::{self, DynamicMixer, DynamicMixerController};
 use rodio::Source;
+use rodio::{cpal, DeviceTrait};
 use sink::Sink;
+use source::equalizer::EqualizerBands;
 use std::num::{NonZeroU16, NonZeroUsize};
+use termusiclib::config::v2::server::equalizer::EqualizerBand;
+use termusiclib::config::v2::server::{AudioOutput, ReplayGainMode, TimeStretchMode};
 use termusiclib::config::ServerOverlay;
 use tokio::runtime::Handle;
 
-use crate::{MediaInfo, Speed, Volume};
+use crate::{MediaInfo, PlayerCmdReply, Speed, Volume};
 
 use self::decoder::buffered_source::BufferedSource;
+use self::decoder::http_range_source::HttpRangeSource;
 use self::decoder::read_seek_source::ReadSeekSource;
 use self::decoder::{MediaTitleRx, MediaTitleType};
 
 use super::{PlayerCmd, PlayerProgress, PlayerTrait};
-use anyhow::{anyhow, Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use parking_lot::Mutex;
 use std::fs::File;
 use std::path::Path;
-use std::sync::atomic::{AtomicU16, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU16, AtomicU32, AtomicU64, Ordering};
 use std::sync::mpsc::{self, Receiver, Sender};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use stream_download::http::{
     reqwest::{
         header::{HeaderMap, HeaderValue},
@@ -46,6 +54,11 @@ use symphonia::core::io::{
 };
 use termusiclib::track::{MediaType, Track};
 
+/// How often to check at most whether the configured output device is available again while the default device is used instead
+const DEVICE_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+/// The device name reported while no output could be opened and the audio is discarded
+const FALLBACK_DEVICE_NAME: &str = "null (no usable output)";
+
 pub type TotalDuration = Option<Duration>;
 pub type ArcTotalDuration = Arc<Mutex<TotalDuration>>;
 
@@ -53,12 +66,13 @@ pub type ArcTotalDuration = Arc<Mutex<TotalDuration>>;
 pub enum PlayerInternalCmd {
     MessageOnEnd,
     /// Enqueue a new track to be played, and skip to it
-    /// (Track, gapless)
-    Play(Box<Track>, bool),
-    Progress(Duration),
+    /// (Track, gapless, replaygain factor)
+    Play(Box<Track>, bool, f32),
+    /// Position and total duration of the source that is currently audible
+    Progress(Duration, Option<Duration>),
     /// Enqueue a new track to be played, but do not skip current track
-    /// (Track, gapless)
-    QueueNext(Box<Track>, bool),
+    /// (Track, gapless, replaygain factor, crossfade length)
+    QueueNext(Box<Track>, bool, f32, Option<Duration>),
     Resume,
     SeekAbsolute(Duration),
     SeekRelative(i64),
@@ -67,12 +81,33 @@ pub enum PlayerInternalCmd {
     Stop,
     TogglePause,
     Volume(u16),
+    /// Change how the speed is applied
+    TimeStretch(TimeStretchMode),
+    /// Change the equalizer bands, empty to disable it
+    Equalizer(EqualizerBands),
+    /// Switch to the output device with the given name, [`None`] for the default device.
+    ///
+    /// Replies with the device actually used once the new device has been opened (or failed to).
+    OutputDevice(Option<String>, PlayerCmdReply<Option<String>>),
+    /// The stream of the current output reported a error, like the device being unplugged
+    OutputLost,
     Eos,
 }
 pub struct RustyBackend {
     volume: Arc<AtomicU16>,
     speed: i32,
     gapless: bool,
+    replaygain: ReplayGainMode,
+    /// Crossfade length in milliseconds, `0` if disabled
+    crossfade_ms: Arc<AtomicU32>,
+    /// Max size of the cache for streamed podcast episodes in bytes, `0` for no limit
+    stream_cache_max_size: Arc<AtomicU64>,
+    /// Media type and album of the last played or enqueued track, to decide whether to crossfade into the next
+    last_queued: Option<(MediaType, Option<String>)>,
+    /// The name of the selected output device, [`None`] for the default device
+    output_device: Option<String>,
+    /// The name of the output device the player thread actually opened, see [`Output::device`]
+    opened_device: Arc<Mutex<Option<String>>>,
     command_tx: Sender<PlayerInternalCmd>,
     position: Arc<Mutex<Duration>>,
     total_duration: ArcTotalDuration,
@@ -97,6 +132,18 @@ impl RustyBackend {
         let volume_local = volume.clone();
         let speed = config.settings.player.speed;
         let gapless = config.settings.player.gapless;
+        let replaygain = config.settings.player.replaygain;
+        let crossfade_ms = Arc::new(AtomicU32::new(config.settings.player.crossfade_ms));
+        let crossfade_ms_local = crossfade_ms.clone();
+        let stream_cache_max_size = Arc::new(AtomicU64::new(mib_to_bytes(
+            config.settings.podcast.stream_cache_max_mib,
+        )));
+        let stream_cache_max_size_local = stream_cache_max_size.clone();
+        let output_device = config.settings.player.output_device.clone();
+        let output_device_local = output_device.clone();
+        let opened_device = Arc::new(Mutex::new(None));
+        let opened_device_local = opened_device.clone();
+        let audio_output = config.get_output().clone();
         let position = Arc::new(Mutex::new(Duration::default()));
         let total_duration = Arc::new(Mutex::new(None));
         let total_duration_local = total_duration.clone();
@@ -122,21 +169,36 @@ impl RustyBackend {
                     position_local,
                     volume_local,
                     speed,
+                    crossfade_ms_local,
+                    stream_cache_max_size_local,
+                    output_device_local,
+                    opened_device_local,
+                    audio_output,
                 ));
             })
             .expect("failed to spawn thread");
 
-        Self {
+        let backend = Self {
             total_duration,
             volume,
             speed,
             gapless,
+            replaygain,
+            crossfade_ms,
+            stream_cache_max_size,
+            last_queued: None,
+            output_device,
+            opened_device,
             command_tx: picmd_tx,
             position,
             media_title,
             radio_downloaded,
             // cmd_tx_outside: cmd_tx,
-        }
+        };
+        backend.set_time_stretch(config.settings.player.time_stretch);
+        backend.set_equalizer(config.settings.player.equalizer.active_bands());
+
+        backend
     }
 
     #[allow(clippy::needless_pass_by_value)]
@@ -149,6 +211,87 @@ impl RustyBackend {
     pub fn message_on_end(&self) {
         self.command(PlayerInternalCmd::MessageOnEnd);
     }
+
+    /// Set which replay gain values to use, only applies to tracks started after this call
+    pub fn set_replaygain(&mut self, mode: ReplayGainMode) {
+        self.replaygain = mode;
+    }
+
+    /// Set how the speed is applied, falls back to [`TimeStretchMode::Sonic`] if `mode` is not compiled-in
+    pub fn set_time_stretch(&self, mode: TimeStretchMode) {
+        let mode = if source::scaletempo::is_supported(mode) {
+            mode
+        } else {
+            warn!("Time stretch mode {mode:?} is not supported by this build, using sonic instead");
+            TimeStretchMode::Sonic
+        };
+        self.command(PlayerInternalCmd::TimeStretch(mode));
+    }
+
+    /// Set the equalizer bands, [`None`] to disable the equalizer
+    pub fn set_equalizer(&self, bands: Option<&[EqualizerBand]>) {
+        self.command(PlayerInternalCmd::Equalizer(Arc::from(
+            bands.unwrap_or_default(),
+        )));
+    }
+
+    /// Switch to the output device with the given name, [`None`] for the default device.
+    ///
+    /// Does nothing if the device is already selected.
+    /// Otherwise this does not wait for the device to be opened, `reply` gets the device actually used once that is done.
+    pub fn set_output_device(
+        &mut self,
+        device: Option<String>,
+        reply: PlayerCmdReply<Option<String>>,
+    ) {
+        if self.output_device == device {
+            reply.send(self.opened_output_device());
+            return;
+        }
+        self.output_device.clone_from(&device);
+        self.command(PlayerInternalCmd::OutputDevice(device, reply));
+    }
+
+    /// Get the name of the output device that is actually used, [`None`] for the default device or a headless output.
+    ///
+    /// This differs from the selected device if it could not be opened or disappeared while playing.
+    /// If no output could be opened at all, this is a placeholder name for the discarding fallback.
+    #[must_use]
+    pub fn opened_output_device(&self) -> Option<String> {
+        self.opened_device.lock().clone()
+    }
+
+    /// Set the crossfade length in milliseconds, `0` to disable
+    pub fn set_crossfade(&mut self, ms: u32) {
+        self.crossfade_ms.store(ms, Ordering::SeqCst);
+    }
+
+    /// Set the max size of the cache for streamed podcast episodes in MiB, `0` for no limit
+    pub fn set_stream_cache_max_size(&mut self, mib: u64) {
+        self.stream_cache_max_size
+            .store(mib_to_bytes(mib), Ordering::SeqCst);
+    }
+
+    /// Get the crossfade length to use from the last queued track into `next`, if any
+    fn crossfade_into(&self, next: &Track) -> Option<Duration> {
+        let ms = self.crossfade_ms.load(Ordering::SeqCst);
+        if ms == 0 {
+            return None;
+        }
+        let (media_type, album) = self.last_queued.as_ref()?;
+
+        // a live stream has no end to fade out of or to time the fade-in with
+        if *media_type == MediaType::LiveRadio || next.media_type == MediaType::LiveRadio {
+            return None;
+        }
+
+        // consecutive tracks of a album are usually meant to flow into each other
+        if album.is_some() && album.as_deref() == next.album() {
+            return None;
+        }
+
+        Some(Duration::from_millis(u64::from(ms)))
+    }
 }
 
 #[async_trait]
@@ -157,7 +300,9 @@ impl PlayerTrait for RustyBackend {
         self.command(PlayerInternalCmd::Play(
             Box::new(track.clone()),
             self.gapless,
+            track.replay_gain().factor(self.replaygain),
         ));
+        self.last_queued = Some((track.media_type, track.album().map(ToString::to_string)));
         self.resume();
     }
 
@@ -236,7 +381,10 @@ impl PlayerTrait for RustyBackend {
         self.command(PlayerInternalCmd::QueueNext(
             Box::new(track.clone()),
             self.gapless,
+            track.replay_gain().factor(self.replaygain),
+            self.crossfade_into(track),
         ));
+        self.last_queued = Some((track.media_type, track.album().map(ToString::to_string)));
     }
 
     fn media_info(&self) -> MediaInfo {
@@ -257,13 +405,14 @@ fn append_to_sink_inner_media_title<F: FnOnce(&mut Symphonia, MediaTitleRx)>(
     trace: &str,
     sink: &Sink,
     gapless: bool,
+    gain: f32,
     func: F,
 ) {
     let mss = MediaSourceStream::new(media_source, MediaSourceStreamOptions::default());
     match Symphonia::new_with_media_title(mss, gapless) {
         Ok((mut decoder, rx)) => {
             func(&mut decoder, rx);
-            sink.append(decoder);
+            sink.append(decoder.amplify(gain));
         }
         Err(e) => error!("error decoding '{trace}' is: {e:?}"),
     }
@@ -275,13 +424,14 @@ fn append_to_sink_inner<F: FnOnce(&mut Symphonia)>(
     trace: &str,
     sink: &Sink,
     gapless: bool,
+    gain: f32,
     func: F,
 ) {
     let mss = MediaSourceStream::new(media_source, MediaSourceStreamOptions::default());
     match Symphonia::new(mss, gapless) {
         Ok(mut decoder) => {
             func(&mut decoder);
-            sink.append(decoder);
+            sink.append(decoder.amplify(gain));
         }
         Err(e) => error!("error decoding '{trace}' is: {e:?}"),
     }
@@ -295,6 +445,7 @@ fn append_to_sink<MT: Fn(MediaTitleType) + Send + 'static>(
     trace: &str,
     sink: &Sink,
     gapless: bool,
+    gain: f32,
     total_duration_local: &ArcTotalDuration,
     media_title_fn: MT,
 ) {
@@ -303,6 +454,7 @@ fn append_to_sink<MT: Fn(MediaTitleType) + Send + 'static>(
         trace,
         sink,
         gapless,
+        gain,
         |decoder, mut media_title_rx| {
             std::mem::swap(
                 &mut *total_duration_local.lock(),
@@ -326,17 +478,18 @@ fn append_to_sink_no_duration(
     trace: &str,
     sink: &Sink,
     gapless: bool,
+    gain: f32,
     total_duration_local: &ArcTotalDuration,
 ) {
-    append_to_sink_inner(media_source, trace, sink, gapless, |_| {
+    append_to_sink_inner(media_source, trace, sink, gapless, gain, |_| {
         // remove old stale duration
         total_duration_local.lock().take();
     });
 }
 
-/// Append the `media_source` to the `sink`, while also setting `next_duration_opt`
+/// Append the `media_source` to the `sink`
 ///
-/// This is used for enqueued entries which do not start immediately
+/// This is used for enqueued entries which do not start immediately, their duration is set once they are audible
 ///
 /// Expects current thread to have a tokio handle
 fn append_to_sink_queue<MT: Fn(MediaTitleType) + Send + 'static>(
@@ -344,8 +497,7 @@ fn append_to_sink_queue<MT: Fn(MediaTitleType) + Send + 'static>(
     trace: &str,
     sink: &Sink,
     gapless: bool,
-    // total_duration_local: &ArcTotalDuration,
-    next_duration_opt: &mut Option<Duration>,
+    gain: f32,
     media_title_fn: MT,
 ) {
     append_to_sink_inner_media_title(
@@ -353,9 +505,8 @@ fn append_to_sink_queue<MT: Fn(MediaTitleType) + Send + 'static>(
         trace,
         sink,
         gapless,
-        |decoder, mut media_title_rx| {
-            std::mem::swap(next_duration_opt, &mut decoder.total_duration());
-            // rely on EOS message to set next duration
+        gain,
+        |_decoder, mut media_title_rx| {
             sink.message_on_end();
 
             let handle = Handle::current();
@@ -369,7 +520,7 @@ fn append_to_sink_queue<MT: Fn(MediaTitleType) + Send + 'static>(
     );
 }
 
-/// Append the `media_source` to the `sink`, while also setting `next_duration_opt` to be unknown (to [`None`])
+/// Append the `media_source` to the `sink`, without a known duration
 ///
 /// This is used for enqueued entries which do not start immediately
 fn append_to_sink_queue_no_duration(
@@ -377,13 +528,9 @@ fn append_to_sink_queue_no_duration(
     trace: &str,
     sink: &Sink,
     gapless: bool,
-    // total_duration_local: &ArcTotalDuration,
-    next_duration_opt: &mut Option<Duration>,
+    gain: f32,
 ) {
-    append_to_sink_inner(media_source, trace, sink, gapless, |_| {
-        // remove potential old stale duration
-        next_duration_opt.take();
-        // rely on EOS message to set next duration
+    append_to_sink_inner(media_source, trace, sink, gapless, gain, |_| {
         sink.message_on_end();
     });
 }
@@ -396,6 +543,289 @@ fn common_media_title_cb(media_title: Arc<Mutex<String>>) -> impl Fn(MediaTitleT
     }
 }
 
+/// Get the names of all available output devices
+///
+/// # Errors
+///
+/// - if the devices could not be listed
+pub fn output_device_names() -> Result<Vec<String>> {
+    let devices = cpal::default_host()
+        .output_devices()
+        .context("Failed to list output devices")?;
+
+    Ok(devices.filter_map(|device| device.name().ok()).collect())
+}
+
+/// Find the output device with the given name
+fn find_output_device(name: &str) -> Option<cpal::Device> {
+    cpal::default_host()
+        .output_devices()
+        .ok()?
+        .find(|device| device.name().is_ok_and(|v| v == name))
+}
+
+/// Open a stream with the default config of `device`, playing everything added to the returned mixer.
+///
+/// The first error of the stream (like the device being unplugged) sets `lost` and sends [`PlayerInternalCmd::OutputLost`].
+fn open_device_stream(
+    device: &cpal::Device,
+    lost: &Arc<AtomicBool>,
+    picmd_tx: &Sender<PlayerInternalCmd>,
+) -> Result<(cpal::Stream, Arc<DynamicMixerController<f32>>)> {
+    let config = device
+        .default_output_config()
+        .context("Failed to get the default output config")?;
+    let (mixer, mixer_rx) = dynamic_mixer::mixer::<f32>(config.channels(), config.sample_rate().0);
+
+    let lost = lost.clone();
+    let picmd_tx = picmd_tx.clone();
+    let on_error = move |err| {
+        error!("Error on the output stream: {err}");
+        if !lost.swap(true, Ordering::SeqCst) {
+            let _ = picmd_tx.send(PlayerInternalCmd::OutputLost);
+        }
+    };
+
+    let stream_config = config.config();
+    let stream = match config.sample_format() {
+        cpal::SampleFormat::F32 => {
+            build_output_stream::<f32>(device, &stream_config, mixer_rx, on_error)
+        }
+        cpal::SampleFormat::F64 => {
+            build_output_stream::<f64>(device, &stream_config, mixer_rx, on_error)
+        }
+        cpal::SampleFormat::I8 => {
+            build_output_stream::<i8>(device, &stream_config, mixer_rx, on_error)
+        }
+        cpal::SampleFormat::I16 => {
+            build_output_stream::<i16>(device, &stream_config, mixer_rx, on_error)
+        }
+        cpal::SampleFormat::I32 => {
+            build_output_stream::<i32>(device, &stream_config, mixer_rx, on_error)
+        }
+        cpal::SampleFormat::I64 => {
+            build_output_stream::<i64>(device, &stream_config, mixer_rx, on_error)
+        }
+        cpal::SampleFormat::U8 => {
+            build_output_stream::<u8>(device, &stream_config, mixer_rx, on_error)
+        }
+        cpal::SampleFormat::U16 => {
+            build_output_stream::<u16>(device, &stream_config, mixer_rx, on_error)
+        }
+        cpal::SampleFormat::U32 => {
+            build_output_stream::<u32>(device, &stream_config, mixer_rx, on_error)
+        }
+        cpal::SampleFormat::U64 => {
+            build_output_stream::<u64>(device, &stream_config, mixer_rx, on_error)
+        }
+        format => bail!("Unsupported sample format {format}"),
+    }
+    .context("Failed to build the output stream")?;
+    stream.play().context("Failed to start the output stream")?;
+
+    Ok((stream, mixer))
+}
+
+/// Build a output stream with samples of type `T`, filled from `mixer_rx`
+fn build_output_stream<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    mut mixer_rx: DynamicMixer<f32>,
+    on_error: impl FnMut(cpal::StreamError) + Send + 'static,
+) -> Result<cpal::Stream, cpal::BuildStreamError>
+where
+    T: cpal::SizedSample + cpal::FromSample<f32>,
+{
+    device.build_output_stream(
+        config,
+        move |data: &mut [T], _| {
+            for sample in data {
+                *sample = mixer_rx.next().map_or(T::EQUILIBRIUM, T::from_sample);
+            }
+        },
+        on_error,
+        None,
+    )
+}
+
+/// Where the audio of a [`Sink`] goes to
+enum OutputKind {
+    /// A audio device, which plays as long as the stream is not dropped
+    Device {
+        _stream: cpal::Stream,
+        mixer: Arc<DynamicMixerController<f32>>,
+    },
+    Headless(HeadlessOutput),
+}
+
+/// A opened output
+struct Output {
+    kind: OutputKind,
+    /// The name of the device actually opened, [`None`] if it is the default device or not a device
+    device: Option<String>,
+    /// Set once the stream reported a error, see [`PlayerInternalCmd::OutputLost`]
+    lost: Arc<AtomicBool>,
+    /// When the available devices have last been listed
+    last_device_check: Instant,
+    /// Whether the audio is only discarded because no output could be opened
+    fallback: bool,
+}
+
+impl Output {
+    /// Open the given output, for [`AudioOutput::Device`] the device with the given name, [`None`] for the default device.
+    ///
+    /// Falls back to the default device if the given device does not exist (anymore) or fails to open.
+    fn open(
+        output: &AudioOutput,
+        name: Option<&str>,
+        picmd_tx: &Sender<PlayerInternalCmd>,
+    ) -> Result<Self> {
+        let lost = Arc::new(AtomicBool::new(false));
+        if let Some(headless) = HeadlessOutput::new(output) {
+            return Ok(Self {
+                kind: OutputKind::Headless(headless),
+                device: None,
+                lost,
+                last_device_check: Instant::now(),
+                fallback: false,
+            });
+        }
+
+        if let Some(name) = name {
+            match find_output_device(name)
+                .map(|device| open_device_stream(&device, &lost, picmd_tx))
+            {
+                Some(Ok((stream, mixer))) => {
+                    return Ok(Self {
+                        kind: OutputKind::Device {
+                            _stream: stream,
+                            mixer,
+                        },
+                        device: Some(name.to_string()),
+                        lost,
+                        last_device_check: Instant::now(),
+                        fallback: false,
+                    })
+                }
+                Some(Err(err)) => {
+                    warn!(
+                        "Failed to open output device {name:?}, using the default device: {err:#}"
+                    );
+                }
+                None => warn!("Output device {name:?} not found, using the default device"),
+            }
+        }
+
+        let Some(device) = cpal::default_host().default_output_device() else {
+            bail!("No default output device available");
+        };
+        let (stream, mixer) = open_device_stream(&device, &lost, picmd_tx)
+            .context("Failed to open the default output device")?;
+
+        Ok(Self {
+            kind: OutputKind::Device {
+                _stream: stream,
+                mixer,
+            },
+            device: None,
+            lost,
+            last_device_check: Instant::now(),
+            fallback: false,
+        })
+    }
+
+    /// Open the given output like [`Self::open`] together with a [`Sink`] on it.
+    ///
+    /// If that fails, the audio is discarded instead, so that the player keeps working without a usable output.
+    fn open_or_fallback(
+        output: &AudioOutput,
+        name: Option<&str>,
+        picmd_tx: &Sender<PlayerInternalCmd>,
+        pcmd_tx: &crate::PlayerCmdSender,
+    ) -> (Self, Sink) {
+        let res = Self::open(output, name, picmd_tx).and_then(|mut output| {
+            let sink = output.new_sink(picmd_tx, pcmd_tx)?;
+            Ok((output, sink))
+        });
+        let err = match res {
+            Ok(v) => return v,
+            Err(err) => err,
+        };
+        error!(
+            "Failed to open the audio output, discarding the audio until it can be opened: {err:#}"
+        );
+
+        let headless = HeadlessOutput::new(&AudioOutput::Null { fast: false })
+            .expect("Null is a headless output");
+        let mut output = Self {
+            kind: OutputKind::Headless(headless),
+            device: Some(FALLBACK_DEVICE_NAME.to_string()),
+            lost: Arc::new(AtomicBool::new(false)),
+            last_device_check: Instant::now(),
+            fallback: true,
+        };
+        // the null output only fails if its thread cannot be spawned, like any other thread
+        let sink = output
+            .new_sink(picmd_tx, pcmd_tx)
+            .expect("failed to spawn thread");
+
+        (output, sink)
+    }
+
+    /// Create a new [`Sink`] playing on this output
+    fn new_sink(
+        &mut self,
+        picmd_tx: &Sender<PlayerInternalCmd>,
+        pcmd_tx: &crate::PlayerCmdSender,
+    ) -> Result<Sink> {
+        match &mut self.kind {
+            OutputKind::Device { mixer, .. } => {
+                Ok(Sink::new(mixer, picmd_tx.clone(), pcmd_tx.clone()))
+            }
+            OutputKind::Headless(headless) => {
+                let (sink, queue_rx) = Sink::new_idle(picmd_tx.clone(), pcmd_tx.clone());
+                headless.play(queue_rx, sink.status())?;
+
+                Ok(sink)
+            }
+        }
+    }
+
+    fn is_headless(&self) -> bool {
+        matches!(self.kind, OutputKind::Headless(_))
+    }
+
+    /// Whether the stream of this output reported a error
+    fn is_lost(&self) -> bool {
+        self.lost.load(Ordering::SeqCst)
+    }
+
+    /// Check whether the `wanted` device should be opened again, because the stream failed or because it is back after a fallback.
+    ///
+    /// Listing the devices is slow, so it is only done while falling back and at most every [`DEVICE_CHECK_INTERVAL`].
+    fn needs_reopen(&mut self, wanted: Option<&str>, now: Instant) -> bool {
+        // also applies to the default device, which is otherwise never listed
+        if self.fallback || self.is_lost() {
+            return true;
+        }
+        if self.is_headless() {
+            return false;
+        }
+        let Some(wanted) = wanted else {
+            return false;
+        };
+        // a disappearing device is reported by the stream error
+        if self.device.as_deref() == Some(wanted)
+            || now.saturating_duration_since(self.last_device_check) < DEVICE_CHECK_INTERVAL
+        {
+            return false;
+        }
+
+        self.last_device_check = now;
+        find_output_device(wanted).is_some()
+    }
+}
+
 /// Player thread loop
 #[allow(
     clippy::cast_precision_loss,
@@ -415,14 +845,21 @@ async fn player_thread(
     position: Arc<Mutex<Duration>>,
     volume_inside: Arc<AtomicU16>,
     mut speed_inside: i32,
+    crossfade_ms: Arc<AtomicU32>,
+    stream_cache_max_size: Arc<AtomicU64>,
+    mut output_device: Option<String>,
+    opened_device: Arc<Mutex<Option<String>>>,
+    audio_output: AudioOutput,
 ) {
     let mut is_radio = false;
 
-    // option to store enqueued's duration
-    // note that the current implementation is only meant to have 1 enqueued next after the current playing song
-    let mut next_duration_opt = None;
-    let (_stream, handle) = OutputStream::try_default().unwrap();
-    let sink = Sink::try_new(&handle, picmd_tx.clone(), pcmd_tx.clone()).unwrap();
+    // the tracks currently in the sink, to re-add them when the sink has to be replaced
+    // (Track, gapless, replaygain factor)
+    let mut current_track: Option<(Box<Track>, bool, f32)> = None;
+    let mut next_track: Option<(Box<Track>, bool, f32)> = None;
+    let (mut output, mut sink) =
+        Output::open_or_fallback(&audio_output, output_device.as_deref(), &picmd_tx, &pcmd_tx);
+    report_opened_device(&output, &opened_device, &pcmd_tx);
     sink.set_speed(speed_inside as f32 / 10.0);
     sink.set_volume(f32::from(volume_inside.load(Ordering::SeqCst)) / 100.0);
     loop {
@@ -432,42 +869,66 @@ async fn player_thread(
         };
 
         match cmd {
-            PlayerInternalCmd::Play(track, gapless) => {
+            PlayerInternalCmd::Play(track, gapless, gain) => {
+                if output.needs_reopen(output_device.as_deref(), Instant::now()) {
+                    match Output::open(&audio_output, output_device.as_deref(), &picmd_tx).and_then(
+                        |mut new_output| {
+                            let new_sink =
+                                replace_sink(&mut new_output, &sink, &picmd_tx, &pcmd_tx)?;
+                            Ok((new_output, new_sink))
+                        },
+                    ) {
+                        Ok((new_output, new_sink)) => {
+                            sink = new_sink;
+                            output = new_output;
+                            report_opened_device(&output, &opened_device, &pcmd_tx);
+                        }
+                        Err(err) => error!("Failed to switch output device: {err:#}"),
+                    }
+                }
+
+                sink.set_next_crossfade(None);
                 if let Err(err) = queue_next(
                     &track,
                     gapless,
+                    gain,
                     &sink,
                     &mut is_radio,
                     &total_duration,
-                    &mut next_duration_opt,
                     &media_title,
                     // &radio_downloaded,
+                    stream_cache_max_size.load(Ordering::SeqCst),
                     false,
                 )
                 .await
                 {
                     error!("Failed to play track: {:#?}", err);
                 }
+                current_track = Some((track, gapless, gain));
+                next_track = None;
             }
             PlayerInternalCmd::TogglePause => {
                 sink.toggle_playback();
             }
-            PlayerInternalCmd::QueueNext(track, gapless) => {
+            PlayerInternalCmd::QueueNext(track, gapless, gain, crossfade) => {
+                sink.set_next_crossfade(crossfade);
                 if let Err(err) = queue_next(
                     &track,
                     gapless,
+                    gain,
                     &sink,
                     &mut is_radio,
                     &total_duration,
-                    &mut next_duration_opt,
                     &media_title,
                     // &radio_downloaded,
+                    stream_cache_max_size.load(Ordering::SeqCst),
                     true,
                 )
                 .await
                 {
                     error!("Failed to queue next track: {:#?}", err);
                 }
+                next_track = Some((track, gapless, gain));
             }
             PlayerInternalCmd::Resume => {
                 sink.play();
@@ -478,6 +939,99 @@ async fn player_thread(
             }
             PlayerInternalCmd::Stop => {
                 sink.stop();
+                current_track = None;
+                next_track = None;
+            }
+            PlayerInternalCmd::TimeStretch(mode) => {
+                sink.set_time_stretch(mode);
+            }
+            PlayerInternalCmd::Equalizer(bands) => {
+                sink.set_equalizer(bands);
+            }
+            cmd @ (PlayerInternalCmd::OutputDevice(..) | PlayerInternalCmd::OutputLost) => {
+                let reply = match cmd {
+                    PlayerInternalCmd::OutputDevice(device, reply) => {
+                        output_device = device;
+                        reply
+                    }
+                    // a error from a output that has already been replaced
+                    _ if !output.is_lost() => continue,
+                    _ => {
+                        warn!("Lost the output device, opening it again");
+                        PlayerCmdReply::default()
+                    }
+                };
+                if output.is_headless() && !output.fallback {
+                    info!("Not switching output device, because a headless output is used");
+                    reply.send(output.device.clone());
+                    continue;
+                }
+                let had_sources = !sink.is_empty();
+                let paused = sink.is_paused();
+                let position = sink.elapsed();
+                match Output::open(&audio_output, output_device.as_deref(), &picmd_tx).and_then(
+                    |mut new_output| {
+                        let new_sink = replace_sink(&mut new_output, &sink, &picmd_tx, &pcmd_tx)?;
+                        Ok((new_output, new_sink))
+                    },
+                ) {
+                    Ok((new_output, new_sink)) => {
+                        sink = new_sink;
+                        output = new_output;
+                        report_opened_device(&output, &opened_device, &pcmd_tx);
+                        info!("Switched output device to {:?}", output.device);
+                    }
+                    Err(err) => error!("Failed to switch output device: {err:#}"),
+                }
+                reply.send(output.device.clone());
+
+                if !had_sources {
+                    continue;
+                }
+
+                // re-add the tracks to the new sink and continue where the old sink was
+                if let Some((track, gapless, gain)) = &current_track {
+                    if let Err(err) = queue_next(
+                        track,
+                        *gapless,
+                        *gain,
+                        &sink,
+                        &mut is_radio,
+                        &total_duration,
+                        &media_title,
+                        stream_cache_max_size.load(Ordering::SeqCst),
+                        false,
+                    )
+                    .await
+                    {
+                        error!("Failed to play track: {:#?}", err);
+                    }
+                    if !is_radio {
+                        sink.seek(position);
+                    }
+                    if paused {
+                        sink.pause();
+                    }
+                }
+                if let Some((track, gapless, gain)) = &next_track {
+                    if let Err(err) = queue_next(
+                        track,
+                        *gapless,
+                        *gain,
+                        &sink,
+                        &mut is_radio,
+                        &total_duration,
+                        &media_title,
+                        stream_cache_max_size.load(Ordering::SeqCst),
+                        true,
+                    )
+                    .await
+                    {
+                        error!("Failed to queue next track: {:#?}", err);
+                    }
+                } else {
+                    sink.message_on_end();
+                }
             }
             PlayerInternalCmd::Volume(volume) => {
                 sink.set_volume(f32::from(volume) / 100.0);
@@ -495,17 +1049,20 @@ async fn player_thread(
                     sink.play();
                 }
             }
-            PlayerInternalCmd::Progress(new_position) => {
-                // let position = sink.elapsed().as_secs() as i64;
-                // error!("position in rusty backend is: {}", position);
+            PlayerInternalCmd::Progress(new_position, new_total_duration) => {
+                // both values come from the same source, so they always belong to the same track, even around gapless transitions
                 *position.lock() = new_position;
+                *total_duration.lock() = new_total_duration;
 
                 // About to finish signal is a simulation of gstreamer, and used for gapless
                 if !is_radio {
-                    if let Some(d) = *total_duration.lock() {
+                    if let Some(d) = new_total_duration {
                         let progress = new_position.as_secs_f64() / d.as_secs_f64();
+                        // the next track needs to be enqueued before the crossfade starts
+                        let crossfade =
+                            Duration::from_millis(u64::from(crossfade_ms.load(Ordering::SeqCst)));
                         if progress >= 0.5
-                            && d.saturating_sub(new_position) < Duration::from_secs(2)
+                            && d.saturating_sub(new_position) < Duration::from_secs(2) + crossfade
                         {
                             if let Err(e) = pcmd_tx.send(PlayerCmd::AboutToFinish) {
                                 error!("command AboutToFinish sent failed: {e}");
@@ -548,27 +1105,101 @@ async fn player_thread(
             }
 
             PlayerInternalCmd::Eos => {
-                // replace the current total_duration with the next one
-                // this is only present when QueueNext was used; which is only used if gapless is enabled
-                if next_duration_opt.is_some() {
-                    *total_duration.lock() = next_duration_opt;
+                // the total duration of the next track is set by its first progress
+                if next_track.is_some() {
+                    current_track = next_track.take();
                 }
             }
         }
     }
 }
 
+/// Create a new [`Sink`] on `output` with the same settings as `old`, and stop `old` without it sending End-of-Stream messages
+/// Publish the device of the newly opened `output`, see [`PlayerCmd::OutputDeviceOpened`]
+fn report_opened_device(
+    output: &Output,
+    opened_device: &Mutex<Option<String>>,
+    pcmd_tx: &crate::PlayerCmdSender,
+) {
+    opened_device.lock().clone_from(&output.device);
+    let _ = pcmd_tx.send(PlayerCmd::OutputDeviceOpened(output.device.clone()));
+}
+
+fn replace_sink(
+    output: &mut Output,
+    old: &Sink,
+    picmd_tx: &Sender<PlayerInternalCmd>,
+    pcmd_tx: &crate::PlayerCmdSender,
+) -> Result<Sink> {
+    let sink = output.new_sink(picmd_tx, pcmd_tx)?;
+    sink.set_speed(old.speed());
+    sink.set_volume(old.volume());
+    sink.set_time_stretch(old.time_stretch());
+    sink.set_equalizer(old.equalizer());
+
+    old.disable_eos();
+    old.stop();
+
+    Ok(sink)
+}
+
+fn mib_to_bytes(mib: u64) -> u64 {
+    mib.saturating_mul(1024 * 1024)
+}
+
+/// Open a remote podcast episode.
+///
+/// Uses range requests with a on-disk cache if the server supports them, so that seeking does not need to download
+/// everything before the new position. Otherwise the episode is streamed from the start.
+///
+/// The cache is limited to `max_cache_size` bytes, `0` for no limit.
+async fn open_remote_podcast(url: &str, max_cache_size: u64) -> Result<Box<dyn MediaSource>> {
+    let range_source = match termusiclib::utils::get_app_cache_path() {
+        Ok(cache_dir) => {
+            HttpRangeSource::open(
+                Client::new(),
+                url,
+                &cache_dir.join("podcast-stream"),
+                max_cache_size,
+            )
+            .await
+        }
+        Err(err) => Err(err),
+    };
+    match range_source {
+        Ok(Some(source)) => {
+            let len = source.byte_len();
+            return Ok(Box::new(ReadSeekSource::new(source, Some(len))));
+        }
+        Ok(None) => info!("Server does not support range requests, streaming from the start"),
+        Err(err) => warn!("Failed to open with range requests, streaming from the start: {err:#}"),
+    }
+
+    let settings = StreamSettings::default();
+    let stream = HttpStream::<Client>::create(url.parse()?).await?;
+    let file_len = stream.content_length();
+    let reader = StreamDownload::from_stream(
+        stream,
+        TempStorageProvider::with_prefix(".termusic-stream-cache-"),
+        settings,
+    )
+    .await?;
+
+    Ok(Box::new(ReadSeekSource::new(reader, file_len)))
+}
+
 /// Queue the given track into the [`Sink`], while also setting all of the other variables
 #[allow(clippy::too_many_arguments, clippy::too_many_lines)]
 async fn queue_next(
     track: &Track,
     gapless: bool,
+    gain: f32,
     sink: &Sink,
 
     is_radio: &mut bool,
     total_duration: &ArcTotalDuration,
-    next_duration_opt: &mut Option<Duration>,
     media_title: &Arc<Mutex<String>>,
+    stream_cache_max_size: u64,
     enqueue: bool,
 ) -> Result<()> {
     let media_type = &track.media_type;
@@ -587,7 +1218,7 @@ async fn queue_next(
                     &file_path,
                     sink,
                     gapless,
-                    next_duration_opt,
+                    gain,
                     common_media_title_cb(media_title.clone()),
                 );
             } else {
@@ -596,6 +1227,7 @@ async fn queue_next(
                     &file_path,
                     sink,
                     gapless,
+                    gain,
                     total_duration,
                     common_media_title_cb(media_title.clone()),
                 );
@@ -615,7 +1247,7 @@ async fn queue_next(
                         &file_path,
                         sink,
                         gapless,
-                        next_duration_opt,
+                        gain,
                         common_media_title_cb(media_title.clone()),
                     );
                 } else {
@@ -624,6 +1256,7 @@ async fn queue_next(
                         &file_path,
                         sink,
                         gapless,
+                        gain,
                         total_duration,
                         common_media_title_cb(media_title.clone()),
                     );
@@ -632,34 +1265,24 @@ async fn queue_next(
             }
 
             let url = file_path;
-            let settings = StreamSettings::default();
-
-            let stream = HttpStream::<Client>::create(url.parse()?).await?;
-
-            let file_len = stream.content_length();
-
-            let reader = StreamDownload::from_stream(
-                stream,
-                TempStorageProvider::with_prefix(".termusic-stream-cache-"),
-                settings,
-            )
-            .await?;
+            let media_source = open_remote_podcast(&url, stream_cache_max_size).await?;
 
             if enqueue {
                 append_to_sink_queue(
-                    Box::new(ReadSeekSource::new(reader, file_len)),
+                    media_source,
                     &url,
                     sink,
                     gapless,
-                    next_duration_opt,
+                    gain,
                     common_media_title_cb(media_title.clone()),
                 );
             } else {
                 append_to_sink(
-                    Box::new(ReadSeekSource::new(reader, file_len)),
+                    media_source,
                     &url,
                     sink,
                     gapless,
+                    gain,
                     total_duration,
                     common_media_title_cb(media_title.clone()),
                 );
@@ -729,15 +1352,9 @@ async fn queue_next(
             };
 
             if enqueue {
-                append_to_sink_queue_no_duration(
-                    media_source,
-                    &url,
-                    sink,
-                    gapless,
-                    next_duration_opt,
-                );
+                append_to_sink_queue_no_duration(media_source, &url, sink, gapless, gain);
             } else {
-                append_to_sink_no_duration(media_source, &url, sink, gapless, total_duration);
+                append_to_sink_no_duration(media_source, &url, sink, gapless, gain, total_duration);
             }
 
             Ok(())
@@ -6,13 +6,15 @@ use std::sync::mpsc::{Receiver, Sender};
 use std::sync::Arc;
 use std::time::Duration;
 
-#[allow(unused_imports)] // used for "rusty-soundtouch"
+use super::source::crossfade::{crossfade, CrossfadeHandle};
+use super::source::equalizer::{equalizer, EqualizerBands};
 use super::source::SourceExt as _;
 use super::PlayerInternalCmd;
 use crate::PlayerCmd;
 use rodio::cpal::FromSample;
+use rodio::dynamic_mixer::DynamicMixerController;
 use rodio::{queue, source::Done, Sample, Source};
-use rodio::{OutputStreamHandle, PlayError};
+use termusiclib::config::v2::server::TimeStretchMode;
 
 /// Handle to an device that outputs sounds.
 ///
@@ -27,11 +29,36 @@ pub struct Sink {
     controls: Arc<Controls>,
     /// Indicates how many sources are currently in the queue.
     sound_count: Arc<AtomicUsize>,
+    /// The crossfade handle of the last added source
+    last_crossfade: Mutex<Option<Arc<CrossfadeHandle>>>,
+    /// The crossfade length to use for the next added source, see [`Sink::set_next_crossfade`]
+    next_crossfade: Mutex<Option<Duration>>,
 
     picmd_tx: Sender<PlayerInternalCmd>,
     pcmd_tx: crate::PlayerCmdSender,
 }
 
+/// Read-only view of the state of a [`Sink`], for outputs that need to know if there is something to play
+#[derive(Debug, Clone)]
+pub struct SinkStatus {
+    controls: Arc<Controls>,
+    sound_count: Arc<AtomicUsize>,
+}
+
+impl SinkStatus {
+    /// Gets if the sink has no sources anymore.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.sound_count.load(Ordering::Relaxed) == 0
+    }
+
+    /// Gets if the sink is paused.
+    #[inline]
+    pub fn is_paused(&self) -> bool {
+        self.controls.pause.load(Ordering::SeqCst)
+    }
+}
+
 /// The Controls for the Sink, most values store the value to be applied while some others store a remaining amount.
 #[derive(Debug)]
 struct Controls {
@@ -47,26 +74,32 @@ struct Controls {
     stopped: AtomicBool,
     /// Stores the speed to be applied.
     speed: Mutex<f32>,
+    /// Stores how the speed is applied.
+    time_stretch: RwLock<TimeStretchMode>,
     /// Stores how many sources should be skipped.
     ///
     /// Used for skipping / clearing while accounting for the case that a new source is added before finishing clearing.
     to_clear: Mutex<u32>,
     /// The current position in the currently playing source (may be off by a few milliseconds).
     position: RwLock<Duration>,
+    /// Stores the equalizer bands to be applied.
+    equalizer: RwLock<EqualizerBands>,
+    /// Stores whether End-of-Stream messages should not be sent anymore, see [`Sink::disable_eos`].
+    eos_disabled: AtomicBool,
 }
 
 #[allow(dead_code)]
 impl Sink {
-    /// Builds a new `Sink`, beginning playback on a stream.
+    /// Builds a new `Sink`, beginning playback on the mixer of a stream.
     #[inline]
-    pub fn try_new(
-        stream: &OutputStreamHandle,
+    pub fn new(
+        mixer: &DynamicMixerController<f32>,
         picmd_tx: Sender<PlayerInternalCmd>,
         pcmd_tx: crate::PlayerCmdSender,
-    ) -> Result<Self, PlayError> {
+    ) -> Self {
         let (sink, queue_rx) = Self::new_idle(picmd_tx, pcmd_tx);
-        stream.play_raw(queue_rx)?;
-        Ok(sink)
+        mixer.add(queue_rx);
+        sink
     }
 
     /// Builds a new `Sink`.
@@ -88,8 +121,13 @@ impl Sink {
                 speed: Mutex::new(1.0),
                 to_clear: Mutex::new(0),
                 position: RwLock::new(Duration::from_secs(0)),
+                time_stretch: RwLock::new(TimeStretchMode::default()),
+                equalizer: RwLock::new(Arc::from([])),
+                eos_disabled: AtomicBool::new(false),
             }),
             sound_count: Arc::new(AtomicUsize::new(0)),
+            last_crossfade: Mutex::new(None),
+            next_crossfade: Mutex::new(None),
             picmd_tx,
             pcmd_tx,
         };
@@ -115,12 +153,18 @@ impl Sink {
         }
 
         let controls = self.controls.clone();
-        #[cfg(feature = "rusty-soundtouch")]
         let controls_tempo = self.controls.clone();
+        let controls_eq = self.controls.clone();
 
         let start_played = AtomicBool::new(false);
 
+        let incoming = self.crossfade_incoming(&source);
+        let (source, handle) = crossfade(source, incoming);
+        *self.last_crossfade.lock() = Some(handle);
+
         let progress_tx = self.picmd_tx.clone();
+        // sent with every position, so that the position and duration are always of the audible source
+        let total_duration = source.total_duration();
         let source = source
             .speed(1.0)
             .track_position()
@@ -132,6 +176,7 @@ impl Sink {
                 progress_tx
                     .send(PlayerInternalCmd::Progress(
                         src.inner().inner().inner().inner().get_pos(),
+                        total_duration,
                     ))
                     .ok();
             })
@@ -161,32 +206,69 @@ impl Sink {
                     amp.inner_mut()
                         .set_paused(controls.pause.load(Ordering::SeqCst));
 
-                    #[cfg(not(feature = "rusty-soundtouch"))]
-                    {
-                        amp.inner_mut()
-                            .inner_mut()
-                            .inner_mut()
-                            .set_factor(*controls.speed.lock());
-                    }
+                    // resample only if the speed is not applied while keeping the pitch
+                    let speed = match *controls.time_stretch.read() {
+                        TimeStretchMode::Off => *controls.speed.lock(),
+                        TimeStretchMode::Sonic | TimeStretchMode::SoundTouch => 1.0,
+                    };
+                    amp.inner_mut().inner_mut().inner_mut().set_factor(speed);
 
                     start_played.store(true, Ordering::SeqCst);
                 }
             })
             .convert_samples();
 
-        #[cfg(feature = "rusty-soundtouch")]
+        let mode = *self.controls.time_stretch.read();
+        let source = source
+            .tempo_stretch(*self.controls.speed.lock(), mode)
+            .periodic_access(Duration::from_millis(100), move |src| {
+                src.set_mode(*controls_tempo.time_stretch.read());
+                src.set_factor(*controls_tempo.speed.lock());
+            });
+
+        let bands = self.controls.equalizer.read().clone();
         let source =
-            source
-                .tempo_stretch(1.0)
-                .periodic_access(Duration::from_millis(500), move |src| {
-                    src.set_factor(f64::from(*controls_tempo.speed.lock()));
-                });
+            equalizer(source, bands).periodic_access(Duration::from_millis(100), move |src| {
+                src.set_bands(&controls_eq.equalizer.read());
+            });
 
         self.sound_count.fetch_add(1, Ordering::Relaxed);
         let source = Done::new(source, self.sound_count.clone());
         *self.sleep_until_end.lock() = Some(self.queue_tx.append_with_signal(source));
     }
 
+    /// Set the length of the crossfade from the last added source into the next added source.
+    ///
+    /// Only applies to the next call of [`Sink::append`], [`None`] to not crossfade.
+    pub fn set_next_crossfade(&self, length: Option<Duration>) {
+        *self.next_crossfade.lock() = length;
+    }
+
+    /// Get the handle of the last added source to crossfade from, if a crossfade is requested and possible.
+    fn crossfade_incoming<S>(&self, source: &S) -> Option<Arc<CrossfadeHandle>>
+    where
+        S: Source,
+        S::Item: Sample,
+    {
+        let length = self.next_crossfade.lock().take()?;
+        let last = self.last_crossfade.lock().take()?;
+
+        // there is nothing to fade from
+        if self.is_empty() {
+            return None;
+        }
+
+        // mixing is done sample-by-sample, which requires the same format
+        if !last.is_compatible(source.channels(), source.sample_rate()) {
+            info!("Not crossfading, as the format of the next source is different");
+            return None;
+        }
+
+        last.set_fade_out(length);
+
+        Some(last)
+    }
+
     /// Gets the volume of the sound.
     ///
     /// The value `1.0` is the "normal" volume (unfiltered input). Any value other than 1.0 will
@@ -223,6 +305,30 @@ impl Sink {
         *self.controls.speed.lock() = value;
     }
 
+    /// Changes how the speed is applied, see [`TimeStretchMode`].
+    #[inline]
+    pub fn set_time_stretch(&self, mode: TimeStretchMode) {
+        *self.controls.time_stretch.write() = mode;
+    }
+
+    /// Gets how the speed is applied.
+    #[inline]
+    pub fn time_stretch(&self) -> TimeStretchMode {
+        *self.controls.time_stretch.read()
+    }
+
+    /// Changes the equalizer bands, an empty list disables the equalizer.
+    #[inline]
+    pub fn set_equalizer(&self, bands: EqualizerBands) {
+        *self.controls.equalizer.write() = bands;
+    }
+
+    /// Gets the equalizer bands currently applied.
+    #[inline]
+    pub fn equalizer(&self) -> EqualizerBands {
+        self.controls.equalizer.read().clone()
+    }
+
     /// Resumes playback of a paused sink.
     ///
     /// No effect if not paused.
@@ -335,10 +441,14 @@ impl Sink {
         if let Some(sleep_until_end) = self.sleep_until_end.lock().take() {
             let pcmd_tx = self.pcmd_tx.clone();
             let picmd_tx = self.picmd_tx.clone();
+            let controls = self.controls.clone();
             std::thread::Builder::new()
                 .name("rusty message_on_end".into())
                 .spawn(move || {
                     let _drop = sleep_until_end.recv();
+                    if controls.eos_disabled.load(Ordering::SeqCst) {
+                        return;
+                    }
                     if let Err(e) = pcmd_tx.send(PlayerCmd::Eos) {
                         error!("Error in message_on_end: {e}");
                     }
@@ -349,6 +459,21 @@ impl Sink {
                 .expect("failed to spawn message_on_end thread");
         }
     }
+
+    /// Get a view of the state of this sink, which stays valid after the sink is dropped.
+    pub fn status(&self) -> SinkStatus {
+        SinkStatus {
+            controls: self.controls.clone(),
+            sound_count: self.sound_count.clone(),
+        }
+    }
+
+    /// Do not send any End-of-Stream messages anymore, not even for already requested ones.
+    ///
+    /// Used when the sink gets replaced and its sources are only stopped to be re-added to the new sink.
+    pub fn disable_eos(&self) {
+        self.controls.eos_disabled.store(true, Ordering::SeqCst);
+    }
 }
 
 impl Drop for Sink {
@@ -358,3 +483,50 @@ impl Drop for Sink {
         self.controls.stopped.store(true, Ordering::Relaxed);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use rodio::buffer::SamplesBuffer;
+
+    use super::Sink;
+    use crate::rusty_backend::PlayerInternalCmd;
+
+    #[test]
+    fn should_report_progress_of_audible_source() {
+        let (picmd_tx, picmd_rx) = std::sync::mpsc::channel();
+        let (pcmd_tx, _pcmd_rx) = tokio::sync::mpsc::unbounded_channel();
+        let (sink, queue_rx) = Sink::new_idle(picmd_tx, pcmd_tx);
+        // 1 channel, 1000 samples per second, 1 and 2 seconds long
+        sink.append(SamplesBuffer::new(1, 1000, vec![0.0f32; 1000]));
+        sink.append(SamplesBuffer::new(1, 1000, vec![0.0f32; 2000]));
+
+        // play both sources, progress is reported every 500ms
+        assert_eq!(queue_rx.take(3000).count(), 3000);
+
+        let progress: Vec<(Duration, Option<Duration>)> = picmd_rx
+            .try_iter()
+            .filter_map(|cmd| match cmd {
+                PlayerInternalCmd::Progress(position, total) => Some((position, total)),
+                _ => None,
+            })
+            .collect();
+        let switch = progress
+            .iter()
+            .position(|(_, total)| *total == Some(Duration::from_secs(2)))
+            .unwrap();
+        assert!(switch > 0);
+        assert!(progress[..switch]
+            .iter()
+            .all(|(_, total)| *total == Some(Duration::from_secs(1))));
+        assert!(progress[switch..]
+            .iter()
+            .all(|(_, total)| *total == Some(Duration::from_secs(2))));
+        // the position starts from 0 again for the second source
+        assert_eq!(progress[switch].0, Duration::ZERO);
+        assert!(progress
+            .iter()
+            .all(|(position, total)| position <= &total.unwrap()));
+    }
+}
@@ -0,0 +1,276 @@
+//! Crossfade between two consecutive sources in a queue.
+//!
+//! The outgoing source is cut a configured amount before its end, and the remaining tail is handed over
+//! to the incoming source, which mixes it into its beginning.
+//! This way the incoming source's position starts at `0` once the outgoing source ended, which keeps position tracking
+//! and End-of-Stream handling the same as without a crossfade.
+
+use std::f32::consts::FRAC_PI_2;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use rodio::{source::SeekError, Sample, Source};
+
+/// The remaining part of a outgoing source after it has been cut
+struct Tail {
+    source: Box<dyn Iterator<Item = f32> + Send>,
+    /// The amount of samples in the tail, used for the fade curve
+    len: u64,
+    /// The amount of samples already mixed
+    pos: u64,
+}
+
+/// Shared state between a source and the source queued after it
+pub struct CrossfadeHandle {
+    channels: u16,
+    sample_rate: u32,
+    /// The length of the fade-out in milliseconds, `0` if there should be no fade-out
+    fade_out_ms: AtomicU64,
+    /// The tail of the source, set once the source has been cut
+    tail: Mutex<Option<Tail>>,
+}
+
+impl CrossfadeHandle {
+    /// Check if a source with the given format can be mixed with the source of this handle
+    pub fn is_compatible(&self, channels: u16, sample_rate: u32) -> bool {
+        self.channels == channels && self.sample_rate == sample_rate
+    }
+
+    /// Make the source of this handle end `length` early, to be mixed into the next source
+    pub fn set_fade_out(&self, length: Duration) {
+        let ms = u64::try_from(length.as_millis()).unwrap_or(u64::MAX);
+        self.fade_out_ms.store(ms, Ordering::SeqCst);
+    }
+}
+
+/// Wrap `input` for crossfading.
+///
+/// If `incoming` is set, the tail of the source of that handle will be mixed into the beginning of `input`.
+/// Returns the handle to use for the source queued after this one.
+pub fn crossfade<I>(
+    input: I,
+    incoming: Option<Arc<CrossfadeHandle>>,
+) -> (Crossfade<I>, Arc<CrossfadeHandle>)
+where
+    I: Source + Send + 'static,
+    I::Item: Sample,
+{
+    let handle = Arc::new(CrossfadeHandle {
+        channels: input.channels(),
+        sample_rate: input.sample_rate(),
+        fade_out_ms: AtomicU64::new(0),
+        tail: Mutex::new(None),
+    });
+
+    let source = Crossfade {
+        total_samples: input
+            .total_duration()
+            .map(|v| duration_to_samples(&input, v)),
+        input: Some(input),
+        handle: handle.clone(),
+        incoming,
+        tail: None,
+        position: 0,
+    };
+
+    (source, handle)
+}
+
+/// Convert a [`Duration`] to the amount of samples (not frames) of the given source
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn duration_to_samples<I: Source>(input: &I, duration: Duration) -> u64
+where
+    I::Item: Sample,
+{
+    (duration.as_secs_f64() * f64::from(input.sample_rate()) * f64::from(input.channels())) as u64
+}
+
+/// Source that can be cut early and can mix in the tail of the previous source, see [`crossfade`]
+pub struct Crossfade<I> {
+    /// The actual source, [`None`] once it has been cut
+    input: Option<I>,
+    /// Handle for the next source
+    handle: Arc<CrossfadeHandle>,
+    /// Handle of the previous source, taken on the first sample
+    incoming: Option<Arc<CrossfadeHandle>>,
+    /// The tail of the previous source currently being mixed in
+    tail: Option<Tail>,
+    /// The current position in samples
+    position: u64,
+    total_samples: Option<u64>,
+}
+
+impl<I> Crossfade<I>
+where
+    I: Source + Send + 'static,
+    I::Item: Sample,
+{
+    /// Get the amount of samples until this source should be cut, if a fade-out is requested
+    fn samples_until_cut(&self, input: &I) -> Option<u64> {
+        let fade_out_ms = self.handle.fade_out_ms.load(Ordering::Relaxed);
+        if fade_out_ms == 0 {
+            return None;
+        }
+        let total = self.total_samples?;
+        let fade_samples = duration_to_samples(input, Duration::from_millis(fade_out_ms));
+
+        Some(
+            total
+                .saturating_sub(fade_samples)
+                .saturating_sub(self.position),
+        )
+    }
+
+    /// Hand the rest of the source over to the next source
+    fn cut(&mut self) {
+        let Some(input) = self.input.take() else {
+            return;
+        };
+        let len = self
+            .total_samples
+            .unwrap_or_default()
+            .saturating_sub(self.position);
+
+        *self.handle.tail.lock() = Some(Tail {
+            source: Box::new(input.map(Sample::to_f32)),
+            len,
+            pos: 0,
+        });
+    }
+}
+
+impl<I> Iterator for Crossfade<I>
+where
+    I: Source + Send + 'static,
+    I::Item: Sample,
+{
+    type Item = f32;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(incoming) = self.incoming.take() {
+            self.tail = incoming.tail.lock().take();
+        }
+
+        let input = self.input.as_ref()?;
+        let channels = u64::from(input.channels());
+        // only cut at frame boundaries, to not swap channels in the next source
+        if self.position % channels == 0 && self.samples_until_cut(input) == Some(0) {
+            self.cut();
+            return None;
+        }
+
+        let sample = self.input.as_mut()?.next()?.to_f32();
+        self.position += 1;
+
+        let Some(tail) = &mut self.tail else {
+            return Some(sample);
+        };
+        let Some(tail_sample) = tail.source.next() else {
+            self.tail = None;
+            return Some(sample);
+        };
+
+        // equal-power curve, to not have a dip in loudness in the middle of the fade
+        #[allow(clippy::cast_precision_loss)]
+        let progress = (tail.pos as f32 / tail.len.max(1) as f32).min(1.0);
+        tail.pos += 1;
+
+        Some(sample * (progress * FRAC_PI_2).sin() + tail_sample * (progress * FRAC_PI_2).cos())
+    }
+}
+
+impl<I> Source for Crossfade<I>
+where
+    I: Source + Send + 'static,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        let input = self.input.as_ref()?;
+        let frame_len = input.current_frame_len();
+
+        // end the current frame at the cut, so that the queue checks the format again after it
+        match self.samples_until_cut(input) {
+            Some(until_cut) => {
+                let until_cut = usize::try_from(until_cut).unwrap_or(usize::MAX);
+                Some(frame_len.map_or(until_cut, |v| v.min(until_cut)))
+            }
+            None => frame_len,
+        }
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input
+            .as_ref()
+            .map_or(self.handle.channels, Source::channels)
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input
+            .as_ref()
+            .map_or(self.handle.sample_rate, Source::sample_rate)
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.as_ref()?.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        let Some(input) = self.input.as_mut() else {
+            return Ok(());
+        };
+        input.try_seek(pos)?;
+        self.position = duration_to_samples(input, pos);
+        // a seek is a hard cut, no need to continue fading
+        self.tail = None;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use rodio::buffer::SamplesBuffer;
+
+    use super::crossfade;
+
+    #[test]
+    fn should_cut_and_mix_tail() {
+        // 1 channel, 10 samples per second, 1 second each
+        let first = SamplesBuffer::new(1, 10, vec![1.0f32; 10]);
+        let second = SamplesBuffer::new(1, 10, vec![0.0f32; 10]);
+
+        let (mut first, handle) = crossfade(first, None);
+        assert!(handle.is_compatible(1, 10));
+        handle.set_fade_out(Duration::from_millis(400));
+        let (second, _) = crossfade(second, Some(handle));
+
+        // the first source ends 4 samples early
+        assert_eq!(first.by_ref().count(), 6);
+
+        let second: Vec<f32> = second.collect();
+        assert_eq!(second.len(), 10);
+        // the tail starts at full volume and fades out
+        assert!((second[0] - 1.0).abs() < f32::EPSILON);
+        assert!(second[0] > second[1] && second[1] > second[2] && second[2] > second[3]);
+        // after the tail, only the second source is left
+        assert!(second[4..].iter().all(|v| v.abs() < f32::EPSILON));
+    }
+
+    #[test]
+    fn should_not_cut_without_fade_out() {
+        let first = SamplesBuffer::new(1, 10, vec![1.0f32; 10]);
+        let (first, _handle) = crossfade(first, None);
+
+        assert_eq!(first.count(), 10);
+    }
+}
@@ -0,0 +1,243 @@
+//! Multi-band equalizer made of peaking biquad filters, see the "Audio EQ Cookbook" by Robert Bristow-Johnson.
+
+use std::f32::consts::PI;
+use std::sync::Arc;
+use std::time::Duration;
+
+use rodio::{source::SeekError, Source};
+use termusiclib::config::v2::server::equalizer::EqualizerBand;
+
+/// Shared list of bands, an empty list disables the equalizer
+pub type EqualizerBands = Arc<[EqualizerBand]>;
+
+/// Normalized coefficients of a biquad filter
+#[derive(Debug, Clone, Copy)]
+struct Coefficients {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+}
+
+impl Coefficients {
+    /// Calculate a peaking filter, returns [`None`] if the band would not have any effect
+    fn peaking(band: &EqualizerBand, sample_rate: u32) -> Option<Self> {
+        #[allow(clippy::cast_precision_loss)]
+        let sample_rate = sample_rate as f32;
+        // frequencies at or above nyquist cannot be represented
+        if band.gain.abs() < 0.01 || band.frequency >= sample_rate / 2.0 || band.q <= 0.0 {
+            return None;
+        }
+
+        let a = 10f32.powf(band.gain / 40.0);
+        let w0 = 2.0 * PI * band.frequency / sample_rate;
+        let alpha = w0.sin() / (2.0 * band.q);
+        let cos_w0 = w0.cos();
+
+        let a0 = 1.0 + alpha / a;
+
+        Some(Self {
+            b0: (1.0 + alpha * a) / a0,
+            b1: (-2.0 * cos_w0) / a0,
+            b2: (1.0 - alpha * a) / a0,
+            a1: (-2.0 * cos_w0) / a0,
+            a2: (1.0 - alpha / a) / a0,
+        })
+    }
+}
+
+/// Filter history of a single biquad for a single channel
+#[derive(Debug, Clone, Copy, Default)]
+struct State {
+    x1: f32,
+    x2: f32,
+    y1: f32,
+    y2: f32,
+}
+
+impl State {
+    #[inline]
+    fn process(&mut self, c: &Coefficients, x: f32) -> f32 {
+        let y = c.b0 * x + c.b1 * self.x1 + c.b2 * self.x2 - c.a1 * self.y1 - c.a2 * self.y2;
+        self.x2 = self.x1;
+        self.x1 = x;
+        self.y2 = self.y1;
+        self.y1 = y;
+
+        y
+    }
+}
+
+/// Apply the given bands to `input`
+pub fn equalizer<I>(input: I, bands: EqualizerBands) -> Equalizer<I>
+where
+    I: Source<Item = f32>,
+{
+    let mut eq = Equalizer {
+        channels: input.channels(),
+        sample_rate: input.sample_rate(),
+        input,
+        bands,
+        filters: Vec::new(),
+        states: Vec::new(),
+        channel: 0,
+    };
+    eq.update_filters();
+
+    eq
+}
+
+/// Source applying a series of peaking filters, see [`equalizer`]
+pub struct Equalizer<I> {
+    input: I,
+    bands: EqualizerBands,
+    filters: Vec<Coefficients>,
+    /// Filter states, `channels` entries for each filter
+    states: Vec<State>,
+    /// The channel the next sample is for
+    channel: usize,
+    /// The format the filters were calculated for
+    channels: u16,
+    sample_rate: u32,
+}
+
+impl<I> Equalizer<I>
+where
+    I: Source<Item = f32>,
+{
+    /// Change the bands, does nothing if they are the same as the current ones
+    pub fn set_bands(&mut self, bands: &EqualizerBands) {
+        if Arc::ptr_eq(&self.bands, bands) {
+            return;
+        }
+        self.bands = bands.clone();
+        self.update_filters();
+    }
+
+    fn update_filters(&mut self) {
+        self.filters = self
+            .bands
+            .iter()
+            .filter_map(|band| Coefficients::peaking(band, self.sample_rate))
+            .collect();
+        // keep the existing history if possible, to not have a audible click
+        self.states.resize(
+            self.filters.len() * usize::from(self.channels),
+            State::default(),
+        );
+    }
+}
+
+impl<I> Iterator for Equalizer<I>
+where
+    I: Source<Item = f32>,
+{
+    type Item = f32;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.channel == 0
+            && (self.input.channels() != self.channels
+                || self.input.sample_rate() != self.sample_rate)
+        {
+            self.channels = self.input.channels();
+            self.sample_rate = self.input.sample_rate();
+            self.states.clear();
+            self.update_filters();
+        }
+
+        let mut sample = self.input.next()?;
+
+        let channels = usize::from(self.channels);
+        for (idx, filter) in self.filters.iter().enumerate() {
+            sample = self.states[idx * channels + self.channel].process(filter, sample);
+        }
+        self.channel = (self.channel + 1) % channels.max(1);
+
+        Some(sample)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> Source for Equalizer<I>
+where
+    I: Source<Item = f32>,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        // the history is not continuous anymore
+        self.states.fill(State::default());
+        self.channel = 0;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+    use std::sync::Arc;
+
+    use rodio::buffer::SamplesBuffer;
+    use termusiclib::config::v2::server::equalizer::EqualizerBand;
+
+    use super::equalizer;
+
+    /// Get the peak of the given frequency after the equalizer, skipping the settling time of the filters
+    #[allow(clippy::cast_precision_loss)]
+    fn peak_after_eq(frequency: f32, bands: &[EqualizerBand]) -> f32 {
+        let sample_rate = 44100;
+        let samples: Vec<f32> = (0..sample_rate)
+            .map(|i| (2.0 * PI * frequency * i as f32 / sample_rate as f32).sin() * 0.1)
+            .collect();
+        let source = SamplesBuffer::new(1, sample_rate, samples);
+
+        equalizer(source, Arc::from(bands))
+            .skip(sample_rate as usize / 2)
+            .fold(0.0, |acc: f32, v| acc.max(v.abs()))
+    }
+
+    #[test]
+    fn should_boost_only_the_band() {
+        let bands = [EqualizerBand::new(1000.0, 12.0)];
+
+        // +12dB is about 4 times the amplitude
+        let boosted = peak_after_eq(1000.0, &bands);
+        assert!((0.35..0.45).contains(&boosted), "{boosted}");
+
+        let untouched = peak_after_eq(50.0, &bands);
+        assert!((0.09..0.11).contains(&untouched), "{untouched}");
+    }
+
+    #[test]
+    fn should_pass_through_without_bands() {
+        let peak = peak_after_eq(1000.0, &[]);
+        assert!((peak - 0.1).abs() < 0.001, "{peak}");
+    }
+}
@@ -1,114 +1,256 @@
-// mod sonic;
+//! Change the speed of a source without changing its pitch
+
+mod sonic;
 
 use std::{collections::VecDeque, time::Duration};
 
 use rodio::Source;
+#[cfg(feature = "rusty-soundtouch")]
 use soundtouch::{Setting, SoundTouch};
+use termusiclib::config::v2::server::TimeStretchMode;
+
+use self::sonic::Sonic;
+
+/// The amount of input samples (not frames) processed at once, if the input does not have a shorter frame
+const BLOCK_SAMPLES: usize = 4096;
+
+/// Check if the given mode can be used in this build
+pub fn is_supported(mode: TimeStretchMode) -> bool {
+    match mode {
+        TimeStretchMode::Off | TimeStretchMode::Sonic => true,
+        TimeStretchMode::SoundTouch => cfg!(feature = "rusty-soundtouch"),
+    }
+}
+
+/// The algorithm doing the actual stretching
+enum Engine {
+    Sonic(Sonic),
+    #[cfg(feature = "rusty-soundtouch")]
+    SoundTouch(SoundTouch),
+}
+
+impl Engine {
+    /// Create the engine for `mode`, [`None`] for [`TimeStretchMode::Off`]
+    fn new(mode: TimeStretchMode, channels: u16, sample_rate: u32) -> Option<Self> {
+        match mode {
+            TimeStretchMode::Off => None,
+            #[cfg(feature = "rusty-soundtouch")]
+            TimeStretchMode::SoundTouch => {
+                let mut st = SoundTouch::new();
+                st.set_channels(u32::from(channels));
+                st.set_sample_rate(sample_rate);
+                Some(Self::SoundTouch(st))
+            }
+            TimeStretchMode::Sonic => Some(Self::Sonic(Sonic::new(sample_rate, channels))),
+            // soundtouch is not compiled-in, the backend already warned about that and uses sonic instead,
+            // see `RustyBackend::set_time_stretch`
+            #[cfg(not(feature = "rusty-soundtouch"))]
+            TimeStretchMode::SoundTouch => Some(Self::Sonic(Sonic::new(sample_rate, channels))),
+        }
+    }
+
+    fn set_speed(&mut self, speed: f32) {
+        match self {
+            Self::Sonic(sonic) => sonic.set_speed(speed),
+            #[cfg(feature = "rusty-soundtouch")]
+            Self::SoundTouch(st) => st.set_tempo(f64::from(speed)),
+        }
+    }
+
+    /// Process interleaved `samples` and append all available output to `out`
+    #[cfg_attr(not(feature = "rusty-soundtouch"), allow(unused_variables))]
+    fn process(&mut self, samples: &[f32], channels: usize, out: &mut VecDeque<f32>) {
+        match self {
+            Self::Sonic(sonic) => {
+                sonic.write(samples);
+                sonic.read(out);
+            }
+            #[cfg(feature = "rusty-soundtouch")]
+            Self::SoundTouch(st) => {
+                st.put_samples(samples, samples.len() / channels);
+                Self::receive_soundtouch(st, channels, out);
+            }
+        }
+    }
+
+    /// Process all buffered input, for the end of the source
+    #[cfg_attr(not(feature = "rusty-soundtouch"), allow(unused_variables))]
+    fn flush(&mut self, channels: usize, out: &mut VecDeque<f32>) {
+        match self {
+            Self::Sonic(sonic) => {
+                sonic.flush();
+                sonic.read(out);
+            }
+            #[cfg(feature = "rusty-soundtouch")]
+            Self::SoundTouch(st) => {
+                // push the buffered input out with silence
+                #[allow(clippy::cast_sign_loss)]
+                let latency = st.get_setting(Setting::InitialLatency) as usize;
+                st.put_samples(&vec![0.0; latency * channels], latency);
+                Self::receive_soundtouch(st, channels, out);
+            }
+        }
+    }
 
-#[allow(clippy::cast_sign_loss)]
-pub fn tempo_stretch<I>(mut input: I, rate: f32) -> TempoStretch<I>
+    #[cfg(feature = "rusty-soundtouch")]
+    fn receive_soundtouch(st: &mut SoundTouch, channels: usize, out: &mut VecDeque<f32>) {
+        let mut buffer = [0.0; BLOCK_SAMPLES];
+        let max_frames = BLOCK_SAMPLES / channels;
+        loop {
+            let read = st.receive_samples(&mut buffer, max_frames);
+            if read == 0 {
+                break;
+            }
+            out.extend(&buffer[..read * channels]);
+        }
+    }
+}
+
+/// Change the speed of `input` by `factor` while keeping the pitch, using the algorithm of `mode`
+pub fn tempo_stretch<I>(input: I, factor: f32, mode: TimeStretchMode) -> TempoStretch<I>
 where
     I: Source<Item = f32>,
 {
-    let channels = input.channels();
-    let mut st = SoundTouch::new();
-    st.set_channels(u32::from(channels));
-    st.set_sample_rate(input.sample_rate());
-    st.set_tempo(f64::from(rate));
-    let min_samples = st.get_setting(Setting::NominalInputSequence) as usize * channels as usize;
-    let initial_latency = st.get_setting(Setting::InitialLatency) as usize * channels as usize;
-    let mut out_buffer = VecDeque::new();
-    out_buffer.resize(initial_latency, 0.0);
-    out_buffer.make_contiguous();
-    let mut initial_input: VecDeque<f32> = input.by_ref().take(initial_latency).collect();
-    let num_samples = initial_input.len() / channels as usize;
-    st.put_samples(initial_input.make_contiguous(), num_samples);
-    let read = st.receive_samples(out_buffer.as_mut_slices().0, num_samples);
-    out_buffer.truncate(read);
-    initial_input.clear();
     TempoStretch {
+        channels: input.channels(),
+        sample_rate: input.sample_rate(),
+        engine: None,
         input,
-        min_samples,
-        soundtouch: st,
-        out_buffer,
-        in_buffer: initial_input,
-        mix: 1.0,
-        factor: 1.0,
+        mode,
+        factor,
+        in_buffer: Vec::with_capacity(BLOCK_SAMPLES),
+        out_buffer: VecDeque::new(),
     }
 }
 
+/// Source changing the speed while keeping the pitch, see [`tempo_stretch`]
 pub struct TempoStretch<I> {
     input: I,
-    soundtouch: SoundTouch,
-    min_samples: usize,
+    mode: TimeStretchMode,
+    factor: f32,
+    /// The active engine, [`None`] while not stretching
+    engine: Option<Engine>,
+    /// The format the engine was created for
+    channels: u16,
+    sample_rate: u32,
+    in_buffer: Vec<f32>,
     out_buffer: VecDeque<f32>,
-    in_buffer: VecDeque<f32>,
-    mix: f32,
-    factor: f64,
 }
 
-impl<I> Iterator for TempoStretch<I>
+impl<I> TempoStretch<I>
 where
     I: Source<Item = f32>,
 {
-    type Item = f32;
+    /// Modifies the speed factor.
+    #[inline]
+    pub fn set_factor(&mut self, factor: f32) {
+        self.factor = factor;
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        // This is to skip calculation if speed is not changed
-        if (self.factor - 1.0).abs() < 0.05 {
-            return self.input.next();
+    /// Change the algorithm, a different algorithm starts with a empty buffer
+    #[inline]
+    pub fn set_mode(&mut self, mode: TimeStretchMode) {
+        if self.mode != mode {
+            self.mode = mode;
+            self.engine = None;
         }
-        self.soundtouch.set_tempo(self.factor);
-        if self.out_buffer.is_empty() {
-            self.in_buffer.clear();
-            self.input
-                .by_ref()
-                .take(self.min_samples)
-                .for_each(|x| self.in_buffer.push_back(x));
-
-            let len_input = self.in_buffer.len() / self.input.channels() as usize;
-            self.soundtouch
-                .put_samples(self.in_buffer.make_contiguous(), len_input);
-
-            self.out_buffer.resize(self.min_samples, 0.0);
-            self.out_buffer.make_contiguous();
-
-            let len_output = self.in_buffer.len() / self.input.channels() as usize;
-            let read = self
-                .soundtouch
-                .receive_samples(self.out_buffer.as_mut_slices().0, len_output);
-
-            self.out_buffer
-                .truncate(read * self.input.channels() as usize);
+    }
+
+    /// Whether samples are currently stretched instead of passed through
+    fn is_stretching(&self) -> bool {
+        // this is to skip calculation if speed is not changed
+        self.mode != TimeStretchMode::Off && (self.factor - 1.0).abs() >= 0.05
+    }
+
+    /// Pull the next block from the input into the engine, returns `false` if the input has ended
+    fn fill(&mut self) -> bool {
+        if self.input.channels() != self.channels || self.input.sample_rate() != self.sample_rate {
+            self.channels = self.input.channels();
+            self.sample_rate = self.input.sample_rate();
+            self.engine = None;
         }
+        let channels = usize::from(self.channels.max(1));
+        let engine = self.engine.get_or_insert_with(|| {
+            Engine::new(self.mode, self.channels, self.sample_rate)
+                .expect("is_stretching checks for mode \"off\"")
+        });
+        engine.set_speed(self.factor);
+
+        // dont read across frames, as the format may change after them, but always read whole sample frames
+        let len = self
+            .input
+            .current_frame_len()
+            .map_or(BLOCK_SAMPLES, |v| v.min(BLOCK_SAMPLES));
+        let len = (len / channels).max(1) * channels;
+        self.in_buffer.clear();
+        self.in_buffer.extend(self.input.by_ref().take(len));
 
-        match (
-            self.out_buffer.pop_front().map(|x| x * self.mix),
-            self.in_buffer.pop_front().map(|x| x * (1.0 - self.mix)),
-        ) {
-            (Some(a), Some(b)) => Some(a + b),
-            (None, None) => None,
-            (None, Some(v)) | (Some(v), None) => Some(v),
+        if self.in_buffer.is_empty() {
+            engine.flush(channels, &mut self.out_buffer);
+            self.engine = None;
+            return false;
         }
+        engine.process(&self.in_buffer, channels, &mut self.out_buffer);
+
+        true
     }
 }
 
-impl<I> ExactSizeIterator for TempoStretch<I> where I: Source<Item = f32> + ExactSizeIterator {}
+impl<I> Iterator for TempoStretch<I>
+where
+    I: Source<Item = f32>,
+{
+    type Item = f32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(sample) = self.out_buffer.pop_front() {
+                return Some(sample);
+            }
+
+            if !self.is_stretching() {
+                if let Some(mut engine) = self.engine.take() {
+                    // play the remaining stretched samples before switching to the input directly
+                    engine.flush(usize::from(self.channels.max(1)), &mut self.out_buffer);
+                    continue;
+                }
+
+                return self.input.next();
+            }
+
+            if !self.fill() && self.out_buffer.is_empty() {
+                return None;
+            }
+        }
+    }
+}
 
 impl<I> Source for TempoStretch<I>
 where
     I: Source<Item = f32>,
 {
     fn current_frame_len(&self) -> Option<usize> {
-        Some(self.min_samples)
+        if self.out_buffer.is_empty() {
+            self.input.current_frame_len()
+        } else {
+            Some(self.out_buffer.len())
+        }
     }
 
     fn channels(&self) -> u16 {
-        self.input.channels()
+        if self.out_buffer.is_empty() {
+            self.input.channels()
+        } else {
+            self.channels
+        }
     }
 
     fn sample_rate(&self) -> u32 {
-        self.input.sample_rate()
+        if self.out_buffer.is_empty() {
+            self.input.sample_rate()
+        } else {
+            self.sample_rate
+        }
     }
 
     fn total_duration(&self) -> Option<std::time::Duration> {
@@ -117,23 +259,32 @@ where
 
     #[inline]
     fn try_seek(&mut self, pos: Duration) -> Result<(), rodio::source::SeekError> {
-        self.input.try_seek(pos)
+        self.input.try_seek(pos)?;
+        // the buffered samples are from before the seek
+        self.out_buffer.clear();
+        self.engine = None;
+
+        Ok(())
     }
 }
 
-#[allow(dead_code)]
-impl<I> TempoStretch<I>
-where
-    I: Source<Item = f32>,
-{
-    /// Modifies the speed factor.
-    #[inline]
-    pub fn set_factor(&mut self, factor: f64) {
-        self.factor = factor;
-    }
+#[cfg(test)]
+mod tests {
+    use rodio::buffer::SamplesBuffer;
+    use termusiclib::config::v2::server::TimeStretchMode;
 
-    #[inline]
-    pub fn set_mix(&mut self, mix: f32) {
-        self.mix = mix;
+    use super::tempo_stretch;
+
+    #[test]
+    fn should_stretch_by_factor() {
+        let source = SamplesBuffer::new(2, 44100, vec![0.1f32; 44100 * 2]);
+        let stretched = tempo_stretch(source, 2.0, TimeStretchMode::Sonic);
+        let frames = stretched.count() / 2;
+        assert!(frames.abs_diff(22050) < 500, "{frames}");
+
+        // "off" leaves the speed to be changed by resampling
+        let source = SamplesBuffer::new(2, 44100, vec![0.1f32; 44100 * 2]);
+        let passed = tempo_stretch(source, 2.0, TimeStretchMode::Off);
+        assert_eq!(passed.count(), 44100 * 2);
     }
 }
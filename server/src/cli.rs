@@ -46,6 +46,17 @@ pub struct Args {
     pub max_depth: Option<u32>,
     #[arg(short, long, default_value_t = Backend::Rusty, env = "TMS_BACKEND")]
     pub backend: Backend,
+    /// Discard the audio instead of playing it, for running without a sound card.
+    /// Only supported by the rusty backend.
+    #[arg(long, group = "output")]
+    pub output_null: bool,
+    /// Write the audio to FILE as WAV instead of playing it.
+    /// Only supported by the rusty backend.
+    #[arg(long, value_name = "FILE", group = "output")]
+    pub output_wav: Option<PathBuf>,
+    /// Process the audio of "output-null" or "output-wav" as fast as possible instead of at real time.
+    #[arg(long, requires = "output")]
+    pub output_fast: bool,
     #[clap(flatten)]
     pub log_options: LogOptions,
 }
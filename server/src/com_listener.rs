@@ -0,0 +1,163 @@
+use std::net::SocketAddr;
+#[cfg(unix)]
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use termusiclib::config::v2::server::ComSettings;
+use termusiclib::player::auth::AuthCheck;
+use termusiclib::player::music_player_server::MusicPlayerServer;
+#[cfg(unix)]
+use termusiclib::utils::random_ascii;
+use tonic::transport::server::TcpIncoming;
+use tonic::transport::Server;
+
+use crate::music_player_service::MusicPlayerService;
+
+/// The bound listener the gRPC server will accept connections on
+pub enum ComListener {
+    Tcp(TcpIncoming, SocketAddr),
+    #[cfg(unix)]
+    Unix(tokio::net::UnixListener, PathBuf),
+}
+
+impl ComListener {
+    /// Bind the listener as configured in `com`.
+    ///
+    /// If a `socket_path` is set, it is used instead of the TCP address.
+    pub async fn bind(com: &ComSettings) -> Result<Self> {
+        if let Some(path) = &com.socket_path {
+            return Self::bind_unix(path);
+        }
+
+        let addr = SocketAddr::from(com);
+
+        // workaround to print address once sever "actually" is started and address is known
+        // see https://github.com/hyperium/tonic/issues/351
+        let tcp_listener = tokio::net::TcpListener::bind(addr)
+            .await
+            .with_context(|| format!("Error binding address: {}", addr))?;
+        let addr = tcp_listener.local_addr()?;
+        let tcp_stream =
+            TcpIncoming::from_listener(tcp_listener, true, None).map_err(|e| anyhow::anyhow!(e))?;
+
+        Ok(Self::Tcp(tcp_stream, addr))
+    }
+
+    #[cfg(unix)]
+    fn bind_unix(path: &Path) -> Result<Self> {
+        use std::os::unix::fs::{DirBuilderExt, FileTypeExt};
+
+        // remove a leftover socket from a previous run that did not exit cleanly,
+        // but never remove anything that is not a socket or a socket that is still in use
+        if let Ok(meta) = std::fs::symlink_metadata(path) {
+            if !meta.file_type().is_socket() {
+                anyhow::bail!(
+                    "Path {} exists and is not a socket, refusing to remove it",
+                    path.display()
+                );
+            }
+            match std::os::unix::net::UnixStream::connect(path) {
+                Ok(_) => anyhow::bail!("another server is already listening on {}", path.display()),
+                Err(err) if err.kind() == std::io::ErrorKind::ConnectionRefused => (),
+                Err(err) => {
+                    return Err(err).with_context(|| {
+                        format!("Checking if old socket {} is in use", path.display())
+                    })
+                }
+            }
+            std::fs::remove_file(path)
+                .with_context(|| format!("Removing old socket {}", path.display()))?;
+        }
+
+        let parent = path
+            .parent()
+            .filter(|v| !v.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        std::fs::DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(parent)
+            .with_context(|| format!("Creating socket directory {}", parent.display()))?;
+
+        // the socket is connectable with the permissions of the umask until they are changed,
+        // so it is bound in a new directory only accessible by the owner and then moved into place
+        let private_dir = parent.join(format!(".termusic-{}", random_ascii(8)));
+        std::fs::DirBuilder::new()
+            .mode(0o700)
+            .create(&private_dir)
+            .with_context(|| format!("Creating socket directory {}", private_dir.display()))?;
+
+        let res = bind_moved(&private_dir.join("socket"), path);
+        let _ = std::fs::remove_dir_all(&private_dir);
+        let listener = res?;
+
+        Ok(Self::Unix(listener, path.to_path_buf()))
+    }
+
+    #[cfg(not(unix))]
+    fn bind_unix(_path: &std::path::Path) -> Result<Self> {
+        anyhow::bail!("Unix sockets are not supported on this platform");
+    }
+
+    /// Get a human readable description of where the server is listening on
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Tcp(_, addr) => addr.to_string(),
+            #[cfg(unix)]
+            Self::Unix(_, path) => format!("unix:{}", path.display()),
+        }
+    }
+
+    /// Spawn the gRPC server on the current runtime, serving `service` on this listener.
+    /// All requests are checked with `auth`.
+    ///
+    /// Returns a guard which cleans up after the server, like removing the socket file.
+    pub fn serve(self, service: MusicPlayerService, auth: AuthCheck) -> ComGuard {
+        let router =
+            Server::builder().add_service(MusicPlayerServer::with_interceptor(service, auth));
+
+        match self {
+            Self::Tcp(incoming, _) => {
+                tokio::spawn(router.serve_with_incoming(incoming));
+                ComGuard { socket_path: None }
+            }
+            #[cfg(unix)]
+            Self::Unix(listener, path) => {
+                tokio::spawn(router.serve_with_incoming(
+                    tokio_stream::wrappers::UnixListenerStream::new(listener),
+                ));
+                ComGuard {
+                    socket_path: Some(path),
+                }
+            }
+        }
+    }
+}
+
+/// Bind a socket at `bind_path`, only allow the owning user to connect and then move it to `path`
+#[cfg(unix)]
+fn bind_moved(bind_path: &Path, path: &Path) -> Result<tokio::net::UnixListener> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let listener = tokio::net::UnixListener::bind(bind_path)
+        .with_context(|| format!("Error binding socket: {}", path.display()))?;
+    std::fs::set_permissions(bind_path, std::fs::Permissions::from_mode(0o600))
+        .with_context(|| format!("Setting permissions on socket {}", path.display()))?;
+    std::fs::rename(bind_path, path)
+        .with_context(|| format!("Moving socket to {}", path.display()))?;
+
+    Ok(listener)
+}
+
+/// Guard to clean up the listener on drop
+pub struct ComGuard {
+    socket_path: Option<std::path::PathBuf>,
+}
+
+impl Drop for ComGuard {
+    fn drop(&mut self) {
+        if let Some(path) = self.socket_path.take() {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
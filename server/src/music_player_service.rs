@@ -3,11 +3,23 @@ use parking_lot::Mutex;
 use std::pin::Pin;
 use std::sync::Arc;
 use termusiclib::player::music_player_server::MusicPlayer;
+use termusiclib::player::playlist_helpers::{
+    PlaylistAddTrack, PlaylistPlaySpecific, PlaylistRemoveTrackIndexed, PlaylistSwapTrack,
+};
+use termusiclib::player::radio_history::RadioHistoryInfo;
 use termusiclib::player::{
-    stream_updates, Empty, GetProgressResponse, PlayerTime, SpeedReply, StreamUpdates,
-    ToggleGaplessReply, TogglePauseResponse, UpdateMissedEvents, VolumeReply,
+    stream_updates, Empty, EqualizerState, GetProgressResponse, OutputDeviceInfo, OutputDevices,
+    PlayerTime, PlaylistTrackToPlay, PlaylistTracks, PlaylistTracksToAdd, PlaylistTracksToRemove,
+    PlaylistTracksToSwap, RadioHistory, RadioHistoryRequest, SetEqualizerPresetRequest,
+    SetOutputDeviceRequest, SetSpeedRequest, SetVolumeRequest, SleepTimerKind, SleepTimerRequest,
+    SleepTimerState, SpeedReply, StreamUpdates, ToggleGaplessReply, TogglePauseResponse,
+    UpdateMissedEvents, VolumeReply,
+};
+use termusiclib::track::MediaType;
+use termusicplayback::{
+    PlayerCmd, PlayerCmdReply, PlayerCmdSender, PlaylistEditError, PlaylistEditReply,
+    SharedPlaylist, StreamTX,
 };
-use termusicplayback::{PlayerCmd, PlayerCmdSender, StreamTX};
 use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
 use tokio_stream::wrappers::BroadcastStream;
 use tokio_stream::{Stream, StreamExt};
@@ -20,16 +32,21 @@ pub struct MusicPlayerService {
     cmd_tx: PlayerCmdSender,
     stream_tx: StreamTX,
     pub(crate) player_stats: Arc<Mutex<PlayerStats>>,
+    playlist: SharedPlaylist,
 }
 
 impl MusicPlayerService {
-    pub fn new(cmd_tx: PlayerCmdSender, stream_tx: StreamTX) -> Self {
-        let player_stats = Arc::new(Mutex::new(PlayerStats::new()));
-
+    pub fn new(
+        cmd_tx: PlayerCmdSender,
+        stream_tx: StreamTX,
+        player_stats: Arc<Mutex<PlayerStats>>,
+        playlist: SharedPlaylist,
+    ) -> Self {
         Self {
             cmd_tx,
-            player_stats,
             stream_tx,
+            player_stats,
+            playlist,
         }
     }
 }
@@ -40,6 +57,38 @@ impl MusicPlayerService {
             error!("error {cmd:?}: {e}");
         }
     }
+
+    /// Send the playlist edit created by `cmd` to the player and wait until it has been applied
+    async fn playlist_edit(
+        &self,
+        cmd: impl FnOnce(PlaylistEditReply) -> PlayerCmd,
+    ) -> Result<(), Status> {
+        let (reply, reply_rx) = PlayerCmdReply::new();
+        self.command(&cmd(reply));
+
+        match reply_rx.await {
+            Ok(Ok(())) => Ok(()),
+            Ok(Err(err @ PlaylistEditError::TrackMismatch { .. })) => {
+                Err(Status::failed_precondition(err.to_string()))
+            }
+            Ok(Err(err)) => Err(Status::invalid_argument(err.to_string())),
+            Err(_) => Err(Status::internal(
+                "Player exited before applying the playlist edit",
+            )),
+        }
+    }
+
+    /// Get the selected and all available output devices
+    async fn output_devices(&self) -> Result<OutputDeviceInfo, Status> {
+        // listing devices may block for a while, depending on the audio system
+        let devices = tokio::task::spawn_blocking(termusicplayback::output_device_names)
+            .await
+            .map_err(|err| Status::internal(err.to_string()))?
+            .map_err(|err| Status::internal(format!("{err:#}")))?;
+        let device = self.player_stats.lock().output_device.clone();
+
+        Ok(OutputDeviceInfo { device, devices })
+    }
 }
 
 #[tonic::async_trait]
@@ -84,6 +133,91 @@ impl MusicPlayer for MusicPlayerService {
         Ok(Response::new(reply))
     }
 
+    async fn get_playlist(
+        &self,
+        _request: Request<Empty>,
+    ) -> Result<Response<PlaylistTracks>, Status> {
+        let reply = self.playlist.read().as_grpc_playlist_tracks();
+
+        Ok(Response::new(reply.into()))
+    }
+
+    async fn playlist_add_track(
+        &self,
+        request: Request<PlaylistTracksToAdd>,
+    ) -> Result<Response<Empty>, Status> {
+        let reply = Empty {};
+        let info = PlaylistAddTrack::try_from(request.into_inner())
+            .map_err(|err| Status::invalid_argument(format!("{err:#}")))?;
+        self.playlist_edit(|reply_tx| PlayerCmd::PlaylistAddTrack(info, reply_tx))
+            .await?;
+
+        Ok(Response::new(reply))
+    }
+
+    async fn playlist_remove_track(
+        &self,
+        request: Request<PlaylistTracksToRemove>,
+    ) -> Result<Response<Empty>, Status> {
+        let reply = Empty {};
+        let info = PlaylistRemoveTrackIndexed::try_from(request.into_inner())
+            .map_err(|err| Status::invalid_argument(format!("{err:#}")))?;
+        self.playlist_edit(|reply_tx| PlayerCmd::PlaylistRemoveTrack(info, reply_tx))
+            .await?;
+
+        Ok(Response::new(reply))
+    }
+
+    async fn playlist_remove_deleted_tracks(
+        &self,
+        _request: Request<Empty>,
+    ) -> Result<Response<Empty>, Status> {
+        let reply = Empty {};
+        self.command(&PlayerCmd::PlaylistRemoveDeletedTracks);
+
+        Ok(Response::new(reply))
+    }
+
+    async fn playlist_swap_tracks(
+        &self,
+        request: Request<PlaylistTracksToSwap>,
+    ) -> Result<Response<Empty>, Status> {
+        let reply = Empty {};
+        let info = PlaylistSwapTrack::try_from(request.into_inner())
+            .map_err(|err| Status::invalid_argument(format!("{err:#}")))?;
+        self.playlist_edit(|reply_tx| PlayerCmd::PlaylistSwapTrack(info, reply_tx))
+            .await?;
+
+        Ok(Response::new(reply))
+    }
+
+    async fn playlist_clear(&self, _request: Request<Empty>) -> Result<Response<Empty>, Status> {
+        let reply = Empty {};
+        self.command(&PlayerCmd::PlaylistClear);
+
+        Ok(Response::new(reply))
+    }
+
+    async fn playlist_shuffle(&self, _request: Request<Empty>) -> Result<Response<Empty>, Status> {
+        let reply = Empty {};
+        self.command(&PlayerCmd::PlaylistShuffle);
+
+        Ok(Response::new(reply))
+    }
+
+    async fn playlist_play_specific(
+        &self,
+        request: Request<PlaylistTrackToPlay>,
+    ) -> Result<Response<Empty>, Status> {
+        let reply = Empty {};
+        let info = PlaylistPlaySpecific::try_from(request.into_inner())
+            .map_err(|err| Status::invalid_argument(format!("{err:#}")))?;
+        self.playlist_edit(|reply_tx| PlayerCmd::PlaylistPlaySpecific(info, reply_tx))
+            .await?;
+
+        Ok(Response::new(reply))
+    }
+
     async fn seek_backward(
         &self,
         _request: Request<Empty>,
@@ -108,6 +242,166 @@ impl MusicPlayer for MusicPlayerService {
         Ok(Response::new(reply))
     }
 
+    async fn seek_to(
+        &self,
+        request: Request<termusiclib::player::Duration>,
+    ) -> Result<Response<PlayerTime>, Status> {
+        self.command(&PlayerCmd::SeekTo(request.into_inner().into()));
+        // This is to let the player update the position within loop
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        let s = self.player_stats.lock();
+        let reply = s.as_playertime();
+
+        Ok(Response::new(reply))
+    }
+
+    async fn set_volume(
+        &self,
+        request: Request<SetVolumeRequest>,
+    ) -> Result<Response<VolumeReply>, Status> {
+        // clamped to u16::MAX, also send is a u16, but protobuf does not support u16 directly
+        #[allow(clippy::cast_possible_truncation)]
+        let volume = request.into_inner().volume.min(u32::from(u16::MAX)) as u16;
+        self.command(&PlayerCmd::SetVolume(volume));
+        // This is to let the player update volume within loop
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        let r = self.player_stats.lock();
+        let reply = VolumeReply {
+            volume: u32::from(r.volume),
+        };
+
+        Ok(Response::new(reply))
+    }
+
+    async fn set_speed(
+        &self,
+        request: Request<SetSpeedRequest>,
+    ) -> Result<Response<SpeedReply>, Status> {
+        self.command(&PlayerCmd::SetSpeed(request.into_inner().speed));
+        // This is to let the player update speed within loop
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        let s = self.player_stats.lock();
+        let reply = SpeedReply { speed: s.speed };
+
+        Ok(Response::new(reply))
+    }
+
+    async fn set_sleep_timer(
+        &self,
+        request: Request<SleepTimerRequest>,
+    ) -> Result<Response<SleepTimerState>, Status> {
+        let kind = SleepTimerKind::try_from(request.into_inner())
+            .map_err(|err| Status::invalid_argument(format!("{err:#}")))?;
+        self.command(&PlayerCmd::SleepTimerSet(kind));
+        // This is to let the player update the timer within loop
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        let s = self.player_stats.lock();
+
+        Ok(Response::new(s.sleep_timer.into()))
+    }
+
+    async fn cancel_sleep_timer(
+        &self,
+        _request: Request<Empty>,
+    ) -> Result<Response<SleepTimerState>, Status> {
+        self.command(&PlayerCmd::SleepTimerCancel);
+        // This is to let the player update the timer within loop
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        let s = self.player_stats.lock();
+
+        Ok(Response::new(s.sleep_timer.into()))
+    }
+
+    async fn get_equalizer(
+        &self,
+        _request: Request<Empty>,
+    ) -> Result<Response<EqualizerState>, Status> {
+        let s = self.player_stats.lock();
+
+        Ok(Response::new(s.equalizer.clone().into()))
+    }
+
+    async fn set_equalizer_preset(
+        &self,
+        request: Request<SetEqualizerPresetRequest>,
+    ) -> Result<Response<EqualizerState>, Status> {
+        let preset: Option<String> = request.into_inner().into();
+        if let Some(preset) = &preset {
+            if !self.player_stats.lock().equalizer.presets.contains(preset) {
+                return Err(Status::invalid_argument(format!(
+                    "Unknown equalizer preset {preset:?}"
+                )));
+            }
+        }
+        self.command(&PlayerCmd::SetEqualizerPreset(preset));
+        // This is to let the player update the equalizer within loop
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        let s = self.player_stats.lock();
+
+        Ok(Response::new(s.equalizer.clone().into()))
+    }
+
+    async fn get_output_devices(
+        &self,
+        _request: Request<Empty>,
+    ) -> Result<Response<OutputDevices>, Status> {
+        Ok(Response::new(self.output_devices().await?.into()))
+    }
+
+    async fn set_output_device(
+        &self,
+        request: Request<SetOutputDeviceRequest>,
+    ) -> Result<Response<OutputDevices>, Status> {
+        let device: Option<String> = request.into_inner().into();
+        let mut info = self.output_devices().await?;
+        if let Some(device) = &device {
+            if !info.devices.contains(device) {
+                return Err(Status::invalid_argument(format!(
+                    "Unknown output device {device:?}"
+                )));
+            }
+        }
+        let (reply, reply_rx) = PlayerCmdReply::new();
+        self.command(&PlayerCmd::SetOutputDevice(device, reply));
+
+        // wait for the player to open the device, to report the device actually used, which may be a fallback
+        info.device = reply_rx
+            .await
+            .map_err(|_| Status::internal("Player exited before opening the output device"))?;
+
+        Ok(Response::new(info.into()))
+    }
+
+    async fn get_radio_history(
+        &self,
+        request: Request<RadioHistoryRequest>,
+    ) -> Result<Response<RadioHistory>, Status> {
+        let url: Option<String> = request.into_inner().into();
+        // default to the current track, if it is a radio station
+        let url = url.or_else(|| {
+            self.playlist
+                .read()
+                .current_track()
+                .filter(|track| track.media_type == MediaType::LiveRadio)
+                .and_then(|track| track.file().map(ToString::to_string))
+        });
+        let reply = match url {
+            Some(url) => self.player_stats.lock().radio_history.get(&url),
+            None => RadioHistoryInfo::default(),
+        };
+
+        Ok(Response::new(reply.into()))
+    }
+
+    async fn get_sleep_timer(
+        &self,
+        _request: Request<Empty>,
+    ) -> Result<Response<SleepTimerState>, Status> {
+        let s = self.player_stats.lock();
+
+        Ok(Response::new(s.sleep_timer.into()))
+    }
+
     async fn skip_next(&self, _request: Request<Empty>) -> Result<Response<Empty>, Status> {
         let reply = Empty {};
         self.command(&PlayerCmd::SkipNext);
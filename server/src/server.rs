@@ -1,30 +1,36 @@
 mod cli;
+mod com_listener;
 mod logger;
 mod music_player_service;
+mod sleep_timer;
 
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant, SystemTime};
 
 use anyhow::{bail, Context, Result};
 use clap::Parser;
+use com_listener::ComListener;
 use music_player_service::MusicPlayerService;
 use parking_lot::Mutex;
+use sleep_timer::SleepTimer;
 use termusiclib::config::v2::server::config_extra::ServerConfigVersionedDefaulted;
-use termusiclib::config::v2::server::ScanDepth;
+use termusiclib::config::v2::server::{AudioOutput, ScanDepth};
 use termusiclib::config::ServerOverlay;
-use termusiclib::player::music_player_server::MusicPlayerServer;
-use termusiclib::player::{GetProgressResponse, PlayerProgress, PlayerTime};
-use termusiclib::track::MediaType;
+use termusiclib::player::auth::AuthCheck;
+use termusiclib::player::radio_history::RadioTitleHistory;
+use termusiclib::player::{
+    EqualizerInfo, GetProgressResponse, PlayerProgress, PlayerTime, SleepTimerInfo, TrackInfo,
+};
+use termusiclib::track::{MediaType, Track};
 use termusiclib::{podcast, utils};
 use termusicplayback::{
     Backend, BackendSelect, GeneralPlayer, PlayerCmd, PlayerCmdReciever, PlayerCmdSender,
-    PlayerTrait, SpeedSigned, Status, VolumeSigned,
+    PlayerTrait, PlaylistEditError, SharedPlaylist, SpeedSigned, Status, VolumeSigned, MAX_SPEED,
+    MIN_SPEED,
 };
 use tokio::runtime::Handle;
 use tokio::sync::{broadcast, oneshot};
-use tonic::transport::server::TcpIncoming;
-use tonic::transport::Server;
 
 #[macro_use]
 extern crate log;
@@ -32,6 +38,9 @@ extern crate log;
 pub const MAX_DEPTH: usize = 4;
 pub const VOLUME_STEP: VolumeSigned = 5;
 pub const SPEED_STEP: SpeedSigned = 1;
+/// Capacity of the update event stream, multi-track playlist changes send one event per track,
+/// so this needs to be big enough to not make receivers lag and reload the whole playlist
+const STREAM_CAPACITY: usize = 64;
 
 /// Stats for the music player responses
 #[derive(Debug, Clone, PartialEq)]
@@ -44,6 +53,13 @@ struct PlayerStats {
     pub gapless: bool,
     pub current_track_updated: bool,
     pub radio_title: String,
+    pub current_track: Option<TrackInfo>,
+    pub sleep_timer: Option<SleepTimerInfo>,
+    pub equalizer: EqualizerInfo,
+    /// The output device actually in use, [`None`] for the default device
+    pub output_device: Option<String>,
+    /// The titles announced by the radio stations played in this session
+    pub radio_history: RadioTitleHistory,
 }
 
 impl PlayerStats {
@@ -60,6 +76,11 @@ impl PlayerStats {
             gapless: true,
             current_track_updated: false,
             radio_title: String::new(),
+            current_track: None,
+            sleep_timer: None,
+            equalizer: EqualizerInfo::default(),
+            output_device: None,
+            radio_history: RadioTitleHistory::default(),
         }
     }
 
@@ -73,6 +94,8 @@ impl PlayerStats {
             gapless: self.gapless,
             current_track_updated: self.current_track_updated,
             radio_title: self.radio_title.clone(),
+            current_track: self.current_track.clone().map(Into::into),
+            sleep_timer: self.sleep_timer.map(|v| Some(v).into()),
         }
     }
 
@@ -103,11 +126,9 @@ async fn actual_main() -> Result<()> {
 
     info!("Server starting...");
     let (cmd_tx, cmd_rx) = tokio::sync::mpsc::unbounded_channel();
-    let (stream_tx, _) = broadcast::channel(3);
+    let (stream_tx, _) = broadcast::channel(STREAM_CAPACITY);
 
-    let music_player_service: MusicPlayerService =
-        MusicPlayerService::new(cmd_tx.clone(), stream_tx.clone());
-    let playerstats = music_player_service.player_stats.clone();
+    let playerstats = Arc::new(Mutex::new(PlayerStats::new()));
 
     let cmd_tx_ctrlc = cmd_tx.clone();
     let cmd_tx_ticker = cmd_tx.clone();
@@ -119,19 +140,21 @@ async fn actual_main() -> Result<()> {
     })
     .expect("Error setting Ctrl-C handler");
 
-    let addr = std::net::SocketAddr::from(config.settings.com);
-
-    // workaround to print address once sever "actually" is started and address is known
-    // see https://github.com/hyperium/tonic/issues/351
-    let tcp_listener = tokio::net::TcpListener::bind(addr)
-        .await
-        .with_context(|| format!("Error binding address: {}", addr))?;
-    info!("Server listening on {}", tcp_listener.local_addr().unwrap());
-    let tcp_stream =
-        TcpIncoming::from_listener(tcp_listener, true, None).map_err(|e| anyhow::anyhow!(e))?;
+    let com = &config.settings.com;
+    let listener = ComListener::bind(com).await?;
+    let listen_description = listener.describe();
+    if com.auth_token.is_none() && com.socket_path.is_none() && !com.address.is_loopback() {
+        warn!("Server is reachable from other machines without a \"auth_token\" set, anyone on the network can control it");
+    }
+    let auth = AuthCheck::new(com.auth_token.as_deref());
+    info!("Server listening on {}", listen_description);
 
     let tokio_handle = Handle::current();
     let (player_handle_os_tx, player_handle_os_rx) = oneshot::channel();
+    let (playlist_tx, playlist_rx) = oneshot::channel();
+    let cmd_tx_service = cmd_tx.clone();
+    let stream_tx_service = stream_tx.clone();
+    let playerstats_service = playerstats.clone();
     let player_handle = std::thread::Builder::new()
         .name("main player loop".into())
         .spawn(move || {
@@ -143,19 +166,29 @@ async fn actual_main() -> Result<()> {
                 config,
                 playerstats,
                 stream_tx,
+                playlist_tx,
             );
             let _ = player_handle_os_tx.send(res);
         })?;
 
-    ticker_thread(cmd_tx_ticker)?;
+    // the playlist is created by the player, if that fails the error is in the player handle
+    let Ok(playlist) = playlist_rx.await else {
+        player_handle_os_rx.await??;
+        bail!("Player loop exited before creating the playlist");
+    };
 
-    tokio::spawn(
-        Server::builder()
-            .add_service(MusicPlayerServer::new(music_player_service))
-            .serve_with_incoming(tcp_stream),
+    let music_player_service = MusicPlayerService::new(
+        cmd_tx_service,
+        stream_tx_service,
+        playerstats_service,
+        playlist,
     );
 
-    info!("Server started and listening on {}", addr);
+    ticker_thread(cmd_tx_ticker)?;
+
+    let _com_guard = listener.serve(music_player_service, auth);
+
+    info!("Server started and listening on {}", listen_description);
 
     // await the oneshot completing in a async fashion
     player_handle_os_rx.await??;
@@ -174,24 +207,36 @@ fn player_loop(
     config: ServerOverlay,
     playerstats: Arc<Mutex<PlayerStats>>,
     stream_tx: termusicplayback::StreamTX,
+    playlist_tx: oneshot::Sender<SharedPlaylist>,
 ) -> Result<()> {
     let mut player = GeneralPlayer::new_backend(backend, config, cmd_tx, stream_tx)?;
+    if playlist_tx.send(player.playlist.clone()).is_err() {
+        bail!("Server exited before the player was ready");
+    }
+    let mut sleep_timer = SleepTimer::default();
+    playerstats.lock().equalizer =
+        EqualizerInfo::from(&player.config.read().settings.player.equalizer);
+    playerstats.lock().output_device = player.output_device();
     while let Some(cmd) = cmd_rx.blocking_recv() {
         #[allow(unreachable_patterns)]
         match cmd {
             PlayerCmd::AboutToFinish => {
                 info!("about to finish signal received");
-                if !player.playlist.is_empty()
-                    && !player.playlist.has_next_track()
-                    && player.config.read().settings.player.gapless
-                {
+                let playlist = player.playlist.read();
+                let should_enqueue = !playlist.is_empty()
+                    && !playlist.has_next_track()
+                    && player.config.read().settings.player.gapless;
+                drop(playlist);
+                if should_enqueue {
                     player.enqueue_next_from_playlist();
                 }
             }
             PlayerCmd::Quit => {
                 info!("PlayerCmd::Quit received");
+                // restore the volume in case of a running fade
+                sleep_timer.cancel(&mut player);
                 player.player_save_last_position();
-                if let Err(e) = player.playlist.save() {
+                if let Err(e) = player.playlist.read().save() {
                     error!("error when saving playlist: {e}");
                 };
                 if let Err(e) =
@@ -199,35 +244,99 @@ fn player_loop(
                 {
                     error!("error when saving config: {e}");
                 };
-                std::process::exit(0);
+                // return normally so that destructors run, like removing the socket file
+                break;
             }
             PlayerCmd::CycleLoop => {
-                player.config.write().settings.player.loop_mode = player.playlist.cycle_loop_mode();
+                let loop_mode = player.playlist.write().cycle_loop_mode();
+                player.config.write().settings.player.loop_mode = loop_mode;
             }
             PlayerCmd::Eos => {
                 info!("Eos received");
-                if player.playlist.is_empty() {
+                if player.playlist.read().is_empty() {
                     player.stop();
                     continue;
                 }
-                debug!(
-                    "current track index: {:?}",
-                    player.playlist.get_current_track_index()
-                );
-                player.playlist.clear_current_track();
+                player.player_update_play_statistics(true);
+                {
+                    let mut playlist = player.playlist.write();
+                    debug!(
+                        "current track index: {:?}",
+                        playlist.get_current_track_index()
+                    );
+                    playlist.clear_current_track();
+                }
+                let sleep_timer_done = sleep_timer.on_track_end(true);
                 player.start_play();
+                if sleep_timer_done {
+                    sleep_timer.finish(&mut player);
+                }
+                playerstats.lock().sleep_timer = sleep_timer.state(Instant::now());
                 debug!(
                     "playing index is: {}",
-                    player.playlist.get_current_track_index()
+                    player.playlist.read().get_current_track_index()
                 );
             }
             PlayerCmd::GetProgress | PlayerCmd::ProcessID => {}
             PlayerCmd::PlaySelected => {
                 info!("play selected");
                 player.player_save_last_position();
-                player.playlist.proceed_false();
+                player.playlist.write().proceed_false();
+                player.next();
+            }
+            PlayerCmd::PlaylistPlaySpecific(info, reply) => {
+                info!("play specific track: {}", info.track_index);
+                player.player_save_last_position();
+                if let Err(err) = player.playlist.write().play_specific(&info) {
+                    error!("Error playing specific track: {err:#}");
+                    reply.send(Err(err));
+                    continue;
+                }
+                reply.send(Ok(()));
+                player.playlist.write().proceed_false();
                 player.next();
             }
+            PlayerCmd::PlaylistAddTrack(info, reply) => {
+                let res = player
+                    .playlist
+                    .write()
+                    .add_tracks(&info, &player.db_podcast, &mut player.db)
+                    .map(|_| ())
+                    .map_err(PlaylistEditError::AddTracks);
+                if let Err(err) = &res {
+                    error!("Error adding tracks: {err}");
+                }
+                save_playlist(&player);
+                reply.send(res);
+            }
+            PlayerCmd::PlaylistRemoveTrack(info, reply) => {
+                let res = player.playlist.write().remove_indexed(&info);
+                if let Err(err) = &res {
+                    error!("Error removing track: {err:#}");
+                }
+                save_playlist(&player);
+                reply.send(res);
+            }
+            PlayerCmd::PlaylistRemoveDeletedTracks => {
+                player.playlist.write().remove_deleted_items();
+                save_playlist(&player);
+            }
+            PlayerCmd::PlaylistSwapTrack(info, reply) => {
+                let res = player.playlist.write().swap_tracks(&info);
+                if let Err(err) = &res {
+                    error!("Error swapping tracks: {err:#}");
+                }
+                save_playlist(&player);
+                reply.send(res);
+            }
+            PlayerCmd::PlaylistClear => {
+                player.playlist.write().clear();
+                save_playlist(&player);
+            }
+            PlayerCmd::PlaylistShuffle => {
+                player.playlist.write().shuffle();
+                save_playlist(&player);
+            }
             PlayerCmd::SkipPrevious => {
                 info!("skip to previous track");
                 player.player_save_last_position();
@@ -237,9 +346,11 @@ fn player_loop(
                 if let Err(err) = player.reload_config() {
                     error!("Reloading config failed, using old: {:#?}", err);
                 }
+                playerstats.lock().equalizer =
+                    EqualizerInfo::from(&player.config.read().settings.player.equalizer);
             }
             PlayerCmd::ReloadPlaylist => {
-                player.playlist.reload_tracks().ok();
+                player.playlist.write().reload_tracks(&mut player.db).ok();
             }
             PlayerCmd::SeekBackward => {
                 player.seek_relative(false);
@@ -255,11 +366,41 @@ fn player_loop(
                     p_tick.progress = progress
                 }
             }
+            PlayerCmd::SeekTo(position) => {
+                player.seek_to(position);
+                let mut p_tick = playerstats.lock();
+                if let Some(progress) = player.get_progress() {
+                    p_tick.progress = progress
+                }
+            }
             PlayerCmd::SkipNext => {
                 info!("skip to next track.");
                 player.player_save_last_position();
+                player.player_update_play_statistics(false);
+                sleep_timer.on_track_end(false);
                 player.next();
             }
+            PlayerCmd::SetEqualizerPreset(preset) => {
+                info!("set equalizer preset: {preset:?}");
+                player.set_equalizer_preset(preset);
+                playerstats.lock().equalizer =
+                    EqualizerInfo::from(&player.config.read().settings.player.equalizer);
+            }
+            PlayerCmd::SetOutputDevice(device, reply) => {
+                info!("set output device: {device:?}");
+                player.set_output_device(device, reply);
+            }
+            PlayerCmd::OutputDeviceOpened(device) => {
+                playerstats.lock().output_device = device;
+            }
+            PlayerCmd::SleepTimerSet(kind) => {
+                sleep_timer.set(kind, Instant::now(), &mut player);
+                playerstats.lock().sleep_timer = sleep_timer.state(Instant::now());
+            }
+            PlayerCmd::SleepTimerCancel => {
+                sleep_timer.cancel(&mut player);
+                playerstats.lock().sleep_timer = sleep_timer.state(Instant::now());
+            }
             PlayerCmd::SpeedDown => {
                 let new_speed = player.add_speed(-SPEED_STEP);
                 info!("after speed down: {}", new_speed);
@@ -268,6 +409,13 @@ fn player_loop(
                 p_tick.speed = new_speed;
             }
 
+            PlayerCmd::SetSpeed(speed) => {
+                let new_speed = player.set_speed(speed.clamp(MIN_SPEED, MAX_SPEED));
+                info!("after set speed: {}", new_speed);
+                player.config.write().settings.player.speed = new_speed;
+                let mut p_tick = playerstats.lock();
+                p_tick.speed = new_speed;
+            }
             PlayerCmd::SpeedUp => {
                 let new_speed = player.add_speed(SPEED_STEP);
                 info!("after speed up: {}", new_speed);
@@ -278,19 +426,25 @@ fn player_loop(
             PlayerCmd::Tick => {
                 // info!("tick received");
                 player.mpris_handle_events();
+                sleep_timer.on_tick(Instant::now(), &mut player);
                 let mut p_tick = playerstats.lock();
-                p_tick.status = player.playlist.status().as_u32();
+                p_tick.sleep_timer = sleep_timer.state(Instant::now());
+                let status = player.playlist.read().status();
+                p_tick.status = status.as_u32();
                 // branch to auto-start playing if status is "stopped"(not paused) and playlist is not empty anymore
-                if player.playlist.status() == Status::Stopped {
-                    if player.playlist.is_empty() {
+                if status == Status::Stopped {
+                    p_tick.current_track = None;
+                    let mut playlist = player.playlist.write();
+                    if playlist.is_empty() {
                         continue;
                     }
                     debug!(
                         "current track index: {:?}",
-                        player.playlist.get_current_track_index()
+                        playlist.get_current_track_index()
                     );
-                    player.playlist.clear_current_track();
-                    player.playlist.proceed_false();
+                    playlist.clear_current_track();
+                    playlist.proceed_false();
+                    drop(playlist);
                     player.start_play();
                     continue;
                 }
@@ -299,16 +453,37 @@ fn player_loop(
                     player.mpris_update_progress(&p_tick.progress);
                 }
                 if player.current_track_updated {
-                    p_tick.current_track_index = player.playlist.get_current_track_index() as u32;
+                    p_tick.current_track_index =
+                        player.playlist.read().get_current_track_index() as u32;
                     p_tick.current_track_updated = player.current_track_updated;
+                    p_tick.current_track = player
+                        .playlist
+                        .read()
+                        .current_track()
+                        .map(Track::as_track_info);
                     player.current_track_updated = false;
                 }
-                if let Some(track) = player.playlist.current_track() {
+                let current_media_type = player
+                    .playlist
+                    .read()
+                    .current_track()
+                    .map(|track| track.media_type);
+                if let Some(media_type) = current_media_type {
                     // if only one backend is enabled, rust will complain that it is the only thing that happens
                     #[allow(irrefutable_let_patterns)]
-                    if MediaType::LiveRadio == track.media_type {
+                    if MediaType::LiveRadio == media_type {
                         // TODO: consider changing "radio_title" and "media_title" to be consistent
-                        p_tick.radio_title = player.media_info().media_title.unwrap_or_default();
+                        let radio_title = player.media_info().media_title.unwrap_or_default();
+                        if radio_title != p_tick.radio_title {
+                            if let Some(url) =
+                                player.playlist.read().current_track().and_then(Track::file)
+                            {
+                                p_tick
+                                    .radio_history
+                                    .push(url, &radio_title, SystemTime::now());
+                            }
+                            p_tick.radio_title = radio_title;
+                        }
 
                         if let Backend::Rusty(ref mut backend) = player.backend {
                             p_tick.progress.total_duration = Some(Duration::from_secs(
@@ -339,11 +514,13 @@ fn player_loop(
             }
             PlayerCmd::TogglePause => {
                 info!("player toggled pause");
+                sleep_timer.on_user_interaction(&mut player);
                 player.toggle_pause();
                 let mut p_tick = playerstats.lock();
-                p_tick.status = player.playlist.status().as_u32();
+                p_tick.status = player.playlist.read().status().as_u32();
             }
             PlayerCmd::VolumeDown => {
+                sleep_timer.on_user_interaction(&mut player);
                 info!("before volumedown: {}", player.volume());
                 let new_volume = player.add_volume(-VOLUME_STEP);
                 player.config.write().settings.player.volume = new_volume;
@@ -352,7 +529,17 @@ fn player_loop(
                 p_tick.volume = new_volume;
                 player.mpris_volume_update();
             }
+            PlayerCmd::SetVolume(volume) => {
+                sleep_timer.on_user_interaction(&mut player);
+                let new_volume = player.set_volume(volume);
+                player.config.write().settings.player.volume = new_volume;
+                info!("after set volume: {}", new_volume);
+                let mut p_tick = playerstats.lock();
+                p_tick.volume = new_volume;
+                player.mpris_volume_update();
+            }
             PlayerCmd::VolumeUp => {
+                sleep_timer.on_user_interaction(&mut player);
                 info!("before volumeup: {}", player.volume());
                 let new_volume = player.add_volume(VOLUME_STEP);
                 player.config.write().settings.player.volume = new_volume;
@@ -362,9 +549,11 @@ fn player_loop(
                 player.mpris_volume_update();
             }
             PlayerCmd::Pause => {
+                sleep_timer.on_user_interaction(&mut player);
                 player.pause();
             }
             PlayerCmd::Play => {
+                sleep_timer.on_user_interaction(&mut player);
                 player.resume();
             }
         }
@@ -373,6 +562,13 @@ fn player_loop(
     Ok(())
 }
 
+/// Save the playlist after it has been modified, so that it is not lost on a unclean exit
+fn save_playlist(player: &GeneralPlayer) {
+    if let Err(err) = player.playlist.read().save() {
+        error!("error when saving playlist: {err}");
+    }
+}
+
 /// Spawn the thread that periodically sends [`PlayerCmd::Tick`]
 fn ticker_thread(cmd_tx: PlayerCmdSender) -> Result<()> {
     std::thread::Builder::new()
@@ -402,11 +598,24 @@ fn get_config(args: &cli::Args) -> Result<ServerOverlay> {
         music_dir_overwrite: music_dir,
         disable_discord_status: args.disable_discord,
         library_scan_depth: max_depth,
+        output: get_output(args),
     };
 
     Ok(overlay)
 }
 
+/// Get the audio output overwrite from the cli, if any
+fn get_output(args: &cli::Args) -> Option<AudioOutput> {
+    let fast = args.output_fast;
+    if args.output_null {
+        Some(AudioOutput::Null { fast })
+    } else {
+        args.output_wav
+            .clone()
+            .map(|path| AudioOutput::Wav { path, fast })
+    }
+}
+
 fn get_path(dir: &Path) -> Result<PathBuf> {
     let mut path = dir.to_path_buf();
 
@@ -0,0 +1,383 @@
+use std::time::{Duration, Instant};
+
+use termusiclib::player::{SleepTimerInfo, SleepTimerKind};
+use termusicplayback::{GeneralPlayer, PlayerTrait, Status, Volume};
+
+/// Time over which the volume is faded out before pausing
+const FADE_DURATION: Duration = Duration::from_secs(10);
+
+/// What the timer is waiting for
+#[derive(Debug, Clone, Copy)]
+enum Target {
+    /// Start fading at the given time
+    Deadline(Instant),
+    /// Pause once this many tracks have finished, including the current one
+    Tracks(u32),
+}
+
+/// The parts of the player controlled by the [`SleepTimer`]
+pub trait SleepTimerPlayer {
+    /// Whether the player is currently playing
+    fn is_running(&self) -> bool;
+    /// The position and total duration of the current track, if known
+    fn track_progress(&self) -> Option<(Duration, Duration)>;
+    /// The configured volume
+    fn current_volume(&self) -> Volume;
+    /// Set the volume of the backend only, without changing the configured volume
+    fn set_backend_volume(&mut self, volume: Volume);
+    fn pause_playback(&mut self);
+}
+
+impl SleepTimerPlayer for GeneralPlayer {
+    fn is_running(&self) -> bool {
+        self.playlist.read().status() == Status::Running
+    }
+
+    fn track_progress(&self) -> Option<(Duration, Duration)> {
+        let progress = self.get_progress()?;
+        Some((progress.position?, progress.total_duration?))
+    }
+
+    fn current_volume(&self) -> Volume {
+        self.volume()
+    }
+
+    fn set_backend_volume(&mut self, volume: Volume) {
+        self.backend.as_player_mut().set_volume(volume);
+    }
+
+    fn pause_playback(&mut self) {
+        self.pause();
+    }
+}
+
+/// A running fade-out
+#[derive(Debug, Clone, Copy)]
+struct Fade {
+    started: Instant,
+    length: Duration,
+    /// The volume before the fade started, to restore it afterwards
+    volume: Volume,
+}
+
+/// Pause playback after a specific time or amount of tracks, fading out the volume beforehand.
+///
+/// The fade is applied to the backend directly, so that the configured volume (and clients) never see the faded volume.
+#[derive(Debug, Default)]
+pub struct SleepTimer {
+    target: Option<Target>,
+    fade: Option<Fade>,
+}
+
+impl SleepTimer {
+    /// Start a new timer, replacing any existing one
+    pub fn set(&mut self, kind: SleepTimerKind, now: Instant, player: &mut impl SleepTimerPlayer) {
+        self.cancel(player);
+
+        let target = match kind {
+            SleepTimerKind::Duration(duration) => Target::Deadline(now + duration),
+            SleepTimerKind::EndOfTrack => Target::Tracks(1),
+            SleepTimerKind::Tracks(tracks) => Target::Tracks(tracks.get()),
+        };
+        info!("Sleep timer set: {target:?}");
+        self.target = Some(target);
+    }
+
+    /// Cancel the timer, restoring the volume if currently fading
+    pub fn cancel(&mut self, player: &mut impl SleepTimerPlayer) {
+        if self.target.take().is_some() {
+            info!("Sleep timer cancelled");
+        }
+        if let Some(fade) = self.fade.take() {
+            player.set_backend_volume(fade.volume);
+        }
+    }
+
+    /// Get the current state for clients, [`None`] if there is no active timer
+    pub fn state(&self, now: Instant) -> Option<SleepTimerInfo> {
+        let target = self.target?;
+
+        let (remaining_time, remaining_tracks) = match target {
+            Target::Deadline(deadline) => (Some(deadline.saturating_duration_since(now)), None),
+            Target::Tracks(tracks) => (None, Some(tracks)),
+        };
+
+        Some(SleepTimerInfo {
+            remaining_time,
+            remaining_tracks,
+            fading: self.fade.is_some(),
+        })
+    }
+
+    /// Advance the timer, should be called regularly
+    pub fn on_tick(&mut self, now: Instant, player: &mut impl SleepTimerPlayer) {
+        let Some(target) = self.target else {
+            return;
+        };
+
+        if let Some(fade) = self.fade {
+            let elapsed = now.saturating_duration_since(fade.started);
+            if elapsed >= fade.length {
+                self.finish(player);
+                return;
+            }
+
+            let remaining = 1.0 - elapsed.as_secs_f64() / fade.length.as_secs_f64();
+            // the result is always between 0 and the original volume
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let volume = (f64::from(fade.volume) * remaining).round() as Volume;
+            player.set_backend_volume(volume);
+
+            return;
+        }
+
+        let is_running = player.is_running();
+
+        let fade_length = match target {
+            Target::Deadline(deadline) => {
+                if now < deadline {
+                    return;
+                }
+                // already not playing anymore, nothing left to do
+                if !is_running {
+                    info!("Sleep timer reached while not playing");
+                    self.cancel(player);
+                    return;
+                }
+                FADE_DURATION
+            }
+            Target::Tracks(tracks) => {
+                // only the last track fades out, and only if its length is known
+                if tracks > 1 || !is_running {
+                    return;
+                }
+                let Some((position, total)) = player.track_progress() else {
+                    return;
+                };
+                let remaining = total.saturating_sub(position);
+                if total.is_zero() || remaining > FADE_DURATION {
+                    return;
+                }
+                remaining
+            }
+        };
+
+        info!("Sleep timer reached, fading out");
+        self.fade = Some(Fade {
+            started: now,
+            length: fade_length,
+            volume: player.current_volume(),
+        });
+    }
+
+    /// Count a finished track, skipped tracks (`finished == false`) are not counted.
+    ///
+    /// Returns `true` if playback should be paused once the next track has been started.
+    pub fn on_track_end(&mut self, finished: bool) -> bool {
+        let Some(Target::Tracks(tracks)) = &mut self.target else {
+            return false;
+        };
+        if !finished {
+            return false;
+        }
+
+        *tracks = tracks.saturating_sub(1);
+
+        *tracks == 0
+    }
+
+    /// Cancel the timer if currently fading, as someone is obviously still awake to change the playback.
+    ///
+    /// Should be called before volume or pause changes are applied, so that the restored volume does not overwrite them.
+    pub fn on_user_interaction(&mut self, player: &mut impl SleepTimerPlayer) {
+        if self.fade.is_some() {
+            info!("Playback changed while fading out, cancelling sleep timer");
+            self.cancel(player);
+        }
+    }
+
+    /// Pause playback and restore the volume, ending the timer
+    pub fn finish(&mut self, player: &mut impl SleepTimerPlayer) {
+        info!("Sleep timer finished, pausing");
+        player.pause_playback();
+        self.cancel(player);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU32;
+    use std::time::{Duration, Instant};
+
+    use termusiclib::player::{SleepTimerInfo, SleepTimerKind};
+    use termusicplayback::Volume;
+
+    use super::{SleepTimer, SleepTimerPlayer, FADE_DURATION};
+
+    struct MockPlayer {
+        running: bool,
+        progress: Option<(Duration, Duration)>,
+        volume: Volume,
+        backend_volume: Volume,
+        paused: bool,
+    }
+
+    impl MockPlayer {
+        fn new() -> Self {
+            Self {
+                running: true,
+                progress: None,
+                volume: 100,
+                backend_volume: 100,
+                paused: false,
+            }
+        }
+    }
+
+    impl SleepTimerPlayer for MockPlayer {
+        fn is_running(&self) -> bool {
+            self.running
+        }
+
+        fn track_progress(&self) -> Option<(Duration, Duration)> {
+            self.progress
+        }
+
+        fn current_volume(&self) -> Volume {
+            self.volume
+        }
+
+        fn set_backend_volume(&mut self, volume: Volume) {
+            self.backend_volume = volume;
+        }
+
+        fn pause_playback(&mut self) {
+            self.paused = true;
+        }
+    }
+
+    fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn should_fade_and_pause_at_deadline() {
+        let mut player = MockPlayer::new();
+        let mut timer = SleepTimer::default();
+        let start = Instant::now();
+
+        timer.set(SleepTimerKind::Duration(secs(60)), start, &mut player);
+        timer.on_tick(start + secs(59), &mut player);
+        assert_eq!(
+            timer.state(start + secs(59)),
+            Some(SleepTimerInfo {
+                remaining_time: Some(secs(1)),
+                remaining_tracks: None,
+                fading: false,
+            })
+        );
+
+        let fade_start = start + secs(60);
+        timer.on_tick(fade_start, &mut player);
+        assert!(timer.state(fade_start).unwrap().fading);
+        timer.on_tick(fade_start + FADE_DURATION / 2, &mut player);
+        assert_eq!(player.backend_volume, 50);
+        assert!(!player.paused);
+
+        timer.on_tick(fade_start + FADE_DURATION, &mut player);
+        assert!(player.paused);
+        assert_eq!(player.backend_volume, 100);
+        assert_eq!(timer.state(fade_start + FADE_DURATION), None);
+    }
+
+    #[test]
+    fn should_cancel_at_deadline_when_not_playing() {
+        let mut player = MockPlayer::new();
+        player.running = false;
+        let mut timer = SleepTimer::default();
+        let start = Instant::now();
+
+        timer.set(SleepTimerKind::Duration(secs(60)), start, &mut player);
+        timer.on_tick(start + secs(60), &mut player);
+
+        assert_eq!(timer.state(start + secs(60)), None);
+        assert!(!player.paused);
+    }
+
+    #[test]
+    fn should_fade_out_the_end_of_track() {
+        let mut player = MockPlayer::new();
+        let mut timer = SleepTimer::default();
+        let start = Instant::now();
+
+        timer.set(SleepTimerKind::EndOfTrack, start, &mut player);
+        player.progress = Some((secs(50), secs(100)));
+        timer.on_tick(start, &mut player);
+        assert!(!timer.state(start).unwrap().fading);
+
+        // fades over the rest of the track
+        player.progress = Some((secs(96), secs(100)));
+        timer.on_tick(start, &mut player);
+        assert!(timer.state(start).unwrap().fading);
+        timer.on_tick(start + secs(2), &mut player);
+        assert_eq!(player.backend_volume, 50);
+
+        assert!(timer.on_track_end(true));
+        timer.finish(&mut player);
+        assert!(player.paused);
+        assert_eq!(player.backend_volume, 100);
+    }
+
+    #[test]
+    fn should_count_finished_tracks_only() {
+        let mut player = MockPlayer::new();
+        let mut timer = SleepTimer::default();
+        let start = Instant::now();
+
+        timer.set(
+            SleepTimerKind::Tracks(NonZeroU32::new(2).unwrap()),
+            start,
+            &mut player,
+        );
+        assert!(!timer.on_track_end(false));
+        assert_eq!(timer.state(start).unwrap().remaining_tracks, Some(2));
+
+        // only the last track fades out
+        player.progress = Some((secs(99), secs(100)));
+        timer.on_tick(start, &mut player);
+        assert!(!timer.state(start).unwrap().fading);
+
+        assert!(!timer.on_track_end(true));
+        assert_eq!(timer.state(start).unwrap().remaining_tracks, Some(1));
+        assert!(!timer.on_track_end(false));
+        assert!(timer.on_track_end(true));
+    }
+
+    #[test]
+    fn should_restore_volume_when_cancelled_while_fading() {
+        let mut player = MockPlayer::new();
+        let mut timer = SleepTimer::default();
+        let start = Instant::now();
+
+        timer.set(SleepTimerKind::Duration(secs(0)), start, &mut player);
+        timer.on_tick(start, &mut player);
+        timer.on_tick(start + FADE_DURATION / 2, &mut player);
+        assert_eq!(player.backend_volume, 50);
+
+        timer.on_user_interaction(&mut player);
+        assert_eq!(player.backend_volume, 100);
+        assert_eq!(timer.state(start), None);
+        assert!(!player.paused);
+
+        // interactions without a fade keep the timer
+        timer.set(SleepTimerKind::Duration(secs(60)), start, &mut player);
+        timer.on_user_interaction(&mut player);
+        assert!(timer.state(start).is_some());
+
+        timer.on_tick(start + secs(60), &mut player);
+        timer.on_tick(start + secs(65), &mut player);
+        timer.cancel(&mut player);
+        assert_eq!(player.backend_volume, 100);
+        assert_eq!(timer.state(start + secs(65)), None);
+    }
+}
@@ -21,7 +21,8 @@
  * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
  * SOFTWARE.
  */
-use clap::{builder::ArgPredicate, ArgAction, Parser, Subcommand, ValueEnum};
+use clap::{builder::ArgPredicate, ArgAction, Args as ClapArgs, Parser, Subcommand, ValueEnum};
+use std::num::NonZeroU32;
 use std::path::PathBuf;
 
 #[derive(Parser, Debug)]
@@ -92,6 +93,82 @@ pub enum Action {
         #[arg(value_name = "FILE")]
         file: PathBuf,
     },
+    /// Control a already running server, without starting the TUI or a server.
+    Ctl(CtlArgs),
+}
+
+#[derive(ClapArgs, Debug)]
+pub struct CtlArgs {
+    /// Print the output as JSON instead of plain text.
+    #[arg(long, global = true)]
+    pub json: bool,
+    #[command(subcommand)]
+    pub command: CtlCommand,
+}
+
+/// Commands to send to the server
+#[derive(Subcommand, Debug, Clone, PartialEq)]
+pub enum CtlCommand {
+    /// Toggle between playing and paused.
+    TogglePause,
+    /// Skip to the next track.
+    Next,
+    /// Skip to the previous track.
+    Previous,
+    /// Seek to a position, either as seconds ("90") or minutes and seconds ("1:30").
+    /// A leading "+" or "-" seeks relative to the current position.
+    Seek {
+        #[arg(allow_hyphen_values = true)]
+        position: String,
+    },
+    /// Set the volume (0-100). A leading "+" or "-" changes it relative to the current volume.
+    Volume {
+        #[arg(allow_hyphen_values = true)]
+        volume: String,
+    },
+    /// Print the current status and track.
+    Status,
+    /// Add tracks (paths or urls) to the end of the playlist.
+    QueueAdd {
+        #[arg(required = true, value_name = "TRACK")]
+        tracks: Vec<String>,
+    },
+    /// Set or cancel the sleep timer, without options the current timer is printed.
+    #[group(multiple = false)]
+    SleepTimer {
+        /// Fade out and pause after this many minutes.
+        #[arg(long, value_name = "MINUTES")]
+        minutes: Option<u64>,
+        /// Fade out and pause at the end of the current track.
+        #[arg(long)]
+        end_of_track: bool,
+        /// Fade out and pause after this many tracks, including the current one.
+        #[arg(long, value_name = "TRACKS")]
+        tracks: Option<NonZeroU32>,
+        /// Cancel the active timer.
+        #[arg(long)]
+        cancel: bool,
+    },
+    /// Switch the equalizer preset, without options the current preset and all presets are printed.
+    #[group(multiple = false)]
+    Equalizer {
+        /// The preset to switch to.
+        #[arg(long, value_name = "NAME")]
+        preset: Option<String>,
+        /// Disable the equalizer.
+        #[arg(long)]
+        off: bool,
+    },
+    /// Switch the audio output device, without options the selected device and all devices are printed.
+    #[group(multiple = false)]
+    OutputDevice {
+        /// The name of the device to switch to.
+        #[arg(long, value_name = "NAME")]
+        device: Option<String>,
+        /// Switch to the system default device.
+        #[arg(long)]
+        default: bool,
+    },
 }
 
 const DEFAULT_LOGFILE_FILENAME: &str = "termusic-tui.log";
@@ -0,0 +1,397 @@
+//! Non-interactive remote control for a already running server, see `termusic ctl --help`.
+
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use termusiclib::player::playlist_helpers::PlaylistAddTrack;
+use termusiclib::player::{
+    EqualizerInfo, GetProgressResponse, OutputDeviceInfo, PlayerProgress, SleepTimerInfo,
+    SleepTimerKind, TrackInfo,
+};
+use termusiclib::track::Track;
+use termusiclib::utils;
+use termusicplayback::{Playlist, Status};
+
+use crate::cli::{CtlArgs, CtlCommand};
+use crate::ui::playback::Playback;
+use crate::CombinedSettings;
+
+/// Execute a single `ctl` command against the server and print the result.
+///
+/// This never spawns a server, if none is running, a error is returned.
+pub async fn run(args: CtlArgs, config: &CombinedSettings) -> Result<()> {
+    let com = crate::get_com(config)?;
+    let client = crate::connect(&com).await.with_context(|| {
+        format!(
+            "Connecting to server at {}, is the server running?",
+            crate::describe_com(&com)
+        )
+    })?;
+    let mut playback = Playback::new(client);
+    let json = args.json;
+
+    match args.command {
+        CtlCommand::TogglePause => {
+            let status = playback.toggle_pause().await?;
+            print_value(json, "status", status_str(status));
+        }
+        CtlCommand::Next => {
+            playback.skip_next().await?;
+            print_ok(json);
+        }
+        CtlCommand::Previous => {
+            playback.skip_previous().await?;
+            print_ok(json);
+        }
+        CtlCommand::Seek { position } => {
+            let current = playback
+                .get_progress()
+                .await?
+                .progress
+                .and_then(|v| PlayerProgress::from(v).position)
+                .map_or(0, |v| v.as_secs());
+            let target = parse_relative(&position, current, parse_seconds)
+                .with_context(|| format!("Invalid seek position {position:?}"))?;
+            let progress = playback.seek_to(Duration::from_secs(target)).await?;
+            let position = progress.position.unwrap_or_default();
+            if json {
+                println!("{}", serde_json::json!({ "position": position.as_secs() }));
+            } else {
+                println!("position: {}", Track::duration_formatted_short(&position));
+            }
+        }
+        CtlCommand::Volume { volume } => {
+            let current = u64::from(playback.get_progress().await?.volume);
+            let target = parse_relative(&volume, current, |v| v.parse().ok())
+                .with_context(|| format!("Invalid volume {volume:?}"))?;
+            // clamped to 100, so it will always fit
+            #[allow(clippy::cast_possible_truncation)]
+            let volume = playback.set_volume(target.min(100) as u16).await?;
+            print_value(json, "volume", volume);
+        }
+        CtlCommand::Status => {
+            let progress = playback.get_progress().await?;
+            print_status(json, progress)?;
+        }
+        CtlCommand::QueueAdd { tracks } => queue_add(&mut playback, json, tracks).await?,
+        CtlCommand::SleepTimer {
+            minutes,
+            end_of_track,
+            tracks,
+            cancel,
+        } => {
+            let kind = if let Some(minutes) = minutes {
+                Some(SleepTimerKind::Duration(Duration::from_secs(
+                    minutes.saturating_mul(60),
+                )))
+            } else if end_of_track {
+                Some(SleepTimerKind::EndOfTrack)
+            } else {
+                tracks.map(SleepTimerKind::Tracks)
+            };
+
+            let state = match kind {
+                Some(kind) => playback.set_sleep_timer(kind).await?,
+                None if cancel => playback.cancel_sleep_timer().await?,
+                None => playback.get_sleep_timer().await?,
+            };
+            print_sleep_timer(json, state);
+        }
+        CtlCommand::Equalizer { preset, off } => {
+            let state = if off || preset.is_some() {
+                playback.set_equalizer_preset(preset).await?
+            } else {
+                playback.get_equalizer().await?
+            };
+            print_equalizer(json, &state);
+        }
+        CtlCommand::OutputDevice { device, default } => {
+            let state = if default || device.is_some() {
+                playback.set_output_device(device).await?
+            } else {
+                playback.get_output_devices().await?
+            };
+            print_output_devices(json, &state);
+        }
+    }
+
+    Ok(())
+}
+
+/// Validate and append the given tracks to the playlist.
+///
+/// Valid tracks are still added if some are invalid, the errors are returned afterwards.
+async fn queue_add(playback: &mut Playback, json: bool, tracks: Vec<String>) -> Result<()> {
+    let mut resolved = Vec::with_capacity(tracks.len());
+    for track in tracks {
+        if track.starts_with("http") {
+            resolved.push(track);
+            continue;
+        }
+        let path = utils::absolute_path(std::path::Path::new(&track))
+            .with_context(|| format!("Resolving path {track:?}"))?;
+        resolved.push(path.to_string_lossy().to_string());
+    }
+
+    let (sources, errors) = Playlist::check_sources(&resolved);
+    let added = sources.len();
+
+    if !sources.is_empty() {
+        playback
+            .add_to_playlist(PlaylistAddTrack::new_append(sources))
+            .await?;
+    }
+
+    print_value(json, "added", added);
+
+    errors.context("Some tracks could not be added")?;
+
+    Ok(())
+}
+
+/// Get the lowercase name of a [`Status`], for use in output.
+fn status_str(status: Status) -> &'static str {
+    match status {
+        Status::Running => "running",
+        Status::Paused => "paused",
+        Status::Stopped => "stopped",
+    }
+}
+
+/// Print a single value, either as `key: value` or as a JSON object.
+fn print_value<T: std::fmt::Display + Into<serde_json::Value>>(json: bool, key: &str, value: T) {
+    if json {
+        let mut map = serde_json::Map::new();
+        map.insert(key.to_string(), value.into());
+        println!("{}", serde_json::Value::Object(map));
+    } else {
+        println!("{key}: {value}");
+    }
+}
+
+/// Print that a command without a result was successful.
+fn print_ok(json: bool) {
+    if json {
+        println!("{}", serde_json::json!({ "ok": true }));
+    } else {
+        println!("ok");
+    }
+}
+
+/// Print the full status of the server.
+fn print_status(json: bool, response: GetProgressResponse) -> Result<()> {
+    let status = status_str(Status::from_u32(response.status));
+    let track = response
+        .current_track
+        .map(TrackInfo::try_from)
+        .transpose()
+        .context("Converting current track")?;
+    let (position, duration) = response
+        .progress
+        .map(PlayerProgress::from)
+        .map_or((None, None), |v| (v.position, v.total_duration));
+    let speed = f64::from(response.speed) / 10.0;
+    let sleep_timer: Option<SleepTimerInfo> = response.sleep_timer.and_then(Into::into);
+
+    if json {
+        let track = track.map(|track| {
+            serde_json::json!({
+                "id": track.id.as_str(),
+                "title": track.title,
+                "artist": track.artist,
+                "album": track.album,
+                "genre": track.genre,
+                "duration": track.duration.map(|v| v.as_secs()),
+            })
+        });
+        let value = serde_json::json!({
+            "status": status,
+            "track": track,
+            "position": position.map(|v| v.as_secs()),
+            "duration": duration.map(|v| v.as_secs()),
+            "volume": response.volume,
+            "speed": speed,
+            "gapless": response.gapless,
+            "current_track_index": response.current_track_index,
+            "radio_title": (!response.radio_title.is_empty()).then_some(response.radio_title),
+            "sleep_timer": sleep_timer.map(sleep_timer_json),
+        });
+        println!("{value}");
+
+        return Ok(());
+    }
+
+    println!("status: {status}");
+    if let Some(track) = track {
+        println!("track: {}", track.id.as_str());
+        for (key, value) in [
+            ("title", track.title),
+            ("artist", track.artist),
+            ("album", track.album),
+            ("genre", track.genre),
+        ] {
+            if let Some(value) = value {
+                println!("{key}: {value}");
+            }
+        }
+    }
+    if let Some(position) = position {
+        println!("position: {}", Track::duration_formatted_short(&position));
+    }
+    if let Some(duration) = duration {
+        println!("duration: {}", Track::duration_formatted_short(&duration));
+    }
+    println!("volume: {}", response.volume);
+    println!("speed: {speed:.1}");
+    println!("gapless: {}", response.gapless);
+    println!("current_track_index: {}", response.current_track_index);
+    if !response.radio_title.is_empty() {
+        println!("radio_title: {}", response.radio_title);
+    }
+    if let Some(sleep_timer) = sleep_timer {
+        if let Some(remaining) = sleep_timer.remaining_time {
+            println!(
+                "sleep_timer: {}",
+                Track::duration_formatted_short(&remaining)
+            );
+        } else if let Some(tracks) = sleep_timer.remaining_tracks {
+            println!("sleep_timer: {tracks} tracks");
+        }
+    }
+
+    Ok(())
+}
+
+/// Convert a active sleep timer to JSON.
+fn sleep_timer_json(state: SleepTimerInfo) -> serde_json::Value {
+    serde_json::json!({
+        "remaining_time": state.remaining_time.map(|v| v.as_secs()),
+        "remaining_tracks": state.remaining_tracks,
+        "fading": state.fading,
+    })
+}
+
+/// Print the state of the sleep timer.
+fn print_sleep_timer(json: bool, state: Option<SleepTimerInfo>) {
+    if json {
+        let value = state.map(sleep_timer_json);
+        println!("{}", serde_json::json!({ "sleep_timer": value }));
+
+        return;
+    }
+
+    let Some(state) = state else {
+        println!("sleep_timer: inactive");
+        return;
+    };
+
+    println!("sleep_timer: active");
+    if let Some(remaining) = state.remaining_time {
+        println!(
+            "remaining_time: {}",
+            Track::duration_formatted_short(&remaining)
+        );
+    }
+    if let Some(tracks) = state.remaining_tracks {
+        println!("remaining_tracks: {tracks}");
+    }
+    println!("fading: {}", state.fading);
+}
+
+/// Print the state of the equalizer.
+fn print_equalizer(json: bool, state: &EqualizerInfo) {
+    if json {
+        println!(
+            "{}",
+            serde_json::json!({ "preset": state.preset, "presets": state.presets })
+        );
+
+        return;
+    }
+
+    println!("preset: {}", state.preset.as_deref().unwrap_or("off"));
+    println!("presets: {}", state.presets.join(", "));
+}
+
+/// Print the selected and all available output devices.
+fn print_output_devices(json: bool, state: &OutputDeviceInfo) {
+    if json {
+        println!(
+            "{}",
+            serde_json::json!({ "device": state.device, "devices": state.devices })
+        );
+
+        return;
+    }
+
+    println!("device: {}", state.device.as_deref().unwrap_or("default"));
+    for device in &state.devices {
+        let marker = if state.device.as_ref() == Some(device) {
+            "*"
+        } else {
+            " "
+        };
+        println!("{marker} {device}");
+    }
+}
+
+/// Parse `input` with `parse`, a leading `+` or `-` makes it relative to `current`.
+///
+/// Relative values saturate at `0`.
+fn parse_relative<F>(input: &str, current: u64, parse: F) -> Result<u64>
+where
+    F: Fn(&str) -> Option<u64>,
+{
+    let input = input.trim();
+    let parsed = if let Some(rest) = input.strip_prefix('+') {
+        parse(rest).map(|v| current.saturating_add(v))
+    } else if let Some(rest) = input.strip_prefix('-') {
+        parse(rest).map(|v| current.saturating_sub(v))
+    } else {
+        parse(input)
+    };
+
+    let Some(parsed) = parsed else {
+        bail!("Expected a number, optionally prefixed with \"+\" or \"-\"");
+    };
+
+    Ok(parsed)
+}
+
+/// Parse seconds in the form of `ss`, `mm:ss` or `hh:mm:ss`.
+fn parse_seconds(input: &str) -> Option<u64> {
+    let mut parts = 0;
+    let mut total: u64 = 0;
+    for part in input.split(':') {
+        parts += 1;
+        if parts > 3 {
+            return None;
+        }
+        total = total.checked_mul(60)?.checked_add(part.parse().ok()?)?;
+    }
+
+    Some(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_seconds() {
+        assert_eq!(parse_seconds("90"), Some(90));
+        assert_eq!(parse_seconds("1:30"), Some(90));
+        assert_eq!(parse_seconds("1:00:05"), Some(3605));
+        assert_eq!(parse_seconds("1:2:3:4"), None);
+        assert_eq!(parse_seconds("abc"), None);
+        assert_eq!(parse_seconds(""), None);
+    }
+
+    #[test]
+    fn should_parse_relative() {
+        assert_eq!(parse_relative("50", 10, parse_seconds).unwrap(), 50);
+        assert_eq!(parse_relative("+1:00", 10, parse_seconds).unwrap(), 70);
+        assert_eq!(parse_relative("-20", 10, parse_seconds).unwrap(), 0);
+        assert!(parse_relative("+", 10, parse_seconds).is_err());
+    }
+}
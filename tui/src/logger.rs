@@ -5,7 +5,7 @@ use std::backtrace::Backtrace;
 use colored::{Color, Colorize};
 use flexi_logger::{style, DeferredNow, FileSpec, Logger, LoggerHandle, Record};
 
-use crate::cli::Args;
+use crate::cli::{Action, Args};
 
 /// Function for setting up the logger
 /// This function is mainly to keep the code structured and sorted
@@ -42,7 +42,8 @@ pub fn setup(args: &Args) -> LoggerHandle {
     };
 
     // manually instead of "flexi_logger"'s "print_message", because that function is async and cannot be awaited, throwing off the rendered tui
-    if args.log_options.log_to_file {
+    // not printed for "ctl", as its output is meant to be parsed
+    if args.log_options.log_to_file && !matches!(args.action, Some(Action::Ctl(_))) {
         println!(
             "Logging to file \"{}\"",
             args.log_options.log_file.to_string_lossy()
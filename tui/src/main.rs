@@ -27,6 +27,7 @@
  * SOFTWARE.
  */
 mod cli;
+mod ctl;
 mod logger;
 mod ui;
 
@@ -38,13 +39,15 @@ use std::path::PathBuf;
 use std::time::{Duration, Instant};
 use std::{error::Error, path::Path};
 use termusiclib::config::v2::server::config_extra::ServerConfigVersionedDefaulted;
-use termusiclib::config::v2::server::ScanDepth;
+use termusiclib::config::v2::server::{ComSettings, ScanDepth};
 use termusiclib::config::v2::tui::config_extra::TuiConfigVersionedDefaulted;
 use termusiclib::config::{
     new_shared_server_settings, new_shared_tui_settings, ServerOverlay, SharedServerSettings,
     SharedTuiSettings, TuiOverlay,
 };
+use termusiclib::player::auth::{AuthToken, AuthedClient};
 use termusiclib::player::music_player_client::MusicPlayerClient;
+use tonic::transport::{Endpoint, Uri};
 
 use sysinfo::{Pid, ProcessStatus, System};
 use termusiclib::{podcast, utils};
@@ -82,7 +85,7 @@ async fn actual_main() -> Result<()> {
     let config = get_config(&args)?;
 
     if let Some(action) = args.action {
-        return execute_action(action, &config);
+        return execute_action(action, &config).await;
     }
 
     // launch the daemon if it isn't already
@@ -155,14 +158,9 @@ async fn actual_main() -> Result<()> {
     info!("Waiting until connected");
 
     let client = {
-        let addr = {
-            let config_read = config.tui.read();
-            SocketAddr::from(*config_read.settings.get_com().ok_or(anyhow::anyhow!(
-                "Expected tui-com settings to be resolved at this point"
-            ))?)
-        };
+        let com = get_com(&config)?;
 
-        wait_till_connected(addr, pid).await?
+        wait_till_connected(&com, pid).await?
     };
     info!("Connected!");
 
@@ -172,6 +170,65 @@ async fn actual_main() -> Result<()> {
     Ok(())
 }
 
+/// Get the settings of where the server is expected to listen on
+fn get_com(config: &CombinedSettings) -> Result<ComSettings> {
+    let config_read = config.tui.read();
+    config_read
+        .settings
+        .get_com()
+        .cloned()
+        .context("Expected tui-com settings to be resolved at this point")
+}
+
+/// Get a human readable description of where the server is expected to listen on
+fn describe_com(com: &ComSettings) -> String {
+    if let Some(path) = &com.socket_path {
+        return format!("unix:{}", path.display());
+    }
+
+    SocketAddr::from(com).to_string()
+}
+
+/// Connect to the server as configured in `com`, either via TCP or a unix socket.
+///
+/// The configured auth token will be sent with every request.
+async fn connect(com: &ComSettings) -> Result<AuthedClient> {
+    let auth = AuthToken::new(com.auth_token.as_deref())?;
+
+    let channel = if let Some(path) = com.socket_path.clone() {
+        // the uri is required by tonic, but is ignored by the connector below
+        Endpoint::from_static("http://[::1]:50101")
+            .connect_with_connector(tower::service_fn(move |_: Uri| connect_unix(path.clone())))
+            .await?
+    } else {
+        let addr = SocketAddr::from(com);
+        Endpoint::from_shared(format!("http://{addr}"))?
+            .connect()
+            .await?
+    };
+
+    Ok(MusicPlayerClient::with_interceptor(channel, auth))
+}
+
+#[cfg(unix)]
+async fn connect_unix(
+    path: PathBuf,
+) -> std::io::Result<hyper_util::rt::TokioIo<tokio::net::UnixStream>> {
+    Ok(hyper_util::rt::TokioIo::new(
+        tokio::net::UnixStream::connect(path).await?,
+    ))
+}
+
+#[cfg(not(unix))]
+async fn connect_unix(
+    _path: PathBuf,
+) -> std::io::Result<hyper_util::rt::TokioIo<tokio::net::TcpStream>> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "Unix sockets are not supported on this platform",
+    ))
+}
+
 /// Timeout to give up connecting
 const WAIT_TIMEOUT: Duration = Duration::from_secs(5);
 /// Time to sleep
@@ -181,10 +238,7 @@ const WAIT_INTERVAL: Duration = Duration::from_millis(100);
 /// - tonic errors anything other than `ConnectionRefused`
 /// - given PID does not exist anymore
 /// - timeout of [`WAIT_TIMEOUT`] reached
-async fn wait_till_connected(
-    socket: SocketAddr,
-    pid: u32,
-) -> Result<MusicPlayerClient<tonic::transport::Channel>> {
+async fn wait_till_connected(com: &ComSettings, pid: u32) -> Result<AuthedClient> {
     let mut sys = sysinfo::System::new();
     let sys_pid = Pid::from_u32(pid);
     let start_time = Instant::now();
@@ -205,11 +259,15 @@ async fn wait_till_connected(
             anyhow::bail!("Process {pid} exited before being able to connect!");
         }
 
-        match MusicPlayerClient::connect(format!("http://{socket}")).await {
+        match connect(com).await {
             Err(err) => {
                 // downcast "tonic::transport::Error" to a "std::io::Error"(kind: Os)
-                if let Some(os_err) = find_source::<std::io::Error>(&err) {
-                    if os_err.kind() == std::io::ErrorKind::ConnectionRefused {
+                if let Some(os_err) = find_source::<std::io::Error>(err.as_ref()) {
+                    // "NotFound" is returned if the unix socket does not exist yet
+                    if matches!(
+                        os_err.kind(),
+                        std::io::ErrorKind::ConnectionRefused | std::io::ErrorKind::NotFound
+                    ) {
                         debug!("Connection refused found!");
                         tokio::time::sleep(WAIT_INTERVAL).await;
                         continue;
@@ -253,6 +311,7 @@ fn get_config(args: &cli::Args) -> Result<CombinedSettings> {
         music_dir_overwrite: music_dir,
         disable_discord_status: args.disable_discord,
         library_scan_depth: max_depth,
+        output: None,
     };
 
     let config_tui = TuiConfigVersionedDefaulted::from_config_path()?.into_settings();
@@ -290,7 +349,7 @@ fn get_path(dir: &Path) -> Result<PathBuf> {
     bail!("Error: non-existing directory '{}'", dir.display());
 }
 
-fn execute_action(action: cli::Action, config: &CombinedSettings) -> Result<()> {
+async fn execute_action(action: cli::Action, config: &CombinedSettings) -> Result<()> {
     match action {
         cli::Action::Import { file } => {
             println!("need to import from file {}", file.display());
@@ -313,6 +372,8 @@ fn execute_action(action: cli::Action, config: &CombinedSettings) -> Result<()>
                 utils::get_app_config_path().context("getting app-config-path")?;
             podcast::export_to_opml(&config_dir_path, &path).context("export opml")?;
         }
+        // "ctl" only talks to a already running server, it should never start the tui or a server
+        cli::Action::Ctl(ctl_args) => ctl::run(ctl_args, config).await?,
     };
 
     Ok(())
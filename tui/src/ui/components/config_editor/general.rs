@@ -24,6 +24,7 @@
 use crate::ui::{ConfigEditorMsg, Msg};
 use crate::CombinedSettings;
 
+use termusiclib::config::v2::server::equalizer::{EqualizerBand, EqualizerSettings};
 use termusiclib::config::v2::tui::{keys::Keys, Alignment as XywhAlign};
 use termusiclib::config::SharedTuiSettings;
 use tui_realm_stdlib::{Input, Radio};
@@ -867,3 +868,110 @@ impl Component<Msg, NoUserEvent> for PlayerPort {
         )
     }
 }
+
+#[derive(MockComponent)]
+pub struct EqualizerPreset {
+    component: Radio,
+    config: SharedTuiSettings,
+}
+
+impl EqualizerPreset {
+    pub fn new(config: SharedTuiSettings, equalizer: &EqualizerSettings) -> Self {
+        let config_r = config.read();
+        let mut choices = vec![String::from("off")];
+        choices.extend(equalizer.preset_names());
+        // index 0 is "off"
+        let value = equalizer
+            .preset
+            .as_ref()
+            .and_then(|preset| equalizer.presets.iter().position(|v| &v.name == preset))
+            .map_or(0, |v| v + 1);
+        let component = Radio::default()
+            .borders(
+                Borders::default()
+                    .color(config_r.settings.theme.library_border())
+                    .modifiers(BorderType::Rounded),
+            )
+            .choices(&choices)
+            .foreground(config_r.settings.theme.library_highlight())
+            .rewind(true)
+            .title(" Equalizer preset: ", Alignment::Left)
+            .value(value);
+
+        drop(config_r);
+        Self { component, config }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for EqualizerPreset {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        // the bands field needs to be updated to show the new preset
+        match ev {
+            Event::Keyboard(KeyEvent {
+                code: Key::Left, ..
+            }) => {
+                self.perform(Cmd::Move(Direction::Left));
+                Some(Msg::ConfigEditor(ConfigEditorMsg::EqualizerPresetChanged))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Right, ..
+            }) => {
+                self.perform(Cmd::Move(Direction::Right));
+                Some(Msg::ConfigEditor(ConfigEditorMsg::EqualizerPresetChanged))
+            }
+            ev => handle_radio_ev(
+                &mut self.component,
+                ev,
+                &self.config.read().settings.keys,
+                Msg::ConfigEditor(ConfigEditorMsg::EqualizerPresetBlurDown),
+                Msg::ConfigEditor(ConfigEditorMsg::EqualizerPresetBlurUp),
+            ),
+        }
+    }
+}
+
+#[derive(MockComponent)]
+pub struct EqualizerBands {
+    component: Input,
+    config: SharedTuiSettings,
+}
+
+impl EqualizerBands {
+    /// Create the band editor for the given bands, [`None`] if no preset is selected
+    pub fn new(config: SharedTuiSettings, bands: Option<&[EqualizerBand]>) -> Self {
+        let config_r = config.read();
+        let component = Input::default()
+            .borders(
+                Borders::default()
+                    .color(config_r.settings.theme.library_border())
+                    .modifiers(BorderType::Rounded),
+            )
+            .foreground(config_r.settings.theme.library_highlight())
+            .input_type(InputType::Text)
+            .placeholder(
+                if bands.is_some() {
+                    "60:3 1000:-2"
+                } else {
+                    "select a preset to edit its bands"
+                },
+                Style::default().fg(Color::Rgb(128, 128, 128)),
+            )
+            .title(" Equalizer bands (frequency:gain[:q]): ", Alignment::Left)
+            .value(bands.map(EqualizerBand::format_list).unwrap_or_default());
+
+        drop(config_r);
+        Self { component, config }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for EqualizerBands {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        handle_input_ev(
+            &mut self.component,
+            ev,
+            &self.config.read().settings.keys,
+            Msg::ConfigEditor(ConfigEditorMsg::EqualizerBandsBlurDown),
+            Msg::ConfigEditor(ConfigEditorMsg::EqualizerBandsBlurUp),
+        )
+    }
+}
@@ -32,6 +32,7 @@ use termusiclib::config::v2::tui::theme::ThemeColors;
 use termusiclib::types::{ConfigEditorMsg, Id, IdConfigEditor, IdKey, KFMsg, Msg};
 use termusiclib::utils::get_app_config_path;
 use termusicplayback::PlayerCmd;
+use tuirealm::{State, StateValue};
 
 impl Model {
     #[allow(clippy::too_many_lines)]
@@ -40,6 +41,8 @@ impl Model {
             ConfigEditorMsg::Open => {
                 self.config_editor.theme = self.config_tui.read().settings.theme.clone();
                 self.config_editor.key_config = self.config_tui.read().settings.keys.clone();
+                self.config_editor.equalizer =
+                    self.config_server.read().settings.player.equalizer.clone();
                 self.mount_config_editor();
             }
             ConfigEditorMsg::CloseCancel => {
@@ -57,7 +60,7 @@ impl Model {
             ConfigEditorMsg::ChangeLayout => self.action_change_layout(),
             ConfigEditorMsg::ConfigChanged => self.config_editor.config_changed = true,
             // Handle focus of general page
-            ConfigEditorMsg::PlayerPortBlurDown | ConfigEditorMsg::ExitConfirmationBlurUp => {
+            ConfigEditorMsg::EqualizerBandsBlurDown | ConfigEditorMsg::ExitConfirmationBlurUp => {
                 self.app
                     .active(&Id::ConfigEditor(IdConfigEditor::MusicDir))
                     .ok();
@@ -139,11 +142,43 @@ impl Model {
                     .ok();
             }
 
-            ConfigEditorMsg::PlayerUseDiscordBlurDown | ConfigEditorMsg::MusicDirBlurUp => {
+            ConfigEditorMsg::PlayerUseDiscordBlurDown | ConfigEditorMsg::EqualizerPresetBlurUp => {
                 self.app
                     .active(&Id::ConfigEditor(IdConfigEditor::PlayerPort))
                     .ok();
             }
+
+            ConfigEditorMsg::PlayerPortBlurDown | ConfigEditorMsg::EqualizerBandsBlurUp => {
+                self.app
+                    .active(&Id::ConfigEditor(IdConfigEditor::EqualizerPreset))
+                    .ok();
+            }
+
+            ConfigEditorMsg::EqualizerPresetBlurDown | ConfigEditorMsg::MusicDirBlurUp => {
+                self.app
+                    .active(&Id::ConfigEditor(IdConfigEditor::EqualizerBands))
+                    .ok();
+            }
+
+            ConfigEditorMsg::EqualizerPresetChanged => {
+                // keep the edits of the previous preset
+                if let Err(err) = self.collect_config_equalizer_bands() {
+                    self.mount_error_popup(err);
+                }
+                if let Ok(State::One(StateValue::Usize(index))) = self
+                    .app
+                    .state(&Id::ConfigEditor(IdConfigEditor::EqualizerPreset))
+                {
+                    let equalizer = &mut self.config_editor.equalizer;
+                    // index 0 is "off"
+                    equalizer.preset = index
+                        .checked_sub(1)
+                        .and_then(|idx| equalizer.presets.get(idx))
+                        .map(|v| v.name.clone());
+                }
+                self.remount_config_equalizer_bands();
+                self.config_editor.config_changed = true;
+            }
             ConfigEditorMsg::ConfigSaveOk => {
                 self.app
                     .umount(&Id::ConfigEditor(IdConfigEditor::ConfigSavePopup))
@@ -28,11 +28,13 @@ use crate::ui::components::{
     ConfigPodcastMarkAllPlayed, ConfigPodcastMarkPlayed, ConfigPodcastRefreshAllFeeds,
     ConfigPodcastRefreshFeed, ConfigPodcastSearchAddFeed, ConfigProgressBackground,
     ConfigProgressBorder, ConfigProgressForeground, ConfigProgressTitle, ConfigSavePopup,
-    ConfigSeekStep, ExitConfirmation, GlobalListener, KillDaemon, MusicDir, PlayerPort,
-    PlayerUseDiscord, PlayerUseMpris, PlaylistDisplaySymbol, PlaylistRandomAlbum,
-    PlaylistRandomTrack, PodcastDir, PodcastMaxRetries, PodcastSimulDownload, SaveLastPosition,
+    ConfigSeekStep, EqualizerBands, EqualizerPreset, ExitConfirmation, GlobalListener, KillDaemon,
+    MusicDir, PlayerPort, PlayerUseDiscord, PlayerUseMpris, PlaylistDisplaySymbol,
+    PlaylistRandomAlbum, PlaylistRandomTrack, PodcastDir, PodcastMaxRetries, PodcastSimulDownload,
+    SaveLastPosition,
 };
 use include_dir::DirEntry;
+use termusiclib::config::v2::server::equalizer::EqualizerBand;
 use termusiclib::config::v2::server::{PositionYesNo, PositionYesNoLower, RememberLastPosition};
 use termusiclib::config::SharedTuiSettings;
 /**
@@ -222,6 +224,16 @@ impl Model {
                     f,
                     chunks_middle_right[6],
                 );
+                self.app.view(
+                    &Id::ConfigEditor(IdConfigEditor::EqualizerPreset),
+                    f,
+                    chunks_middle_right[7],
+                );
+                self.app.view(
+                    &Id::ConfigEditor(IdConfigEditor::EqualizerBands),
+                    f,
+                    chunks_middle_right[8],
+                );
 
                 self.app
                     .view(&Id::ConfigEditor(IdConfigEditor::Footer), f, chunks_main[2]);
@@ -1889,6 +1901,19 @@ impl Model {
                 vec![]
             )
             .is_ok());
+
+        assert!(self
+            .app
+            .remount(
+                Id::ConfigEditor(IdConfigEditor::EqualizerPreset),
+                Box::new(EqualizerPreset::new(
+                    self.config_tui.clone(),
+                    &self.config_editor.equalizer
+                )),
+                vec![]
+            )
+            .is_ok());
+        self.remount_config_equalizer_bands();
         let config = self.config_tui.clone();
         self.remount_config_color(&config, None);
 
@@ -2805,6 +2830,16 @@ impl Model {
             .umount(&Id::ConfigEditor(IdConfigEditor::PlayerPort))
             .is_ok());
 
+        assert!(self
+            .app
+            .umount(&Id::ConfigEditor(IdConfigEditor::EqualizerPreset))
+            .is_ok());
+
+        assert!(self
+            .app
+            .umount(&Id::ConfigEditor(IdConfigEditor::EqualizerBands))
+            .is_ok());
+
         assert!(self
             .app
             .umount(&Id::ConfigEditor(IdConfigEditor::CEThemeSelect))
@@ -3320,6 +3355,8 @@ impl Model {
 
     #[allow(clippy::too_many_lines)]
     pub fn collect_config_data(&mut self) -> Result<()> {
+        self.collect_config_equalizer_bands()?;
+
         let mut config_tui = self.config_tui.write();
         match self.config_editor.key_config.check_keys() {
             Ok(()) => config_tui.settings.keys = self.config_editor.key_config.clone(),
@@ -3496,6 +3533,52 @@ impl Model {
                 }
             }
         }
+
+        config_server.settings.player.equalizer = self.config_editor.equalizer.clone();
+
+        Ok(())
+    }
+
+    /// Remount the equalizer band editor with the bands of the currently selected preset
+    pub fn remount_config_equalizer_bands(&mut self) {
+        let equalizer = &self.config_editor.equalizer;
+        let bands = equalizer
+            .preset
+            .as_deref()
+            .and_then(|name| equalizer.get_preset(name))
+            .map(|v| v.bands.as_slice());
+
+        assert!(self
+            .app
+            .remount(
+                Id::ConfigEditor(IdConfigEditor::EqualizerBands),
+                Box::new(EqualizerBands::new(self.config_tui.clone(), bands)),
+                vec![]
+            )
+            .is_ok());
+    }
+
+    /// Store the bands from the band editor into the currently selected preset
+    pub fn collect_config_equalizer_bands(&mut self) -> Result<()> {
+        let Ok(State::One(StateValue::String(bands))) = self
+            .app
+            .state(&Id::ConfigEditor(IdConfigEditor::EqualizerBands))
+        else {
+            return Ok(());
+        };
+        let equalizer = &mut self.config_editor.equalizer;
+        let Some(name) = equalizer.preset.as_deref() else {
+            return Ok(());
+        };
+        let Some(preset) = equalizer.presets.iter_mut().find(|v| v.name == name) else {
+            return Ok(());
+        };
+
+        match EqualizerBand::parse_list(&bands) {
+            Ok(bands) => preset.bands = bands,
+            Err(err) => bail!(" Equalizer preset {:?}: {err:#} ", preset.name),
+        }
+
         Ok(())
     }
 
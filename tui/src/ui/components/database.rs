@@ -1,3 +1,4 @@
+use crate::ui::model::rating_stars;
 use crate::ui::Model;
 use std::path::Path;
 use termusiclib::config::SharedTuiSettings;
@@ -52,6 +53,14 @@ impl DBListCriteria {
                         .add_col(TextSpan::from("Directory"))
                         .add_row()
                         .add_col(TextSpan::from("Playlists"))
+                        .add_row()
+                        .add_col(TextSpan::from("Most Played"))
+                        .add_row()
+                        .add_col(TextSpan::from("Recently Played"))
+                        .add_row()
+                        .add_col(TextSpan::from("Never Played"))
+                        .add_row()
+                        .add_col(TextSpan::from("Favourites"))
                         .build(),
                 )
         };
@@ -412,6 +421,21 @@ impl Component<Msg, NoUserEvent> for DBListSearchTracks {
             Event::Keyboard(keyevent) if keyevent == keys.database_keys.add_all.get() => {
                 return Some(Msg::DataBase(DBMsg::AddAllToPlaylist))
             }
+            Event::Keyboard(keyevent) if keyevent == keys.library_keys.open_tag_editor.get() => {
+                return Some(Msg::DataBase(DBMsg::OpenTagEditor))
+            }
+            Event::Keyboard(keyevent) if keyevent == keys.database_keys.rating_up.get() => {
+                if let State::One(StateValue::Usize(index)) = self.state() {
+                    return Some(Msg::DataBase(DBMsg::RatingUp(index)));
+                }
+                CmdResult::None
+            }
+            Event::Keyboard(keyevent) if keyevent == keys.database_keys.rating_down.get() => {
+                if let State::One(StateValue::Usize(index)) = self.state() {
+                    return Some(Msg::DataBase(DBMsg::RatingDown(index)));
+                }
+                CmdResult::None
+            }
 
             Event::Keyboard(keyevent) if keyevent == keys.library_keys.search.get() => {
                 return Some(Msg::GeneralSearch(crate::ui::GSMsg::PopupShowDatabase))
@@ -444,6 +468,15 @@ impl Model {
                     record.title.clone()
                 }
             };
+            let name = match self.dw.criteria {
+                SearchCriteria::MostPlayed => format!("{name} ({} plays)", record.play_count),
+                SearchCriteria::RecentlyPlayed => match record.last_played_formatted() {
+                    Some(date) => format!("{name} ({date})"),
+                    None => name,
+                },
+                SearchCriteria::Favourites => format!("{name} {}", rating_stars(record.rating)),
+                _ => name,
+            };
 
             table
                 .add_col(TextSpan::from(format!("{}", idx + 1)))
@@ -535,6 +568,11 @@ impl Model {
         }
         self.database_sync_results();
         self.app.active(&Id::DBListSearchResult).ok();
+
+        // statistics only have one result, so directly show the tracks
+        if self.dw.criteria.is_statistic() {
+            self.database_update_search_tracks(0);
+        }
     }
 
     fn database_get_playlist(&self) -> Vec<String> {
@@ -1,7 +1,11 @@
+use crate::ui::utils::copy_to_clipboard;
 use crate::ui::{model::TermusicLayout, Model};
 use termusiclib::library_db::const_unknown::{UNKNOWN_ARTIST, UNKNOWN_TITLE};
+use termusiclib::player::radio_history::{
+    heard_on_radio_path, save_heard_on_radio, RadioHistoryInfo, RadioTitleInfo,
+};
 use termusiclib::podcast::episode::Episode;
-use termusiclib::track::MediaType;
+use termusiclib::track::{MediaType, Track};
 use termusiclib::types::{Id, LyricMsg, Msg};
 
 use anyhow::{anyhow, Result};
@@ -28,6 +32,9 @@ lazy_static! {
     static ref RE_MULT_LINE_BREAKS: Regex = Regex::new(r"((\r\n)|\r|\n){3,}").expect("Regex error");
 }
 
+/// The amount of lines before the first title of the radio history
+const RADIO_HISTORY_HEADER_LINES: usize = 3;
+
 #[derive(MockComponent)]
 pub struct Lyric {
     component: Textarea,
@@ -111,6 +118,17 @@ impl Component<Msg, NoUserEvent> for Lyric {
             Event::Keyboard(key) if key == keys.navigation_keys.goto_bottom.get() => {
                 self.perform(Cmd::GoTo(Position::End))
             }
+
+            Event::Keyboard(key) if key == keys.radio_history_keys.copy_title.get() => {
+                return Some(Msg::LyricMessage(LyricMsg::RadioTitleCopy(
+                    self.component.states.list_index,
+                )))
+            }
+            Event::Keyboard(key) if key == keys.radio_history_keys.save_title.get() => {
+                return Some(Msg::LyricMessage(LyricMsg::RadioTitleSave(
+                    self.component.states.list_index,
+                )))
+            }
             _ => return None,
         };
         // "Textarea::perform" currently always returns "CmdResult::None", so always redraw on event
@@ -130,6 +148,10 @@ impl Model {
             )
             .is_ok());
         self.lyric_update_title();
+        if let Some(history) = self.radio_history.take() {
+            self.lyric_update_for_radio(history);
+            return;
+        }
         let lyric_line = self.lyric_line.clone();
         self.lyric_set_lyric(&lyric_line);
     }
@@ -247,6 +269,7 @@ impl Model {
                 AttrValue::Payload(PropPayload::Vec(final_vec)),
             )
             .ok();
+        self.radio_history = None;
     }
 
     pub fn lyric_update(&mut self) {
@@ -283,21 +306,109 @@ impl Model {
         }
     }
 
-    pub fn lyric_update_for_radio<T: AsRef<str>>(&mut self, radio_title: T) {
-        if let Some(song) = self.playlist.current_track() {
-            if MediaType::LiveRadio == song.media_type {
-                let radio_title = radio_title.as_ref();
-                if radio_title.is_empty() {
-                    return;
-                }
-                self.lyric_set_lyric(format!("Currently Playing: {radio_title}"));
-            }
+    /// Check if the shown radio history is not for the current station and `radio_title` anymore
+    pub fn lyric_radio_history_outdated(&self, radio_title: &str) -> bool {
+        let radio_title = radio_title.trim();
+        if radio_title.is_empty() {
+            return false;
+        }
+        let Some(url) = self
+            .playlist
+            .current_track()
+            .filter(|track| MediaType::LiveRadio == track.media_type)
+            .and_then(Track::file)
+        else {
+            return false;
+        };
+
+        !self.radio_history.as_ref().is_some_and(|history| {
+            history.url == url
+                && history
+                    .titles
+                    .first()
+                    .is_some_and(|current| current.title == radio_title)
+        })
+    }
+
+    /// Show the current radio title and all titles heard before on the station
+    pub fn lyric_update_for_radio(&mut self, history: RadioHistoryInfo) {
+        let Some(current) = history.titles.first() else {
+            return;
+        };
+        let current_line = format!("Currently Playing: {}", current.title);
+
+        let mut lines = Vec::with_capacity(RADIO_HISTORY_HEADER_LINES + history.titles.len());
+        lines.push(PropValue::TextSpan(TextSpan::from(&current_line).bold()));
+        lines.push(PropValue::TextSpan(TextSpan::from("   ")));
+        lines.push(PropValue::TextSpan(
+            TextSpan::from("Heard on this station:").bold(),
+        ));
+        lines.extend(history.titles.iter().map(|title| {
+            PropValue::TextSpan(TextSpan::from(format!(
+                "{}  {}",
+                title.time_formatted(),
+                title.title
+            )))
+        }));
+
+        self.app
+            .attr(
+                &Id::Lyric,
+                Attribute::Text,
+                AttrValue::Payload(PropPayload::Vec(lines)),
+            )
+            .ok();
+        self.lyric_line = current_line;
+        self.radio_history = Some(history);
+    }
+
+    /// Get the radio title shown at `line` of the lyric view, the first line is the current title
+    fn lyric_radio_title_at(&self, line: usize) -> Option<&RadioTitleInfo> {
+        let index = if line == 0 {
+            0
+        } else {
+            line.checked_sub(RADIO_HISTORY_HEADER_LINES)?
+        };
+
+        self.radio_history.as_ref()?.titles.get(index)
+    }
+
+    /// Copy the radio title at `line` to the clipboard
+    pub fn lyric_radio_title_copy(&mut self, line: usize) {
+        let Some(title) = self.lyric_radio_title_at(line).map(|v| v.title.clone()) else {
+            return;
+        };
+
+        match copy_to_clipboard(&title) {
+            Ok(()) => self.update_show_message_timeout("Copied to clipboard", &title, None),
+            Err(err) => self.mount_error_popup(anyhow!(err).context("copy radio title")),
+        }
+    }
+
+    /// Save the radio title at `line` to the "heard on radio" list
+    pub fn lyric_radio_title_save(&mut self, line: usize) {
+        let Some(history) = &self.radio_history else {
+            return;
+        };
+        let Some(title) = self.lyric_radio_title_at(line) else {
+            return;
+        };
+
+        let res = heard_on_radio_path()
+            .and_then(|path| save_heard_on_radio(&path, title, &history.url).map(|()| path));
+        match res {
+            Ok(path) => self.update_show_message_timeout(
+                "Saved to heard on radio",
+                &format!("{} ({})", title.title, path.display()),
+                None,
+            ),
+            Err(err) => self.mount_error_popup(err.context("save radio title")),
         }
     }
 
     fn lyric_set_lyric<T: Into<String>>(&mut self, text: T) {
         let text = text.into();
-        if self.lyric_line == *text {
+        if self.lyric_line == *text && self.radio_history.is_none() {
             return;
         }
         self.app
@@ -310,6 +421,7 @@ impl Model {
             )
             .ok();
         self.lyric_line = text;
+        self.radio_history = None;
     }
 
     pub fn lyric_cycle(&mut self) {
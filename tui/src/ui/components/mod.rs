@@ -32,6 +32,7 @@ mod playlist;
 mod podcast;
 mod popups;
 mod progress;
+mod radio_stations;
 #[allow(
     clippy::match_bool,
     clippy::redundant_closure_for_method_calls,
@@ -53,6 +54,7 @@ pub use playlist::Playlist;
 pub use podcast::{EpisodeList, FeedsList};
 pub use popups::general_search::{GSInputPopup, GSTablePopup, Source};
 pub use progress::Progress;
+pub use radio_stations::RadioStationList;
 pub use tag_editor::*;
 use termusiclib::config::v2::tui::keys::Keys;
 use termusiclib::config::SharedTuiSettings;
@@ -152,6 +154,10 @@ impl Component<Msg, NoUserEvent> for GlobalListener {
                 Some(Msg::LayoutPodCast)
             }
 
+            Event::Keyboard(keyevent) if keyevent == keys.select_view_keys.view_radio.get() => {
+                Some(Msg::LayoutRadio)
+            }
+
             Event::Keyboard(keyevent) if keyevent == keys.player_keys.toggle_prefetch.get() => {
                 Some(Msg::PlayerToggleGapless)
             }
@@ -290,6 +296,10 @@ impl Model {
                 SubEventClause::Keyboard(keys.select_view_keys.view_podcasts.get()),
                 Self::no_popup_mounted_clause(),
             ),
+            Sub::new(
+                SubEventClause::Keyboard(keys.select_view_keys.view_radio.get()),
+                Self::no_popup_mounted_clause(),
+            ),
             Sub::new(
                 SubEventClause::Keyboard(keys.move_cover_art_keys.move_left.get()),
                 SubClause::Always,
@@ -332,7 +342,13 @@ impl Model {
             Box::new(SubClause::IsMounted(Id::FeedDeleteConfirmRadioPopup)),
             Box::new(SubClause::Or(
                 Box::new(SubClause::IsMounted(Id::FeedDeleteConfirmInputPopup)),
-                Box::new(SubClause::IsMounted(Id::PodcastSearchTablePopup)),
+                Box::new(SubClause::Or(
+                    Box::new(SubClause::IsMounted(Id::PodcastSearchTablePopup)),
+                    Box::new(SubClause::Or(
+                        Box::new(SubClause::IsMounted(Id::RadioStationEditPopup)),
+                        Box::new(SubClause::IsMounted(Id::RadioStationDeleteConfirmPopup)),
+                    )),
+                )),
             )),
         )))
     }
@@ -377,8 +393,18 @@ impl Model {
                                                             Box::new(SubClause::IsMounted(
                                                                 Id::SavePlaylistConfirm,
                                                             )),
-                                                            Box::new(SubClause::IsMounted(
-                                                                Id::PodcastAddPopup,
+                                                            Box::new(SubClause::Or(
+                                                                Box::new(SubClause::IsMounted(
+                                                                    Id::PodcastAddPopup,
+                                                                )),
+                                                                Box::new(SubClause::Or(
+                                                                    Box::new(SubClause::IsMounted(
+                                                                        Id::RadioSearchInputPopup,
+                                                                    )),
+                                                                    Box::new(SubClause::IsMounted(
+                                                                        Id::RadioSearchTablePopup,
+                                                                    )),
+                                                                )),
                                                             )),
                                                         )),
                                                     )),
@@ -4,15 +4,20 @@ use anyhow::{anyhow, bail, Result};
 use rand::seq::SliceRandom;
 use std::borrow::Cow;
 use std::ffi::OsString;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use termusiclib::config::SharedTuiSettings;
 use termusiclib::library_db::const_unknown::{UNKNOWN_ALBUM, UNKNOWN_ARTIST};
 use termusiclib::library_db::SearchCriteria;
 use termusiclib::library_db::TrackDB;
+use termusiclib::player::playlist_helpers::{
+    PlaylistAddTrack, PlaylistPlaySpecific, PlaylistRemoveTrackIndexed, PlaylistSwapTrack,
+    PlaylistTrackSource,
+};
 use termusiclib::track::Track;
-use termusiclib::types::{GSMsg, Id, Msg, PLMsg};
+use termusiclib::types::{GSMsg, Id, Msg, PLMsg, RSMsg};
 use termusiclib::utils::{filetype_supported, get_parent_folder, is_playlist, playlist_get_vec};
-use termusicplayback::PlayerCmd;
+use termusicplayback::playlist::PlaylistAddErrorVec;
+use termusicplayback::{PlayerCmd, PlayerCmdReply};
 
 use tui_realm_stdlib::Table;
 use tuirealm::props::Borders;
@@ -177,6 +182,21 @@ impl Component<Msg, NoUserEvent> for Playlist {
             Event::Keyboard(key) if key == keys.playlist_keys.add_random_songs.get() => {
                 return Some(Msg::Playlist(PLMsg::AddRandomTracks));
             }
+            Event::Keyboard(key) if key == keys.playlist_keys.search_radio.get() => {
+                return Some(Msg::RadioSearch(RSMsg::InputPopupShow));
+            }
+            Event::Keyboard(key) if key == keys.playlist_keys.rating_up.get() => {
+                if let State::One(StateValue::Usize(index)) = self.state() {
+                    return Some(Msg::Playlist(PLMsg::RatingUp(index)));
+                }
+                CmdResult::None
+            }
+            Event::Keyboard(key) if key == keys.playlist_keys.rating_down.get() => {
+                if let State::One(StateValue::Usize(index)) = self.state() {
+                    return Some(Msg::Playlist(PLMsg::RatingDown(index)));
+                }
+                CmdResult::None
+            }
             _ => CmdResult::None,
         };
         match cmd_result {
@@ -259,15 +279,29 @@ impl Model {
         self.playlist_sync();
     }
 
+    /// Check the given Paths/Urls and request the server to append all valid ones to the playlist.
+    ///
+    /// # Errors
+    /// - When any of the items are invalid, all valid items are still added
+    fn playlist_add_sources<T: AsRef<str>>(
+        &mut self,
+        vec: &[T],
+    ) -> Result<(), PlaylistAddErrorVec> {
+        let (sources, res) = termusicplayback::Playlist::check_sources(vec);
+        if !sources.is_empty() {
+            self.command(&PlayerCmd::PlaylistAddTrack(
+                PlaylistAddTrack::new_append(sources),
+                PlayerCmdReply::default(),
+            ));
+        }
+
+        res
+    }
+
     /// Add a playlist (like m3u) to the playlist.
     fn playlist_add_playlist(&mut self, current_node: &str) -> Result<()> {
         let vec = playlist_get_vec(current_node)?;
-        // "add_playlist" will still add all possible things, but return errors for which did not work
-        // so sync still needs to happen
-        let res = self.playlist.add_playlist(&vec);
-        self.player_sync_playlist()?;
-        self.playlist_sync();
-        res?;
+        self.playlist_add_sources(&vec)?;
         Ok(())
     }
 
@@ -285,9 +319,11 @@ impl Model {
             .episodes
             .get(episode_index)
             .ok_or_else(|| anyhow!("get episode selected failed."))?;
-        self.playlist.add_episode(episode_selected);
-        self.player_sync_playlist()?;
-        self.playlist_sync();
+        let source = PlaylistTrackSource::PodcastUrl(episode_selected.url.clone());
+        self.command(&PlayerCmd::PlaylistAddTrack(
+            PlaylistAddTrack::new_append(vec![source]),
+            PlayerCmdReply::default(),
+        ));
         Ok(())
     }
 
@@ -301,16 +337,10 @@ impl Model {
         }
         if p.is_dir() {
             let new_items_vec = Self::library_dir_children(p);
-            // "add_playlist" will still add all possible things, but return errors for which did not work
-            // so sync still needs to happen
-            let res = self.playlist.add_playlist(&new_items_vec);
-            self.player_sync_playlist()?;
-            self.playlist_sync();
-            res?;
+            self.playlist_add_sources(&new_items_vec)?;
             return Ok(());
         }
         self.playlist_add_item(current_node)?;
-        self.playlist_sync();
         Ok(())
     }
 
@@ -320,22 +350,14 @@ impl Model {
             self.playlist_add_playlist(current_node)?;
             return Ok(());
         }
-        self.playlist.add_track(&current_node)?;
-        self.player_sync_playlist()?;
+        self.playlist_add_sources(&[current_node])?;
         Ok(())
     }
 
     /// Add [`TrackDB`] to the playlist
     pub fn playlist_add_all_from_db(&mut self, vec: &[TrackDB]) {
         let vec2: Vec<&str> = vec.iter().map(|f| f.file.as_str()).collect();
-        // "add_playlist" will still add all possible things, but return errors for which did not work
-        // so sync still needs to happen
-        let res = self.playlist.add_playlist(&vec2);
-        if let Err(e) = self.player_sync_playlist() {
-            self.mount_error_popup(e.context("player sync playlist"));
-        }
-        self.playlist_sync();
-        if let Err(e) = res {
+        if let Err(e) = self.playlist_add_sources(&vec2) {
             self.mount_error_popup(anyhow!(e).context("add all to playlist from database"));
         }
     }
@@ -477,38 +499,100 @@ impl Model {
     }
 
     pub fn playlist_delete_item(&mut self, index: usize) {
-        if self.playlist.is_empty() {
+        let Some(id) = self
+            .playlist
+            .tracks()
+            .get(index)
+            .map(Track::as_track_source)
+        else {
             return;
-        }
-        self.playlist.remove(index);
-        if let Err(e) = self.player_sync_playlist() {
-            self.mount_error_popup(e.context("player sync playlist"));
-        }
-        self.playlist_sync();
+        };
+        self.command(&PlayerCmd::PlaylistRemoveTrack(
+            PlaylistRemoveTrackIndexed {
+                at_index: index,
+                id,
+            },
+            PlayerCmdReply::default(),
+        ));
     }
 
     pub fn playlist_clear(&mut self) {
-        self.playlist.clear();
-        if let Err(e) = self.player_sync_playlist() {
-            self.mount_error_popup(e.context("player sync playlist"));
-        }
-        self.playlist_sync();
+        self.command(&PlayerCmd::PlaylistClear);
     }
 
     pub fn playlist_shuffle(&mut self) {
-        self.playlist.shuffle();
-        if let Err(e) = self.player_sync_playlist() {
-            self.mount_error_popup(e.context("player sync playlist"));
-        }
-        self.playlist_sync();
+        self.command(&PlayerCmd::PlaylistShuffle);
     }
 
     pub fn playlist_update_library_delete(&mut self) {
-        self.playlist.remove_deleted_items();
-        if let Err(e) = self.player_sync_playlist() {
-            self.mount_error_popup(e.context("player sync playlist"));
+        self.command(&PlayerCmd::PlaylistRemoveDeletedTracks);
+    }
+
+    /// Request the server to replace every occurrence of a renamed file with its new file,
+    /// `renamed` being pairs of `(old_file, new_file)`.
+    ///
+    /// All indexes are computed from the current playlist, as it only changes once the server processed the requests.
+    pub fn playlist_replace_renamed(&mut self, renamed: &[(PathBuf, PathBuf)]) {
+        let mut replaces: Vec<(usize, PlaylistTrackSource, &Path)> = self
+            .playlist
+            .tracks()
+            .iter()
+            .enumerate()
+            .filter_map(|(index, track)| {
+                let file = Path::new(track.file()?);
+                renamed
+                    .iter()
+                    .find(|(old_file, _)| old_file == file)
+                    .map(|(_, new_file)| (index, track.as_track_source(), new_file.as_path()))
+            })
+            .collect();
+        // highest index first, so that the indexes of the not yet replaced tracks do not change
+        replaces.sort_by_key(|(index, ..)| std::cmp::Reverse(*index));
+        for (index, old_id, new_file) in replaces {
+            self.command(&PlayerCmd::PlaylistAddTrack(
+                PlaylistAddTrack {
+                    at_index: index,
+                    tracks: vec![PlaylistTrackSource::Path(
+                        new_file.to_string_lossy().to_string(),
+                    )],
+                },
+                PlayerCmdReply::default(),
+            ));
+            // the old entry moved one down by the insert
+            self.command(&PlayerCmd::PlaylistRemoveTrack(
+                PlaylistRemoveTrackIndexed {
+                    at_index: index + 1,
+                    id: old_id,
+                },
+                PlayerCmdReply::default(),
+            ));
+        }
+    }
+
+    /// Swap the `index` with the one below(+1) it, if there is one.
+    pub fn playlist_swap_down(&mut self, index: usize) {
+        if index < self.playlist.len().saturating_sub(1) {
+            self.command(&PlayerCmd::PlaylistSwapTrack(
+                PlaylistSwapTrack {
+                    index_a: index,
+                    index_b: index + 1,
+                },
+                PlayerCmdReply::default(),
+            ));
+        }
+    }
+
+    /// Swap the `index` with the one above(-1) it, if there is one.
+    pub fn playlist_swap_up(&mut self, index: usize) {
+        if index > 0 && index < self.playlist.len() {
+            self.command(&PlayerCmd::PlaylistSwapTrack(
+                PlaylistSwapTrack {
+                    index_a: index,
+                    index_b: index - 1,
+                },
+                PlayerCmdReply::default(),
+            ));
         }
-        self.playlist_sync();
     }
 
     pub fn playlist_update_title(&mut self) {
@@ -537,11 +621,21 @@ impl Model {
             .ok();
     }
     pub fn playlist_play_selected(&mut self, index: usize) {
-        self.playlist.set_current_track_index(index);
-        if let Err(e) = self.player_sync_playlist() {
-            self.mount_error_popup(e.context("player sync playlist"));
-        }
-        self.command(&PlayerCmd::PlaySelected);
+        let Some(id) = self
+            .playlist
+            .tracks()
+            .get(index)
+            .map(Track::as_track_source)
+        else {
+            return;
+        };
+        self.command(&PlayerCmd::PlaylistPlaySpecific(
+            PlaylistPlaySpecific {
+                track_index: index,
+                id,
+            },
+            PlayerCmdReply::default(),
+        ));
     }
 
     pub fn playlist_update_search(&mut self, input: &str) {
@@ -134,6 +134,9 @@ impl HelpPopup {
                         .add_col(Self::key(&[&keys.select_view_keys.view_podcasts]))
                         .add_col(Self::comment("Switch layout to podcast"))
                         .add_row()
+                        .add_col(Self::key(&[&keys.select_view_keys.view_radio]))
+                        .add_col(Self::comment("Switch layout to radio stations"))
+                        .add_row()
                         .add_col(Self::key(&[
                             &keys.move_cover_art_keys.move_left,
                             &keys.move_cover_art_keys.move_right,
@@ -170,7 +173,9 @@ impl HelpPopup {
                         .add_col(Self::comment("Search or download track from youtube"))
                         .add_row()
                         .add_col(Self::key(&[&keys.library_keys.open_tag_editor]))
-                        .add_col(Self::comment("Open tag editor for tag and lyric download"))
+                        .add_col(Self::comment(
+                            "Open tag editor for tag and lyric download, on a folder for all its tracks",
+                        ))
                         .add_row()
                         .add_col(Self::key(&[
                             &keys.library_keys.yank,
@@ -228,6 +233,15 @@ impl HelpPopup {
                         ]))
                         .add_col(Self::comment("Select random tracks/albums to playlist"))
                         .add_row()
+                        .add_col(Self::key(&[&keys.playlist_keys.search_radio]))
+                        .add_col(Self::comment("Search radio stations to add to playlist"))
+                        .add_row()
+                        .add_col(Self::key(&[
+                            &keys.playlist_keys.rating_up,
+                            &keys.playlist_keys.rating_down,
+                        ]))
+                        .add_col(Self::comment("Rate selected track one star up/down"))
+                        .add_row()
                         .add_col(TextSpan::new("Database").bold().fg(Color::LightYellow))
                         .add_row()
                         .add_col(Self::key(&[
@@ -236,6 +250,15 @@ impl HelpPopup {
                         ]))
                         .add_col(Self::comment("Add one/all track(s) to playlist"))
                         .add_row()
+                        .add_col(Self::key(&[
+                            &keys.database_keys.rating_up,
+                            &keys.database_keys.rating_down,
+                        ]))
+                        .add_col(Self::comment("Rate selected track one star up/down"))
+                        .add_row()
+                        .add_col(Self::key(&[&keys.library_keys.open_tag_editor]))
+                        .add_col(Self::comment("Open tag editor for all listed tracks"))
+                        .add_row()
                         // TODO: add search key to database
                         .add_col(Self::key(&[&keys.library_keys.search]))
                         .add_col(Self::comment("Search in database"))
@@ -271,6 +294,32 @@ impl HelpPopup {
                         .add_row()
                         .add_col(Self::key(&[&keys.library_keys.search]))
                         .add_col(Self::comment("Search through added Feeds / Episodes"))
+                        .add_row()
+                        .add_col(
+                            TextSpan::new("Radio Stations")
+                                .bold()
+                                .fg(Color::LightYellow),
+                        )
+                        .add_row()
+                        .add_col(Self::key(&[&keys.radio_station_keys.search]))
+                        .add_col(Self::comment("Search radio stations to save"))
+                        .add_row()
+                        .add_col(Self::key(&[
+                            &keys.radio_station_keys.add_station,
+                            &keys.radio_station_keys.edit_station,
+                        ]))
+                        .add_col(Self::comment("Add a new station / edit selected station"))
+                        .add_row()
+                        .add_col(Self::key(&[&keys.radio_station_keys.delete_station]))
+                        .add_col(Self::comment("Delete selected station"))
+                        .add_row()
+                        .add_col(TextSpan::new("Radio History").bold().fg(Color::LightYellow))
+                        .add_row()
+                        .add_col(Self::key(&[&keys.radio_history_keys.copy_title]))
+                        .add_col(Self::comment("Copy selected title to clipboard"))
+                        .add_row()
+                        .add_col(Self::key(&[&keys.radio_history_keys.save_title]))
+                        .add_col(Self::comment("Save selected title to heard on radio list"))
                         .build(),
                 )
         };
@@ -8,6 +8,8 @@ mod message;
 mod mock_yn_confirm;
 mod podcast;
 mod quit;
+pub mod radio_search;
+pub mod radio_station;
 mod saveplaylist;
 pub mod youtube_search;
 
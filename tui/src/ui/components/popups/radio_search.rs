@@ -0,0 +1,230 @@
+use crate::ui::model::Model;
+use termusiclib::config::{SharedTuiSettings, TuiOverlay};
+use termusiclib::types::{Id, Msg, RSMsg};
+use tui_realm_stdlib::{Input, Table};
+use tuirealm::command::{Cmd, CmdResult, Direction, Position};
+use tuirealm::event::{Key, KeyEvent, KeyModifiers, NoUserEvent};
+use tuirealm::props::{Alignment, BorderType, Borders, InputType, TableBuilder, TextSpan};
+use tuirealm::{Component, Event, MockComponent, State, StateValue};
+
+#[derive(MockComponent)]
+pub struct RSInputPopup {
+    component: Input,
+}
+
+impl RSInputPopup {
+    pub fn new(config: &TuiOverlay) -> Self {
+        let settings = &config.settings;
+        Self {
+            component: Input::default()
+                .background(settings.theme.fallback_background())
+                .foreground(settings.theme.fallback_foreground())
+                .borders(
+                    Borders::default()
+                        .color(settings.theme.fallback_border())
+                        .modifiers(BorderType::Rounded),
+                )
+                .input_type(InputType::Text)
+                .title(
+                    " Search stations (name tag: country: codec:): ",
+                    Alignment::Left,
+                ),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for RSInputPopup {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        let cmd_result = match ev {
+            Event::Keyboard(KeyEvent {
+                code: Key::Left, ..
+            }) => self.perform(Cmd::Move(Direction::Left)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Right, ..
+            }) => self.perform(Cmd::Move(Direction::Right)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Home, ..
+            }) => self.perform(Cmd::GoTo(Position::Begin)),
+            Event::Keyboard(KeyEvent { code: Key::End, .. }) => {
+                self.perform(Cmd::GoTo(Position::End))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Delete, ..
+            }) => self.perform(Cmd::Cancel),
+            Event::Keyboard(KeyEvent {
+                code: Key::Backspace,
+                ..
+            }) => self.perform(Cmd::Delete),
+            Event::Keyboard(KeyEvent {
+                code: Key::Char(ch),
+                modifiers: KeyModifiers::SHIFT | KeyModifiers::NONE,
+            }) => self.perform(Cmd::Type(ch)),
+            Event::Keyboard(KeyEvent { code: Key::Esc, .. }) => {
+                return Some(Msg::RadioSearch(RSMsg::InputPopupCloseCancel));
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Enter, ..
+            }) => self.perform(Cmd::Submit),
+            _ => CmdResult::None,
+        };
+        match cmd_result {
+            CmdResult::Submit(State::One(StateValue::String(input_string))) => {
+                Some(Msg::RadioSearch(RSMsg::InputPopupCloseOk(input_string)))
+            }
+
+            CmdResult::None => None,
+            _ => Some(Msg::ForceRedraw),
+        }
+    }
+}
+
+#[derive(MockComponent)]
+pub struct RSTablePopup {
+    component: Table,
+    config: SharedTuiSettings,
+}
+
+impl RSTablePopup {
+    pub fn new(config: SharedTuiSettings) -> Self {
+        let component = {
+            let config = config.read();
+            Table::default()
+                .background(config.settings.theme.fallback_background())
+                .foreground(config.settings.theme.fallback_foreground())
+                .borders(
+                    Borders::default()
+                        .color(config.settings.theme.fallback_border())
+                        .modifiers(BorderType::Rounded),
+                )
+                .title(
+                    format!(
+                        " Enter to add the station to the playlist, <{}> to save it ",
+                        config.settings.keys.radio_station_keys.add_station
+                    ),
+                    Alignment::Left,
+                )
+                .scroll(true)
+                .highlighted_color(config.settings.theme.fallback_highlight())
+                .highlighted_str(&config.settings.theme.style.library.highlight_symbol)
+                .rewind(false)
+                .step(4)
+                .row_height(1)
+                .headers(&["Name", "Country", "Codec", "Tags"])
+                .column_spacing(2)
+                .widths(&[35, 15, 12, 38])
+                .table(
+                    TableBuilder::default()
+                        .add_col(TextSpan::from("Empty result."))
+                        .add_col(TextSpan::from("Loading..."))
+                        .build(),
+                )
+        };
+
+        Self { component, config }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for RSTablePopup {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        let config = self.config.clone();
+        let keys = &config.read().settings.keys;
+        let cmd_result = match ev {
+            Event::Keyboard(KeyEvent { code: Key::Esc, .. }) => {
+                return Some(Msg::RadioSearch(RSMsg::TablePopupCloseCancel))
+            }
+            Event::Keyboard(keyevent) if keyevent == keys.quit.get() => {
+                return Some(Msg::RadioSearch(RSMsg::TablePopupCloseCancel))
+            }
+            Event::Keyboard(KeyEvent { code: Key::Up, .. }) => {
+                self.perform(Cmd::Move(Direction::Up))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Down, ..
+            }) => self.perform(Cmd::Move(Direction::Down)),
+
+            Event::Keyboard(keyevent) if keyevent == keys.navigation_keys.down.get() => {
+                self.perform(Cmd::Move(Direction::Down))
+            }
+
+            Event::Keyboard(keyevent) if keyevent == keys.navigation_keys.up.get() => {
+                self.perform(Cmd::Move(Direction::Up))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::PageDown,
+                ..
+            }) => self.perform(Cmd::Scroll(Direction::Down)),
+            Event::Keyboard(KeyEvent {
+                code: Key::PageUp, ..
+            }) => self.perform(Cmd::Scroll(Direction::Up)),
+            Event::Keyboard(keyevent) if keyevent == keys.navigation_keys.goto_top.get() => {
+                self.perform(Cmd::GoTo(Position::Begin))
+            }
+            Event::Keyboard(keyevent) if keyevent == keys.navigation_keys.goto_bottom.get() => {
+                self.perform(Cmd::GoTo(Position::End))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Enter, ..
+            }) => {
+                if let State::One(StateValue::Usize(index)) = self.state() {
+                    return Some(Msg::RadioSearch(RSMsg::TablePopupCloseOk(index)));
+                }
+                CmdResult::None
+            }
+            Event::Keyboard(keyevent) if keyevent == keys.radio_station_keys.add_station.get() => {
+                if let State::One(StateValue::Usize(index)) = self.state() {
+                    return Some(Msg::RadioSearch(RSMsg::TablePopupSave(index)));
+                }
+                CmdResult::None
+            }
+            _ => CmdResult::None,
+        };
+        match cmd_result {
+            CmdResult::None => None,
+            _ => Some(Msg::ForceRedraw),
+        }
+    }
+}
+
+impl Model {
+    pub fn mount_radio_search_input(&mut self) {
+        assert!(self
+            .app
+            .remount(
+                Id::RadioSearchInputPopup,
+                Box::new(RSInputPopup::new(&self.config_tui.read())),
+                vec![]
+            )
+            .is_ok());
+        assert!(self.app.active(&Id::RadioSearchInputPopup).is_ok());
+    }
+
+    pub fn umount_radio_search_input(&mut self) {
+        if self.app.mounted(&Id::RadioSearchInputPopup) {
+            assert!(self.app.umount(&Id::RadioSearchInputPopup).is_ok());
+        }
+    }
+
+    pub fn mount_radio_search_table(&mut self) {
+        assert!(self
+            .app
+            .remount(
+                Id::RadioSearchTablePopup,
+                Box::new(RSTablePopup::new(self.config_tui.clone())),
+                vec![]
+            )
+            .is_ok());
+        assert!(self.app.active(&Id::RadioSearchTablePopup).is_ok());
+        if let Err(e) = self.update_photo() {
+            self.mount_error_popup(e.context("update_photo"));
+        }
+    }
+
+    pub fn umount_radio_search_table(&mut self) {
+        if self.app.mounted(&Id::RadioSearchTablePopup) {
+            assert!(self.app.umount(&Id::RadioSearchTablePopup).is_ok());
+        }
+        if let Err(e) = self.update_photo() {
+            self.mount_error_popup(e.context("update_photo"));
+        }
+    }
+}
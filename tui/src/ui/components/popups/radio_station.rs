@@ -0,0 +1,253 @@
+use crate::ui::model::Model;
+use termusiclib::config::{SharedTuiSettings, TuiOverlay};
+use termusiclib::types::{Id, Msg, RLMsg, RadioStationFields};
+use tui_realm_stdlib::Input;
+use tuirealm::command::{Cmd, CmdResult, Direction, Position};
+use tuirealm::event::{Key, KeyEvent, KeyModifiers, NoUserEvent};
+use tuirealm::props::{Alignment, AttrValue, Attribute, BorderType, Borders, InputType};
+use tuirealm::ratatui::layout::{Constraint, Layout, Rect};
+use tuirealm::{Component, Event, Frame, MockComponent, State, StateValue};
+
+use super::{YNConfirm, YNConfirmStyle};
+
+/// The index of the url in [`RadioStationEditPopup::inputs`]
+const URL_INPUT: usize = 1;
+
+/// Form to add or edit a saved radio station, with one input per field
+pub struct RadioStationEditPopup {
+    /// Inputs for name, url, genre and favicon, in that order
+    inputs: [Input; 4],
+    /// The currently active input
+    active: usize,
+    focus: bool,
+}
+
+impl RadioStationEditPopup {
+    pub fn new(config: &TuiOverlay, fields: &RadioStationFields) -> Self {
+        let input = |title: &str, value: &str| {
+            let settings = &config.settings;
+            Input::default()
+                .foreground(settings.theme.library_foreground())
+                .background(settings.theme.library_background())
+                .borders(
+                    Borders::default()
+                        .color(settings.theme.library_border())
+                        .modifiers(BorderType::Rounded),
+                )
+                .input_type(InputType::Text)
+                .title(title, Alignment::Left)
+                .value(value)
+        };
+
+        Self {
+            inputs: [
+                input(" Name (Tab: next field, Enter: save): ", &fields.name),
+                input(" Url: ", &fields.url),
+                input(" Genre: ", &fields.genre),
+                input(" Favicon url: ", &fields.favicon),
+            ],
+            active: 0,
+            focus: false,
+        }
+    }
+
+    /// Get the current value of the input at `index`
+    fn value(&self, index: usize) -> String {
+        match self.inputs[index].state() {
+            State::One(StateValue::String(value)) => value.trim().to_string(),
+            _ => String::new(),
+        }
+    }
+
+    fn fields(&self) -> RadioStationFields {
+        RadioStationFields {
+            name: self.value(0),
+            url: self.value(1),
+            genre: self.value(2),
+            favicon: self.value(3),
+        }
+    }
+}
+
+impl MockComponent for RadioStationEditPopup {
+    fn view(&mut self, frame: &mut Frame<'_>, area: Rect) {
+        let chunks = Layout::vertical([Constraint::Length(3); 4]).split(area);
+        for (index, (input, chunk)) in self.inputs.iter_mut().zip(chunks.iter()).enumerate() {
+            input.attr(
+                Attribute::Focus,
+                AttrValue::Flag(self.focus && index == self.active),
+            );
+            input.view(frame, *chunk);
+        }
+    }
+
+    fn query(&self, attr: Attribute) -> Option<AttrValue> {
+        match attr {
+            Attribute::Focus => Some(AttrValue::Flag(self.focus)),
+            attr => self.inputs[self.active].query(attr),
+        }
+    }
+
+    fn attr(&mut self, attr: Attribute, value: AttrValue) {
+        match attr {
+            Attribute::Focus => self.focus = value.unwrap_flag(),
+            attr => self.inputs[self.active].attr(attr, value),
+        }
+    }
+
+    fn state(&self) -> State {
+        State::Vec(
+            (0..self.inputs.len())
+                .map(|index| StateValue::String(self.value(index)))
+                .collect(),
+        )
+    }
+
+    fn perform(&mut self, cmd: Cmd) -> CmdResult {
+        match cmd {
+            Cmd::Change => {
+                self.active = (self.active + 1) % self.inputs.len();
+                CmdResult::Changed(self.state())
+            }
+            cmd => self.inputs[self.active].perform(cmd),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for RadioStationEditPopup {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        let cmd_result = match ev {
+            Event::Keyboard(KeyEvent {
+                code: Key::Left, ..
+            }) => self.perform(Cmd::Move(Direction::Left)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Right, ..
+            }) => self.perform(Cmd::Move(Direction::Right)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Home, ..
+            }) => self.perform(Cmd::GoTo(Position::Begin)),
+            Event::Keyboard(KeyEvent { code: Key::End, .. }) => {
+                self.perform(Cmd::GoTo(Position::End))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Delete, ..
+            }) => self.perform(Cmd::Cancel),
+            Event::Keyboard(KeyEvent {
+                code: Key::Backspace,
+                ..
+            }) => self.perform(Cmd::Delete),
+            Event::Keyboard(KeyEvent {
+                code: Key::Tab | Key::Down,
+                ..
+            }) => self.perform(Cmd::Change),
+            Event::Keyboard(KeyEvent {
+                code: Key::BackTab | Key::Up,
+                ..
+            }) => {
+                // cycle backwards by going around once
+                for _ in 1..self.inputs.len() {
+                    self.perform(Cmd::Change);
+                }
+                CmdResult::Changed(self.state())
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Char(ch),
+                modifiers: KeyModifiers::SHIFT | KeyModifiers::NONE,
+            }) => self.perform(Cmd::Type(ch)),
+            Event::Keyboard(KeyEvent { code: Key::Esc, .. }) => {
+                return Some(Msg::RadioStation(RLMsg::EditPopupCloseCancel));
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Enter, ..
+            }) => {
+                let fields = self.fields();
+                // a station cannot be played without a url
+                if fields.url.is_empty() {
+                    self.active = URL_INPUT;
+                    return Some(Msg::ForceRedraw);
+                }
+                return Some(Msg::RadioStation(RLMsg::EditPopupCloseOk(fields)));
+            }
+            _ => CmdResult::None,
+        };
+        match cmd_result {
+            CmdResult::None => None,
+            _ => Some(Msg::ForceRedraw),
+        }
+    }
+}
+
+#[derive(MockComponent)]
+pub struct RadioStationDeleteConfirmPopup {
+    component: YNConfirm,
+}
+
+impl RadioStationDeleteConfirmPopup {
+    pub fn new(config: SharedTuiSettings) -> Self {
+        let component =
+            YNConfirm::new_with_cb(config, " Are you sure to delete the station? ", |config| {
+                YNConfirmStyle {
+                    foreground_color: config.settings.theme.library_foreground(),
+                    background_color: config.settings.theme.library_background(),
+                    border_color: config.settings.theme.library_border(),
+                    title_alignment: Alignment::Left,
+                }
+            });
+
+        Self { component }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for RadioStationDeleteConfirmPopup {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        self.component.on(
+            ev,
+            Msg::RadioStation(RLMsg::DeleteCloseOk),
+            Msg::RadioStation(RLMsg::DeleteCloseCancel),
+        )
+    }
+}
+
+impl Model {
+    pub fn mount_radio_station_edit(&mut self, fields: &RadioStationFields) {
+        assert!(self
+            .app
+            .remount(
+                Id::RadioStationEditPopup,
+                Box::new(RadioStationEditPopup::new(&self.config_tui.read(), fields)),
+                vec![]
+            )
+            .is_ok());
+        assert!(self.app.active(&Id::RadioStationEditPopup).is_ok());
+        if let Err(e) = self.update_photo() {
+            self.mount_error_popup(e.context("update_photo"));
+        }
+    }
+
+    pub fn umount_radio_station_edit(&mut self) {
+        if self.app.mounted(&Id::RadioStationEditPopup) {
+            assert!(self.app.umount(&Id::RadioStationEditPopup).is_ok());
+        }
+        if let Err(e) = self.update_photo() {
+            self.mount_error_popup(e.context("update_photo"));
+        }
+    }
+
+    pub fn mount_radio_station_delete_confirm(&mut self) {
+        assert!(self
+            .app
+            .remount(
+                Id::RadioStationDeleteConfirmPopup,
+                Box::new(RadioStationDeleteConfirmPopup::new(self.config_tui.clone())),
+                vec![]
+            )
+            .is_ok());
+        assert!(self.app.active(&Id::RadioStationDeleteConfirmPopup).is_ok());
+    }
+
+    pub fn umount_radio_station_delete_confirm(&mut self) {
+        if self.app.mounted(&Id::RadioStationDeleteConfirmPopup) {
+            assert!(self.app.umount(&Id::RadioStationDeleteConfirmPopup).is_ok());
+        }
+    }
+}
@@ -122,6 +122,28 @@ impl Model {
         self.progress_set(new_prog, total_duration);
     }
 
+    /// Get the text to display for a active sleep timer
+    fn sleep_timer_text(&self) -> Option<String> {
+        let sleep_timer = self.sleep_timer.as_ref()?;
+
+        if sleep_timer.fading {
+            return Some("Sleep: fading out".to_string());
+        }
+
+        if let Some(remaining) = sleep_timer.remaining_time {
+            return Some(format!(
+                "Sleep in {}",
+                Track::duration_formatted_short(&remaining)
+            ));
+        }
+
+        match sleep_timer.remaining_tracks {
+            Some(1) => Some("Sleep after this track".to_string()),
+            Some(tracks) => Some(format!("Sleep after {tracks} tracks")),
+            None => None,
+        }
+    }
+
     fn progress_safeguard(progress: f64) -> f64 {
         let new_prog = progress / 100.0;
         new_prog.clamp(0.0, 1.0)
@@ -136,16 +158,18 @@ impl Model {
             )
             .ok();
 
+        let mut text = format!(
+            "{}    -    {}",
+            Track::duration_formatted_short(&self.time_pos),
+            Track::duration_formatted_short(&total_duration)
+        );
+        if let Some(sleep_timer) = self.sleep_timer_text() {
+            text.push_str("    |    ");
+            text.push_str(&sleep_timer);
+        }
+
         self.app
-            .attr(
-                &Id::Progress,
-                Attribute::Text,
-                AttrValue::String(format!(
-                    "{}    -    {}",
-                    Track::duration_formatted_short(&self.time_pos),
-                    Track::duration_formatted_short(&total_duration)
-                )),
-            )
+            .attr(&Id::Progress, Attribute::Text, AttrValue::String(text))
             .ok();
         // self.force_redraw();
     }
@@ -0,0 +1,146 @@
+use termusiclib::config::SharedTuiSettings;
+use termusiclib::types::{Msg, RLMsg, RSMsg};
+use tui_realm_stdlib::Table;
+use tuirealm::command::{Cmd, CmdResult, Direction, Position};
+use tuirealm::props::{Alignment, BorderType, Borders, TableBuilder, TextSpan};
+use tuirealm::{
+    event::{Key, KeyEvent, KeyModifiers, NoUserEvent},
+    Component, Event, MockComponent, State, StateValue,
+};
+
+#[derive(MockComponent)]
+pub struct RadioStationList {
+    component: Table,
+    config: SharedTuiSettings,
+}
+
+impl RadioStationList {
+    pub fn new(config: SharedTuiSettings) -> Self {
+        let component = {
+            let config = config.read();
+            Table::default()
+                .borders(
+                    Borders::default()
+                        .modifiers(BorderType::Rounded)
+                        .color(config.settings.theme.library_border()),
+                )
+                .background(config.settings.theme.library_background())
+                .foreground(config.settings.theme.library_foreground())
+                .title(" Radio Stations: ", Alignment::Left)
+                .scroll(true)
+                .highlighted_color(config.settings.theme.library_highlight())
+                .highlighted_str(&config.settings.theme.style.library.highlight_symbol)
+                .rewind(false)
+                .step(4)
+                .row_height(1)
+                .headers(&["Name", "Genre", "Last played"])
+                .column_spacing(1)
+                .widths(&[50, 25, 25])
+                .table(
+                    TableBuilder::default()
+                        .add_col(TextSpan::from("Empty"))
+                        .build(),
+                )
+        };
+
+        Self { component, config }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for RadioStationList {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        let config = self.config.clone();
+        let keys = &config.read().settings.keys;
+        let cmd_result = match ev {
+            Event::Keyboard(KeyEvent {
+                code: Key::Down,
+                modifiers: KeyModifiers::NONE,
+            }) => self.perform(Cmd::Move(Direction::Down)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Up,
+                modifiers: KeyModifiers::NONE,
+            }) => self.perform(Cmd::Move(Direction::Up)),
+            Event::Keyboard(key) if key == keys.navigation_keys.down.get() => {
+                self.perform(Cmd::Move(Direction::Down))
+            }
+            Event::Keyboard(key) if key == keys.navigation_keys.up.get() => {
+                self.perform(Cmd::Move(Direction::Up))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::PageDown,
+                modifiers: KeyModifiers::NONE,
+            }) => self.perform(Cmd::Scroll(Direction::Down)),
+            Event::Keyboard(KeyEvent {
+                code: Key::PageUp,
+                modifiers: KeyModifiers::NONE,
+            }) => self.perform(Cmd::Scroll(Direction::Up)),
+            Event::Keyboard(key) if key == keys.navigation_keys.goto_top.get() => {
+                self.perform(Cmd::GoTo(Position::Begin))
+            }
+            Event::Keyboard(key) if key == keys.navigation_keys.goto_bottom.get() => {
+                self.perform(Cmd::GoTo(Position::End))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Home,
+                modifiers: KeyModifiers::NONE,
+            }) => self.perform(Cmd::GoTo(Position::Begin)),
+            Event::Keyboard(KeyEvent {
+                code: Key::End,
+                modifiers: KeyModifiers::NONE,
+            }) => self.perform(Cmd::GoTo(Position::End)),
+
+            Event::Keyboard(KeyEvent {
+                code: Key::Enter | Key::Right,
+                modifiers: KeyModifiers::NONE,
+            }) => {
+                if let State::One(StateValue::Usize(index)) = self.state() {
+                    return Some(Msg::RadioStation(RLMsg::AddPlaylist(index)));
+                }
+                CmdResult::None
+            }
+            Event::Keyboard(key) if key == keys.navigation_keys.right.get() => {
+                if let State::One(StateValue::Usize(index)) = self.state() {
+                    return Some(Msg::RadioStation(RLMsg::AddPlaylist(index)));
+                }
+                CmdResult::None
+            }
+
+            Event::Keyboard(
+                KeyEvent {
+                    code: Key::Tab,
+                    modifiers: KeyModifiers::NONE,
+                }
+                | KeyEvent {
+                    code: Key::BackTab,
+                    modifiers: KeyModifiers::SHIFT,
+                },
+            ) => return Some(Msg::RadioStation(RLMsg::StationsBlur)),
+
+            Event::Keyboard(keyevent) if keyevent == keys.radio_station_keys.search.get() => {
+                return Some(Msg::RadioSearch(RSMsg::InputPopupShow));
+            }
+            Event::Keyboard(keyevent) if keyevent == keys.radio_station_keys.add_station.get() => {
+                return Some(Msg::RadioStation(RLMsg::AddPopupShow));
+            }
+            Event::Keyboard(keyevent) if keyevent == keys.radio_station_keys.edit_station.get() => {
+                if let State::One(StateValue::Usize(index)) = self.state() {
+                    return Some(Msg::RadioStation(RLMsg::EditPopupShow(index)));
+                }
+                CmdResult::None
+            }
+            Event::Keyboard(keyevent)
+                if keyevent == keys.radio_station_keys.delete_station.get() =>
+            {
+                if let State::One(StateValue::Usize(index)) = self.state() {
+                    return Some(Msg::RadioStation(RLMsg::DeleteShow(index)));
+                }
+                CmdResult::None
+            }
+            _ => CmdResult::None,
+        };
+        match cmd_result {
+            CmdResult::None => None,
+            _ => Some(Msg::ForceRedraw),
+        }
+    }
+}
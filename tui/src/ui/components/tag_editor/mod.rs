@@ -23,6 +23,7 @@
  */
 
 /// -- modules
+mod te_batch;
 mod te_counter_delete_lyric;
 mod te_footer;
 mod te_input;
@@ -33,6 +34,7 @@ mod update;
 mod view;
 
 // -- exports
+pub use te_batch::TETableBatch;
 pub use te_counter_delete_lyric::TECounterDelete;
 pub use te_input::*;
 pub use te_select_lyric::TESelectLyric;
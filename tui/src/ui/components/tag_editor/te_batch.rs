@@ -0,0 +1,222 @@
+use crate::ui::Model;
+use anyhow::{bail, Result};
+use termusiclib::config::SharedTuiSettings;
+use termusiclib::types::{Id, IdTagEditor, Msg, TEMsg, TFMsg};
+use tui_realm_stdlib::Table;
+use tuirealm::command::{Cmd, CmdResult, Direction, Position};
+use tuirealm::event::{Key, KeyEvent, KeyModifiers, NoUserEvent};
+use tuirealm::props::{Alignment, BorderType, Borders, TableBuilder, TextSpan};
+use tuirealm::{AttrValue, Attribute, Component, Event, MockComponent, State, StateValue};
+
+#[derive(MockComponent)]
+pub struct TETableBatch {
+    component: Table,
+    config: SharedTuiSettings,
+}
+
+impl TETableBatch {
+    pub fn new(config: SharedTuiSettings) -> Self {
+        let component = {
+            let config = config.read();
+            Table::default()
+                .borders(
+                    Borders::default()
+                        .modifiers(BorderType::Rounded)
+                        .color(config.settings.theme.library_border()),
+                )
+                .foreground(config.settings.theme.library_foreground())
+                .background(config.settings.theme.library_background())
+                .title(" Tracks ", Alignment::Left)
+                .scroll(true)
+                .highlighted_color(config.settings.theme.library_highlight())
+                .highlighted_str(&config.settings.theme.style.library.highlight_symbol)
+                .rewind(false)
+                .step(4)
+                .row_height(1)
+                .headers(&["Disc", "Track", "Title", "Artist", "Album", "Year", "File"])
+                .column_spacing(1)
+                .widths(&[5, 5, 25, 20, 20, 5, 20])
+                .table(
+                    TableBuilder::default()
+                        .add_col(TextSpan::from("Empty"))
+                        .build(),
+                )
+        };
+
+        Self { component, config }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for TETableBatch {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        let config = self.config.clone();
+        let keys = &config.read().settings.keys;
+        let cmd_result = match ev {
+            Event::Keyboard(KeyEvent { code: Key::Tab, .. }) => {
+                return Some(Msg::TagEditor(TEMsg::TEFocus(TFMsg::TableBatchBlurDown)))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::BackTab,
+                modifiers: KeyModifiers::SHIFT,
+            }) => return Some(Msg::TagEditor(TEMsg::TEFocus(TFMsg::TableBatchBlurUp))),
+
+            Event::Keyboard(keyevent) if keyevent == keys.config_keys.save.get() => {
+                return Some(Msg::TagEditor(TEMsg::TERename))
+            }
+
+            Event::Keyboard(k) if k == keys.quit.get() => {
+                return Some(Msg::TagEditor(TEMsg::TagEditorClose(None)))
+            }
+            Event::Keyboard(k) if k == keys.escape.get() => {
+                return Some(Msg::TagEditor(TEMsg::TagEditorClose(None)))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Down, ..
+            }) => self.perform(Cmd::Move(Direction::Down)),
+            Event::Keyboard(KeyEvent { code: Key::Up, .. }) => {
+                self.perform(Cmd::Move(Direction::Up))
+            }
+            Event::Keyboard(k) if k == keys.navigation_keys.down.get() => {
+                self.perform(Cmd::Move(Direction::Down))
+            }
+            Event::Keyboard(k) if k == keys.navigation_keys.up.get() => {
+                self.perform(Cmd::Move(Direction::Up))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::PageDown,
+                ..
+            }) => self.perform(Cmd::Scroll(Direction::Down)),
+            Event::Keyboard(KeyEvent {
+                code: Key::PageUp, ..
+            }) => self.perform(Cmd::Scroll(Direction::Up)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Home, ..
+            }) => self.perform(Cmd::GoTo(Position::Begin)),
+            Event::Keyboard(KeyEvent { code: Key::End, .. }) => {
+                self.perform(Cmd::GoTo(Position::End))
+            }
+            Event::Keyboard(k) if k == keys.navigation_keys.goto_top.get() => {
+                self.perform(Cmd::GoTo(Position::Begin))
+            }
+            Event::Keyboard(k) if k == keys.navigation_keys.goto_bottom.get() => {
+                self.perform(Cmd::GoTo(Position::End))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Enter, ..
+            }) => return Some(Msg::TagEditor(TEMsg::TEBatchNumber)),
+
+            _ => CmdResult::None,
+        };
+        match cmd_result {
+            CmdResult::None => None,
+            _ => Some(Msg::ForceRedraw),
+        }
+    }
+}
+
+impl Model {
+    pub fn te_batch_sync_table(&mut self) {
+        let mut table: TableBuilder = TableBuilder::default();
+
+        for (idx, track) in self.tageditor_batch.iter().enumerate() {
+            if idx > 0 {
+                table.add_row();
+            }
+            let number = |v: Option<u32>| v.map(|v| v.to_string()).unwrap_or_default();
+            let file_name = track
+                .file()
+                .and_then(|file| std::path::Path::new(file).file_name())
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+
+            table
+                .add_col(TextSpan::new(number(track.disc_number())))
+                .add_col(TextSpan::new(number(track.track_number())))
+                .add_col(TextSpan::new(track.title().unwrap_or_default()).bold())
+                .add_col(TextSpan::new(track.artist().unwrap_or_default()))
+                .add_col(TextSpan::new(track.album().unwrap_or_default()))
+                .add_col(TextSpan::new(number(track.year())))
+                .add_col(TextSpan::new(file_name));
+        }
+        let table = table.build();
+        assert!(self
+            .app
+            .attr(
+                &Id::TagEditor(IdTagEditor::TableBatch),
+                Attribute::Content,
+                AttrValue::Table(table),
+            )
+            .is_ok());
+    }
+
+    /// Number all tracks of the batch tag editor from `1` in the listed order, written on save
+    pub fn te_batch_number(&mut self) {
+        for (number, track) in (1..).zip(self.tageditor_batch.iter_mut()) {
+            track.set_track_number(Some(number));
+        }
+        self.te_batch_sync_table();
+    }
+
+    /// Write the values of the inputs, which are not empty, and the track numbers to all tracks of the batch tag editor
+    ///
+    /// All tracks are tried to be saved, even if some of them fail.
+    pub fn te_batch_save(&mut self) -> Result<()> {
+        let artist = self.te_batch_text_field(IdTagEditor::InputArtist);
+        let album = self.te_batch_text_field(IdTagEditor::InputAlbum);
+        let genre = self.te_batch_text_field(IdTagEditor::InputGenre);
+        let year = self.te_number_field(IdTagEditor::InputYear, "year")?;
+
+        let mut tracks = std::mem::take(&mut self.tageditor_batch);
+        let mut failures = Vec::new();
+        let mut renamed = Vec::new();
+        for track in &mut tracks {
+            if let Some(artist) = &artist {
+                track.set_artist(artist);
+            }
+            if let Some(album) = &album {
+                track.set_album(album);
+            }
+            if let Some(genre) = &genre {
+                track.set_genre(genre);
+            }
+            if year.is_some() {
+                track.set_year(year);
+            }
+
+            match self.te_write_song(track) {
+                Ok(Some(pair)) => renamed.push(pair),
+                Ok(None) => (),
+                Err(err) => failures.push(format!("{}: {err:#}", track.file().unwrap_or_default())),
+            }
+        }
+        let total = tracks.len();
+        self.tageditor_batch = tracks;
+
+        if !renamed.is_empty() {
+            self.playlist_replace_renamed(&renamed);
+            self.library_reload_tree();
+        }
+        self.te_batch_sync_table();
+
+        if !failures.is_empty() {
+            bail!(
+                "{} of {total} tracks could not be saved:\n{}",
+                failures.len(),
+                failures.join("\n")
+            );
+        }
+        self.update_show_message_timeout("Tag editor", &format!("Saved {total} tracks"), None);
+
+        Ok(())
+    }
+
+    /// Get the trimmed value of a text input, `None` if it is empty
+    fn te_batch_text_field(&self, id: IdTagEditor) -> Option<String> {
+        match self.app.state(&Id::TagEditor(id)) {
+            Ok(State::One(StateValue::String(value))) if !value.trim().is_empty() => {
+                Some(value.trim().to_string())
+            }
+            _ => None,
+        }
+    }
+}
@@ -282,11 +282,8 @@ impl Model {
             {
                 song.set_lyric_selected_index(song.lyric_selected_index() - 1);
             }
-            match song.save_tag() {
-                Ok(()) => self.init_by_song(&song),
-                Err(e) => {
-                    self.mount_error_popup(e);
-                }
+            if let Err(e) = self.te_save_song(&mut song) {
+                self.mount_error_popup(e);
             }
         }
     }
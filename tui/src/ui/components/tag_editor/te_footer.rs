@@ -44,6 +44,36 @@ impl TEFooter {
     }
 }
 
+impl TEFooter {
+    /// Footer for the batch tag editor
+    pub fn new_batch(config: &TuiOverlay) -> Self {
+        Self {
+            component: (LabelSpan::new(
+                config,
+                &[
+                    TextSpan::new(" Save all: ").fg(config.settings.theme.library_foreground()),
+                    TextSpan::new(format!("<{}>", config.settings.keys.config_keys.save))
+                        .bold()
+                        .fg(config.settings.theme.library_highlight()),
+                    TextSpan::new(" Exit: ").fg(config.settings.theme.library_foreground()),
+                    TextSpan::new(format!("<{}>", config.settings.keys.escape))
+                        .bold()
+                        .fg(config.settings.theme.library_highlight()),
+                    TextSpan::new(" Change field: ").fg(config.settings.theme.library_foreground()),
+                    TextSpan::new("<Tab/ShiftTab>")
+                        .bold()
+                        .fg(config.settings.theme.library_highlight()),
+                    TextSpan::new(" Number tracks: ")
+                        .fg(config.settings.theme.library_foreground()),
+                    TextSpan::new("<ENTER>")
+                        .bold()
+                        .fg(config.settings.theme.library_highlight()),
+                ],
+            )),
+        }
+    }
+}
+
 impl Component<Msg, NoUserEvent> for TEFooter {
     fn on(&mut self, _ev: Event<NoUserEvent>) -> Option<Msg> {
         None
@@ -225,3 +225,164 @@ impl Component<Msg, NoUserEvent> for TEInputGenre {
         )
     }
 }
+
+#[derive(MockComponent)]
+pub struct TEInputAlbumArtist {
+    component: EditField,
+}
+
+impl TEInputAlbumArtist {
+    pub fn new(config: SharedTuiSettings) -> Self {
+        Self {
+            component: EditField::new(config, " Album artist "),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for TEInputAlbumArtist {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        self.component.on(
+            ev,
+            Msg::TagEditor(TEMsg::TEFocus(TFMsg::InputAlbumArtistBlurDown)),
+            Msg::TagEditor(TEMsg::TEFocus(TFMsg::InputAlbumArtistBlurUp)),
+        )
+    }
+}
+
+#[derive(MockComponent)]
+pub struct TEInputYear {
+    component: EditField,
+}
+
+impl TEInputYear {
+    pub fn new(config: SharedTuiSettings) -> Self {
+        Self {
+            component: EditField::new(config, " Year "),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for TEInputYear {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        self.component.on(
+            ev,
+            Msg::TagEditor(TEMsg::TEFocus(TFMsg::InputYearBlurDown)),
+            Msg::TagEditor(TEMsg::TEFocus(TFMsg::InputYearBlurUp)),
+        )
+    }
+}
+
+#[derive(MockComponent)]
+pub struct TEInputTrackNumber {
+    component: EditField,
+}
+
+impl TEInputTrackNumber {
+    pub fn new(config: SharedTuiSettings) -> Self {
+        Self {
+            component: EditField::new(config, " Track "),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for TEInputTrackNumber {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        self.component.on(
+            ev,
+            Msg::TagEditor(TEMsg::TEFocus(TFMsg::InputTrackNumberBlurDown)),
+            Msg::TagEditor(TEMsg::TEFocus(TFMsg::InputTrackNumberBlurUp)),
+        )
+    }
+}
+
+#[derive(MockComponent)]
+pub struct TEInputDiscNumber {
+    component: EditField,
+}
+
+impl TEInputDiscNumber {
+    pub fn new(config: SharedTuiSettings) -> Self {
+        Self {
+            component: EditField::new(config, " Disc "),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for TEInputDiscNumber {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        self.component.on(
+            ev,
+            Msg::TagEditor(TEMsg::TEFocus(TFMsg::InputDiscNumberBlurDown)),
+            Msg::TagEditor(TEMsg::TEFocus(TFMsg::InputDiscNumberBlurUp)),
+        )
+    }
+}
+
+#[derive(MockComponent)]
+pub struct TEInputComposer {
+    component: EditField,
+}
+
+impl TEInputComposer {
+    pub fn new(config: SharedTuiSettings) -> Self {
+        Self {
+            component: EditField::new(config, " Composer "),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for TEInputComposer {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        self.component.on(
+            ev,
+            Msg::TagEditor(TEMsg::TEFocus(TFMsg::InputComposerBlurDown)),
+            Msg::TagEditor(TEMsg::TEFocus(TFMsg::InputComposerBlurUp)),
+        )
+    }
+}
+
+#[derive(MockComponent)]
+pub struct TEInputPerformer {
+    component: EditField,
+}
+
+impl TEInputPerformer {
+    pub fn new(config: SharedTuiSettings) -> Self {
+        Self {
+            component: EditField::new(config, " Performer "),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for TEInputPerformer {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        self.component.on(
+            ev,
+            Msg::TagEditor(TEMsg::TEFocus(TFMsg::InputPerformerBlurDown)),
+            Msg::TagEditor(TEMsg::TEFocus(TFMsg::InputPerformerBlurUp)),
+        )
+    }
+}
+
+#[derive(MockComponent)]
+pub struct TEInputComment {
+    component: EditField,
+}
+
+impl TEInputComment {
+    pub fn new(config: SharedTuiSettings) -> Self {
+        Self {
+            component: EditField::new(config, " Comment "),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for TEInputComment {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        self.component.on(
+            ev,
+            Msg::TagEditor(TEMsg::TEFocus(TFMsg::InputCommentBlurDown)),
+            Msg::TagEditor(TEMsg::TEFocus(TFMsg::InputCommentBlurUp)),
+        )
+    }
+}
@@ -1,6 +1,6 @@
 use crate::ui::Model;
 use anyhow::{anyhow, Context, Result};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use termusiclib::config::SharedTuiSettings;
 /**
  * MIT License
@@ -26,6 +26,7 @@ use termusiclib::config::SharedTuiSettings;
  * SOFTWARE.
  */
 use termusiclib::songtag::{search, SongTag};
+use termusiclib::track::Track;
 use termusiclib::types::{Id, IdTagEditor, Msg, SearchLyricState, TEMsg, TFMsg};
 use tokio::runtime::Handle;
 use tui_realm_stdlib::Table;
@@ -290,13 +291,114 @@ impl Model {
             {
                 song.set_genre(&genre);
             }
-            song.save_tag()?;
-            self.init_by_song(&song);
-            self.playlist_update_library_delete();
+            if let Ok(State::One(StateValue::String(album_artist))) = self
+                .app
+                .state(&Id::TagEditor(IdTagEditor::InputAlbumArtist))
+            {
+                song.set_album_artist(&album_artist);
+            }
+            if let Ok(State::One(StateValue::String(composer))) =
+                self.app.state(&Id::TagEditor(IdTagEditor::InputComposer))
+            {
+                song.set_composer(&composer);
+            }
+            if let Ok(State::One(StateValue::String(performer))) =
+                self.app.state(&Id::TagEditor(IdTagEditor::InputPerformer))
+            {
+                song.set_performer(&performer);
+            }
+            if let Ok(State::One(StateValue::String(comment))) =
+                self.app.state(&Id::TagEditor(IdTagEditor::InputComment))
+            {
+                song.set_comment(&comment);
+            }
+            song.set_year(self.te_number_field(IdTagEditor::InputYear, "year")?);
+            song.set_track_number(self.te_number_field(IdTagEditor::InputTrackNumber, "track")?);
+            song.set_disc_number(self.te_number_field(IdTagEditor::InputDiscNumber, "disc")?);
+            self.te_save_song(&mut song)?;
         }
         Ok(())
     }
 
+    /// Save the tag of `song` and rename it by the configured template, updating the library and the playlist
+    pub fn te_save_song(&mut self, song: &mut Track) -> Result<()> {
+        if let Some(renamed) = self.te_write_song(song)? {
+            self.playlist_replace_renamed(&[renamed]);
+            self.library_reload_tree();
+        }
+        self.init_by_song(song);
+
+        Ok(())
+    }
+
+    /// Save the tag of `song` and rename it by the configured template, updating the library database
+    ///
+    /// Returns `(old_file, new_file)` if the file has been renamed, in which case the playlist still needs to be
+    /// updated with [`Self::playlist_replace_renamed`].
+    pub fn te_write_song(&mut self, song: &mut Track) -> Result<Option<(PathBuf, PathBuf)>> {
+        song.save_tag()?;
+
+        let Some(file) = song.file().map(PathBuf::from) else {
+            return Ok(None);
+        };
+        let music_dir = self.te_music_dir_of(&file);
+
+        let template = self
+            .config_tui
+            .read()
+            .settings
+            .behavior
+            .rename_on_save
+            .clone();
+        let renamed = if template.is_empty() {
+            Ok(None)
+        } else {
+            let base_dir = music_dir
+                .clone()
+                .or_else(|| file.parent().map(Path::to_path_buf))
+                .unwrap_or_default();
+            song.rename_by_template(&template, &base_dir)
+        };
+        let old_file = renamed.as_ref().ok().cloned().flatten();
+
+        // only tracks in the music directories are part of the library,
+        // which needs the saved tags even if renaming failed
+        if music_dir.is_some() {
+            let old_file = old_file.as_deref().unwrap_or(&file);
+            self.db
+                .rename_record(&old_file.to_string_lossy(), song)
+                .context("update the library database")?;
+        }
+        renamed?;
+
+        Ok(old_file.zip(song.file().map(PathBuf::from)))
+    }
+
+    /// Get the configured music directory `file` is in
+    fn te_music_dir_of(&self, file: &Path) -> Option<PathBuf> {
+        let config_server = self.config_server.read();
+        config_server
+            .settings
+            .player
+            .music_dirs
+            .iter()
+            .chain(config_server.music_dir_overwrite.as_ref())
+            .map(|dir| shellexpand::path::tilde(dir).into_owned())
+            .find(|dir| file.starts_with(dir))
+    }
+
+    /// Parse the value of a number input, `None` if it is empty
+    pub fn te_number_field(&self, id: IdTagEditor, name: &str) -> Result<Option<u32>> {
+        match self.app.state(&Id::TagEditor(id)) {
+            Ok(State::One(StateValue::String(value))) if !value.trim().is_empty() => value
+                .trim()
+                .parse::<u32>()
+                .map(Some)
+                .with_context(|| format!("{name} has to be a number, got \"{value}\"")),
+            _ => Ok(None),
+        }
+    }
+
     pub fn te_load_lyric_and_photo(&mut self, index: usize) -> Result<()> {
         if self.songtag_options.is_empty() {
             return Ok(());
@@ -332,10 +434,7 @@ impl Model {
                 song.set_photo(artwork);
             }
 
-            song.save_tag()?;
-            self.init_by_song(&song);
-            self.playlist_update_library_delete();
-            // self.library_sync(song.file());
+            self.te_save_song(&mut song)?;
         }
         Ok(())
     }
@@ -21,6 +21,7 @@
  * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
  * SOFTWARE.
  */
+use crate::ui::model::TermusicLayout;
 use crate::ui::{Id, IdTagEditor, Model, TEMsg, TFMsg};
 
 impl Model {
@@ -30,7 +31,13 @@ impl Model {
                 self.mount_tageditor(node_id);
             }
             TEMsg::TagEditorClose(_song) => {
-                if let Some(s) = self.tageditor_song.clone() {
+                if !self.tageditor_batch.is_empty() {
+                    self.library_reload_with_node_focus(None);
+                    // the database view lists are reset by the reload, so start over from the criteria
+                    if self.layout == TermusicLayout::DataBase {
+                        self.app.active(&Id::DBListCriteria).ok();
+                    }
+                } else if let Some(s) = self.tageditor_song.clone() {
                     self.library_reload_with_node_focus(s.file());
                 }
                 self.umount_tageditor();
@@ -46,7 +53,10 @@ impl Model {
                 }
             }
             TEMsg::TESearch => {
-                self.te_songtag_search();
+                // there is nothing to search for multiple tracks at once
+                if self.tageditor_batch.is_empty() {
+                    self.te_songtag_search();
+                }
             }
             TEMsg::TEDownload(index) => {
                 if let Err(e) = self.te_songtag_download(*index) {
@@ -59,15 +69,25 @@ impl Model {
                 }
             }
             TEMsg::TERename => {
-                if let Err(e) = self.te_rename_song_by_tag() {
+                if !self.tageditor_batch.is_empty() {
+                    if let Err(e) = self.te_batch_save() {
+                        self.mount_error_popup(e.context("save tracks"));
+                    }
+                } else if let Err(e) = self.te_rename_song_by_tag() {
                     self.mount_error_popup(e.context("rename song by tag"));
                 }
             }
+            TEMsg::TEBatchNumber => self.te_batch_number(),
             TEMsg::TEFocus(m) => self.update_tag_editor_focus(*m),
         }
     }
 
     fn update_tag_editor_focus(&mut self, msg: TFMsg) {
+        if !self.tageditor_batch.is_empty() {
+            self.update_tag_editor_batch_focus(msg);
+            return;
+        }
+
         match msg {
             TFMsg::TextareaLyricBlurDown | TFMsg::InputTitleBlurUp => {
                 self.app
@@ -79,17 +99,50 @@ impl Model {
                     .active(&Id::TagEditor(IdTagEditor::InputTitle))
                     .ok();
             }
-            TFMsg::InputTitleBlurDown | TFMsg::InputGenreBlurUp => {
+            TFMsg::InputTitleBlurDown | TFMsg::InputAlbumArtistBlurUp => {
                 self.app
                     .active(&Id::TagEditor(IdTagEditor::InputAlbum))
                     .ok();
             }
-            TFMsg::InputAlbumBlurDown | TFMsg::TableLyricOptionsBlurUp => {
+            TFMsg::InputAlbumBlurDown | TFMsg::InputGenreBlurUp => {
+                self.app
+                    .active(&Id::TagEditor(IdTagEditor::InputAlbumArtist))
+                    .ok();
+            }
+            TFMsg::InputAlbumArtistBlurDown | TFMsg::InputYearBlurUp => {
                 self.app
                     .active(&Id::TagEditor(IdTagEditor::InputGenre))
                     .ok();
             }
-            TFMsg::InputGenreBlurDown | TFMsg::SelectLyricBlurUp => {
+            TFMsg::InputGenreBlurDown | TFMsg::InputTrackNumberBlurUp => {
+                self.app.active(&Id::TagEditor(IdTagEditor::InputYear)).ok();
+            }
+            TFMsg::InputYearBlurDown | TFMsg::InputDiscNumberBlurUp => {
+                self.app
+                    .active(&Id::TagEditor(IdTagEditor::InputTrackNumber))
+                    .ok();
+            }
+            TFMsg::InputTrackNumberBlurDown | TFMsg::InputComposerBlurUp => {
+                self.app
+                    .active(&Id::TagEditor(IdTagEditor::InputDiscNumber))
+                    .ok();
+            }
+            TFMsg::InputDiscNumberBlurDown | TFMsg::InputPerformerBlurUp => {
+                self.app
+                    .active(&Id::TagEditor(IdTagEditor::InputComposer))
+                    .ok();
+            }
+            TFMsg::InputComposerBlurDown | TFMsg::InputCommentBlurUp => {
+                self.app
+                    .active(&Id::TagEditor(IdTagEditor::InputPerformer))
+                    .ok();
+            }
+            TFMsg::InputPerformerBlurDown | TFMsg::TableLyricOptionsBlurUp => {
+                self.app
+                    .active(&Id::TagEditor(IdTagEditor::InputComment))
+                    .ok();
+            }
+            TFMsg::InputCommentBlurDown | TFMsg::SelectLyricBlurUp => {
                 self.app
                     .active(&Id::TagEditor(IdTagEditor::TableLyricOptions))
                     .ok();
@@ -109,6 +162,21 @@ impl Model {
                     .active(&Id::TagEditor(IdTagEditor::TextareaLyric))
                     .ok();
             }
+            // only in the batch tag editor
+            TFMsg::TableBatchBlurDown | TFMsg::TableBatchBlurUp => {}
         }
     }
+
+    /// The batch tag editor only has the artist, album, genre and year inputs and the track table
+    fn update_tag_editor_batch_focus(&mut self, msg: TFMsg) {
+        let id = match msg {
+            TFMsg::TableBatchBlurDown | TFMsg::InputAlbumBlurUp => IdTagEditor::InputArtist,
+            TFMsg::InputArtistBlurDown | TFMsg::InputGenreBlurUp => IdTagEditor::InputAlbum,
+            TFMsg::InputAlbumBlurDown | TFMsg::InputYearBlurUp => IdTagEditor::InputGenre,
+            TFMsg::InputGenreBlurDown | TFMsg::TableBatchBlurUp => IdTagEditor::InputYear,
+            TFMsg::InputYearBlurDown | TFMsg::InputArtistBlurUp => IdTagEditor::TableBatch,
+            _ => return,
+        };
+        self.app.active(&Id::TagEditor(id)).ok();
+    }
 }
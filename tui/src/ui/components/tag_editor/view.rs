@@ -23,15 +23,18 @@ use crate::ui::components::tag_editor::te_footer::TEFooter;
  * SOFTWARE.
  */
 use crate::ui::components::{
-    LabelGeneric, TECounterDelete, TEInputAlbum, TEInputArtist, TEInputGenre, TEInputTitle,
-    TESelectLyric, TETableLyricOptions, TETextareaLyric,
+    LabelGeneric, TECounterDelete, TEInputAlbum, TEInputAlbumArtist, TEInputArtist, TEInputComment,
+    TEInputComposer, TEInputDiscNumber, TEInputGenre, TEInputPerformer, TEInputTitle,
+    TEInputTrackNumber, TEInputYear, TESelectLyric, TETableBatch, TETableLyricOptions,
+    TETextareaLyric,
 };
 use crate::ui::model::Model;
 use crate::ui::utils::{draw_area_in_absolute, draw_area_top_right_absolute};
 use std::convert::TryFrom;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use termusiclib::track::Track;
 use termusiclib::types::{Id, IdTagEditor};
+use termusiclib::utils::{filetype_supported, natural_cmp};
 use tuirealm::props::{Alignment, AttrValue, Attribute, PropPayload, PropValue, TextSpan};
 use tuirealm::ratatui::layout::{Constraint, Direction, Layout};
 use tuirealm::ratatui::widgets::Clear;
@@ -58,7 +61,8 @@ impl Model {
                                 Constraint::Length(1),
                                 Constraint::Length(3),
                                 Constraint::Length(3),
-                                // Constraint::Length(3),
+                                Constraint::Length(3),
+                                Constraint::Length(3),
                                 Constraint::Min(2),
                                 Constraint::Length(1),
                             ]
@@ -84,11 +88,24 @@ impl Model {
                             .as_ref(),
                         )
                         .split(chunks_main[2]);
+                    let chunks_row3 = Layout::default()
+                        .direction(Direction::Horizontal)
+                        .margin(0)
+                        .constraints(
+                            [
+                                Constraint::Ratio(1, 8),
+                                Constraint::Ratio(1, 8),
+                                Constraint::Ratio(3, 8),
+                                Constraint::Ratio(3, 8),
+                            ]
+                            .as_ref(),
+                        )
+                        .split(chunks_main[3]);
                     let chunks_row4 = Layout::default()
                         .direction(Direction::Horizontal)
                         .margin(0)
                         .constraints([Constraint::Ratio(3, 5), Constraint::Ratio(2, 5)].as_ref())
-                        .split(chunks_main[3]);
+                        .split(chunks_main[5]);
 
                     let chunks_row4_right = Layout::default()
                         .direction(Direction::Vertical)
@@ -117,12 +134,12 @@ impl Model {
                                 ]
                                 .as_ref(),
                             )
-                            .split(chunks_main[4]);
+                            .split(chunks_main[6]);
 
                         self.app.view(&Id::DownloadSpinner, f, chunks_footer[1]);
                         self.app.view(&Id::Label, f, chunks_footer[2]);
                     } else {
-                        self.app.view(&Id::Label, f, chunks_main[4]);
+                        self.app.view(&Id::Label, f, chunks_main[6]);
                     }
 
                     self.app
@@ -134,8 +151,37 @@ impl Model {
                         .view(&Id::TagEditor(IdTagEditor::InputTitle), f, chunks_row1[1]);
                     self.app
                         .view(&Id::TagEditor(IdTagEditor::InputAlbum), f, chunks_row2[0]);
+                    self.app.view(
+                        &Id::TagEditor(IdTagEditor::InputAlbumArtist),
+                        f,
+                        chunks_row2[1],
+                    );
+                    self.app
+                        .view(&Id::TagEditor(IdTagEditor::InputGenre), f, chunks_row2[2]);
+                    self.app
+                        .view(&Id::TagEditor(IdTagEditor::InputYear), f, chunks_row2[3]);
+                    self.app.view(
+                        &Id::TagEditor(IdTagEditor::InputTrackNumber),
+                        f,
+                        chunks_row3[0],
+                    );
+                    self.app.view(
+                        &Id::TagEditor(IdTagEditor::InputDiscNumber),
+                        f,
+                        chunks_row3[1],
+                    );
+                    self.app.view(
+                        &Id::TagEditor(IdTagEditor::InputComposer),
+                        f,
+                        chunks_row3[2],
+                    );
+                    self.app.view(
+                        &Id::TagEditor(IdTagEditor::InputPerformer),
+                        f,
+                        chunks_row3[3],
+                    );
                     self.app
-                        .view(&Id::TagEditor(IdTagEditor::InputGenre), f, chunks_row2[1]);
+                        .view(&Id::TagEditor(IdTagEditor::InputComment), f, chunks_main[4]);
                     self.app.view(
                         &Id::TagEditor(IdTagEditor::TableLyricOptions),
                         f,
@@ -172,11 +218,74 @@ impl Model {
             .expect("Expected to draw without error");
     }
 
+    pub fn view_tag_editor_batch(&mut self) {
+        self.terminal
+            .raw_mut()
+            .draw(|f| {
+                f.render_widget(Clear, f.area());
+                let chunks_main = Layout::default()
+                    .direction(Direction::Vertical)
+                    .margin(0)
+                    .constraints(
+                        [
+                            Constraint::Length(1),
+                            Constraint::Length(3),
+                            Constraint::Min(2),
+                            Constraint::Length(1),
+                        ]
+                        .as_ref(),
+                    )
+                    .split(f.area());
+                let chunks_inputs = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .margin(0)
+                    .constraints(
+                        [
+                            Constraint::Ratio(2, 7),
+                            Constraint::Ratio(2, 7),
+                            Constraint::Ratio(2, 7),
+                            Constraint::Ratio(1, 7),
+                        ]
+                        .as_ref(),
+                    )
+                    .split(chunks_main[1]);
+
+                self.app
+                    .view(&Id::TagEditor(IdTagEditor::LabelHint), f, chunks_main[0]);
+                self.app.view(
+                    &Id::TagEditor(IdTagEditor::InputArtist),
+                    f,
+                    chunks_inputs[0],
+                );
+                self.app
+                    .view(&Id::TagEditor(IdTagEditor::InputAlbum), f, chunks_inputs[1]);
+                self.app
+                    .view(&Id::TagEditor(IdTagEditor::InputGenre), f, chunks_inputs[2]);
+                self.app
+                    .view(&Id::TagEditor(IdTagEditor::InputYear), f, chunks_inputs[3]);
+                self.app
+                    .view(&Id::TagEditor(IdTagEditor::TableBatch), f, chunks_main[2]);
+                self.app.view(&Id::Label, f, chunks_main[3]);
+
+                if self.app.mounted(&Id::MessagePopup) {
+                    let popup = draw_area_top_right_absolute(f.area(), 25, 4);
+                    f.render_widget(Clear, popup);
+                    self.app.view(&Id::MessagePopup, f, popup);
+                }
+                if self.app.mounted(&Id::ErrorPopup) {
+                    let popup = draw_area_in_absolute(f.area(), 50, 4);
+                    f.render_widget(Clear, popup);
+                    self.app.view(&Id::ErrorPopup, f, popup);
+                }
+            })
+            .expect("Expected to draw without error");
+    }
+
     #[allow(clippy::too_many_lines)]
     pub fn mount_tageditor(&mut self, node_id: &str) {
         let node_path: &Path = Path::new(node_id);
         if node_path.is_dir() {
-            self.mount_error_popup(anyhow::anyhow!("{node_path:?} directory doesn't have tag!"));
+            self.mount_tageditor_batch(node_path);
             return;
         }
 
@@ -239,6 +348,62 @@ impl Model {
                 Vec::new()
             )
             .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::TagEditor(IdTagEditor::InputAlbumArtist),
+                Box::new(TEInputAlbumArtist::new(self.config_tui.clone())),
+                Vec::new()
+            )
+            .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::TagEditor(IdTagEditor::InputYear),
+                Box::new(TEInputYear::new(self.config_tui.clone())),
+                Vec::new()
+            )
+            .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::TagEditor(IdTagEditor::InputTrackNumber),
+                Box::new(TEInputTrackNumber::new(self.config_tui.clone())),
+                Vec::new()
+            )
+            .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::TagEditor(IdTagEditor::InputDiscNumber),
+                Box::new(TEInputDiscNumber::new(self.config_tui.clone())),
+                Vec::new()
+            )
+            .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::TagEditor(IdTagEditor::InputComposer),
+                Box::new(TEInputComposer::new(self.config_tui.clone())),
+                Vec::new()
+            )
+            .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::TagEditor(IdTagEditor::InputPerformer),
+                Box::new(TEInputPerformer::new(self.config_tui.clone())),
+                Vec::new()
+            )
+            .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::TagEditor(IdTagEditor::InputComment),
+                Box::new(TEInputComment::new(self.config_tui.clone())),
+                Vec::new()
+            )
+            .is_ok());
         assert!(self
             .app
             .remount(
@@ -282,7 +447,206 @@ impl Model {
         }
     }
 
+    /// Open the batch tag editor on all supported tracks directly in `dir`
+    ///
+    /// The tracks are ordered by their existing disc and track numbers, tracks without them last and in file name order.
+    pub fn mount_tageditor_batch(&mut self, dir: &Path) {
+        let files: Vec<PathBuf> = match std::fs::read_dir(dir) {
+            Ok(entries) => entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.is_file() && filetype_supported(&path.to_string_lossy()))
+                .collect(),
+            Err(err) => {
+                self.mount_error_popup(anyhow::Error::new(err).context("read directory"));
+                return;
+            }
+        };
+
+        let (mut tracks, failures) = Self::te_batch_read(&files);
+        if tracks.is_empty() {
+            self.mount_error_popup(anyhow::anyhow!("{dir:?} does not have any tracks to edit"));
+            return;
+        }
+        tracks.sort_by(|a, b| {
+            let numbers = |track: &Track| {
+                (
+                    track.disc_number().is_none(),
+                    track.disc_number(),
+                    track.track_number().is_none(),
+                    track.track_number(),
+                )
+            };
+            numbers(a).cmp(&numbers(b)).then_with(|| {
+                natural_cmp(a.file().unwrap_or_default(), b.file().unwrap_or_default())
+            })
+        });
+
+        self.mount_tageditor_batch_tracks(tracks, &failures);
+    }
+
+    /// Open the batch tag editor on a selection of `files`, like the tracks listed in the database view
+    ///
+    /// The tracks keep the order of `files`.
+    pub fn mount_tageditor_batch_files(&mut self, files: &[PathBuf]) {
+        let (tracks, failures) = Self::te_batch_read(files);
+        if tracks.is_empty() {
+            self.mount_error_popup(anyhow::anyhow!(
+                "There are no tracks to edit:\n{}",
+                failures.join("\n")
+            ));
+            return;
+        }
+
+        self.mount_tageditor_batch_tracks(tracks, &failures);
+    }
+
+    /// Read the tracks of `files` for the batch tag editor, returning the tracks and a message for each file which could not be read
+    fn te_batch_read(files: &[PathBuf]) -> (Vec<Track>, Vec<String>) {
+        let mut tracks = Vec::with_capacity(files.len());
+        let mut failures = Vec::new();
+        for file in files {
+            match Track::read_from_path(file, false) {
+                Ok(track) => tracks.push(track),
+                Err(err) => failures.push(format!("{}: {err:#}", file.display())),
+            }
+        }
+
+        (tracks, failures)
+    }
+
+    /// Mount the batch tag editor for the non-empty `tracks`, `failures` being the files which could not be read
+    #[allow(clippy::too_many_lines)]
+    fn mount_tageditor_batch_tracks(&mut self, tracks: Vec<Track>, failures: &[String]) {
+        let amount = tracks.len();
+        self.tageditor_batch = tracks;
+
+        assert!(self
+            .app
+            .remount(
+                Id::Label,
+                Box::new(TEFooter::new_batch(&self.config_tui.read())),
+                Vec::default(),
+            )
+            .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::TagEditor(IdTagEditor::LabelHint),
+                Box::new(LabelGeneric::new(
+                    &self.config_tui.read(),
+                    &format!("Values for all {amount} tracks, empty fields are not changed:")
+                )),
+                Vec::new()
+            )
+            .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::TagEditor(IdTagEditor::InputArtist),
+                Box::new(TEInputArtist::new(self.config_tui.clone())),
+                Vec::new()
+            )
+            .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::TagEditor(IdTagEditor::InputAlbum),
+                Box::new(TEInputAlbum::new(self.config_tui.clone())),
+                Vec::new()
+            )
+            .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::TagEditor(IdTagEditor::InputGenre),
+                Box::new(TEInputGenre::new(self.config_tui.clone())),
+                Vec::new()
+            )
+            .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::TagEditor(IdTagEditor::InputYear),
+                Box::new(TEInputYear::new(self.config_tui.clone())),
+                Vec::new()
+            )
+            .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::TagEditor(IdTagEditor::TableBatch),
+                Box::new(TETableBatch::new(self.config_tui.clone())),
+                Vec::new()
+            )
+            .is_ok());
+        assert!(self
+            .app
+            .attr(
+                &Id::TagEditor(IdTagEditor::InputArtist),
+                Attribute::Title,
+                AttrValue::Title((" Artist ".to_string(), Alignment::Left)),
+            )
+            .is_ok());
+
+        // pre-fill the values all tracks have in common
+        let common = |value: fn(&Track) -> Option<String>| {
+            let first = value(&self.tageditor_batch[0])?;
+            self.tageditor_batch
+                .iter()
+                .all(|track| value(track).as_ref() == Some(&first))
+                .then_some(first)
+        };
+        let fields = [
+            (
+                IdTagEditor::InputArtist,
+                common(|t| t.artist().map(ToString::to_string)),
+            ),
+            (
+                IdTagEditor::InputAlbum,
+                common(|t| t.album().map(ToString::to_string)),
+            ),
+            (
+                IdTagEditor::InputGenre,
+                common(|t| t.genre().map(ToString::to_string)),
+            ),
+            (
+                IdTagEditor::InputYear,
+                common(|t| t.year().map(|v| v.to_string())),
+            ),
+        ];
+        for (id, value) in fields {
+            if let Some(value) = value {
+                assert!(self
+                    .app
+                    .attr(
+                        &Id::TagEditor(id),
+                        Attribute::Value,
+                        AttrValue::String(value)
+                    )
+                    .is_ok());
+            }
+        }
+
+        self.te_batch_sync_table();
+        self.app
+            .active(&Id::TagEditor(IdTagEditor::InputArtist))
+            .ok();
+
+        if let Err(e) = self.update_photo() {
+            self.mount_error_popup(e.context("update_photo"));
+        }
+        if !failures.is_empty() {
+            self.mount_error_popup(anyhow::anyhow!(
+                "{} tracks could not be read and are not edited:\n{}",
+                failures.len(),
+                failures.join("\n")
+            ));
+        }
+    }
+
     pub fn umount_tageditor(&mut self) {
+        self.tageditor_batch.clear();
         self.mount_label_help();
         self.app.umount(&Id::TagEditor(IdTagEditor::LabelHint)).ok();
         self.app
@@ -297,6 +661,25 @@ impl Model {
         self.app
             .umount(&Id::TagEditor(IdTagEditor::InputGenre))
             .ok();
+        self.app
+            .umount(&Id::TagEditor(IdTagEditor::InputAlbumArtist))
+            .ok();
+        self.app.umount(&Id::TagEditor(IdTagEditor::InputYear)).ok();
+        self.app
+            .umount(&Id::TagEditor(IdTagEditor::InputTrackNumber))
+            .ok();
+        self.app
+            .umount(&Id::TagEditor(IdTagEditor::InputDiscNumber))
+            .ok();
+        self.app
+            .umount(&Id::TagEditor(IdTagEditor::InputComposer))
+            .ok();
+        self.app
+            .umount(&Id::TagEditor(IdTagEditor::InputPerformer))
+            .ok();
+        self.app
+            .umount(&Id::TagEditor(IdTagEditor::InputComment))
+            .ok();
         // self.app.umount(&Id::TagEditor(IdTagEditor::RadioTag)).ok();
         self.app
             .umount(&Id::TagEditor(IdTagEditor::TableLyricOptions))
@@ -310,6 +693,9 @@ impl Model {
         self.app
             .umount(&Id::TagEditor(IdTagEditor::TextareaLyric))
             .ok();
+        self.app
+            .umount(&Id::TagEditor(IdTagEditor::TableBatch))
+            .ok();
         if let Err(e) = self.update_photo() {
             self.mount_error_popup(e.context("update_photo"));
         }
@@ -363,6 +749,46 @@ impl Model {
                 .is_ok());
         }
 
+        let fields = [
+            (
+                IdTagEditor::InputAlbumArtist,
+                s.album_artist().map(ToString::to_string),
+            ),
+            (
+                IdTagEditor::InputComposer,
+                s.composer().map(ToString::to_string),
+            ),
+            (
+                IdTagEditor::InputPerformer,
+                s.performer().map(ToString::to_string),
+            ),
+            (
+                IdTagEditor::InputComment,
+                s.comment().map(ToString::to_string),
+            ),
+            (IdTagEditor::InputYear, s.year().map(|v| v.to_string())),
+            (
+                IdTagEditor::InputTrackNumber,
+                s.track_number().map(|v| v.to_string()),
+            ),
+            (
+                IdTagEditor::InputDiscNumber,
+                s.disc_number().map(|v| v.to_string()),
+            ),
+        ];
+        for (id, value) in fields {
+            if let Some(value) = value {
+                assert!(self
+                    .app
+                    .attr(
+                        &Id::TagEditor(id),
+                        Attribute::Value,
+                        AttrValue::String(value)
+                    )
+                    .is_ok());
+            }
+        }
+
         if s.lyric_frames_is_empty() {
             self.init_by_song_no_lyric();
             return;
@@ -97,11 +97,22 @@ impl Model {
         if self.app.mounted(&Id::TagEditor(IdTagEditor::InputTitle)) {
             return true;
         }
+        if self.app.mounted(&Id::TagEditor(IdTagEditor::TableBatch)) {
+            return true;
+        }
 
         if self.app.mounted(&Id::YoutubeSearchTablePopup) {
             return true;
         }
 
+        if self.app.mounted(&Id::RadioSearchTablePopup) {
+            return true;
+        }
+
+        if self.app.mounted(&Id::RadioStationEditPopup) {
+            return true;
+        }
+
         if self.app.mounted(&Id::GeneralSearchInput) {
             return true;
         }
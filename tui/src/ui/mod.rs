@@ -23,7 +23,7 @@
  */
 pub mod components;
 pub mod model;
-mod playback;
+pub mod playback;
 pub mod utils;
 
 use anyhow::Context;
@@ -33,16 +33,16 @@ use model::{Model, TermusicLayout};
 use playback::Playback;
 use std::time::Duration;
 use sysinfo::System;
-use termusiclib::player::music_player_client::MusicPlayerClient;
+use termusiclib::player::auth::AuthedClient;
+use termusiclib::player::playlist_helpers::{PlaylistAddTrack, PlaylistRemoveTrackIndexed};
 use termusiclib::player::PlayerProgress;
 use termusiclib::player::StreamUpdates;
-use termusiclib::player::UpdateEvents;
+use termusiclib::player::{UpdateEvents, UpdatePlaylistEvents};
 pub use termusiclib::types::*;
 use termusicplayback::{PlayerCmd, Status};
 use tokio::sync::mpsc::{self, UnboundedReceiver};
 use tokio_stream::Stream;
 use tokio_stream::StreamExt;
-use tonic::transport::Channel;
 use tuirealm::application::PollStrategy;
 use tuirealm::{Application, Update};
 
@@ -70,7 +70,7 @@ impl UI {
         // }
     }
     /// Instantiates a new Ui
-    pub async fn new(config: CombinedSettings, client: MusicPlayerClient<Channel>) -> Result<Self> {
+    pub async fn new(config: CombinedSettings, client: AuthedClient) -> Result<Self> {
         let (cmd_tx, cmd_rx) = mpsc::unbounded_channel();
         let mut model = Model::new(config, cmd_tx).await;
         model.init_config();
@@ -101,6 +101,8 @@ impl UI {
     /// This function does NOT handle initializing and finializing the terminal
     async fn run_inner(&mut self) -> Result<()> {
         let mut stream_updates = self.playback.subscribe_to_stream_updates().await?;
+        // the server owns the playlist, so get its current state before anything is displayed
+        self.load_playlist_from_server().await?;
 
         // Main loop
         let mut progress_interval = 0;
@@ -247,6 +249,7 @@ impl UI {
                 }
                 PlayerCmd::GetProgress => {
                     let response = self.playback.get_progress().await?;
+                    self.model.sleep_timer = response.sleep_timer.and_then(Into::into);
                     let pprogress: PlayerProgress = response.progress.unwrap_or_default().into();
                     self.model.progress_update(
                         pprogress.position,
@@ -256,7 +259,13 @@ impl UI {
                         self.handle_current_track_index(response.current_track_index as usize);
                     }
 
-                    self.model.lyric_update_for_radio(response.radio_title);
+                    if self
+                        .model
+                        .lyric_radio_history_outdated(&response.radio_title)
+                    {
+                        let history = self.playback.get_radio_history(None).await?;
+                        self.model.lyric_update_for_radio(history);
+                    }
 
                     self.handle_status(Status::from_u32(response.status));
                 }
@@ -268,9 +277,17 @@ impl UI {
                     // This line is required to show current playing message
                     // self.model.playlist.set_current_track_index(None);
                 }
+                cmd @ (PlayerCmd::PlaylistAddTrack(..)
+                | PlayerCmd::PlaylistRemoveTrack(..)
+                | PlayerCmd::PlaylistRemoveDeletedTracks
+                | PlayerCmd::PlaylistSwapTrack(..)
+                | PlayerCmd::PlaylistClear
+                | PlayerCmd::PlaylistShuffle
+                | PlayerCmd::PlaylistPlaySpecific(..)) => self.run_playback_playlist(cmd).await?,
                 PlayerCmd::SkipPrevious => self.playback.skip_previous().await?,
                 PlayerCmd::ReloadConfig => self.playback.reload_config().await?,
-                PlayerCmd::ReloadPlaylist => self.playback.reload_playlist().await?,
+                // the server owns the playlist, so "reload" means getting it from the server
+                PlayerCmd::ReloadPlaylist => self.load_playlist_from_server().await?,
                 PlayerCmd::SeekBackward => {
                     let pprogress = self.playback.seek_backward().await?;
                     self.model.progress_update(
@@ -318,6 +335,45 @@ impl UI {
         Ok(())
     }
 
+    /// Send the playlist modifying commands to the server
+    ///
+    /// The local playlist is updated once the server sends the change events.
+    async fn run_playback_playlist(&mut self, cmd: PlayerCmd) -> Result<()> {
+        match cmd {
+            PlayerCmd::PlaylistAddTrack(info, _) => self.playback.add_to_playlist(info).await?,
+            PlayerCmd::PlaylistRemoveTrack(info, _) => {
+                self.playback.remove_from_playlist(info).await?;
+            }
+            PlayerCmd::PlaylistRemoveDeletedTracks => {
+                self.playback.remove_deleted_from_playlist().await?;
+            }
+            PlayerCmd::PlaylistSwapTrack(info, _) => self.playback.swap_tracks(info).await?,
+            PlayerCmd::PlaylistClear => self.playback.clear_playlist().await?,
+            PlayerCmd::PlaylistShuffle => self.playback.shuffle_playlist().await?,
+            PlayerCmd::PlaylistPlaySpecific(info, _) => self.playback.play_specific(info).await?,
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// Replace the local playlist with the one from the server.
+    async fn load_playlist_from_server(&mut self) -> Result<()> {
+        let tracks = self
+            .playback
+            .get_playlist()
+            .await
+            .context("get playlist from server")?;
+        self.model.playlist.load_from_grpc(
+            &tracks,
+            &self.model.podcast.db_podcast,
+            &mut self.model.db,
+        );
+        self.model.playlist_sync();
+
+        Ok(())
+    }
+
     /// Handle Stream updates from the provided stream.
     ///
     /// In case of lag, sends a [`PlayerCmd::GetProgress`] to `self.model`.
@@ -345,6 +401,8 @@ impl UI {
                     warn!("Stream Lagged, missed events: {amount}");
                     // we know that we missed events, force to get full information from GetProgress endpoint
                     self.model.command(&PlayerCmd::GetProgress);
+                    // and also the playlist, as any change to it could have been missed
+                    self.model.command(&PlayerCmd::ReloadPlaylist);
                 }
                 UpdateEvents::VolumeChanged { volume } => {
                     self.model.config_server.write().settings.player.volume = volume;
@@ -371,7 +429,16 @@ impl UI {
                     }
 
                     if let Some(title) = track_changed_info.title {
-                        self.model.lyric_update_for_radio(title);
+                        // the history is fetched alongside the progress
+                        if self.model.lyric_radio_history_outdated(&title) {
+                            self.model.command(&PlayerCmd::GetProgress);
+                        }
+                    }
+                }
+                UpdateEvents::PlaylistChanged(ev) => {
+                    if let Err(err) = self.handle_playlist_events(ev) {
+                        warn!("Applying playlist event failed, reloading playlist: {err:#}");
+                        self.model.command(&PlayerCmd::ReloadPlaylist);
                     }
                 }
             }
@@ -379,4 +446,53 @@ impl UI {
 
         Ok(())
     }
+
+    /// Apply a playlist change from the server to the local playlist.
+    ///
+    /// # Errors
+    /// - When the change cannot be applied, meaning the local playlist is out-of-sync
+    fn handle_playlist_events(&mut self, ev: UpdatePlaylistEvents) -> Result<()> {
+        match ev {
+            UpdatePlaylistEvents::PlaylistAddTrack(info) => {
+                let info = PlaylistAddTrack {
+                    at_index: info.at_index,
+                    tracks: info.tracks,
+                };
+                // tracks that cannot be read locally are still added, to keep the indexes in sync with the server
+                self.model.playlist.add_tracks_lenient(
+                    &info,
+                    &self.model.podcast.db_podcast,
+                    &mut self.model.db,
+                );
+            }
+            UpdatePlaylistEvents::PlaylistRemoveTrack(info) => {
+                self.model
+                    .playlist
+                    .remove_indexed(&PlaylistRemoveTrackIndexed {
+                        at_index: info.at_index,
+                        id: info.id,
+                    })?;
+            }
+            UpdatePlaylistEvents::PlaylistCleared => self.model.playlist.clear(),
+            UpdatePlaylistEvents::PlaylistLoopMode(loop_mode) => {
+                self.model.config_server.write().settings.player.loop_mode = loop_mode;
+                self.model.playlist.set_loop_mode(loop_mode);
+            }
+            UpdatePlaylistEvents::PlaylistSwapTracks(info) => {
+                self.model.playlist.swap(info.index_a, info.index_b)?;
+            }
+            UpdatePlaylistEvents::PlaylistShuffled(tracks)
+            | UpdatePlaylistEvents::PlaylistReplaced(tracks) => {
+                self.model.playlist.load_from_grpc(
+                    &tracks,
+                    &self.model.podcast.db_podcast,
+                    &mut self.model.db,
+                );
+            }
+        }
+
+        self.model.playlist_sync();
+
+        Ok(())
+    }
 }
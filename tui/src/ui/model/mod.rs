@@ -1,4 +1,7 @@
 mod download_tracker;
+mod radio_search;
+mod radio_stations;
+mod rating;
 /**
  * MIT License
  *
@@ -29,9 +32,11 @@ mod youtube_options;
 use crate::ui::Application;
 use crate::CombinedSettings;
 use download_tracker::DownloadTracker;
+pub use rating::rating_stars;
+use termusiclib::config::v2::server::equalizer::EqualizerSettings;
 use termusiclib::config::v2::tui::keys::Keys;
 use termusiclib::config::v2::tui::theme::ThemeWrap;
-use termusiclib::library_db::{DataBase, SearchCriteria};
+use termusiclib::library_db::{DataBase, RadioStationDB, SearchCriteria};
 use termusiclib::types::{Id, Msg, SearchLyricState, YoutubeOptions};
 use termusiclib::xywh;
 
@@ -39,13 +44,16 @@ use termusiclib::track::{MediaType, Track};
 #[cfg(all(feature = "cover-ueberzug", not(target_os = "windows")))]
 use termusiclib::ueberzug::UeInstance;
 
-use anyhow::{anyhow, Result};
+use anyhow::anyhow;
 use std::path::PathBuf;
 use std::sync::mpsc::{self, Receiver, Sender};
 use std::time::{Duration, Instant};
 use termusiclib::config::{ServerOverlay, SharedServerSettings, SharedTuiSettings};
 use termusiclib::library_db::TrackDB;
+use termusiclib::player::radio_history::RadioHistoryInfo;
+use termusiclib::player::SleepTimerInfo;
 use termusiclib::podcast::{db::Database as DBPod, Podcast, PodcastFeed};
+use termusiclib::radio_browser::RadioStation;
 use termusiclib::songtag::SongTag;
 use termusiclib::taskpool::TaskPool;
 use termusiclib::utils::get_app_config_path;
@@ -60,6 +68,7 @@ pub enum TermusicLayout {
     TreeView,
     DataBase,
     Podcast,
+    Radio,
 }
 
 #[derive(Debug, PartialEq, Clone, Copy, Eq)]
@@ -116,6 +125,15 @@ pub struct PodcastWidgetData {
     pub search_results: Option<Vec<PodcastFeed>>,
 }
 
+/// All data specific to the Radio Stations Widget / View
+#[derive(Debug, Default)]
+pub struct RadioWidgetData {
+    /// Loaded and displayed saved stations
+    pub stations: Vec<RadioStationDB>,
+    /// The id of the station being edited or deleted, [`None`] when adding a new station
+    pub selected_id: Option<u64>,
+}
+
 /// All data specific to the Config Editor Widget / View
 #[derive(Debug)]
 pub struct ConfigEditorData {
@@ -125,6 +143,8 @@ pub struct ConfigEditorData {
     pub theme: ThemeWrap,
     /// The Keybindings to preview before saving
     pub key_config: Keys,
+    /// The equalizer presets to edit before saving
+    pub equalizer: EqualizerSettings,
     /// The current tab in the config editor
     pub layout: ConfigEditorLayout,
     /// Indicator to prompt a save on config editor exit
@@ -153,13 +173,20 @@ pub struct Model {
     pub library: MusicLibraryData,
     pub dw: DatabaseWidgetData,
     pub podcast: PodcastWidgetData,
+    pub radio: RadioWidgetData,
     pub config_editor: ConfigEditorData,
 
     /// Clone of `playlist.current_track`, but kept around when playlist goes empty but song is still playing
     pub current_song: Option<Track>,
     pub tageditor_song: Option<Track>,
+    /// Tracks of the batch tag editor, empty if it is not open
+    pub tageditor_batch: Vec<Track>,
     pub time_pos: Duration,
+    /// The server's sleep timer, if active
+    pub sleep_timer: Option<SleepTimerInfo>,
     pub lyric_line: String,
+    /// The radio title history shown in the lyric view, [`None`] if something else is shown
+    pub radio_history: Option<RadioHistoryInfo>,
     pub playlist: Playlist,
 
     #[cfg(all(feature = "cover-ueberzug", not(target_os = "windows")))]
@@ -168,6 +195,8 @@ pub struct Model {
     pub xywh: xywh::Xywh,
 
     youtube_options: YoutubeOptions,
+    /// The results of the last radio station search
+    pub radio_search_results: Vec<RadioStation>,
     pub songtag_options: Vec<SongTag>,
     pub sender_songtag: Sender<SearchLyricState>,
     pub receiver_songtag: Receiver<SearchLyricState>,
@@ -245,7 +274,8 @@ impl Model {
         ));
         let (tx_to_main, rx_to_main) = mpsc::channel();
 
-        let playlist = Playlist::new(&config_server).unwrap_or_default();
+        // the actual tracks are fetched from the server once connected
+        let playlist = Playlist::new_empty(&config_server, None);
         let app = Self::init_app(&tree, &config_tui);
 
         // This line is required, in order to show the playing message for the first track
@@ -264,8 +294,11 @@ impl Model {
             config_tui,
             // current_song: None,
             tageditor_song: None,
+            tageditor_batch: Vec::new(),
             time_pos: Duration::default(),
+            sleep_timer: None,
             lyric_line: String::new(),
+            radio_history: None,
 
             library: MusicLibraryData {
                 tree_path: path,
@@ -277,6 +310,7 @@ impl Model {
             youtube_options: tokio::task::spawn_blocking(YoutubeOptions::default)
                 .await
                 .expect("Failed to initialize YoutubeOptions in a blocking task due to a panic"),
+            radio_search_results: Vec::new(),
             #[cfg(all(feature = "cover-ueberzug", not(target_os = "windows")))]
             ueberzug_instance,
             songtag_options: vec![],
@@ -296,10 +330,12 @@ impl Model {
                 db_podcast,
                 search_results: None,
             },
+            radio: RadioWidgetData::default(),
             config_editor: ConfigEditorData {
                 themes: Vec::new(),
                 theme: ce_theme,
                 key_config: Keys::default(),
+                equalizer: EqualizerSettings::default(),
                 layout: ConfigEditorLayout::General,
                 config_changed: false,
             },
@@ -380,12 +416,6 @@ impl Model {
         self.lyric_update_title();
     }
 
-    pub fn player_sync_playlist(&mut self) -> Result<()> {
-        self.playlist.save()?;
-        self.command(&PlayerCmd::ReloadPlaylist);
-        Ok(())
-    }
-
     pub fn player_update_current_track_after(&mut self) {
         self.time_pos = Duration::default();
         if let Err(e) = self.update_photo() {
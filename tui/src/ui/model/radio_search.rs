@@ -0,0 +1,112 @@
+use super::{Model, TermusicLayout};
+use termusiclib::library_db::RadioStationDBInsertable;
+use termusiclib::player::playlist_helpers::{PlaylistAddTrack, PlaylistTrackSource};
+use termusiclib::radio_browser::{RadioBrowser, StationQuery};
+use termusiclib::types::{Id, Msg, RSMsg};
+use termusicplayback::{PlayerCmd, PlayerCmdReply};
+use tuirealm::props::{AttrValue, Attribute, TableBuilder, TextSpan};
+
+impl Model {
+    /// Search the radio station directory for `input`, see [`StationQuery::parse`] for the format.
+    ///
+    /// This function requires to be run in a tokio Runtime context
+    pub fn radio_search(&mut self, input: &str) {
+        let query = StationQuery::parse(input);
+        let tx = self.tx_to_main.clone();
+        tokio::spawn(async move {
+            let res = match RadioBrowser::new() {
+                Ok(browser) => browser.search(&query).await,
+                Err(err) => Err(err),
+            };
+            let msg = match res {
+                Ok(stations) => RSMsg::SearchSuccess(stations),
+                Err(err) => RSMsg::SearchFail(format!("{err:#}")),
+            };
+            tx.send(Msg::RadioSearch(msg)).ok();
+        });
+    }
+
+    /// Add the found station at `index` to the playlist, does nothing while no stations are found.
+    ///
+    /// The station is saved to the radio station library if its url is not saved yet,
+    /// as the playlist gets the name of radio tracks from there.
+    pub fn radio_search_add(&mut self, index: usize) {
+        let Some(station) = self.radio_search_results.get(index) else {
+            return;
+        };
+        let url = station.stream_url().to_string();
+        let name = station.name.trim().to_string();
+        match self.db.get_radio_station_by_url(&url) {
+            Ok(Some(_)) => (),
+            Ok(None) => self.radio_search_save(index),
+            Err(err) => error!("Could not look up radio station {url:?}: {err}"),
+        }
+
+        self.command(&PlayerCmd::PlaylistAddTrack(
+            PlaylistAddTrack::new_append(vec![PlaylistTrackSource::Url(url)]),
+            PlayerCmdReply::default(),
+        ));
+        self.update_show_message_timeout("Station added", &name, None);
+    }
+
+    /// Save the found station at `index` to the radio station library
+    pub fn radio_search_save(&mut self, index: usize) {
+        let Some(station) = self.radio_search_results.get(index) else {
+            return;
+        };
+        // only the first tag, as the genre
+        let genre = station.tags.split(',').next().unwrap_or_default();
+        let insertable = RadioStationDBInsertable {
+            name: station.name.trim(),
+            url: station.stream_url(),
+            genre: genre.trim(),
+            favicon: &station.favicon,
+        };
+        let name = insertable.name.to_string();
+        if let Err(err) = self.db.add_radio_station(&insertable) {
+            self.mount_error_popup(anyhow::Error::new(err).context("save radio station"));
+            return;
+        }
+
+        if self.layout == TermusicLayout::Radio {
+            self.radio_stations_sync();
+        }
+        self.update_show_message_timeout("Station saved", &name, None);
+    }
+
+    pub fn radio_search_sync_table(&mut self) {
+        let mut table: TableBuilder = TableBuilder::default();
+        if self.radio_search_results.is_empty() {
+            table
+                .add_col(TextSpan::from("No results."))
+                .add_col(TextSpan::from(""))
+                .add_col(TextSpan::from(""))
+                .add_col(TextSpan::from(""));
+        }
+
+        for (idx, station) in self.radio_search_results.iter().enumerate() {
+            if idx > 0 {
+                table.add_row();
+            }
+            let codec = if station.bitrate > 0 {
+                format!("{} {}k", station.codec, station.bitrate)
+            } else {
+                station.codec.clone()
+            };
+
+            table
+                .add_col(TextSpan::new(station.name.trim()).bold())
+                .add_col(TextSpan::new(&station.country))
+                .add_col(TextSpan::new(codec))
+                .add_col(TextSpan::new(&station.tags));
+        }
+
+        self.app
+            .attr(
+                &Id::RadioSearchTablePopup,
+                Attribute::Content,
+                AttrValue::Table(table.build()),
+            )
+            .ok();
+    }
+}
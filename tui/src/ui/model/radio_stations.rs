@@ -0,0 +1,130 @@
+use super::Model;
+use termusiclib::library_db::RadioStationDBInsertable;
+use termusiclib::player::playlist_helpers::{PlaylistAddTrack, PlaylistTrackSource};
+use termusiclib::types::{Id, RadioStationFields};
+use termusicplayback::{PlayerCmd, PlayerCmdReply};
+use tuirealm::props::{AttrValue, Attribute, TableBuilder, TextSpan};
+
+impl Model {
+    /// Reload the saved stations from the database and display them
+    pub fn radio_stations_sync(&mut self) {
+        match self.db.get_radio_stations() {
+            Ok(stations) => self.radio.stations = stations,
+            Err(err) => {
+                self.mount_error_popup(anyhow::Error::new(err).context("load radio stations"));
+                return;
+            }
+        }
+
+        let mut table: TableBuilder = TableBuilder::default();
+        if self.radio.stations.is_empty() {
+            table.add_col(TextSpan::from("empty station list"));
+        }
+
+        for (idx, station) in self.radio.stations.iter().enumerate() {
+            if idx > 0 {
+                table.add_row();
+            }
+            table
+                .add_col(TextSpan::new(&station.name).bold())
+                .add_col(TextSpan::new(&station.genre))
+                .add_col(TextSpan::new(
+                    station
+                        .last_played_formatted()
+                        .unwrap_or_else(|| "never".to_string()),
+                ));
+        }
+
+        self.app
+            .attr(
+                &Id::RadioStations,
+                Attribute::Content,
+                AttrValue::Table(table.build()),
+            )
+            .ok();
+    }
+
+    /// Add the saved station at `index` to the playlist
+    pub fn radio_station_add_playlist(&mut self, index: usize) {
+        let Some(station) = self.radio.stations.get(index) else {
+            return;
+        };
+        let source = PlaylistTrackSource::Url(station.url.clone());
+        let name = station.name.clone();
+        self.command(&PlayerCmd::PlaylistAddTrack(
+            PlaylistAddTrack::new_append(vec![source]),
+            PlayerCmdReply::default(),
+        ));
+        self.update_show_message_timeout("Station added", &name, None);
+    }
+
+    /// Open the form for a new station
+    pub fn radio_station_add_show(&mut self) {
+        self.radio.selected_id = None;
+        self.mount_radio_station_edit(&RadioStationFields::default());
+    }
+
+    /// Open the form for the saved station at `index`
+    pub fn radio_station_edit_show(&mut self, index: usize) {
+        let Some(station) = self.radio.stations.get(index) else {
+            return;
+        };
+        self.radio.selected_id = Some(station.id);
+        let fields = RadioStationFields {
+            name: station.name.clone(),
+            url: station.url.clone(),
+            genre: station.genre.clone(),
+            favicon: station.favicon.clone(),
+        };
+        self.mount_radio_station_edit(&fields);
+    }
+
+    /// Save the values from the form, either as a new station or to the station being edited
+    pub fn radio_station_save(&mut self, fields: &RadioStationFields) {
+        // a station without a name is still recognizable by its url
+        let name = if fields.name.is_empty() {
+            &fields.url
+        } else {
+            &fields.name
+        };
+        let station = RadioStationDBInsertable {
+            name,
+            url: &fields.url,
+            genre: &fields.genre,
+            favicon: &fields.favicon,
+        };
+
+        let res = match self.radio.selected_id.take() {
+            Some(id) => self.db.update_radio_station(id, &station),
+            None => self.db.add_radio_station(&station),
+        };
+        if let Err(err) = res {
+            self.mount_error_popup(anyhow::Error::new(err).context("save radio station"));
+            return;
+        }
+
+        self.radio_stations_sync();
+    }
+
+    /// Ask to delete the saved station at `index`
+    pub fn radio_station_delete_show(&mut self, index: usize) {
+        let Some(station) = self.radio.stations.get(index) else {
+            return;
+        };
+        self.radio.selected_id = Some(station.id);
+        self.mount_radio_station_delete_confirm();
+    }
+
+    /// Delete the station selected with [`Self::radio_station_delete_show`]
+    pub fn radio_station_delete(&mut self) {
+        let Some(id) = self.radio.selected_id.take() else {
+            return;
+        };
+        if let Err(err) = self.db.delete_radio_station(id) {
+            self.mount_error_popup(anyhow::Error::new(err).context("delete radio station"));
+            return;
+        }
+
+        self.radio_stations_sync();
+    }
+}
@@ -0,0 +1,69 @@
+use super::Model;
+use termusiclib::track::{MediaType, Track, MAX_RATING};
+
+/// Format a rating like `★★★☆☆`
+pub fn rating_stars(rating: u8) -> String {
+    let rating = rating.min(MAX_RATING);
+    let mut stars = "★".repeat(usize::from(rating));
+    stars.push_str(&"☆".repeat(usize::from(MAX_RATING - rating)));
+    stars
+}
+
+impl Model {
+    /// Rate the playlist track at `index` one star higher or lower
+    pub fn playlist_change_rating(&mut self, index: usize, up: bool) {
+        let Some(file) = self
+            .playlist
+            .tracks()
+            .get(index)
+            .filter(|track| track.media_type == MediaType::Music)
+            .and_then(Track::file)
+            .map(ToString::to_string)
+        else {
+            return;
+        };
+        self.track_change_rating(&file, up);
+    }
+
+    /// Rate the database view track at `index` one star higher or lower
+    pub fn database_change_rating(&mut self, index: usize, up: bool) {
+        let Some(file) = self.dw.search_tracks.get(index).map(|v| v.file.clone()) else {
+            return;
+        };
+        self.track_change_rating(&file, up);
+    }
+
+    /// Rate the local track at `file` one star higher or lower, saving the rating to the tag and the library database
+    fn track_change_rating(&mut self, file: &str, up: bool) {
+        let mut track = match Track::read_from_path(file, true) {
+            Ok(v) => v,
+            Err(err) => {
+                self.mount_error_popup(err.context("read track for rating"));
+                return;
+            }
+        };
+        let rating = if up {
+            track.rating().saturating_add(1)
+        } else {
+            track.rating().saturating_sub(1)
+        };
+
+        if let Err(err) = track.save_rating(rating) {
+            self.mount_error_popup(err.context("save rating"));
+            return;
+        }
+        let rating = track.rating();
+        if let Err(err) = self.db.set_track_rating(file, rating) {
+            self.mount_error_popup(anyhow::Error::new(err).context("save rating to database"));
+            return;
+        }
+
+        for record in self.dw.search_tracks.iter_mut().filter(|v| v.file == file) {
+            record.rating = rating;
+        }
+        self.database_sync_tracks();
+
+        let title = track.title().unwrap_or(file).to_string();
+        self.update_show_message_timeout(&format!("Rated {}", rating_stars(rating)), &title, None);
+    }
+}
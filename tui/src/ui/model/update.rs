@@ -24,12 +24,13 @@
 
 use crate::ui::{model::TermusicLayout, Model};
 use anyhow::anyhow;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 use termusiclib::library_db::SearchCriteria;
 use termusiclib::track::MediaType;
 use termusiclib::types::{
-    DBMsg, DLMsg, GSMsg, Id, IdTagEditor, LIMsg, LyricMsg, Msg, PCMsg, PLMsg, XYWHMsg, YSMsg,
+    DBMsg, DLMsg, GSMsg, Id, IdTagEditor, LIMsg, LyricMsg, Msg, PCMsg, PLMsg, RLMsg, RSMsg,
+    XYWHMsg, YSMsg,
 };
 use termusicplayback::PlayerCmd;
 use tokio::runtime::Handle;
@@ -111,6 +112,14 @@ impl Update<Msg> for Model {
                 self.update_youtube_search(m);
                 None
             }
+            Msg::RadioSearch(m) => {
+                self.update_radio_search(m);
+                None
+            }
+            Msg::RadioStation(m) => {
+                self.update_radio_station(m);
+                None
+            }
             Msg::LyricCycle => {
                 self.lyric_cycle();
                 None
@@ -129,7 +138,7 @@ impl Update<Msg> for Model {
                 }
                 None
             }
-            Msg::LayoutDataBase | Msg::LayoutTreeView | Msg::LayoutPodCast => {
+            Msg::LayoutDataBase | Msg::LayoutTreeView | Msg::LayoutPodCast | Msg::LayoutRadio => {
                 self.update_layout(&msg)
             }
 
@@ -209,7 +218,16 @@ impl Model {
                 TermusicLayout::TreeView => self.app.active(&Id::Library).ok(),
                 TermusicLayout::DataBase => self.app.active(&Id::DBListCriteria).ok(),
                 TermusicLayout::Podcast => self.app.active(&Id::Podcast).ok(),
+                TermusicLayout::Radio => self.app.active(&Id::RadioStations).ok(),
             },
+            LyricMsg::RadioTitleCopy(line) => {
+                self.lyric_radio_title_copy(line);
+                None
+            }
+            LyricMsg::RadioTitleSave(line) => {
+                self.lyric_radio_title_save(line);
+                None
+            }
         };
         None
     }
@@ -461,6 +479,7 @@ impl Model {
         }
         None
     }
+    #[allow(clippy::too_many_lines)]
     fn update_layout(&mut self, msg: &Msg) -> Option<Msg> {
         match msg {
             Msg::LayoutDataBase => {
@@ -552,6 +571,30 @@ impl Model {
                 self.playlist_switch_layout();
                 None
             }
+
+            Msg::LayoutRadio => {
+                let mut need_to_set_focus = true;
+                if let Ok(Some(AttrValue::Flag(true))) =
+                    self.app.query(&Id::Playlist, Attribute::Focus)
+                {
+                    need_to_set_focus = false;
+                }
+
+                if let Ok(Some(AttrValue::Flag(true))) =
+                    self.app.query(&Id::Lyric, Attribute::Focus)
+                {
+                    need_to_set_focus = false;
+                }
+
+                if need_to_set_focus {
+                    self.app.active(&Id::RadioStations).ok();
+                }
+
+                self.layout = TermusicLayout::Radio;
+                self.radio_stations_sync();
+                self.playlist_switch_layout();
+                None
+            }
             _ => None,
         }
     }
@@ -590,6 +633,19 @@ impl Model {
                 let db_search_tracks = self.dw.search_tracks.clone();
                 self.playlist_add_all_from_db(&db_search_tracks);
             }
+            DBMsg::RatingUp(index) => self.database_change_rating(*index, true),
+            DBMsg::RatingDown(index) => self.database_change_rating(*index, false),
+            DBMsg::OpenTagEditor => {
+                let files: Vec<PathBuf> = self
+                    .dw
+                    .search_tracks
+                    .iter()
+                    .map(|track| PathBuf::from(&track.file))
+                    .collect();
+                if !files.is_empty() {
+                    self.mount_tageditor_batch_files(&files);
+                }
+            }
         }
         None
     }
@@ -680,6 +736,80 @@ impl Model {
         }
     }
 
+    fn update_radio_search(&mut self, msg: RSMsg) {
+        match msg {
+            RSMsg::InputPopupShow => {
+                self.mount_radio_search_input();
+            }
+            RSMsg::InputPopupCloseCancel => {
+                self.umount_radio_search_input();
+            }
+            RSMsg::InputPopupCloseOk(input) => {
+                self.umount_radio_search_input();
+                if input.trim().is_empty() {
+                    return;
+                }
+                self.radio_search_results.clear();
+                self.mount_radio_search_table();
+                self.radio_search(&input);
+            }
+            RSMsg::TablePopupCloseCancel => {
+                self.umount_radio_search_table();
+            }
+            RSMsg::TablePopupCloseOk(index) => {
+                self.radio_search_add(index);
+            }
+            RSMsg::TablePopupSave(index) => {
+                self.radio_search_save(index);
+            }
+            RSMsg::SearchSuccess(stations) => {
+                self.radio_search_results = stations;
+                self.radio_search_sync_table();
+                self.redraw = true;
+            }
+            RSMsg::SearchFail(e) => {
+                self.redraw = true;
+                self.mount_error_popup(anyhow!("Radio station search fail: {e}"));
+            }
+        }
+    }
+
+    fn update_radio_station(&mut self, msg: RLMsg) {
+        match msg {
+            RLMsg::StationsBlur => {
+                self.app.active(&Id::Playlist).ok();
+            }
+            RLMsg::AddPlaylist(index) => {
+                self.radio_station_add_playlist(index);
+            }
+            RLMsg::AddPopupShow => {
+                self.radio_station_add_show();
+            }
+            RLMsg::EditPopupShow(index) => {
+                self.radio_station_edit_show(index);
+            }
+            RLMsg::EditPopupCloseCancel => {
+                self.radio.selected_id = None;
+                self.umount_radio_station_edit();
+            }
+            RLMsg::EditPopupCloseOk(fields) => {
+                self.umount_radio_station_edit();
+                self.radio_station_save(&fields);
+            }
+            RLMsg::DeleteShow(index) => {
+                self.radio_station_delete_show(index);
+            }
+            RLMsg::DeleteCloseCancel => {
+                self.radio.selected_id = None;
+                self.umount_radio_station_delete_confirm();
+            }
+            RLMsg::DeleteCloseOk => {
+                self.umount_radio_station_delete_confirm();
+                self.radio_station_delete();
+            }
+        }
+    }
+
     #[allow(clippy::too_many_lines)]
     fn update_general_search(&mut self, msg: &GSMsg) {
         match msg {
@@ -845,15 +975,14 @@ impl Model {
                 self.playlist_play_selected(*index);
             }
             PLMsg::LoopModeCycle => {
+                // the new loop mode is applied once the server sends the change event
                 self.command(&PlayerCmd::CycleLoop);
-                self.config_server.write().settings.player.loop_mode =
-                    self.playlist.cycle_loop_mode();
-                self.playlist_update_title();
             }
             PLMsg::PlaylistTableBlurDown => match self.layout {
                 TermusicLayout::TreeView => assert!(self.app.active(&Id::Library).is_ok()),
                 TermusicLayout::DataBase => assert!(self.app.active(&Id::DBListCriteria).is_ok()),
                 TermusicLayout::Podcast => assert!(self.app.active(&Id::Lyric).is_ok()),
+                TermusicLayout::Radio => assert!(self.app.active(&Id::RadioStations).is_ok()),
             },
             PLMsg::NextSong => {
                 self.command(&PlayerCmd::SkipNext);
@@ -863,19 +992,13 @@ impl Model {
                 self.player_previous();
             }
             PLMsg::SwapDown(index) => {
-                self.playlist.swap_down(*index);
-                self.playlist_sync();
-                if let Err(e) = self.player_sync_playlist() {
-                    self.mount_error_popup(e.context("playlist sync playlist"));
-                }
+                self.playlist_swap_down(*index);
             }
             PLMsg::SwapUp(index) => {
-                self.playlist.swap_up(*index);
-                self.playlist_sync();
-                if let Err(e) = self.player_sync_playlist() {
-                    self.mount_error_popup(e.context("playlist sync playlist"));
-                }
+                self.playlist_swap_up(*index);
             }
+            PLMsg::RatingUp(index) => self.playlist_change_rating(*index, true),
+            PLMsg::RatingDown(index) => self.playlist_change_rating(*index, false),
             PLMsg::AddRandomAlbum => {
                 self.playlist_add_random_album();
             }
@@ -888,6 +1011,7 @@ impl Model {
                     assert!(self.app.active(&Id::DBListSearchTracks).is_ok());
                 }
                 TermusicLayout::Podcast => assert!(self.app.active(&Id::Episode).is_ok()),
+                TermusicLayout::Radio => assert!(self.app.active(&Id::RadioStations).is_ok()),
             },
         }
     }
@@ -903,6 +1027,11 @@ impl Model {
             let name = track.name().unwrap_or("Unknown Song");
             self.update_show_message_timeout("Currently Playing", name, None);
 
+            // the server updated the last played time of the station
+            if self.layout == TermusicLayout::Radio {
+                self.radio_stations_sync();
+            }
+
             // TODO: is there a better way to update only a single / 2 columns (prev/next) instead of re-doing the whole playist; OR a way to decide at draw-time?
             // sync playlist to update any dynamic parts added to the columns (like current playing symbol)
             self.playlist_sync();
@@ -943,7 +1072,8 @@ impl Model {
                     self.update_layout(&Msg::LayoutPodCast);
                 }
                 MediaType::Music | MediaType::LiveRadio => match self.layout {
-                    TermusicLayout::TreeView | TermusicLayout::DataBase => {}
+                    TermusicLayout::TreeView | TermusicLayout::DataBase | TermusicLayout::Radio => {
+                    }
                     TermusicLayout::Podcast => {
                         self.update_layout(&Msg::LayoutTreeView);
                     }
@@ -1,7 +1,7 @@
 use crate::ui::components::{
     DBListCriteria, DBListSearchResult, DBListSearchTracks, DownloadSpinner, EpisodeList,
     FeedsList, Footer, GSInputPopup, GSTablePopup, GlobalListener, LabelSpan, Lyric, MusicLibrary,
-    Playlist, Progress, Source,
+    Playlist, Progress, RadioStationList, Source,
 };
 use crate::ui::model::{ConfigEditorLayout, Model, TermusicLayout};
 use crate::ui::utils::{
@@ -144,6 +144,13 @@ impl Model {
                 vec![]
             )
             .is_ok());
+        assert!(app
+            .mount(
+                Id::RadioStations,
+                Box::new(RadioStationList::new(config.clone())),
+                vec![]
+            )
+            .is_ok());
         assert!(app
             .mount(
                 Id::DownloadSpinner,
@@ -169,7 +176,10 @@ impl Model {
         if self.redraw {
             self.redraw = false;
             self.last_redraw = Instant::now();
-            if self
+            if self.app.mounted(&Id::TagEditor(IdTagEditor::TableBatch)) {
+                self.view_tag_editor_batch();
+                return;
+            } else if self
                 .app
                 .mounted(&Id::TagEditor(IdTagEditor::TableLyricOptions))
             {
@@ -189,10 +199,49 @@ impl Model {
                 TermusicLayout::TreeView => self.view_layout_treeview(),
                 TermusicLayout::DataBase => self.view_layout_database(),
                 TermusicLayout::Podcast => self.view_layout_podcast(),
+                TermusicLayout::Radio => self.view_layout_radio(),
             }
         }
     }
 
+    pub fn view_layout_radio(&mut self) {
+        self.terminal
+            .raw_mut()
+            .draw(|f| {
+                let chunks_main = Layout::default()
+                    .direction(Direction::Vertical)
+                    .margin(0)
+                    .constraints([Constraint::Min(2), Constraint::Length(1)].as_ref())
+                    .split(f.area());
+                let chunks_left = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .margin(0)
+                    .constraints([Constraint::Ratio(1, 3), Constraint::Ratio(2, 3)].as_ref())
+                    .split(chunks_main[0]);
+                let chunks_right = Layout::default()
+                    .direction(Direction::Vertical)
+                    .margin(0)
+                    .constraints(
+                        [
+                            Constraint::Min(2),
+                            Constraint::Length(3),
+                            Constraint::Length(4),
+                        ]
+                        .as_ref(),
+                    )
+                    .split(chunks_left[1]);
+
+                self.app.view(&Id::RadioStations, f, chunks_left[0]);
+                self.app.view(&Id::Playlist, f, chunks_right[0]);
+                self.app.view(&Id::Progress, f, chunks_right[1]);
+                self.app.view(&Id::Lyric, f, chunks_right[2]);
+                self.app.view(&Id::Label, f, chunks_main[1]);
+
+                Self::view_layout_commons(f, &mut self.app, self.download_tracker.visible());
+            })
+            .expect("Expected to draw without error");
+    }
+
     pub fn view_layout_podcast(&mut self) {
         self.terminal
             .raw_mut()
@@ -257,7 +306,7 @@ impl Model {
                     .margin(0)
                     .constraints(
                         [
-                            Constraint::Length(10),
+                            Constraint::Length(11),
                             Constraint::Length(10),
                             Constraint::Min(2),
                         ]
@@ -415,6 +464,22 @@ impl Model {
             let popup = draw_area_in_relative(f.area(), 65, 68);
             f.render_widget(Clear, popup);
             app.view(&Id::YoutubeSearchTablePopup, f, popup);
+        } else if app.mounted(&Id::RadioSearchInputPopup) {
+            let popup = draw_area_in_absolute(f.area(), 60, 3);
+            f.render_widget(Clear, popup);
+            app.view(&Id::RadioSearchInputPopup, f, popup);
+        } else if app.mounted(&Id::RadioSearchTablePopup) {
+            let popup = draw_area_in_relative(f.area(), 80, 68);
+            f.render_widget(Clear, popup);
+            app.view(&Id::RadioSearchTablePopup, f, popup);
+        } else if app.mounted(&Id::RadioStationEditPopup) {
+            let popup = draw_area_in_absolute(f.area(), 76, 12);
+            f.render_widget(Clear, popup);
+            app.view(&Id::RadioStationEditPopup, f, popup);
+        } else if app.mounted(&Id::RadioStationDeleteConfirmPopup) {
+            let popup = draw_area_in_absolute(f.area(), 60, 3);
+            f.render_widget(Clear, popup);
+            app.view(&Id::RadioStationDeleteConfirmPopup, f, popup);
         } else if app.mounted(&Id::PodcastSearchTablePopup) {
             let popup = draw_area_in_relative(f.area(), 65, 68);
             f.render_widget(Clear, popup);
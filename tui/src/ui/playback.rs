@@ -1,16 +1,24 @@
 use anyhow::Result;
-use termusiclib::player::music_player_client::MusicPlayerClient;
-use termusiclib::player::{Empty, GetProgressResponse, PlayerProgress};
+use std::time::Duration;
+use termusiclib::player::auth::AuthedClient;
+use termusiclib::player::playlist_helpers::{
+    PlaylistAddTrack, PlaylistPlaySpecific, PlaylistRemoveTrackIndexed, PlaylistSwapTrack,
+    PlaylistTracks,
+};
+use termusiclib::player::radio_history::RadioHistoryInfo;
+use termusiclib::player::{
+    Empty, EqualizerInfo, GetProgressResponse, OutputDeviceInfo, PlayerProgress, SetVolumeRequest,
+    SleepTimerInfo, SleepTimerKind,
+};
 use termusicplayback::Status;
 use tokio_stream::{Stream, StreamExt as _};
-use tonic::transport::Channel;
 
 pub struct Playback {
-    client: MusicPlayerClient<Channel>,
+    client: AuthedClient,
 }
 
 impl Playback {
-    pub fn new(client: MusicPlayerClient<Channel>) -> Self {
+    pub fn new(client: AuthedClient) -> Self {
         Self { client }
     }
     pub async fn toggle_pause(&mut self) -> Result<Status> {
@@ -105,17 +113,93 @@ impl Playback {
         Ok(response.into())
     }
 
-    pub async fn reload_config(&mut self) -> Result<()> {
+    pub async fn seek_to(&mut self, position: Duration) -> Result<PlayerProgress> {
+        let request = tonic::Request::new(position.into());
+        let response = self.client.seek_to(request).await?;
+        let response = response.into_inner();
+        info!("Got response from server: {:?}", response);
+        Ok(response.into())
+    }
+
+    pub async fn set_volume(&mut self, volume: u16) -> Result<u16> {
+        let request = tonic::Request::new(SetVolumeRequest {
+            volume: u32::from(volume),
+        });
+        let response = self.client.set_volume(request).await?;
+        let response = response.into_inner();
+        info!("Got response from server: {:?}", response);
+        // clamped to u16::MAX, also send is a u16, but protobuf does not support u16 directly
+        #[allow(clippy::cast_possible_truncation)]
+        Ok(response.volume.min(u32::from(u16::MAX)) as u16)
+    }
+
+    pub async fn set_sleep_timer(
+        &mut self,
+        kind: SleepTimerKind,
+    ) -> Result<Option<SleepTimerInfo>> {
+        let request = tonic::Request::new(kind.into());
+        let response = self.client.set_sleep_timer(request).await?;
+        let response = response.into_inner();
+        info!("Got response from server: {:?}", response);
+        Ok(response.into())
+    }
+
+    pub async fn cancel_sleep_timer(&mut self) -> Result<Option<SleepTimerInfo>> {
         let request = tonic::Request::new(Empty {});
-        let response = self.client.reload_config(request).await?;
+        let response = self.client.cancel_sleep_timer(request).await?;
         let response = response.into_inner();
         info!("Got response from server: {:?}", response);
-        Ok(())
+        Ok(response.into())
+    }
+
+    pub async fn get_sleep_timer(&mut self) -> Result<Option<SleepTimerInfo>> {
+        let request = tonic::Request::new(Empty {});
+        let response = self.client.get_sleep_timer(request).await?;
+        let response = response.into_inner();
+        Ok(response.into())
+    }
+
+    pub async fn get_equalizer(&mut self) -> Result<EqualizerInfo> {
+        let request = tonic::Request::new(Empty {});
+        let response = self.client.get_equalizer(request).await?;
+        let response = response.into_inner();
+        Ok(response.into())
+    }
+
+    pub async fn set_equalizer_preset(&mut self, preset: Option<String>) -> Result<EqualizerInfo> {
+        let request = tonic::Request::new(preset.into());
+        let response = self.client.set_equalizer_preset(request).await?;
+        let response = response.into_inner();
+        info!("Got response from server: {:?}", response);
+        Ok(response.into())
     }
 
-    pub async fn reload_playlist(&mut self) -> Result<()> {
+    pub async fn get_output_devices(&mut self) -> Result<OutputDeviceInfo> {
         let request = tonic::Request::new(Empty {});
-        let response = self.client.reload_playlist(request).await?;
+        let response = self.client.get_output_devices(request).await?;
+        let response = response.into_inner();
+        Ok(response.into())
+    }
+
+    pub async fn set_output_device(&mut self, device: Option<String>) -> Result<OutputDeviceInfo> {
+        let request = tonic::Request::new(device.into());
+        let response = self.client.set_output_device(request).await?;
+        let response = response.into_inner();
+        info!("Got response from server: {:?}", response);
+        Ok(response.into())
+    }
+
+    /// Get the title history of the radio station at `url`, or of the current track if [`None`]
+    pub async fn get_radio_history(&mut self, url: Option<String>) -> Result<RadioHistoryInfo> {
+        let request = tonic::Request::new(url.into());
+        let response = self.client.get_radio_history(request).await?;
+        let response = response.into_inner();
+        Ok(response.into())
+    }
+
+    pub async fn reload_config(&mut self) -> Result<()> {
+        let request = tonic::Request::new(Empty {});
+        let response = self.client.reload_config(request).await?;
         let response = response.into_inner();
         info!("Got response from server: {:?}", response);
         Ok(())
@@ -137,6 +221,73 @@ impl Playback {
         Ok(())
     }
 
+    pub async fn get_playlist(&mut self) -> Result<PlaylistTracks> {
+        let request = tonic::Request::new(Empty {});
+        let response = self.client.get_playlist(request).await?;
+        let response = response.into_inner();
+        info!(
+            "Got playlist response from server with {} tracks",
+            response.tracks.len()
+        );
+        PlaylistTracks::try_from(response)
+    }
+
+    pub async fn add_to_playlist(&mut self, info: PlaylistAddTrack) -> Result<()> {
+        let request = tonic::Request::new(info.into());
+        let response = self.client.playlist_add_track(request).await?;
+        let response = response.into_inner();
+        info!("Got response from server: {:?}", response);
+        Ok(())
+    }
+
+    pub async fn remove_from_playlist(&mut self, info: PlaylistRemoveTrackIndexed) -> Result<()> {
+        let request = tonic::Request::new(info.into());
+        let response = self.client.playlist_remove_track(request).await?;
+        let response = response.into_inner();
+        info!("Got response from server: {:?}", response);
+        Ok(())
+    }
+
+    pub async fn remove_deleted_from_playlist(&mut self) -> Result<()> {
+        let request = tonic::Request::new(Empty {});
+        let response = self.client.playlist_remove_deleted_tracks(request).await?;
+        let response = response.into_inner();
+        info!("Got response from server: {:?}", response);
+        Ok(())
+    }
+
+    pub async fn swap_tracks(&mut self, info: PlaylistSwapTrack) -> Result<()> {
+        let request = tonic::Request::new(info.into());
+        let response = self.client.playlist_swap_tracks(request).await?;
+        let response = response.into_inner();
+        info!("Got response from server: {:?}", response);
+        Ok(())
+    }
+
+    pub async fn clear_playlist(&mut self) -> Result<()> {
+        let request = tonic::Request::new(Empty {});
+        let response = self.client.playlist_clear(request).await?;
+        let response = response.into_inner();
+        info!("Got response from server: {:?}", response);
+        Ok(())
+    }
+
+    pub async fn shuffle_playlist(&mut self) -> Result<()> {
+        let request = tonic::Request::new(Empty {});
+        let response = self.client.playlist_shuffle(request).await?;
+        let response = response.into_inner();
+        info!("Got response from server: {:?}", response);
+        Ok(())
+    }
+
+    pub async fn play_specific(&mut self, info: PlaylistPlaySpecific) -> Result<()> {
+        let request = tonic::Request::new(info.into());
+        let response = self.client.playlist_play_specific(request).await?;
+        let response = response.into_inner();
+        info!("Got response from server: {:?}", response);
+        Ok(())
+    }
+
     pub async fn subscribe_to_stream_updates(
         &mut self,
     ) -> Result<impl Stream<Item = Result<termusiclib::player::StreamUpdates>>> {
@@ -1,3 +1,6 @@
+use std::io::Write;
+
+use base64::Engine;
 use tuirealm::ratatui::layout::{Constraint, Direction, Layout, Rect};
 
 // /// Get block
@@ -90,6 +93,21 @@ pub fn draw_area_top_right_absolute(parent: Rect, width: u16, height: u16) -> Re
         .split(new_area[1])[1]
 }
 
+/// Build the OSC 52 escape sequence that sets the system clipboard to `text`
+fn osc52_sequence(text: &str) -> String {
+    let encoded = base64::engine::general_purpose::STANDARD.encode(text);
+    format!("\x1b]52;c;{encoded}\x07")
+}
+
+/// Copy `text` to the system clipboard through the terminal (OSC 52).
+///
+/// This also works over ssh, but requires the terminal to support (and allow) OSC 52.
+pub fn copy_to_clipboard(text: &str) -> std::io::Result<()> {
+    let mut stdout = std::io::stdout().lock();
+    stdout.write_all(osc52_sequence(text).as_bytes())?;
+    stdout.flush()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -103,4 +121,12 @@ mod tests {
         assert_eq!(child.width, 272);
         assert_eq!(child.height, 55);
     }
+
+    #[test]
+    fn should_encode_osc52() {
+        assert_eq!(
+            osc52_sequence("Artist - Title"),
+            "\x1b]52;c;QXJ0aXN0IC0gVGl0bGU=\x07"
+        );
+    }
 }